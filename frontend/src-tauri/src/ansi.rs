@@ -0,0 +1,35 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+// CSI sequences (colours, cursor moves), OSC strings (titles, hyperlinks),
+// charset selection and the remaining two-byte escapes.
+static ESCAPES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[()][0-9A-Za-z]|\x1b[@-Z\\-_]")
+        .unwrap()
+});
+
+// `text` without terminal escape sequences, for views that only want the
+// plain text: log export, search, diffing.
+pub fn strip(text: &str) -> String {
+    ESCAPES.replace_all(text, "").into_owned()
+}
+
+pub fn strip_if(text: String, strip_ansi: bool) -> String {
+    if strip_ansi && text.contains('\u{1b}') {
+        strip(&text)
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strip;
+
+    #[test]
+    fn escapes_are_removed_and_text_kept() {
+        let colored = "\u{1b}[1;32mConverged\u{1b}[0m E = -1.5\n\u{1b}]0;arc\u{7}\u{1b}(Bdone";
+        assert_eq!(strip(colored), "Converged E = -1.5\ndone");
+        assert_eq!(strip("plain [1m] text"), "plain [1m] text");
+    }
+}
@@ -0,0 +1,176 @@
+use once_cell::sync::OnceCell;
+use serde_json::Value as JsonValue;
+use std::path::{Path, PathBuf};
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::{AppHandle, Manager, Runtime};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+
+const LOG_DIR: &str = "logs";
+const LOG_PREFIX: &str = "orchestrator";
+const KEEP_FILES: usize = 7;
+
+static DIR: OnceCell<PathBuf> = OnceCell::new();
+// flushes the file writer when the app exits
+static GUARD: OnceCell<WorkerGuard> = OnceCell::new();
+
+// Logs go to stderr and to one file a day under app_data_dir/logs, of which
+// the last KEEP_FILES are kept. Without an app data dir only stderr is left.
+pub fn init(app: &AppHandle) {
+    let level = if cfg!(debug_assertions) {
+        tracing::Level::DEBUG
+    } else {
+        tracing::Level::INFO
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_ansi(false);
+    let file = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| {
+            let dir = dir.join(LOG_DIR);
+            RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(LOG_PREFIX)
+                .filename_suffix("log")
+                .max_log_files(KEEP_FILES)
+                .build(&dir)
+                .map(|appender| (dir, appender))
+                .map_err(|e| e.to_string())
+        });
+    match file {
+        Ok((dir, appender)) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = builder.with_writer(writer.and(std::io::stderr)).try_init();
+            let _ = GUARD.set(guard);
+            let _ = DIR.set(dir);
+        }
+        Err(e) => {
+            let _ = builder.with_writer(std::io::stderr).try_init();
+            tracing::warn!("no log directory, logging to stderr only: {e}");
+        }
+    }
+}
+
+// The profile, session and target a command's arguments name, looked up
+// directly and in a `payload` body. The profile is only user@host.
+pub fn command_fields(args: &JsonValue) -> [Option<String>; 3] {
+    let get = |key: &str| {
+        args.get(key)
+            .or_else(|| args.get("payload").and_then(|p| p.get(key)))
+    };
+    let text = |key: &str| get(key).and_then(JsonValue::as_str).map(str::to_string);
+    let profile = get("profile").map(|p| {
+        let field = |k| p.get(k).and_then(JsonValue::as_str).unwrap_or("?");
+        format!("{}@{}", field("user"), field("host"))
+    });
+    let target = text("target")
+        .or_else(|| text("windowId"))
+        .or_else(|| text("window_id"));
+    [profile, text("session"), target]
+}
+
+// Opens a span for an IPC call with what command_fields finds in its
+// arguments, and logs the call in it.
+pub fn command_span(command: &str, args: Option<&JsonValue>) -> tracing::Span {
+    let [profile, session, target] = args.map(command_fields).unwrap_or_default();
+    let span = tracing::info_span!(
+        "command",
+        name = command,
+        profile = profile.as_deref(),
+        session = session.as_deref(),
+        target = target.as_deref(),
+    );
+    span.in_scope(|| tracing::debug!("invoked"));
+    span
+}
+
+// Wraps the generated IPC handler so every command is dispatched inside its
+// command_span.
+pub fn traced<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let args = match invoke.message.payload() {
+            InvokeBody::Json(args) => Some(args),
+            InvokeBody::Raw(_) => None,
+        };
+        let span = command_span(invoke.message.command(), args);
+        let _entered = span.enter();
+        handler(invoke)
+    }
+}
+
+// The last `lines` lines of the newest log files, oldest first.
+fn tail(dir: &Path, lines: usize) -> Result<Vec<String>, String> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("read {}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(LOG_PREFIX))
+        })
+        .collect();
+    // the date in the name sorts them by age
+    files.sort();
+    let mut recent: Vec<String> = Vec::new();
+    for path in files.iter().rev() {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("read {}: {e}", path.display()))?;
+        let mut older: Vec<String> = text.lines().map(str::to_string).collect();
+        older.append(&mut recent);
+        recent = older;
+        if recent.len() >= lines {
+            break;
+        }
+    }
+    let skip = recent.len().saturating_sub(lines);
+    Ok(recent.split_off(skip))
+}
+
+pub fn recent(lines: usize) -> Result<Vec<String>, String> {
+    let dir = DIR.get().ok_or("logging to a file is not set up")?;
+    tail(dir, lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command_fields, tail};
+    use serde_json::json;
+
+    #[test]
+    fn fields_come_from_either_shape_and_tail_spans_files() {
+        let args = json!({
+            "payload": {
+                "profile": { "user": "calvin", "host": "hpc", "password": "secret" },
+                "session": "arc",
+                "window_id": "@3",
+            }
+        });
+        assert_eq!(
+            command_fields(&args),
+            [
+                Some("calvin@hpc".to_string()),
+                Some("arc".to_string()),
+                Some("@3".to_string())
+            ]
+        );
+        assert_eq!(
+            command_fields(&json!({ "session": "arc", "target": "arc:1" })),
+            [None, Some("arc".to_string()), Some("arc:1".to_string())]
+        );
+
+        let dir = std::env::temp_dir().join(format!("applog-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("orchestrator.2026-10-14.log"), "a\nb\n").unwrap();
+        std::fs::write(dir.join("orchestrator.2026-10-15.log"), "c\n").unwrap();
+        std::fs::write(dir.join("other.txt"), "x\n").unwrap();
+        assert_eq!(tail(&dir, 2).unwrap(), ["b", "c"]);
+        assert_eq!(tail(&dir, 10).unwrap(), ["a", "b", "c"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
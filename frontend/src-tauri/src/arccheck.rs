@@ -0,0 +1,119 @@
+use crate::{conda, runs};
+use crate::{creds_from, run_remote_cmd, HostProfile};
+use serde::Serialize;
+use std::borrow::Cow;
+
+const SEPARATOR: &str = "---";
+
+// Nothing here needs ARC's python to work, so a broken env still reports
+// where the checkout is and which commit it sits on.
+const CHECKOUT_PROBE: &str = r#"dir=$(dirname "$arc")
+test -f "$arc" && echo present || echo absent
+echo ---
+sed -n "s/^__version__ *= *['\"]\([^'\"]*\)['\"].*/\1/p" "$dir/arc/version.py" 2>/dev/null
+echo ---
+git -C "$dir" rev-parse --abbrev-ref HEAD 2>/dev/null
+echo ---
+git -C "$dir" rev-parse --short HEAD 2>/dev/null
+echo ---
+git -C "$dir" status --porcelain --untracked-files=no 2>/dev/null | head -n 1"#;
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ArcCheck {
+    pub host: String,
+    pub arc_path: String,
+    pub exists: bool,
+    pub version: Option<String>,
+    pub branch: Option<String>, // None on a detached HEAD or outside git
+    pub commit: Option<String>,
+    pub dirty: bool,                  // tracked files have local changes
+    pub missing: Vec<String>,         // ARC dependencies the env's python can't import
+    pub python_error: Option<String>, // the env's python did not run at all
+    pub ok: bool,
+}
+
+fn parse_checkout(stdout: &str, check: &mut ArcCheck) {
+    let sections: Vec<&str> = stdout.split(&format!("{SEPARATOR}\n")).collect();
+    let section = |idx: usize| {
+        let text = sections.get(idx).copied().unwrap_or("").trim();
+        (!text.is_empty()).then(|| text.to_string())
+    };
+    check.exists = section(0).as_deref() == Some("present");
+    check.version = section(1);
+    check.branch = section(2).filter(|b| b != "HEAD");
+    check.commit = section(3);
+    check.dirty = section(4).is_some();
+}
+
+fn python_command(python_path: &str, conda_env: Option<&str>) -> String {
+    let script = shell_escape::escape(Cow::from(conda::import_script()));
+    match conda_env {
+        Some(env) => format!(
+            "conda activate {} && python -c {script}",
+            shell_escape::escape(Cow::from(env))
+        ),
+        None => format!(
+            "{} -c {script}",
+            shell_escape::escape(Cow::from(python_path))
+        ),
+    }
+}
+
+// Without a conda env the profile's python override is used, then `python`.
+pub fn remote_check_arc(
+    profile: &HostProfile,
+    arc_path: &str,
+    conda_env: Option<&str>,
+) -> Result<ArcCheck, String> {
+    let creds = creds_from(profile);
+    let mut check = ArcCheck {
+        host: runs::host_label(profile),
+        arc_path: arc_path.to_string(),
+        ..ArcCheck::default()
+    };
+
+    let arc = shell_escape::escape(Cow::from(arc_path));
+    let out = run_remote_cmd(&creds, format!("arc={arc}\n{CHECKOUT_PROBE}"))?;
+    parse_checkout(&out.stdout, &mut check);
+
+    let python_path = profile.overrides.python_path.as_deref().unwrap_or("python");
+    let out = run_remote_cmd(&creds, python_command(python_path, conda_env))?;
+    if out.code == 0 {
+        check.missing = out.stdout.lines().map(str::to_string).collect();
+    } else {
+        let said = out.stderr.trim().lines().last().unwrap_or("").to_string();
+        check.python_error = Some(if said.is_empty() {
+            format!("python exited with code {}", out.code)
+        } else {
+            said
+        });
+    }
+    check.ok = check.exists && check.missing.is_empty() && check.python_error.is_none();
+    Ok(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_checkout, ArcCheck};
+
+    #[test]
+    fn parses_checkout_probe() {
+        let mut check = ArcCheck::default();
+        parse_checkout(
+            "present\n---\n1.1.0\n---\nmain\n---\na1b2c3d\n---\n M arc/main.py\n",
+            &mut check,
+        );
+        assert!(check.exists);
+        assert_eq!(check.version.as_deref(), Some("1.1.0"));
+        assert_eq!(check.branch.as_deref(), Some("main"));
+        assert_eq!(check.commit.as_deref(), Some("a1b2c3d"));
+        assert!(check.dirty);
+
+        let mut check = ArcCheck::default();
+        parse_checkout("absent\n---\n---\nHEAD\n---\na1b2c3d\n---\n", &mut check);
+        assert!(!check.exists);
+        assert_eq!(check.version, None);
+        assert_eq!(check.branch, None);
+        assert!(!check.dirty);
+    }
+}
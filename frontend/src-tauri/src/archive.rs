@@ -0,0 +1,231 @@
+use crate::model::{ARCRun, RunStatus};
+use crate::runs::{self, RunRegistry};
+use crate::{cleanup, creds_from, fetch, run_remote_cmd, ssh, HostProfile};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+// A directory destination gets `<run name>-<short id>.tar.gz` inside it;
+// anything else is taken as the archive file path.
+fn archive_target(run: &ARCRun, dest: &Path) -> PathBuf {
+    if dest.is_dir() {
+        let short_id: String = run.id.chars().take(8).collect();
+        dest.join(format!("{}-{}.tar.gz", run.name, short_id))
+    } else {
+        dest.to_path_buf()
+    }
+}
+
+fn count_files(dir: &Path) -> std::io::Result<usize> {
+    let mut count = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let kind = entry.file_type()?;
+        if kind.is_dir() {
+            count += count_files(&entry.path())?;
+        } else {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn write_tarball(src: &Path, archive: &Path) -> Result<(), String> {
+    let root = src
+        .file_name()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("work_dir"));
+    let file = File::create(archive).map_err(|e| format!("create archive: {e}"))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    builder.follow_symlinks(false);
+    builder
+        .append_dir_all(&root, src)
+        .map_err(|e| format!("archive {}: {e}", src.display()))?;
+    builder
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(|e| format!("finish archive: {e}"))?;
+    Ok(())
+}
+
+// Reads the whole tarball back and counts the files in it.
+fn archived_files(archive: &Path) -> Result<usize, String> {
+    let file = File::open(archive).map_err(|e| format!("open archive: {e}"))?;
+    let mut tarball = tar::Archive::new(GzDecoder::new(file));
+    let mut archived = 0;
+    for entry in tarball
+        .entries()
+        .map_err(|e| format!("read archive: {e}"))?
+    {
+        let mut entry = entry.map_err(|e| format!("read archive: {e}"))?;
+        if !entry.header().entry_type().is_dir() {
+            archived += 1;
+        }
+        std::io::copy(&mut entry, &mut std::io::sink())
+            .map_err(|e| format!("read archive: {e}"))?;
+    }
+    Ok(archived)
+}
+
+fn check_count(archive: &Path, expected: usize, src: &Path) -> Result<(), String> {
+    let archived = archived_files(archive)?;
+    if archived != expected {
+        return Err(format!(
+            "archive holds {archived} files but {} has {expected}",
+            src.display()
+        ));
+    }
+    Ok(())
+}
+
+// Checks the tarball holds every file of `src`.
+fn verify_tarball(src: &Path, archive: &Path) -> Result<(), String> {
+    let expected = count_files(src).map_err(|e| format!("scan {}: {e}", src.display()))?;
+    check_count(archive, expected, src)
+}
+
+fn write_local(run: &ARCRun, partial: &Path) -> Result<(), String> {
+    if !run.work_dir.is_dir() {
+        return Err(format!("work_dir not found: {}", run.work_dir.display()));
+    }
+    write_tarball(&run.work_dir, partial).and_then(|_| verify_tarball(&run.work_dir, partial))
+}
+
+// Tars the work_dir on the host, downloads the tarball over SFTP and checks
+// it against the file count the host reports.
+fn write_remote(run: &ARCRun, profile: &HostProfile, partial: &Path) -> Result<(), String> {
+    let creds = creds_from(profile);
+    let work_dir = shell_escape::escape(run.work_dir.to_string_lossy());
+    let count = format!("test -d {work_dir} && find {work_dir} ! -type d | wc -l");
+    let out = run_remote_cmd(&creds, count)?;
+    if out.code != 0 {
+        return Err(format!("work_dir not found: {}", run.work_dir.display()));
+    }
+    let expected: usize = out
+        .stdout
+        .trim()
+        .parse()
+        .map_err(|_| format!("unexpected file count: {}", out.stdout.trim()))?;
+
+    let remote_tarball = PathBuf::from(format!("/tmp/arc-archive-{}.tar.gz", run.id));
+    let out = run_remote_cmd(&creds, fetch::tar_command(&run.work_dir, &remote_tarball)?)?;
+    let downloaded = if out.code != 0 {
+        Err(format!(
+            "tar {} failed: {}",
+            run.work_dir.display(),
+            out.stderr.trim()
+        ))
+    } else {
+        ssh::download(&creds, &remote_tarball, partial, |_, _| {}).map(drop)
+    };
+    let rm = format!(
+        "rm -f {}",
+        shell_escape::escape(remote_tarball.to_string_lossy())
+    );
+    let _ = run_remote_cmd(&creds, rm);
+    downloaded?;
+    check_count(partial, expected, &run.work_dir)
+}
+
+// Remote runs are tarred on their host and the tarball is brought back, so
+// the archive always ends up at `dest_path` on this machine. Deleting the
+// work_dir afterwards follows the same rules as cleaning it up.
+pub fn archive_run(
+    id: String,
+    dest_path: String,
+    delete_original: bool,
+    force: bool,
+) -> Result<ARCRun, String> {
+    let run = runs::get_run(id.clone())?;
+    if run.status == RunStatus::Queued || runs::is_active(&run.status) {
+        return Err("cannot archive a run that is queued or active".into());
+    }
+    let profile = runs::remote_profile(&run)?;
+    // checked up front, so a refused delete does not leave an archive behind
+    let delete = if delete_original {
+        cleanup::check_cleanable(&run.status, force)?;
+        let root = cleanup::work_root(&id)?;
+        Some(cleanup::ensure_inside(
+            profile.as_ref(),
+            &root,
+            &run.work_dir,
+        )?)
+    } else {
+        None
+    };
+    let archive = archive_target(&run, Path::new(&dest_path));
+    if profile.is_none() && archive.starts_with(&run.work_dir) {
+        return Err("archive must be written outside the work_dir".into());
+    }
+
+    // write next to the target and rename, so a failed write never leaves a
+    // truncated tarball under the final name
+    let partial = archive.with_extension("gz.partial");
+    let written = match &profile {
+        Some(profile) => write_remote(&run, profile, &partial),
+        None => write_local(&run, &partial),
+    };
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, &archive).map_err(|e| format!("rename archive: {e}"))?;
+
+    if let Some(work_dir) = delete {
+        cleanup::remove_dir(profile.as_ref(), &work_dir)?;
+    }
+    RunRegistry::global()
+        .update(&id, |r| r.archive_path = Some(archive))
+        .ok_or_else(|| format!("unknown run: {id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{archive_run, verify_tarball, write_tarball};
+    use crate::model::{ARCRun, RunStatus};
+    use crate::runs::RunRegistry;
+
+    #[test]
+    fn tarball_roundtrip_verifies_file_count() {
+        let base = std::env::temp_dir().join(format!("archive-{}", uuid::Uuid::new_v4()));
+        let work = base.join("run1");
+        std::fs::create_dir_all(work.join("calcs/Species/CH4")).unwrap();
+        std::fs::write(work.join("input.yml"), "project: run1\n").unwrap();
+        std::fs::write(work.join("calcs/Species/CH4/input.gjf"), "#p opt\n").unwrap();
+
+        let archive = base.join("run1.tar.gz");
+        write_tarball(&work, &archive).unwrap();
+        assert!(verify_tarball(&work, &archive).is_ok());
+
+        std::fs::write(work.join("late.log"), "written after archiving\n").unwrap();
+        assert!(verify_tarball(&work, &archive).is_err());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn delete_original_refuses_runs_that_may_still_be_going() {
+        let base = std::env::temp_dir().join(format!("archive-{}", uuid::Uuid::new_v4()));
+        let work = base.join("run1");
+        std::fs::create_dir_all(&work).unwrap();
+        std::fs::write(work.join("arc.log"), "Starting project run1\n").unwrap();
+        for status in [RunStatus::Unknown, RunStatus::Orphaned] {
+            let run = ARCRun {
+                work_dir: work.clone(),
+                ..ARCRun::for_test(&uuid::Uuid::new_v4().to_string(), status)
+            };
+            RunRegistry::global().insert(run.clone());
+            let dest = base.to_string_lossy().into_owned();
+            assert!(archive_run(run.id, dest, true, false).is_err());
+        }
+        assert!(work.join("arc.log").is_file());
+        assert!(!base.read_dir().unwrap().any(|e| {
+            e.unwrap()
+                .file_name()
+                .to_string_lossy()
+                .ends_with(".tar.gz")
+        }));
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::errors::no_server;
+use crate::ssh::ExecOut;
+use crate::{
+    apply_window_names, build_tmux_send_keys_commands, ensure_window_ids, is_placeholder_name,
+    listcache, tmux_exec, utf8, Detail, HostProfile, TmuxSession, TmuxWindow, WINDOW_NAMES_FORMAT,
+};
+
+const SESSIONS_FORMAT: &str = "#S|#{session_windows}|#{?session_attached,1,0}";
+pub const WINDOWS_FORMAT: &str =
+    "#{window_index}|#{window_id}|#{window_name}|#{?window_active,1,0}|#{window_panes}";
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+pub fn parse_sessions(stdout: &str) -> Vec<TmuxSession> {
+    stdout
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let mut it = line.split('|');
+            let name = it.next().unwrap_or("").to_string();
+            let windows = it.next().unwrap_or("0").parse().unwrap_or(0);
+            let attached = it.next().unwrap_or("0") == "1";
+            TmuxSession {
+                name,
+                windows,
+                attached,
+            }
+        })
+        .collect()
+}
+
+// Parses a WINDOWS_FORMAT listing.
+pub fn parse_windows(stdout: &str) -> Vec<TmuxWindow> {
+    stdout
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|line| {
+            let mut it = line.split('|'); // NOTE: '|' (not tab)
+            let index = it.next().unwrap_or("0").trim().parse().unwrap_or(0);
+            let id = it.next().unwrap_or("").trim().to_string();
+            let name = it
+                .next()
+                .unwrap_or("")
+                .trim_end_matches(['\r', '\n'])
+                .to_string();
+            let active = it.next().unwrap_or("0").trim() == "1";
+            let panes = it.next().unwrap_or("1").trim().parse().unwrap_or(1);
+            TmuxWindow {
+                index,
+                id,
+                name,
+                active,
+                panes,
+            }
+        })
+        .collect()
+}
+
+// The tmux operations the commands offer, for the local server and for a
+// profile's host alike. An implementation only says how one tmux invocation
+// runs; everything else is written once, here, on top of that.
+pub trait TmuxBackend {
+    // the host, or None for the local server
+    fn profile(&self) -> Option<&HostProfile>;
+
+    fn exec(&self, args: &[String]) -> Result<ExecOut, String>;
+
+    // stdout of a tmux invocation that has to succeed
+    fn run(&self, args: &[String]) -> Result<String, String> {
+        let out = self.exec(args)?;
+        if out.code != 0 {
+            return Err(out.stderr);
+        }
+        Ok(out.stdout)
+    }
+
+    fn list_sessions(&self) -> Result<Vec<TmuxSession>, String> {
+        listcache::cached(self.profile(), None, || {
+            let out = self.exec(&args(&["list-sessions", "-F", SESSIONS_FORMAT]))?;
+            if out.code != 0 {
+                if no_server(&out.stderr) {
+                    return Ok(vec![]);
+                }
+                return Err(out.stderr);
+            }
+            Ok(parse_sessions(&out.stdout))
+        })
+    }
+
+    fn list_windows(&self, session: &str, detail: Detail) -> Result<Vec<TmuxWindow>, String> {
+        listcache::cached(self.profile(), Some(&detail.cache_key(session)), || {
+            let out = self.exec(&args(&[
+                "list-windows",
+                "-t",
+                session,
+                "-F",
+                WINDOWS_FORMAT,
+            ]))?;
+            if out.code != 0 {
+                if no_server(&out.stderr) {
+                    return Ok(vec![]);
+                }
+                return Err(out.stderr);
+            }
+            let mut windows = parse_windows(&out.stdout);
+            if detail == Detail::Full {
+                self.hydrate_names(session, &mut windows)?;
+            }
+            ensure_window_ids(session, &mut windows);
+            Ok(windows)
+        })
+    }
+
+    // Names placeholder-named windows after their pane's command, with one
+    // more listing of the session.
+    fn hydrate_names(&self, session: &str, windows: &mut [TmuxWindow]) -> Result<(), String> {
+        if !windows
+            .iter()
+            .any(|w| is_placeholder_name(&w.name, w.index))
+        {
+            return Ok(());
+        }
+        let out = self.exec(&args(&[
+            "list-windows",
+            "-t",
+            session,
+            "-F",
+            WINDOW_NAMES_FORMAT,
+        ]))?;
+        if out.code == 0 {
+            apply_window_names(windows, &out.stdout);
+        }
+        Ok(())
+    }
+
+    // The last `lines` lines of `target`; a pane without a tmux server is
+    // empty.
+    fn capture(&self, target: &str, lines: u32) -> Result<String, String> {
+        let out = self.exec(&args(&[
+            "capture-pane",
+            "-p",
+            "-t",
+            target,
+            "-S",
+            &format!("-{lines}"),
+            "-e",
+            "-J",
+        ]))?;
+        if out.code != 0 {
+            if no_server(&out.stderr) {
+                return Ok(String::new());
+            }
+            return Err(out.stderr);
+        }
+        Ok(utf8::mark(out.stdout, out.lossy))
+    }
+
+    fn send_keys(&self, target: &str, keys: &str, with_enter: bool) -> Result<(), String> {
+        for command in build_tmux_send_keys_commands(target, keys, with_enter) {
+            self.run(&command.args)?;
+        }
+        Ok(())
+    }
+
+    fn start_server(&self) -> Result<(), String> {
+        self.run(&args(&["start-server"])).map(drop)
+    }
+
+    fn new_session(&self, session: &str) -> Result<(), String> {
+        self.run(&args(&["new-session", "-d", "-s", session]))
+            .map(drop)
+    }
+
+    fn rename_session(&self, session: &str, new_name: &str) -> Result<(), String> {
+        self.run(&args(&["rename-session", "-t", session, new_name]))
+            .map(drop)
+    }
+
+    fn kill_session(&self, session: &str) -> Result<(), String> {
+        self.run(&args(&["kill-session", "-t", session])).map(drop)
+    }
+
+    // A named window keeps its name: automatic-rename is turned off for it.
+    fn new_window(
+        &self,
+        session: &str,
+        name: Option<&str>,
+        cmd: Option<&str>,
+    ) -> Result<(), String> {
+        let mut new_window = args(&["new-window", "-P", "-F", "#{window_id}", "-t", session]);
+        if let Some(name) = name {
+            new_window.extend(args(&["-n", name]));
+        }
+        new_window.extend(cmd.map(str::to_string));
+        let id = self.run(&new_window)?;
+        let id = id.trim();
+        if name.is_some() && !id.is_empty() {
+            let _ = self.exec(&args(&[
+                "set-window-option",
+                "-t",
+                id,
+                "automatic-rename",
+                "off",
+            ]));
+        }
+        Ok(())
+    }
+
+    fn rename_window(&self, target: &str, new_name: &str) -> Result<(), String> {
+        self.run(&args(&["rename-window", "-t", target, new_name]))?;
+        let _ = self.exec(&args(&[
+            "set-window-option",
+            "-t",
+            target,
+            "automatic-rename",
+            "off",
+        ]));
+        Ok(())
+    }
+
+    fn kill_window(&self, target: &str) -> Result<(), String> {
+        self.run(&args(&["kill-window", "-t", target])).map(drop)
+    }
+}
+
+pub struct LocalBackend;
+
+impl TmuxBackend for LocalBackend {
+    fn profile(&self) -> Option<&HostProfile> {
+        None
+    }
+
+    fn exec(&self, args: &[String]) -> Result<ExecOut, String> {
+        tmux_exec(None, args)
+    }
+}
+
+pub struct SshBackend(pub HostProfile);
+
+impl TmuxBackend for SshBackend {
+    fn profile(&self) -> Option<&HostProfile> {
+        Some(&self.0)
+    }
+
+    fn exec(&self, args: &[String]) -> Result<ExecOut, String> {
+        tmux_exec(Some(&self.0), args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_sessions, parse_windows};
+
+    #[test]
+    fn listings_parse_the_same_for_every_backend() {
+        let sessions = parse_sessions("arc|3|1\nscratch|1|0\n");
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].name, "arc");
+        assert_eq!(sessions[0].windows, 3);
+        assert!(sessions[0].attached && !sessions[1].attached);
+
+        let windows = parse_windows("0|@1|arc|1|2\n1|@4|run 1|0|1\r\n\n");
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].id, "@1");
+        assert!(windows[0].active && !windows[1].active);
+        assert_eq!(windows[0].panes, 2);
+        assert_eq!(windows[1].name, "run 1");
+        assert_eq!(windows[1].panes, 1);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::tmuxcmd::quote;
+use crate::{creds_from, listcache, run_remote_cmd, HostProfile};
+
+// printed after every step with the step's exit code
+const STEP_MARKER: &str = "\n__ARC_STEP__ ";
+
+// Several tmux commands sent to a host as one script, so a flow like
+// new-window, set-window-option, send-keys costs one SSH round trip
+// instead of one each. Every step reports its own exit code; a failing
+// step stops the rest unless it was added as optional.
+#[derive(Debug, Default)]
+pub struct RemoteBatch {
+    steps: Vec<(String, bool)>, // (shell line, stop the batch when it fails)
+    mutates: bool,              // some step changes what the listings show
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepOut {
+    pub code: i32,
+    pub stdout: String,
+}
+
+#[derive(Debug)]
+pub struct BatchOut {
+    pub steps: Vec<StepOut>, // the steps that ran, in order
+    pub stderr: String,      // all steps' stderr together
+}
+
+// An argument that stands for the output of an earlier `tmux_into` step.
+// Real arguments never contain NUL.
+pub fn var(name: &str) -> String {
+    format!("\u{0}{name}")
+}
+
+fn tmux_line<S: AsRef<str>>(args: &[S]) -> String {
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| match arg.as_ref().strip_prefix('\u{0}') {
+            Some(name) => format!("\"${name}\""),
+            None => quote(arg.as_ref()),
+        })
+        .collect();
+    format!("tmux {}", args.join(" "))
+}
+
+impl RemoteBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push<S: AsRef<str>>(&mut self, line: String, args: &[S], required: bool) -> &mut Self {
+        let args: Vec<String> = args.iter().map(|a| a.as_ref().to_string()).collect();
+        self.mutates |= listcache::invalidates(&args);
+        self.steps.push((line, required));
+        self
+    }
+
+    pub fn tmux<S: AsRef<str>>(&mut self, args: &[S]) -> &mut Self {
+        self.push(tmux_line(args), args, true)
+    }
+
+    // A step whose failure does not stop the ones after it.
+    pub fn tmux_optional<S: AsRef<str>>(&mut self, args: &[S]) -> &mut Self {
+        self.push(tmux_line(args), args, false)
+    }
+
+    // Keeps the step's output, less trailing newlines, in shell variable
+    // `name` for later steps to use through var(name); it is still reported.
+    pub fn tmux_into<S: AsRef<str>>(&mut self, name: &str, args: &[S]) -> &mut Self {
+        let line = format!("{name}=$({}) && printf %s \"${name}\"", tmux_line(args));
+        self.push(line, args, true)
+    }
+
+    pub fn script(&self) -> String {
+        self.steps
+            .iter()
+            .map(|(line, required)| {
+                let stop = if *required {
+                    "; [ \"$rc\" -eq 0 ] || exit 0"
+                } else {
+                    ""
+                };
+                format!("{line}\nrc=$?; printf '\\n__ARC_STEP__ %s\\n' \"$rc\"{stop}\n")
+            })
+            .collect()
+    }
+
+    pub fn run(&self, profile: &HostProfile) -> Result<BatchOut, String> {
+        let out = run_remote_cmd(&creds_from(profile), self.script())?;
+        if self.mutates {
+            listcache::invalidate(Some(profile));
+        }
+        Ok(BatchOut {
+            steps: split_steps(&out.stdout),
+            stderr: out.stderr,
+        })
+    }
+}
+
+impl BatchOut {
+    // The step outputs, or the batch's stderr when any step failed.
+    pub fn check(self) -> Result<Vec<StepOut>, String> {
+        if self.steps.iter().any(|s| s.code != 0) {
+            return Err(self.stderr);
+        }
+        Ok(self.steps)
+    }
+}
+
+fn split_steps(stdout: &str) -> Vec<StepOut> {
+    let mut steps = Vec::new();
+    let mut rest = stdout;
+    while let Some(at) = rest.find(STEP_MARKER) {
+        let after = &rest[at + STEP_MARKER.len()..];
+        let (code, next) = after.split_once('\n').unwrap_or((after, ""));
+        steps.push(StepOut {
+            code: code.trim().parse().unwrap_or(1),
+            stdout: rest[..at].to_string(),
+        });
+        rest = next;
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_steps, var, RemoteBatch, StepOut};
+
+    #[test]
+    fn batches_run_as_one_script_with_step_markers() {
+        let mut batch = RemoteBatch::new();
+        batch
+            .tmux_into(
+                "id",
+                &["new-window", "-P", "-F", "#{window_id}", "-n", "run 1"],
+            )
+            .tmux_optional(&[
+                "set-window-option",
+                "-t",
+                &var("id"),
+                "automatic-rename",
+                "off",
+            ])
+            .tmux(&["send-keys", "-t", &var("id"), "-l", "arc input.yml"]);
+        let script = batch.script();
+        assert!(script.starts_with(
+            "id=$(tmux new-window -P -F '#{window_id}' -n 'run 1') && printf %s \"$id\"\n"
+        ));
+        assert!(script.contains("tmux set-window-option -t \"$id\" automatic-rename off\nrc=$?; printf '\\n__ARC_STEP__ %s\\n' \"$rc\"\n"));
+        assert!(script.contains("-l 'arc input.yml'\nrc=$?; printf '\\n__ARC_STEP__ %s\\n' \"$rc\"; [ \"$rc\" -eq 0 ] || exit 0\n"));
+
+        let steps = split_steps("@7\n__ARC_STEP__ 0\n\n__ARC_STEP__ 1\n");
+        assert_eq!(
+            steps,
+            vec![
+                StepOut {
+                    code: 0,
+                    stdout: "@7".into()
+                },
+                StepOut {
+                    code: 1,
+                    stdout: String::new()
+                },
+            ]
+        );
+    }
+}
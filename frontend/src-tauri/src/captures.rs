@@ -0,0 +1,105 @@
+use crate::{runs, HostProfile};
+use once_cell::sync::Lazy;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+// What an `if_changed` capture returns when the pane looks as it did on the
+// previous `if_changed` capture of the same target. NUL never shows up in
+// captured text.
+pub const NOT_MODIFIED: &str = "\u{0}not-modified\u{0}";
+
+// Prefix of a capture whose window id no longer existed, so the window at
+// the same index was captured instead; what the capture returned follows.
+pub const STALE_ID_PREFIX: &str = "\u{0}stale-id\u{0}";
+
+pub fn mark_stale(text: String, stale_id: bool) -> String {
+    if stale_id {
+        format!("{STALE_ID_PREFIX}{text}")
+    } else {
+        text
+    }
+}
+
+// fingerprint of the last `if_changed` capture per (host label or "local",
+// tmux target)
+static LAST: Lazy<Mutex<HashMap<(String, String), String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn key(profile: Option<&HostProfile>, target: &str) -> (String, String) {
+    let host = profile
+        .map(runs::host_label)
+        .unwrap_or_else(|| "local".into());
+    (host, target.to_string())
+}
+
+// Stores the fingerprint and tells whether it differs from the last one.
+fn changed(profile: Option<&HostProfile>, target: &str, fingerprint: String) -> bool {
+    let mut last = LAST.lock().unwrap();
+    last.insert(key(profile, target), fingerprint.clone()) != Some(fingerprint)
+}
+
+pub fn local_if_changed(target: &str, text: String) -> String {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    if changed(None, target, format!("{:x}", hasher.finish())) {
+        text
+    } else {
+        NOT_MODIFIED.to_string()
+    }
+}
+
+// Wraps a remote capture so the host sums it first and only sends the text
+// when the sum differs from the one it was last seen with. `echo x` keeps
+// the trailing newlines $(...) would strip.
+pub fn remote_command(profile: &HostProfile, target: &str, capture: &str) -> String {
+    let known = LAST
+        .lock()
+        .unwrap()
+        .get(&key(Some(profile), target))
+        .cloned()
+        .unwrap_or_default();
+    format!(
+        "out=$({capture} && echo x) || exit $?; out=${{out%x}}; \
+         sum=$(printf %s \"$out\" | cksum); echo \"$sum\"; \
+         [ \"$sum\" = {} ] || printf %s \"$out\"",
+        shell_escape::escape(known.into())
+    )
+}
+
+// The first line of remote_command's output is the sum, the rest the text.
+fn split_sum(stdout: &str) -> (&str, &str) {
+    stdout.split_once('\n').unwrap_or((stdout, ""))
+}
+
+pub fn remote_if_changed(profile: &HostProfile, target: &str, stdout: &str) -> String {
+    let (sum, text) = split_sum(stdout);
+    if changed(Some(profile), target, sum.trim().to_string()) {
+        text.to_string()
+    } else {
+        NOT_MODIFIED.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{local_if_changed, split_sum, NOT_MODIFIED};
+
+    #[test]
+    fn unchanged_captures_come_back_as_the_marker() {
+        let target = "captures-test:0";
+        assert_eq!(local_if_changed(target, "$ ls\n".into()), "$ ls\n");
+        assert_eq!(local_if_changed(target, "$ ls\n".into()), NOT_MODIFIED);
+        assert_eq!(
+            local_if_changed(target, "$ ls\na.yml\n".into()),
+            "$ ls\na.yml\n"
+        );
+
+        assert_eq!(
+            split_sum("3015617425 6\n$ ls\n\n"),
+            ("3015617425 6", "$ ls\n\n")
+        );
+        assert_eq!(split_sum("3015617425 6\n"), ("3015617425 6", ""));
+    }
+}
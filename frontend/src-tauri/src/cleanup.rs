@@ -0,0 +1,290 @@
+use crate::diskusage;
+use crate::model::RunStatus;
+use crate::{creds_from, progress, run_remote_cmd, runs, HostProfile};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scope {
+    Scratch,   // ESS scratch directories left behind by jobs
+    Converged, // calcs of species ARC reported as converged
+    All,       // the whole work_dir
+}
+
+impl Scope {
+    fn parse(scope: &str) -> Result<Self, String> {
+        match scope {
+            "scratch" => Ok(Scope::Scratch),
+            "converged" => Ok(Scope::Converged),
+            "all" => Ok(Scope::All),
+            other => Err(format!("unknown cleanup scope: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupReport {
+    pub removed: Vec<PathBuf>,
+    pub bytes_freed: u64,
+}
+
+const SCRATCH_DIRS: &[&str] = &["scratch", "scr", "tmp"];
+
+// `root` and `target` with symlinks resolved, on the profile's host for a
+// remote run.
+fn resolve(
+    profile: Option<&HostProfile>,
+    root: &Path,
+    target: &Path,
+) -> Result<(PathBuf, PathBuf), String> {
+    let Some(profile) = profile else {
+        let root = root
+            .canonicalize()
+            .map_err(|e| format!("default_work_dir {}: {e}", root.display()))?;
+        let target = target
+            .canonicalize()
+            .map_err(|e| format!("{}: {e}", target.display()))?;
+        return Ok((root, target));
+    };
+    let [r, t] = [root, target].map(|p| shell_escape::escape(p.to_string_lossy()).into_owned());
+    let out = run_remote_cmd(
+        &creds_from(profile),
+        format!("cd {r} && pwd -P && cd {t} && pwd -P"),
+    )?;
+    let mut lines = out.stdout.lines();
+    match (out.code, lines.next(), lines.next()) {
+        (0, Some(root), Some(target)) => Ok((root.into(), target.into())),
+        _ => Err(format!("{}: {}", target.display(), out.stderr.trim())),
+    }
+}
+
+// Nothing is deleted unless it resolves strictly below the configured
+// default_work_dir, so a bad work_dir or a symlink can't reach elsewhere.
+pub fn ensure_inside(
+    profile: Option<&HostProfile>,
+    root: &Path,
+    target: &Path,
+) -> Result<PathBuf, String> {
+    let (root, target) = resolve(profile, root, target)?;
+    if target == root || !target.starts_with(&root) {
+        return Err(format!(
+            "refusing to delete {}: not under {}",
+            target.display(),
+            root.display()
+        ));
+    }
+    Ok(target)
+}
+
+// The default_work_dir the run was launched under, on its own host.
+pub fn work_root(id: &str) -> Result<PathBuf, String> {
+    let config = runs::launch_config(id)
+        .ok_or_else(|| "launch config for this run is unknown".to_string())?;
+    Ok(PathBuf::from(&config.default_work_dir))
+}
+
+pub fn remove_dir(profile: Option<&HostProfile>, dir: &Path) -> Result<(), String> {
+    let Some(profile) = profile else {
+        return std::fs::remove_dir_all(dir).map_err(|e| format!("remove {}: {e}", dir.display()));
+    };
+    let rm = format!("rm -rf {}", shell_escape::escape(dir.to_string_lossy()));
+    let out = run_remote_cmd(&creds_from(profile), rm)?;
+    if out.code != 0 {
+        return Err(format!("remove {}: {}", dir.display(), out.stderr.trim()));
+    }
+    Ok(())
+}
+
+// Lines of a remote command's output, one path each.
+fn remote_paths(profile: &HostProfile, command: String) -> Result<Vec<PathBuf>, String> {
+    let out = run_remote_cmd(&creds_from(profile), command)?;
+    if out.code != 0 {
+        return Err(out.stderr.trim().to_string());
+    }
+    Ok(out.stdout.lines().map(PathBuf::from).collect())
+}
+
+fn find_scratch_dirs(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let path = entry.path();
+        let name = entry.file_name();
+        if SCRATCH_DIRS.contains(&name.to_string_lossy().as_ref()) {
+            found.push(path);
+        } else {
+            find_scratch_dirs(&path, found);
+        }
+    }
+}
+
+fn remote_scratch_dirs(profile: &HostProfile, work_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let names: Vec<String> = SCRATCH_DIRS.iter().map(|n| format!("-name {n}")).collect();
+    let dir = shell_escape::escape(work_dir.to_string_lossy());
+    remote_paths(
+        profile,
+        format!(
+            "find {dir} -mindepth 1 -type d \\( {} \\) -prune -print",
+            names.join(" -o ")
+        ),
+    )
+}
+
+// ARC keeps per-species job folders at <project>/calcs/Species/<label>.
+fn species_dirs(project: &Path, species: &[String]) -> Vec<PathBuf> {
+    species
+        .iter()
+        .map(|label| project.join("calcs").join("Species").join(label))
+        .collect()
+}
+
+fn converged_calc_dirs(work_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let log = runs::find_project_file(work_dir, "arc.log")
+        .ok_or_else(|| format!("no arc.log under {}", work_dir.display()))?;
+    let species = progress::converged_species(&log).map_err(|e| format!("read arc.log: {e}"))?;
+    let project = log.parent().unwrap_or(work_dir);
+    Ok(species_dirs(project, &species)
+        .into_iter()
+        .filter(|p| p.is_dir())
+        .collect())
+}
+
+fn remote_converged_dirs(profile: &HostProfile, work_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let log = runs::locate_project_file(Some(profile), work_dir, "arc.log")
+        .ok_or_else(|| format!("no arc.log under {}", work_dir.display()))?;
+    let cat = format!("cat {}", shell_escape::escape(log.to_string_lossy()));
+    let out = run_remote_cmd(&creds_from(profile), cat)?;
+    if out.code != 0 {
+        return Err(format!("read arc.log: {}", out.stderr.trim()));
+    }
+    let species = progress::converged_in(&out.stdout);
+    let dirs: Vec<String> = species_dirs(log.parent().unwrap_or(work_dir), &species)
+        .iter()
+        .map(|d| shell_escape::escape(d.to_string_lossy()).into_owned())
+        .collect();
+    if dirs.is_empty() {
+        return Ok(Vec::new());
+    }
+    remote_paths(
+        profile,
+        format!(
+            "for d in {}; do test -d \"$d\" && printf '%s\\n' \"$d\"; done; true",
+            dirs.join(" ")
+        ),
+    )
+}
+
+fn targets(
+    scope: Scope,
+    profile: Option<&HostProfile>,
+    work_dir: &Path,
+) -> Result<Vec<PathBuf>, String> {
+    match (scope, profile) {
+        (Scope::Scratch, Some(profile)) => remote_scratch_dirs(profile, work_dir),
+        (Scope::Scratch, None) => {
+            let mut found = Vec::new();
+            find_scratch_dirs(work_dir, &mut found);
+            Ok(found)
+        }
+        (Scope::Converged, Some(profile)) => remote_converged_dirs(profile, work_dir),
+        (Scope::Converged, None) => converged_calc_dirs(work_dir),
+        (Scope::All, _) => Ok(vec![work_dir.to_path_buf()]),
+    }
+}
+
+// An Unknown run may still be going on a host that could not be reached, and
+// an Orphaned one lost its window but may have left ARC running, so the
+// latter is only cleaned up when asked to with `force`.
+pub fn check_cleanable(status: &RunStatus, force: bool) -> Result<(), String> {
+    match status {
+        RunStatus::Queued | RunStatus::Unknown => {
+            Err("cannot clean up a run that is queued or whose state is unknown".into())
+        }
+        RunStatus::Orphaned if !force => {
+            Err("run is orphaned and may still be running; pass force to clean it up".into())
+        }
+        status if runs::is_active(status) => Err("cannot clean up a run that is active".into()),
+        _ => Ok(()),
+    }
+}
+
+// Remote runs are cleaned up on their host; their work_dir is never looked
+// up on this machine.
+pub fn cleanup_run(id: String, scope: String, force: bool) -> Result<CleanupReport, String> {
+    let scope = Scope::parse(&scope)?;
+    let run = runs::get_run(id.clone())?;
+    check_cleanable(&run.status, force)?;
+    let profile = runs::remote_profile(&run)?;
+    let profile = profile.as_ref();
+    let root = work_root(&id)?;
+    ensure_inside(profile, &root, &run.work_dir)?;
+
+    let mut report = CleanupReport::default();
+    for target in targets(scope, profile, &run.work_dir)? {
+        let target = ensure_inside(profile, &root, &target)?;
+        let size = diskusage::measure(profile, &target).unwrap_or(0);
+        remove_dir(profile, &target)?;
+        report.bytes_freed += size;
+        report.removed.push(target);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_cleanable, cleanup_run, ensure_inside, find_scratch_dirs};
+    use crate::model::{ARCRun, RunStatus};
+    use crate::runs::RunRegistry;
+
+    #[test]
+    fn cleanup_stays_under_default_work_dir() {
+        let base = std::env::temp_dir().join(format!("cleanup-{}", uuid::Uuid::new_v4()));
+        let root = base.join("runs");
+        let work = root.join("run1");
+        std::fs::create_dir_all(work.join("calcs/Species/CH4/opt_a1/scratch")).unwrap();
+        std::fs::create_dir_all(base.join("elsewhere")).unwrap();
+
+        assert!(ensure_inside(None, &root, &work).is_ok());
+        assert!(ensure_inside(None, &root, &root).is_err());
+        assert!(ensure_inside(None, &root, &base.join("elsewhere")).is_err());
+        assert!(ensure_inside(None, &root, &work.join("../../elsewhere")).is_err());
+
+        let mut found = Vec::new();
+        find_scratch_dirs(&work, &mut found);
+        assert_eq!(found, vec![work.join("calcs/Species/CH4/opt_a1/scratch")]);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn unknown_runs_are_refused_and_orphaned_ones_need_force() {
+        assert!(check_cleanable(&RunStatus::Finished, false).is_ok());
+        assert!(check_cleanable(&RunStatus::Queued, true).is_err());
+        assert!(check_cleanable(&RunStatus::Running, true).is_err());
+        assert!(check_cleanable(&RunStatus::Unknown, true).is_err());
+        assert!(check_cleanable(&RunStatus::Orphaned, false).is_err());
+        assert!(check_cleanable(&RunStatus::Orphaned, true).is_ok());
+    }
+
+    #[test]
+    fn a_remote_run_is_never_removed_locally() {
+        let work = std::env::temp_dir().join(format!("cleanup-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(work.join("scratch")).unwrap();
+        let run = ARCRun {
+            work_dir: work.clone(),
+            host: Some("u@hpc:22".into()),
+            ..ARCRun::for_test(&uuid::Uuid::new_v4().to_string(), RunStatus::Finished)
+        };
+        RunRegistry::global().insert(run.clone());
+
+        // no saved login for the host, so nothing can be checked or removed
+        for scope in ["scratch", "all"] {
+            assert!(cleanup_run(run.id.clone(), scope.into(), false).is_err());
+        }
+        assert!(work.join("scratch").is_dir());
+        let _ = std::fs::remove_dir_all(&work);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{runs, HostProfile};
+use once_cell::sync::Lazy;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+type Outcome = Result<Arc<dyn Any + Send + Sync>, String>;
+
+// a request in flight; callers asking for the same thing wait on it
+#[derive(Default)]
+struct Flight {
+    outcome: Mutex<Option<Outcome>>,
+    done: Condvar,
+}
+
+// (host label or "local", request key)
+type Slot = (String, String);
+
+static IN_FLIGHT: Lazy<Mutex<HashMap<Slot, Arc<Flight>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+// per host: tokens left and when they were last topped up
+static BUCKETS: Lazy<Mutex<HashMap<String, (f64, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+// commands per second each host may start, in bursts of up to as many
+static RATE: AtomicU32 = AtomicU32::new(8);
+
+fn host(profile: Option<&HostProfile>) -> String {
+    profile
+        .map(runs::host_label)
+        .unwrap_or_else(|| "local".into())
+}
+
+// 0 turns the limiter off.
+pub fn set_rate(per_sec: u32) {
+    RATE.store(per_sec, Ordering::Relaxed);
+    BUCKETS.lock().unwrap().clear();
+}
+
+// How long to wait before the host has a token, taking it if there is one.
+fn take_token(host: &str, rate: f64, now: Instant) -> Duration {
+    let mut buckets = BUCKETS.lock().unwrap();
+    let (tokens, at) = buckets.entry(host.to_string()).or_insert((rate, now));
+    *tokens = (*tokens + now.saturating_duration_since(*at).as_secs_f64() * rate).min(rate);
+    *at = now;
+    if *tokens >= 1.0 {
+        *tokens -= 1.0;
+        Duration::ZERO
+    } else {
+        Duration::from_secs_f64((1.0 - *tokens) / rate)
+    }
+}
+
+fn throttle(host: &str) {
+    loop {
+        let rate = RATE.load(Ordering::Relaxed);
+        if rate == 0 {
+            return;
+        }
+        let wait = take_token(host, rate as f64, Instant::now());
+        if wait.is_zero() {
+            return;
+        }
+        thread::sleep(wait);
+    }
+}
+
+// Runs `work` for (profile, key) unless the same request is already in
+// flight, in which case this waits for that one and returns its result.
+// Work that does run first waits its turn under the host's rate limit.
+pub fn shared<T: Clone + Send + Sync + 'static>(
+    profile: Option<&HostProfile>,
+    key: String,
+    work: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let host = host(profile);
+    let slot = (host.clone(), key);
+    let (flight, leader) = {
+        let mut in_flight = IN_FLIGHT.lock().unwrap();
+        match in_flight.get(&slot) {
+            Some(flight) => (flight.clone(), false),
+            None => {
+                let flight = Arc::new(Flight::default());
+                in_flight.insert(slot.clone(), flight.clone());
+                (flight, true)
+            }
+        }
+    };
+
+    if !leader {
+        let mut outcome = flight.outcome.lock().unwrap();
+        while outcome.is_none() {
+            outcome = flight.done.wait(outcome).unwrap();
+        }
+        return match outcome.as_ref().unwrap() {
+            Ok(value) => value
+                .downcast_ref::<T>()
+                .cloned()
+                .ok_or_else(|| "coalesced request returned another type".to_string()),
+            Err(e) => Err(e.clone()),
+        };
+    }
+
+    let mut landing = Landing {
+        flight,
+        slot,
+        outcome: None,
+    };
+    throttle(&host);
+    let result = work();
+    landing.outcome = Some(
+        result
+            .clone()
+            .map(|v| Arc::new(v) as Arc<dyn Any + Send + Sync>),
+    );
+    result
+}
+
+// Hands the leader's outcome to the waiting callers and frees the slot when
+// the leader is done, and also when its work panicked, so nobody waits on a
+// flight that never lands.
+struct Landing {
+    flight: Arc<Flight>,
+    slot: Slot,
+    outcome: Option<Outcome>,
+}
+
+impl Drop for Landing {
+    fn drop(&mut self) {
+        let outcome = self
+            .outcome
+            .take()
+            .unwrap_or_else(|| Err("coalesced request panicked".to_string()));
+        *self
+            .flight
+            .outcome
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(outcome);
+        IN_FLIGHT
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.slot);
+        self.flight.done.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shared, take_token};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn duplicate_requests_share_one_run() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let runs = runs.clone();
+                thread::spawn(move || {
+                    shared(None, "coalesce-test".into(), || {
+                        runs.fetch_add(1, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(200));
+                        Ok("@1".to_string())
+                    })
+                })
+            })
+            .collect();
+        for h in handles {
+            assert_eq!(h.join().unwrap(), Ok("@1".to_string()));
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        let now = Instant::now();
+        assert!(take_token("bucket-test", 2.0, now).is_zero());
+        assert!(take_token("bucket-test", 2.0, now).is_zero());
+        assert_eq!(
+            take_token("bucket-test", 2.0, now),
+            Duration::from_millis(500)
+        );
+        let later = now + Duration::from_millis(500);
+        assert!(take_token("bucket-test", 2.0, later).is_zero());
+    }
+
+    #[test]
+    fn a_panicking_leader_does_not_strand_its_followers() {
+        let leader = thread::spawn(|| {
+            shared::<String>(None, "coalesce-panic-test".into(), || {
+                thread::sleep(Duration::from_millis(200));
+                panic!("work blew up")
+            })
+        });
+        thread::sleep(Duration::from_millis(50));
+        let follower = shared(None, "coalesce-panic-test".into(), || Ok("ran".to_string()));
+        assert_eq!(follower, Err("coalesced request panicked".to_string()));
+        assert!(leader.join().is_err());
+        // the slot is free again for later requests
+        let later = shared(None, "coalesce-panic-test".into(), || Ok("ran".to_string()));
+        assert_eq!(later, Ok("ran".to_string()));
+    }
+}
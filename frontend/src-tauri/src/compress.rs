@@ -0,0 +1,91 @@
+use crate::utf8;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+// Captures above this many bytes are gzipped when the caller asks for
+// compression without naming a threshold.
+pub const DEFAULT_THRESHOLD: usize = 64 * 1024;
+
+// Prefix of a packed capture; the rest is base64 of the gzipped text. Like
+// captures::NOT_MODIFIED it starts with NUL, which captured text never has.
+pub const GZIP_PREFIX: &str = "\u{0}gzip\u{0}";
+
+// Gzips `text` for the IPC hop when it is larger than `threshold`; smaller
+// text, and text that would not shrink, goes as it is.
+pub fn pack(text: String, threshold: Option<usize>) -> String {
+    let Some(threshold) = threshold else {
+        return text;
+    };
+    if text.len() <= threshold {
+        return text;
+    }
+    let mut gz = GzEncoder::new(Vec::new(), Compression::fast());
+    if gz.write_all(text.as_bytes()).is_err() {
+        return text;
+    }
+    match gz.finish() {
+        Ok(bytes) if bytes.len() * 4 / 3 + GZIP_PREFIX.len() < text.len() => {
+            format!("{GZIP_PREFIX}{}", STANDARD.encode(bytes))
+        }
+        _ => text,
+    }
+}
+
+fn gunzip(encoded: &str) -> Result<String, String> {
+    let compact: String = encoded.split_whitespace().collect();
+    let bytes = STANDARD
+        .decode(compact)
+        .map_err(|e| format!("compressed capture: {e}"))?;
+    let mut text = Vec::new();
+    GzDecoder::new(&bytes[..])
+        .read_to_end(&mut text)
+        .map_err(|e| format!("compressed capture: {e}"))?;
+    let (text, lossy) = utf8::decode(&text);
+    Ok(utf8::mark(text, lossy))
+}
+
+// Wraps a remote capture so the host gzips it before it crosses SSH when it
+// is larger than `threshold` and gzip is there. The first line says which:
+// "gzip" or "raw". `echo x` keeps the trailing newlines $(...) would strip.
+pub fn remote_command(capture: &str, threshold: usize) -> String {
+    format!(
+        "(out=$({capture} && echo x) || exit $?; out=${{out%x}}; \
+         if [ ${{#out}} -gt {threshold} ] && command -v gzip >/dev/null 2>&1 \
+         && command -v base64 >/dev/null 2>&1; then \
+         echo gzip; printf %s \"$out\" | gzip -c | base64; \
+         else echo raw; printf %s \"$out\"; fi)"
+    )
+}
+
+// Turns remote_command's output back into the captured text.
+pub fn remote_output(stdout: &str) -> Result<String, String> {
+    match stdout.split_once('\n') {
+        Some(("gzip", rest)) => gunzip(rest),
+        Some(("raw", rest)) => Ok(rest.to_string()),
+        _ => Ok(stdout.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gunzip, pack, remote_output, GZIP_PREFIX};
+
+    #[test]
+    fn large_captures_are_packed_and_come_back_intact() {
+        let text = "Step 12: E = -1234.5678 Hartree\n".repeat(200);
+        assert_eq!(pack(text.clone(), None), text);
+        assert_eq!(pack(text.clone(), Some(text.len())), text);
+
+        let packed = pack(text.clone(), Some(1024));
+        let encoded = packed.strip_prefix(GZIP_PREFIX).unwrap();
+        assert!(packed.len() < text.len() / 4);
+        assert_eq!(gunzip(encoded).unwrap(), text);
+
+        assert_eq!(remote_output("raw\n$ ls\n").unwrap(), "$ ls\n");
+        let wrapped = format!("gzip\n{}\n", encoded);
+        assert_eq!(remote_output(&wrapped).unwrap(), text);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{creds_from, run_remote_cmd, HostProfile};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::process::Command as PCommand;
+
+// Modules ARC imports at start-up; an env missing any of them can't run it.
+const ARC_MODULES: &[&str] = &["rmgpy", "arkane", "rdkit", "openbabel", "yaml", "paramiko"];
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CondaEnv {
+    pub name: String,
+    pub path: String,
+    pub active: bool,         // marked with `*` by conda
+    pub missing: Vec<String>, // ARC dependencies the env's python can't import
+    pub arc_ready: bool,
+}
+
+// `conda env list` prints `name [*] path` rows under `#` comments; envs
+// created with --prefix have no name and show only their path.
+fn parse_env_list(stdout: &str) -> Vec<CondaEnv> {
+    stdout
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            let (name, active, path) = match cols.as_slice() {
+                [name, "*", path] => (name.to_string(), true, *path),
+                ["*", path] => (String::new(), true, *path),
+                [name, path] => (name.to_string(), false, *path),
+                [path] => (String::new(), false, *path),
+                _ => return None,
+            };
+            let name = if name.is_empty() {
+                path.rsplit('/').next().unwrap_or(path).to_string()
+            } else {
+                name
+            };
+            Some(CondaEnv {
+                name,
+                path: path.to_string(),
+                active,
+                missing: Vec::new(),
+                arc_ready: false,
+            })
+        })
+        .collect()
+}
+
+// Prints the modules that fail to import, one per line.
+pub fn import_script() -> String {
+    format!(
+        "import importlib\nfor m in {:?}:\n    try:\n        importlib.import_module(m)\n    except Exception:\n        print(m)\n",
+        ARC_MODULES
+    )
+}
+
+fn import_check(env_path: &str) -> String {
+    format!(
+        "{}/bin/python -c {}",
+        shell_escape::escape(Cow::from(env_path)),
+        shell_escape::escape(Cow::from(import_script()))
+    )
+}
+
+pub fn local_sh(command: &str) -> Result<(i32, String), String> {
+    let out = PCommand::new("bash")
+        .args(["-lc", command])
+        .output()
+        .map_err(|e| format!("run bash: {e}"))?;
+    Ok((
+        out.status.code().unwrap_or(-1),
+        String::from_utf8_lossy(&out.stdout).into_owned(),
+    ))
+}
+
+pub fn remote_sh(profile: &HostProfile, command: &str) -> Result<(i32, String), String> {
+    let out = run_remote_cmd(&creds_from(profile), command.to_string())?;
+    Ok((out.code, out.stdout))
+}
+
+fn list_envs(sh: impl Fn(&str) -> Result<(i32, String), String>) -> Result<Vec<CondaEnv>, String> {
+    let (code, stdout) = sh("conda env list")?;
+    if code != 0 {
+        return Err("conda env list failed; is conda on the PATH?".into());
+    }
+    let mut envs = parse_env_list(&stdout);
+    for env in envs.iter_mut() {
+        env.missing = match sh(&import_check(&env.path)) {
+            Ok((0, stdout)) => stdout.lines().map(str::to_string).collect(),
+            // no usable python at all
+            _ => ARC_MODULES.iter().map(|m| m.to_string()).collect(),
+        };
+        env.arc_ready = env.missing.is_empty();
+    }
+    Ok(envs)
+}
+
+pub fn list_local_envs() -> Result<Vec<CondaEnv>, String> {
+    list_envs(local_sh)
+}
+
+pub fn list_remote_envs(profile: &HostProfile) -> Result<Vec<CondaEnv>, String> {
+    list_envs(|command| remote_sh(profile, command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_env_list;
+
+    #[test]
+    fn env_list_rows_parse_with_and_without_names() {
+        let envs = parse_env_list(
+            "# conda environments:\n#\nbase                     /opt/conda\narc_env               *  /opt/conda/envs/arc_env\n                         /scratch/u/envs/rmg\n",
+        );
+        let rows: Vec<(&str, &str, bool)> = envs
+            .iter()
+            .map(|e| (e.name.as_str(), e.path.as_str(), e.active))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("base", "/opt/conda", false),
+                ("arc_env", "/opt/conda/envs/arc_env", true),
+                ("rmg", "/scratch/u/envs/rmg", false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use crate::model::AppConfig;
+use crate::profilecheck::{check, Check, CheckState};
+use crate::pyenvs;
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command as PCommand;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReport {
+    pub ok: bool, // no check failed
+    pub checks: Vec<Check>,
+}
+
+fn python_check(config: &AppConfig) -> Check {
+    let out = match config.conda_env.as_deref() {
+        Some(env) => PCommand::new("bash")
+            .args([
+                "-lc",
+                &format!(
+                    "conda run -n {} python --version",
+                    shell_escape::escape(env.into())
+                ),
+            ])
+            .output(),
+        None => PCommand::new(&config.python_path).arg("--version").output(),
+    };
+    match out {
+        Ok(out) if out.status.success() => {
+            // python 2 prints its version on stderr
+            let said = format!(
+                "{}{}",
+                String::from_utf8_lossy(&out.stdout),
+                String::from_utf8_lossy(&out.stderr)
+            );
+            check(
+                "python",
+                CheckState::Ok,
+                said.lines().next().unwrap_or("").trim(),
+            )
+        }
+        Ok(out) => check(
+            "python",
+            CheckState::Fail,
+            String::from_utf8_lossy(&out.stderr).trim(),
+        ),
+        Err(e) => check(
+            "python",
+            CheckState::Fail,
+            format!("{}: {e}", config.python_path),
+        ),
+    }
+}
+
+// ARC.py sits at the top of the checkout, next to the `arc` package.
+fn arc_check(arc_path: &Path) -> Check {
+    if !arc_path.is_file() {
+        return check(
+            "arc path",
+            CheckState::Fail,
+            format!("{} not found", arc_path.display()),
+        );
+    }
+    let package = arc_path.with_file_name("arc").join("__init__.py");
+    if package.is_file() {
+        check("arc path", CheckState::Ok, arc_path.display().to_string())
+    } else {
+        check(
+            "arc path",
+            CheckState::Warn,
+            format!(
+                "{} has no arc package next to it; is it an ARC checkout?",
+                arc_path.display()
+            ),
+        )
+    }
+}
+
+// The work dir may not exist yet; its nearest existing parent has to take
+// a file then.
+fn work_dir_check(work_dir: &Path) -> Check {
+    let Some(existing) = work_dir.ancestors().find(|p| p.exists()) else {
+        return check(
+            "work dir",
+            CheckState::Fail,
+            format!("{} is not reachable", work_dir.display()),
+        );
+    };
+    if !existing.is_dir() {
+        return check(
+            "work dir",
+            CheckState::Fail,
+            format!("{} is not a directory", existing.display()),
+        );
+    }
+    let probe = existing.join(format!(".arc-write-test-{}", uuid::Uuid::new_v4()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            check("work dir", CheckState::Ok, work_dir.display().to_string())
+        }
+        Err(e) => check(
+            "work dir",
+            CheckState::Fail,
+            format!("{} is not writable: {e}", existing.display()),
+        ),
+    }
+}
+
+// Every run is at least one busy python process, so more runs than cores
+// mostly makes them all slower.
+fn cap_check(cap: u32, cpus: usize) -> Check {
+    match cap {
+        0 => check(
+            "concurrency cap",
+            CheckState::Fail,
+            "must allow at least one run",
+        ),
+        cap if cap as usize > cpus => check(
+            "concurrency cap",
+            CheckState::Warn,
+            format!("{cap} runs on {cpus} CPUs"),
+        ),
+        cap => check("concurrency cap", CheckState::Ok, format!("{cap} runs")),
+    }
+}
+
+pub fn validate(config: &AppConfig) -> ConfigReport {
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut checks = Vec::new();
+    if let Err(e) = pyenvs::check_known(config.python_env.as_deref()) {
+        checks.push(check("python env", CheckState::Fail, e));
+    }
+    let config = &pyenvs::apply(config.clone());
+    checks.extend([
+        python_check(config),
+        arc_check(Path::new(&config.arc_path)),
+        work_dir_check(Path::new(&config.default_work_dir)),
+        cap_check(config.concurrency_cap, cpus),
+    ]);
+    ConfigReport {
+        ok: checks.iter().all(|c| c.state != CheckState::Fail),
+        checks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{arc_check, cap_check, work_dir_check};
+    use crate::profilecheck::CheckState;
+
+    #[test]
+    fn checks_arc_checkout_work_dir_and_cap() {
+        let base = std::env::temp_dir().join(format!("configcheck-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(base.join("ARC/arc")).unwrap();
+        let arc_py = base.join("ARC/ARC.py");
+        std::fs::write(&arc_py, "").unwrap();
+        assert_eq!(arc_check(&arc_py).state, CheckState::Warn);
+        std::fs::write(base.join("ARC/arc/__init__.py"), "").unwrap();
+        assert_eq!(arc_check(&arc_py).state, CheckState::Ok);
+        assert_eq!(arc_check(&base.join("nope.py")).state, CheckState::Fail);
+
+        assert_eq!(
+            work_dir_check(&base.join("runs/not/yet")).state,
+            CheckState::Ok
+        );
+        assert_eq!(work_dir_check(&arc_py.join("runs")).state, CheckState::Fail);
+
+        assert_eq!(cap_check(0, 8).state, CheckState::Fail);
+        assert_eq!(cap_check(16, 8).state, CheckState::Warn);
+        assert_eq!(cap_check(4, 8).state, CheckState::Ok);
+        std::fs::remove_dir_all(base).unwrap();
+    }
+}
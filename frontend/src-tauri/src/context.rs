@@ -0,0 +1,145 @@
+use crate::{profiles, runs};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const STORE_FILE: &str = "context.json";
+const EVENT: &str = "context-changed";
+// key in `viewed` for the local tmux server
+const LOCAL: &str = "local";
+
+static STORE: OnceCell<PathBuf> = OnceCell::new();
+static CONTEXT: Lazy<Mutex<AppContext>> = Lazy::new(|| Mutex::new(AppContext::default()));
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ViewedSession {
+    pub session: String,
+    pub window: Option<String>, // tmux window id
+}
+
+// What the UI was looking at, so a restart or a second window opens on the
+// same host and session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AppContext {
+    pub current_profile: Option<String>, // profile id; None means local
+    #[serde(default)]
+    pub viewed: HashMap<String, ViewedSession>, // by profile id, or "local"
+}
+
+fn load(path: &Path) -> Result<AppContext, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => {
+            serde_json::from_str(&text).map_err(|e| format!("parse {}: {e}", path.display()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(AppContext::default()),
+        Err(e) => Err(format!("read {}: {e}", path.display())),
+    }
+}
+
+fn save(context: &AppContext) -> Result<(), String> {
+    let Some(path) = STORE.get() else {
+        return Ok(());
+    };
+    let text = serde_json::to_string_pretty(context).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, text).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("replace {}: {e}", path.display()))
+}
+
+// Saves and tells every window, so they stay on the same context.
+fn change(f: impl FnOnce(&mut AppContext)) -> Result<AppContext, String> {
+    let mut context = CONTEXT.lock().unwrap();
+    let before = context.clone();
+    f(&mut context);
+    if *context != before {
+        save(&context)?;
+        runs::emit(EVENT, context.clone());
+    }
+    Ok(context.clone())
+}
+
+pub fn init(app: &AppHandle) {
+    let path = match app.path().app_data_dir() {
+        Ok(dir) => dir.join(STORE_FILE),
+        Err(e) => {
+            tracing::warn!("no app data dir, context will not be kept: {e}");
+            return;
+        }
+    };
+    match load(&path) {
+        Ok(context) => *CONTEXT.lock().unwrap() = context,
+        Err(e) => tracing::warn!("loading context failed: {e}"),
+    }
+    let _ = STORE.set(path);
+}
+
+pub fn get() -> AppContext {
+    CONTEXT.lock().unwrap().clone()
+}
+
+pub fn set_profile(id: Option<String>) -> Result<AppContext, String> {
+    if let Some(id) = &id {
+        if !profiles::list().iter().any(|p| &p.id == id) {
+            return Err(format!("unknown profile: {id}"));
+        }
+    }
+    change(|context| context.current_profile = id)
+}
+
+pub fn set_viewed(
+    profile: Option<String>,
+    session: String,
+    window: Option<String>,
+) -> Result<AppContext, String> {
+    let key = profile.unwrap_or_else(|| LOCAL.into());
+    change(|context| {
+        context
+            .viewed
+            .insert(key, ViewedSession { session, window });
+    })
+}
+
+// Called when a profile is deleted.
+pub fn forget_profile(id: &str) {
+    let forgotten = change(|context| {
+        if context.current_profile.as_deref() == Some(id) {
+            context.current_profile = None;
+        }
+        context.viewed.remove(id);
+    });
+    if let Err(e) = forgotten {
+        tracing::warn!("saving context failed: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load, AppContext, ViewedSession};
+
+    #[test]
+    fn context_loads_defaults_and_saved_views() {
+        let dir = std::env::temp_dir().join(format!("context-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("context.json");
+        assert_eq!(load(&path).unwrap(), AppContext::default());
+
+        std::fs::write(
+            &path,
+            r#"{"current_profile": "p1", "viewed": {"p1": {"session": "arc", "window": "@3"}}}"#,
+        )
+        .unwrap();
+        let context = load(&path).unwrap();
+        assert_eq!(context.current_profile.as_deref(), Some("p1"));
+        assert_eq!(
+            context.viewed["p1"],
+            ViewedSession {
+                session: "arc".into(),
+                window: Some("@3".into()),
+            }
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
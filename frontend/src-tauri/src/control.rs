@@ -1,4 +1,6 @@
 use crate::ssh;
+use crate::tmuxcmd::RemoteTmuxCommand;
+use crate::utf8::Utf8Decoder;
 use crate::{creds_from, HostProfile};
 use once_cell::sync::Lazy;
 use serde_json::json;
@@ -55,10 +57,7 @@ impl ControlManager {
 
         let creds = creds_from(&profile);
         let mut channel = ssh::open_channel(&creds)?;
-        let cmd = format!(
-            "tmux -CC attach-session -t {}",
-            shell_escape::escape(session.clone().into())
-        );
+        let cmd = RemoteTmuxCommand::from_args(&["-CC", "attach-session", "-t", &session]).build();
         channel
             .exec(&cmd)
             .map_err(|e| format!("tmux control exec: {e}"))?;
@@ -82,6 +81,7 @@ impl ControlManager {
             send_event("started", None);
             let mut buf = [0u8; 4096];
             let mut pending = String::new();
+            let mut decoder = Utf8Decoder::default();
 
             loop {
                 if stop_rx.try_recv().is_ok() {
@@ -113,8 +113,7 @@ impl ControlManager {
                         thread::sleep(Duration::from_millis(20));
                     }
                     Ok(n) => {
-                        let chunk = String::from_utf8_lossy(&buf[..n]);
-                        pending.push_str(&chunk);
+                        pending.push_str(&decoder.push(&buf[..n]));
                         while let Some(idx) = pending.find('\n') {
                             let line = pending[..idx].to_string();
                             let rest = pending[idx + 1..].to_string();
@@ -190,3 +189,21 @@ pub fn stop_control(profile: HostProfile, session: String) -> Result<(), String>
 pub fn send_command(profile: HostProfile, session: String, command: String) -> Result<(), String> {
     ControlManager::global().send(profile, session, command)
 }
+
+// tmux sizes control clients from `refresh-client -C`; without it the
+// window keeps whatever width the last attached client had.
+pub fn set_client_size(
+    profile: HostProfile,
+    session: String,
+    cols: u32,
+    rows: u32,
+) -> Result<(), String> {
+    if cols == 0 || rows == 0 {
+        return Err("client size must be non-zero".into());
+    }
+    send_command(
+        profile,
+        session,
+        format!("refresh-client -C {},{}", cols, rows),
+    )
+}
@@ -0,0 +1,269 @@
+use crate::model::ARCRun;
+use crate::runs::{self, RunRegistry};
+use crate::{creds_from, run_remote_cmd, watchdog, HostProfile};
+use std::path::{Path, PathBuf};
+
+const LOG_LINES: usize = 200;
+const ERROR_FILE_LINES: usize = 100;
+const BUNDLE_DIR: &str = "diagnostics";
+
+// ESS jobs leave their stderr in err.txt (ARC's own submit scripts) or a
+// scheduler-named *.err file.
+fn is_error_file(name: &str) -> bool {
+    name == "err.txt" || name.ends_with(".err")
+}
+
+fn find_error_files(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() && entry.file_name() != BUNDLE_DIR => {
+                find_error_files(&path, found)
+            }
+            Ok(t) if t.is_file() && is_error_file(&entry.file_name().to_string_lossy()) => {
+                found.push(path)
+            }
+            _ => {}
+        }
+    }
+}
+
+// calcs/Species/OH/opt_a3/err.txt -> calcs__Species__OH__opt_a3__err.txt
+fn flat_name(work_dir: &Path, file: &Path) -> String {
+    let rel = file.strip_prefix(work_dir).unwrap_or(file);
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("__")
+}
+
+fn tail(text: &str, count: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    lines[lines.len().saturating_sub(count)..].join("\n")
+}
+
+fn remote_text(profile: &HostProfile, command: String) -> Option<String> {
+    let out = run_remote_cmd(&creds_from(profile), command).ok()?;
+    (out.code == 0).then_some(out.stdout)
+}
+
+// (file name in the bundle, contents) for every ESS error file of the run.
+fn error_files(profile: Option<&HostProfile>, work_dir: &Path) -> Vec<(String, String)> {
+    match profile {
+        Some(profile) => {
+            let dir = shell_escape::escape(work_dir.to_string_lossy());
+            let listing = remote_text(
+                profile,
+                format!("find {dir} -type f \\( -name err.txt -o -name '*.err' \\)"),
+            )
+            .unwrap_or_default();
+            listing
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .filter_map(|file| {
+                    let path = Path::new(file.trim());
+                    let quoted = shell_escape::escape(path.to_string_lossy());
+                    let text =
+                        remote_text(profile, format!("tail -n {ERROR_FILE_LINES} {quoted}"))?;
+                    Some((flat_name(work_dir, path), text))
+                })
+                .collect()
+        }
+        None => {
+            let mut found = Vec::new();
+            find_error_files(work_dir, &mut found);
+            found.sort();
+            found
+                .iter()
+                .filter_map(|file| {
+                    let text = std::fs::read_to_string(file).ok()?;
+                    Some((flat_name(work_dir, file), tail(&text, ERROR_FILE_LINES)))
+                })
+                .collect()
+        }
+    }
+}
+
+fn read_input(profile: Option<&HostProfile>, input: &Path) -> Option<String> {
+    match profile {
+        Some(profile) => remote_text(
+            profile,
+            format!("cat {}", shell_escape::escape(input.to_string_lossy())),
+        ),
+        None => std::fs::read_to_string(input).ok(),
+    }
+}
+
+fn summary(run: &ARCRun) -> String {
+    let field = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".into());
+    format!(
+        "name: {}\nid: {}\nhost: {}\nstatus: {:?}\nstarted_at: {}\nfinished_at: {}\nwork_dir: {}\nlast_stderr: {}\n",
+        run.name,
+        run.id,
+        run.host.as_deref().unwrap_or("local"),
+        run.status,
+        field(&run.started_at),
+        field(&run.finished_at),
+        run.work_dir.display(),
+        field(&run.last_stderr),
+    )
+}
+
+// Buckets checked in order; the first whose markers show up wins.
+const CAUSES: &[(&str, &[&str])] = &[
+    (
+        "out_of_memory",
+        &[
+            "memoryerror",
+            "out of memory",
+            "out_of_memory",
+            "oom-kill",
+            "killed",
+        ],
+    ),
+    ("time_limit", &["time limit", "walltime", "timeout"]),
+    (
+        "ess_error",
+        &[
+            "error termination",
+            "orca finished by error",
+            "erroneous write",
+            "scf not converged",
+        ],
+    ),
+    (
+        "missing_file",
+        &["no such file", "filenotfounderror", "not found"],
+    ),
+    (
+        "connection",
+        &["ssh", "connection refused", "authentication", "timed out"],
+    ),
+    ("window_lost", &["window no longer exists"]),
+    ("batch_job", &["batch job"]),
+    ("python_error", &["traceback", "error:"]),
+];
+
+fn classify(text: &str) -> &'static str {
+    let text = text.to_lowercase();
+    CAUSES
+        .iter()
+        .find(|(_, markers)| markers.iter().any(|m| text.contains(m)))
+        .map(|(cause, _)| *cause)
+        .unwrap_or("other")
+}
+
+// Reads the run's last error plus whatever its diagnostics bundle holds.
+pub fn failure_cause(run: &ARCRun) -> &'static str {
+    let mut text = run.last_stderr.clone().unwrap_or_default();
+    if let Some(dir) = &run.diagnostics_path {
+        for file in [dir.join("arc.log.tail"), dir.join("pane.txt")] {
+            text.push('\n');
+            text.push_str(&std::fs::read_to_string(file).unwrap_or_default());
+        }
+        let mut ess = Vec::new();
+        find_error_files(&dir.join("ess"), &mut ess);
+        for file in ess {
+            text.push('\n');
+            text.push_str(&std::fs::read_to_string(file).unwrap_or_default());
+        }
+    }
+    classify(&text)
+}
+
+// Local runs keep the bundle next to their output; remote ones get a local
+// folder so it can be read without the cluster.
+fn bundle_dir(run: &ARCRun, remote: bool) -> PathBuf {
+    if remote {
+        std::env::temp_dir().join("arc-diagnostics").join(&run.id)
+    } else {
+        run.work_dir.join(BUNDLE_DIR)
+    }
+}
+
+fn write(dir: &Path, name: &str, text: &str) -> Result<(), String> {
+    let path = dir.join(name);
+    std::fs::write(&path, text).map_err(|e| format!("write {}: {e}", path.display()))
+}
+
+// Rebuilds the bundle from scratch and records where it went on the run.
+pub fn collect(id: String) -> Result<PathBuf, String> {
+    let run = runs::get_run(id)?;
+    let profile = runs::run_profile(&run.id);
+    let profile = profile.as_ref();
+    let dir = bundle_dir(&run, profile.is_some());
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|e| format!("clear {}: {e}", dir.display()))?;
+    }
+    std::fs::create_dir_all(dir.join("ess"))
+        .map_err(|e| format!("create {}: {e}", dir.display()))?;
+
+    write(&dir, "summary.txt", &summary(&run))?;
+    let log = watchdog::last_log_lines(profile, &run.work_dir, LOG_LINES);
+    write(&dir, "arc.log.tail", &log.join("\n"))?;
+    if let Some(window_id) = run.window_id.as_deref() {
+        if let Ok(out) = runs::capture_window(profile, window_id) {
+            if out.code == 0 {
+                write(&dir, "pane.txt", &out.stdout)?;
+            }
+        }
+    }
+    if let (Some(name), Some(text)) = (
+        run.input_path.file_name(),
+        read_input(profile, &run.input_path),
+    ) {
+        write(&dir, &name.to_string_lossy(), &text)?;
+    }
+    for (name, text) in error_files(profile, &run.work_dir) {
+        write(&dir.join("ess"), &name, &text)?;
+    }
+
+    RunRegistry::global().update(&run.id, |r| r.diagnostics_path = Some(dir.clone()));
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, find_error_files, flat_name};
+
+    #[test]
+    fn finds_ess_error_files_outside_the_bundle() {
+        let work = std::env::temp_dir().join(format!("diag-{}", uuid::Uuid::new_v4()));
+        let job = work.join("calcs/Species/OH/opt_a3");
+        std::fs::create_dir_all(&job).unwrap();
+        std::fs::create_dir_all(work.join("diagnostics")).unwrap();
+        std::fs::write(job.join("err.txt"), "Erroneous write").unwrap();
+        std::fs::write(job.join("slurm-42.err"), "").unwrap();
+        std::fs::write(job.join("input.gjf"), "").unwrap();
+        std::fs::write(work.join("diagnostics/old.err"), "").unwrap();
+
+        let mut found = Vec::new();
+        find_error_files(&work, &mut found);
+        found.sort();
+        assert_eq!(found, vec![job.join("err.txt"), job.join("slurm-42.err")]);
+        assert_eq!(
+            flat_name(&work, &found[0]),
+            "calcs__Species__OH__opt_a3__err.txt"
+        );
+        let _ = std::fs::remove_dir_all(&work);
+    }
+
+    #[test]
+    fn classifies_failure_causes() {
+        assert_eq!(
+            classify("slurmstepd: error: Detected 1 oom-kill event"),
+            "out_of_memory"
+        );
+        assert_eq!(classify("DUE TO TIME LIMIT"), "time_limit");
+        assert_eq!(classify("Error termination via Lnk1e"), "ess_error");
+        assert_eq!(classify("run window no longer exists"), "window_lost");
+        assert_eq!(
+            classify("Traceback (most recent call last):"),
+            "python_error"
+        );
+        assert_eq!(classify(""), "other");
+    }
+}
@@ -0,0 +1,253 @@
+use crate::hostinfo::{self, ToolFlavor};
+use crate::model::AppConfig;
+use crate::runs;
+use crate::{creds_from, run_remote_cmd, HostProfile};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// host label or "local", plus the measured path
+type CacheKey = (String, PathBuf);
+
+static CACHE: Lazy<Mutex<HashMap<CacheKey, (DiskUsage, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// walking a big work_dir or running `du` over SSH is slow, so results are
+// reused for a while
+const CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+const WARNING_EVENT: &str = "disk-usage-warning";
+const LOW_SPACE_EVENT: &str = "disk-space-low";
+const GB: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DiskUsage {
+    pub host: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub measured_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunUsage {
+    pub id: String,
+    pub name: String,
+    pub usage: DiskUsage,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RootUsage {
+    pub usage: DiskUsage,
+    pub threshold_bytes: Option<u64>,
+    pub over_threshold: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorkdirsUsage {
+    pub roots: Vec<RootUsage>, // one per default_work_dir runs were launched with
+    pub runs: Vec<RunUsage>,
+}
+
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| match e.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&e.path()),
+            Ok(_) => e.metadata().map(|m| m.len()).unwrap_or(0),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+// `du -sb` prints "<bytes>\t<path>"; BSD `du -sk` the same in KiB.
+fn parse_du(stdout: &str, unit: u64) -> Option<u64> {
+    let count: u64 = stdout.split_whitespace().next()?.parse().ok()?;
+    Some(count * unit)
+}
+
+pub fn measure(profile: Option<&HostProfile>, path: &Path) -> Result<u64, String> {
+    match profile {
+        Some(profile) => {
+            let dir = shell_escape::escape(path.to_string_lossy());
+            let (du, unit) = hostinfo::tool_flavor(profile).du();
+            let out = run_remote_cmd(&creds_from(profile), format!("{du} {dir}"))?;
+            if out.code != 0 {
+                return Err(format!("du {}: {}", path.display(), out.stderr.trim()));
+            }
+            parse_du(&out.stdout, unit)
+                .ok_or_else(|| format!("unexpected du output: {}", out.stdout))
+        }
+        None if path.is_dir() => Ok(dir_size(path)),
+        None => Err(format!("not a directory: {}", path.display())),
+    }
+}
+
+// `df --output=avail` prints a header, then the KiB available. BSD df has
+// no --output, so `df -kP` is used there and Available is its fourth column.
+fn parse_df(stdout: &str) -> Option<u64> {
+    let columns: Vec<&str> = stdout.lines().last()?.split_whitespace().collect();
+    let avail = match columns[..] {
+        [avail] => avail,
+        [_, _, _, avail, ..] => avail,
+        _ => return None,
+    };
+    let kib: u64 = avail.parse().ok()?;
+    Some(kib * 1024)
+}
+
+pub fn free_bytes(profile: &HostProfile, path: &Path) -> Result<u64, String> {
+    let dir = shell_escape::escape(path.to_string_lossy());
+    let df = match hostinfo::tool_flavor(profile) {
+        ToolFlavor::Gnu => "df -k --output=avail",
+        ToolFlavor::Bsd => "df -kP",
+    };
+    let out = run_remote_cmd(&creds_from(profile), format!("{df} {dir}"))?;
+    if out.code != 0 {
+        return Err(format!("df {}: {}", path.display(), out.stderr.trim()));
+    }
+    parse_df(&out.stdout).ok_or_else(|| format!("unexpected df output: {}", out.stdout))
+}
+
+// Checked just before a remote launch, since ARC dies on a full disk with
+// errors that rarely mention it. A host whose df cannot report free space
+// (no GNU coreutils) is not held back.
+pub fn check_free_space(
+    profile: &HostProfile,
+    work_dir: &Path,
+    config: &AppConfig,
+) -> Result<(), String> {
+    let Some(min_gb) = config.remote_min_free_gb else {
+        return Ok(());
+    };
+    let Ok(free) = free_bytes(profile, work_dir) else {
+        return Ok(());
+    };
+    if free >= min_gb * GB {
+        return Ok(());
+    }
+    let host = runs::host_label(profile);
+    let message = format!(
+        "only {:.1} GB free for {} on {host}, the minimum is {min_gb} GB",
+        free as f64 / GB as f64,
+        work_dir.display()
+    );
+    if !config.remote_low_space_warn_only {
+        return Err(message);
+    }
+    let warning = json!({
+        "host": host,
+        "path": work_dir,
+        "free_bytes": free,
+        "min_bytes": min_gb * GB,
+        "message": message,
+    });
+    runs::emit(LOW_SPACE_EVENT, warning);
+    Ok(())
+}
+
+fn usage(profile: Option<&HostProfile>, path: &Path) -> Result<DiskUsage, String> {
+    let host = profile
+        .map(runs::host_label)
+        .unwrap_or_else(|| "local".into());
+    let key = (host.clone(), path.to_path_buf());
+    if let Some((cached, at)) = CACHE.lock().unwrap().get(&key) {
+        if at.elapsed() < CACHE_TTL {
+            return Ok(cached.clone());
+        }
+    }
+    let found = DiskUsage {
+        host,
+        path: path.to_path_buf(),
+        bytes: measure(profile, path)?,
+        measured_at: chrono::Utc::now().to_rfc3339(),
+    };
+    CACHE
+        .lock()
+        .unwrap()
+        .insert(key, (found.clone(), Instant::now()));
+    Ok(found)
+}
+
+pub fn run_disk_usage(id: String) -> Result<DiskUsage, String> {
+    let run = runs::get_run(id)?;
+    usage(runs::run_profile(&run.id).as_ref(), &run.work_dir)
+}
+
+pub fn workdirs_disk_usage() -> WorkdirsUsage {
+    let mut report = WorkdirsUsage::default();
+    let mut roots: HashMap<(String, String), (Option<HostProfile>, Option<u64>)> = HashMap::new();
+    for run in runs::list_runs() {
+        let profile = runs::run_profile(&run.id);
+        if let Ok(found) = usage(profile.as_ref(), &run.work_dir) {
+            report.runs.push(RunUsage {
+                id: run.id.clone(),
+                name: run.name.clone(),
+                usage: found,
+            });
+        }
+        if let Some(config) = runs::launch_config(&run.id) {
+            let host = profile
+                .as_ref()
+                .map(runs::host_label)
+                .unwrap_or_else(|| "local".into());
+            let threshold = config.disk_warning_gb.map(|gb| gb * GB);
+            roots
+                .entry((host, config.default_work_dir))
+                .or_insert((profile, threshold));
+        }
+    }
+
+    for ((_, dir), (profile, threshold_bytes)) in roots {
+        let Ok(found) = usage(profile.as_ref(), Path::new(&dir)) else {
+            continue;
+        };
+        let over_threshold = threshold_bytes.is_some_and(|t| found.bytes > t);
+        if over_threshold {
+            let warning = json!({
+                "host": found.host,
+                "path": found.path,
+                "bytes": found.bytes,
+                "threshold_bytes": threshold_bytes,
+            });
+            runs::emit(WARNING_EVENT, warning);
+        }
+        report.roots.push(RootUsage {
+            usage: found,
+            threshold_bytes,
+            over_threshold,
+        });
+    }
+    report.roots.sort_by(|a, b| a.usage.host.cmp(&b.usage.host));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dir_size, parse_df, parse_du};
+
+    #[test]
+    fn sizes_local_dirs_and_parses_du() {
+        let dir = std::env::temp_dir().join(format!("du-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("calcs")).unwrap();
+        std::fs::write(dir.join("arc.log"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.join("calcs/out.log"), vec![0u8; 28]).unwrap();
+        assert_eq!(dir_size(&dir), 128);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(parse_du("52428800\t/scratch/u/run1\n", 1), Some(52428800));
+        assert_eq!(parse_du("51200\t/Users/u/run1\n", 1024), Some(52428800));
+        assert_eq!(parse_du("", 1), None);
+        assert_eq!(parse_df(" Avail\n1048576\n"), Some(1024 * 1024 * 1024));
+        assert_eq!(
+            parse_df("Filesystem 1024-blocks Used Available Capacity Mounted on\n/dev/disk3s5 971350180 500000000 1048576 35% /System/Volumes/Data\n"),
+            Some(1024 * 1024 * 1024)
+        );
+        assert_eq!(parse_df("df: unrecognized option '--output=avail'\n"), None);
+    }
+}
@@ -0,0 +1,179 @@
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::{json, Value as JsonValue};
+
+// What the tmux and SSH commands fail with. It reaches the frontend as
+// {code, message, context}, so the UI can branch on `code` instead of
+// matching English text.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum OrchestratorError {
+    #[error("{0}")]
+    TmuxNotFound(String),
+    #[error("no tmux server running")]
+    NoServer,
+    #[error("can't find session: {session}")]
+    SessionMissing { session: String },
+    #[error("can't find window: {target}")]
+    WindowMissing { target: String },
+    #[error("invalid {kind} name {name:?}: {reason}")]
+    InvalidName {
+        kind: &'static str,
+        name: String,
+        reason: String,
+    },
+    #[error("{0}")]
+    SshAuth(String),
+    #[error("{0}")]
+    Timeout(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl OrchestratorError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            OrchestratorError::TmuxNotFound(_) => "tmux_not_found",
+            OrchestratorError::NoServer => "no_server",
+            OrchestratorError::SessionMissing { .. } => "session_missing",
+            OrchestratorError::WindowMissing { .. } => "window_missing",
+            OrchestratorError::InvalidName { .. } => "invalid_name",
+            OrchestratorError::SshAuth(_) => "ssh_auth",
+            OrchestratorError::Timeout(_) => "timeout",
+            OrchestratorError::Other(_) => "other",
+        }
+    }
+
+    pub fn context(&self) -> JsonValue {
+        match self {
+            OrchestratorError::SessionMissing { session } => json!({ "session": session }),
+            OrchestratorError::WindowMissing { target } => json!({ "target": target }),
+            OrchestratorError::InvalidName { kind, name, reason } => {
+                json!({ "kind": kind, "name": name, "reason": reason })
+            }
+            _ => json!({}),
+        }
+    }
+
+    // Sorts a message from tmux, ssh2 or the helpers around them into a
+    // variant; whatever is not recognised stays Other.
+    pub fn classify(message: &str) -> Self {
+        let message = message.trim();
+        let lower = message.to_lowercase();
+        if lower.contains("no server running")
+            || lower.contains("failed to connect to server")
+            || lower.contains("no sessions")
+            || (lower.contains("error connecting to") && lower.contains("no such file"))
+        {
+            return OrchestratorError::NoServer;
+        }
+        // tmux before 2.x said "session not found" and "window not found"
+        if let Some(session) = after(message, &["can't find session", "session not found"]) {
+            return OrchestratorError::SessionMissing { session };
+        }
+        let window = ["can't find window", "can't find pane", "window not found"];
+        if let Some(target) = after(message, &window) {
+            return OrchestratorError::WindowMissing { target };
+        }
+        if lower.contains("cannot find binary path") || lower.contains("tmux: command not found") {
+            return OrchestratorError::TmuxNotFound(message.to_string());
+        }
+        let auth_step = ["password auth:", "pubkey auth:", "agent"]
+            .iter()
+            .any(|p| lower.starts_with(p));
+        if auth_step || lower.contains("authentication failed") {
+            return OrchestratorError::SshAuth(message.to_string());
+        }
+        if lower.contains("timed out") || lower.starts_with("no answer within") {
+            return OrchestratorError::Timeout(message.to_string());
+        }
+        OrchestratorError::Other(message.to_string())
+    }
+}
+
+// What follows the first of `phrases` found in `message`, e.g. the name in
+// "can't find session: arc".
+fn after(message: &str, phrases: &[&str]) -> Option<String> {
+    let lower = message.to_lowercase();
+    phrases.iter().find_map(|phrase| {
+        let at = lower.find(phrase)?;
+        let rest = message.get(at + phrase.len()..).unwrap_or("");
+        Some(rest.trim_start_matches(':').trim().to_string())
+    })
+}
+
+// stderr meaning there is no tmux server to ask. Listings and captures,
+// local or remote, answer that with an empty result instead of an error.
+pub fn no_server(stderr: &str) -> bool {
+    OrchestratorError::classify(stderr) == OrchestratorError::NoServer
+}
+
+// stderr of a target that names a window or pane tmux does not have.
+pub fn window_missing(stderr: &str) -> bool {
+    matches!(
+        OrchestratorError::classify(stderr),
+        OrchestratorError::WindowMissing { .. }
+    )
+}
+
+impl From<String> for OrchestratorError {
+    fn from(message: String) -> Self {
+        OrchestratorError::classify(&message)
+    }
+}
+
+impl Serialize for OrchestratorError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("code", self.code())?;
+        map.serialize_entry("message", &self.to_string())?;
+        map.serialize_entry("context", &self.context())?;
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{no_server, window_missing, OrchestratorError};
+    use serde_json::json;
+
+    #[test]
+    fn messages_are_classified_and_serialized_with_a_code() {
+        let err = OrchestratorError::from("can't find session: arc-run\n".to_string());
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            json!({
+                "code": "session_missing",
+                "message": "can't find session: arc-run",
+                "context": { "session": "arc-run" },
+            })
+        );
+        let classify = OrchestratorError::classify;
+        assert_eq!(
+            classify("no server running on /tmp/tmux-1000/default"),
+            OrchestratorError::NoServer
+        );
+        assert!(no_server(
+            "error connecting to /tmp/tmux-1000/default (No such file or directory)"
+        ));
+        assert!(!no_server("can't find window: 3"));
+        assert_eq!(
+            classify("can't find window: @12"),
+            OrchestratorError::WindowMissing {
+                target: "@12".into()
+            }
+        );
+        assert!(window_missing("can't find pane: %4"));
+        assert_eq!(
+            classify("session not found: arc"),
+            OrchestratorError::SessionMissing {
+                session: "arc".into()
+            }
+        );
+        assert_eq!(classify("cannot find binary path").code(), "tmux_not_found");
+        assert_eq!(
+            classify("pubkey auth: Username/PublicKey combination invalid").code(),
+            "ssh_auth"
+        );
+        assert_eq!(classify("no answer within 5s").code(), "timeout");
+        assert_eq!(classify("tcp: connection refused").code(), "other");
+    }
+}
@@ -0,0 +1,76 @@
+use crate::model::{ARCRun, RunProgress, RunStatus};
+use crate::{history, runs};
+use chrono::{DateTime, Utc};
+
+// Past runs count as similar when they ran on the same host and considered
+// between half and twice as many species.
+fn similar(run: &ARCRun, species_total: u32, past: &ARCRun) -> bool {
+    let Some(total) = past.progress.as_ref().map(|p| p.species_total) else {
+        return false;
+    };
+    past.id != run.id
+        && past.status == RunStatus::Finished
+        && past.host == run.host
+        && total * 2 >= species_total
+        && total <= species_total * 2
+}
+
+// Blends two guesses: extrapolating the fraction of species settled so far,
+// and the mean duration of similar past runs. Early on the history dominates;
+// as more species settle the run's own pace takes over.
+fn estimate(elapsed: i64, progress: &RunProgress, past_durations: &[i64]) -> Option<i64> {
+    let settled = progress.species_converged + progress.species_failed;
+    let fraction = (progress.species_total > 0 && settled > 0)
+        .then(|| (settled as f64 / progress.species_total as f64).min(1.0));
+    let from_pace = fraction.map(|f| elapsed as f64 / f - elapsed as f64);
+    let from_history = (!past_durations.is_empty()).then(|| {
+        let mean = past_durations.iter().sum::<i64>() as f64 / past_durations.len() as f64;
+        (mean - elapsed as f64).max(0.0)
+    });
+    let eta = match (fraction, from_pace, from_history) {
+        (Some(f), Some(pace), Some(hist)) => f * pace + (1.0 - f) * hist,
+        (_, Some(pace), None) => pace,
+        (_, None, Some(hist)) => hist,
+        _ => return None,
+    };
+    Some(eta.round() as i64)
+}
+
+pub fn estimate_for(run: &ARCRun, progress: &RunProgress) -> Option<i64> {
+    let started = DateTime::parse_from_rfc3339(run.started_at.as_deref()?).ok()?;
+    let elapsed = (Utc::now() - started.with_timezone(&Utc))
+        .num_seconds()
+        .max(0);
+    let past: Vec<i64> = runs::list_runs()
+        .iter()
+        .filter(|past| similar(run, progress.species_total, past))
+        .filter_map(history::duration_secs)
+        .collect();
+    estimate(elapsed, progress, &past)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::estimate;
+    use crate::model::RunProgress;
+
+    #[test]
+    fn estimate_blends_pace_with_history() {
+        let quarter = RunProgress {
+            species_total: 4,
+            species_converged: 1,
+            ..RunProgress::default()
+        };
+        // 1h for a quarter of the species -> 3h left at this pace
+        assert_eq!(estimate(3600, &quarter, &[]), Some(3 * 3600));
+        // similar runs took 2h: 0.25 * 3h + 0.75 * 1h
+        assert_eq!(estimate(3600, &quarter, &[7200]), Some(5400));
+
+        let nothing_settled = RunProgress {
+            species_total: 4,
+            ..RunProgress::default()
+        };
+        assert_eq!(estimate(3600, &nothing_settled, &[7200]), Some(3600));
+        assert_eq!(estimate(3600, &nothing_settled, &[]), None);
+    }
+}
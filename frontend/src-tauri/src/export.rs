@@ -0,0 +1,176 @@
+use crate::model::{ARCRun, RunNote};
+use crate::{history, results, runs};
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    fn parse(format: &str) -> Result<Self, String> {
+        match format.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            other => Err(format!("unknown export format: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ExportRow {
+    pub name: String,
+    pub host: String,
+    pub status: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub duration_secs: Option<i64>,
+    pub input_path: String,
+    pub species_total: Option<usize>,
+    pub species_converged: Option<usize>,
+    pub reactions: Option<usize>,
+    pub failed_jobs: Option<usize>,
+    pub notes: Vec<RunNote>,
+}
+
+const CSV_HEADER: &[&str] = &[
+    "name",
+    "host",
+    "status",
+    "started_at",
+    "finished_at",
+    "duration_secs",
+    "input_path",
+    "species_total",
+    "species_converged",
+    "reactions",
+    "failed_jobs",
+    "notes",
+];
+
+// Result columns stay empty for runs that are still going or whose output
+// can't be parsed; the rest of the report is still worth having.
+fn to_row(run: &ARCRun) -> ExportRow {
+    let results = results::run_results(run.id.clone()).ok();
+    ExportRow {
+        name: run.name.clone(),
+        host: run.host.clone().unwrap_or_else(|| "local".into()),
+        status: format!("{:?}", run.status),
+        started_at: run.started_at.clone(),
+        finished_at: run.finished_at.clone(),
+        duration_secs: history::duration_secs(run),
+        input_path: run.input_path.to_string_lossy().into_owned(),
+        species_total: results.as_ref().map(|r| r.species.len()),
+        species_converged: results.as_ref().map(|r| {
+            r.species
+                .iter()
+                .filter(|s| s.converged == Some(true))
+                .count()
+        }),
+        reactions: results.as_ref().map(|r| r.reactions.len()),
+        failed_jobs: results.as_ref().map(|r| r.failed_jobs.len()),
+        notes: run.notes.clone(),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn opt<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+// All notes share one cell, oldest first.
+fn notes_cell(notes: &[RunNote]) -> String {
+    let notes: Vec<String> = notes
+        .iter()
+        .map(|n| format!("{} {}", n.at, n.text))
+        .collect();
+    notes.join(" | ")
+}
+
+fn to_csv(rows: &[ExportRow]) -> String {
+    let mut out = CSV_HEADER.join(",");
+    out.push('\n');
+    for row in rows {
+        let fields = [
+            row.name.clone(),
+            row.host.clone(),
+            row.status.clone(),
+            opt(&row.started_at),
+            opt(&row.finished_at),
+            opt(&row.duration_secs),
+            row.input_path.clone(),
+            opt(&row.species_total),
+            opt(&row.species_converged),
+            opt(&row.reactions),
+            opt(&row.failed_jobs),
+            notes_cell(&row.notes),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+// Writes the report for `ids` (every run when empty) and returns how many
+// runs it covers.
+pub fn export_runs(ids: Vec<String>, format: String, dest: String) -> Result<usize, String> {
+    let format = Format::parse(&format)?;
+    let selected: Vec<ARCRun> = if ids.is_empty() {
+        runs::list_runs()
+    } else {
+        ids.into_iter()
+            .map(runs::get_run)
+            .collect::<Result<_, _>>()?
+    };
+    let rows: Vec<ExportRow> = selected.iter().map(to_row).collect();
+    let text = match format {
+        Format::Csv => to_csv(&rows),
+        Format::Json => serde_json::to_string_pretty(&rows).map_err(|e| e.to_string())?,
+    };
+    std::fs::write(Path::new(&dest), text).map_err(|e| format!("write {dest}: {e}"))?;
+    Ok(rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_csv, ExportRow};
+    use crate::model::RunNote;
+
+    #[test]
+    fn csv_quotes_awkward_fields_and_leaves_gaps_empty() {
+        let row = ExportRow {
+            name: "CH4, \"opt\"".into(),
+            host: "local".into(),
+            status: "Finished".into(),
+            started_at: Some("2024-03-01T12:00:00Z".into()),
+            finished_at: None,
+            duration_secs: Some(3600),
+            input_path: "/work/input.yml".into(),
+            species_total: Some(2),
+            species_converged: Some(1),
+            reactions: None,
+            failed_jobs: Some(1),
+            notes: vec![RunNote {
+                at: "2024-03-01T12:30:00Z".into(),
+                text: "restarted OH".into(),
+            }],
+        };
+        let csv = to_csv(&[row]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0].split(',').count(), 12);
+        assert_eq!(
+            lines[1],
+            "\"CH4, \"\"opt\"\"\",local,Finished,2024-03-01T12:00:00Z,,3600,/work/input.yml,2,1,,1,2024-03-01T12:30:00Z restarted OH"
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use crate::model::ARCRun;
+use crate::runs::{self, RunRegistry};
+use crate::{creds_from, run_remote_cmd, ssh};
+use flate2::read::GzDecoder;
+use serde_json::json;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const PROGRESS_EVENT: &str = "run-fetch-progress";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+// Results land in `<dest>/<run name>-<short id>`, next to a partial tarball
+// that is removed once extracted.
+fn local_target(run: &ARCRun, dest: &Path) -> PathBuf {
+    let short_id: String = run.id.chars().take(8).collect();
+    dest.join(format!("{}-{}", run.name, short_id))
+}
+
+// Tars the output directory from its parent so the archive holds `output/...`.
+pub fn tar_command(output: &Path, tarball: &Path) -> Result<String, String> {
+    let parent = output
+        .parent()
+        .ok_or_else(|| format!("no parent for {}", output.display()))?;
+    let name = output
+        .file_name()
+        .ok_or_else(|| format!("no directory name in {}", output.display()))?;
+    Ok(format!(
+        "tar -czf {} -C {} {}",
+        shell_escape::escape(tarball.to_string_lossy()),
+        shell_escape::escape(parent.to_string_lossy()),
+        shell_escape::escape(name.to_string_lossy()),
+    ))
+}
+
+fn extract(tarball: &Path, into: &Path) -> Result<(), String> {
+    let file = File::open(tarball).map_err(|e| format!("open {}: {e}", tarball.display()))?;
+    std::fs::create_dir_all(into).map_err(|e| format!("create {}: {e}", into.display()))?;
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(into)
+        .map_err(|e| format!("extract into {}: {e}", into.display()))
+}
+
+pub fn fetch_results(id: String, dest: String) -> Result<ARCRun, String> {
+    let run = runs::get_run(id.clone())?;
+    let Some(host) = run.host.as_deref() else {
+        return Err(format!(
+            "run is local, its results are already in {}",
+            run.work_dir.display()
+        ));
+    };
+    let profile =
+        runs::run_profile(&id).ok_or_else(|| format!("no saved login for {host}, reconnect"))?;
+    let creds = creds_from(&profile);
+    let output = runs::locate_project_file(Some(&profile), &run.work_dir, "output")
+        .ok_or_else(|| format!("no output directory under {}", run.work_dir.display()))?;
+
+    let remote_tarball = PathBuf::from(format!("/tmp/arc-results-{id}.tar.gz"));
+    let out = run_remote_cmd(&creds, tar_command(&output, &remote_tarball)?)?;
+    if out.code != 0 {
+        return Err(format!(
+            "tar {} failed: {}",
+            output.display(),
+            out.stderr.trim()
+        ));
+    }
+
+    let dest = Path::new(&dest);
+    std::fs::create_dir_all(dest).map_err(|e| format!("create {}: {e}", dest.display()))?;
+    let target = local_target(&run, dest);
+    let mut local_tarball = target.clone().into_os_string();
+    local_tarball.push(".tar.gz.partial");
+    let local_tarball = PathBuf::from(local_tarball);
+    let mut last_emit: Option<Instant> = None;
+    let downloaded = ssh::download(&creds, &remote_tarball, &local_tarball, |bytes, total| {
+        if bytes == total || last_emit.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) {
+            last_emit = Some(Instant::now());
+            runs::emit(
+                PROGRESS_EVENT,
+                json!({ "id": id, "bytes": bytes, "total": total }),
+            );
+        }
+    });
+    let rm = format!(
+        "rm -f {}",
+        shell_escape::escape(remote_tarball.to_string_lossy())
+    );
+    let _ = run_remote_cmd(&creds, rm);
+    let extracted = downloaded.and_then(|_| extract(&local_tarball, &target));
+    let _ = std::fs::remove_file(&local_tarball);
+    extracted?;
+
+    let local_output = target.join(output.file_name().unwrap_or_default());
+    RunRegistry::global()
+        .update(&id, |r| r.results_path = Some(local_output))
+        .ok_or_else(|| format!("unknown run: {id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract, tar_command};
+    use std::path::Path;
+
+    #[test]
+    fn tars_from_the_parent_and_extracts_locally() {
+        let command = tar_command(
+            Path::new("/scratch/u/run 1/CH4/output"),
+            Path::new("/tmp/arc-results-r1.tar.gz"),
+        )
+        .unwrap();
+        assert_eq!(
+            command,
+            "tar -czf /tmp/arc-results-r1.tar.gz -C '/scratch/u/run 1/CH4' output"
+        );
+
+        let base = std::env::temp_dir().join(format!("fetch-{}", uuid::Uuid::new_v4()));
+        let tarball = base.join("results.tar.gz");
+        std::fs::create_dir_all(&base).unwrap();
+        let file = std::fs::File::create(&tarball).unwrap();
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            file,
+            flate2::Compression::default(),
+        ));
+        let body = b"project: CH4\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(body.len() as u64);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, "output/output.yml", &body[..])
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let target = base.join("CH4-r1");
+        extract(&tarball, &target).unwrap();
+        let text = std::fs::read_to_string(target.join("output/output.yml")).unwrap();
+        assert_eq!(text, "project: CH4\n");
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
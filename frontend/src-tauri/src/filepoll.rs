@@ -0,0 +1,184 @@
+use crate::hostinfo::{self, ToolFlavor};
+use crate::model::ARCRun;
+use crate::runs;
+use crate::{creds_from, run_remote_cmd, HostProfile};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const EVENT: &str = "run-files-changed";
+// files ARC touches as it makes progress, in the work dir or its project dir
+const WATCHED: &[&str] = &["arc.log", "restart.yml", "output"];
+// a remote pane is still captured this often when none of its files moved,
+// so a crash that never reaches arc.log is noticed
+const CAPTURE_FALLBACK: Duration = Duration::from_secs(60);
+
+// run id -> the stamps of its files at the last poll
+static SEEN: Lazy<Mutex<HashMap<String, Stamps>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static LAST_CAPTURE: Lazy<Mutex<HashMap<String, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// path -> (mtime, size)
+type Stamps = HashMap<String, (i64, u64)>;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FileStat {
+    pub path: String,
+    pub mtime: i64, // seconds since the epoch
+    pub size: u64,
+}
+
+// One `stat` over every watched file of every run on the host; globs that
+// match nothing stay literal and their errors are dropped.
+fn stat_command(work_dirs: &[&Path], tools: ToolFlavor) -> String {
+    let mut args = Vec::new();
+    for dir in work_dirs {
+        let dir = shell_escape::escape(dir.to_string_lossy());
+        for name in WATCHED {
+            args.push(format!("{dir}/{name}"));
+            args.push(format!("{dir}/*/{name}"));
+        }
+    }
+    format!(
+        "{} {} 2>/dev/null; true",
+        tools.stat("%Y|%s|%n"),
+        args.join(" ")
+    )
+}
+
+fn parse_stat(stdout: &str) -> Vec<FileStat> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '|');
+            Some(FileStat {
+                mtime: parts.next()?.trim().parse().ok()?,
+                size: parts.next()?.trim().parse().ok()?,
+                path: parts.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+// The run whose work dir holds `path`; the deepest one when work dirs nest.
+fn owner<'a>(runs: &[&'a ARCRun], path: &str) -> Option<&'a ARCRun> {
+    runs.iter()
+        .filter(|r| Path::new(path).starts_with(&r.work_dir))
+        .max_by_key(|r| r.work_dir.components().count())
+        .copied()
+}
+
+// Files that are new or whose mtime or size moved. The first poll of a run
+// only records a baseline.
+fn diff(prev: Option<&Stamps>, current: &[FileStat]) -> Vec<FileStat> {
+    let Some(prev) = prev else {
+        return Vec::new();
+    };
+    current
+        .iter()
+        .filter(|f| prev.get(&f.path) != Some(&(f.mtime, f.size)))
+        .cloned()
+        .collect()
+}
+
+fn poll_host(profile: &HostProfile, runs: &[&ARCRun]) -> HashSet<String> {
+    let dirs: Vec<&Path> = runs.iter().map(|r| r.work_dir.as_path()).collect();
+    let Ok(out) = run_remote_cmd(
+        &creds_from(profile),
+        stat_command(&dirs, hostinfo::tool_flavor(profile)),
+    ) else {
+        return HashSet::new();
+    };
+    let mut per_run: HashMap<&str, Vec<FileStat>> =
+        runs.iter().map(|r| (r.id.as_str(), Vec::new())).collect();
+    for stat in parse_stat(&out.stdout) {
+        if let Some(run) = owner(runs, &stat.path) {
+            per_run.entry(run.id.as_str()).or_default().push(stat);
+        }
+    }
+    let mut changed = HashSet::new();
+    let mut seen = SEEN.lock().unwrap();
+    for (id, files) in per_run {
+        let moved = diff(seen.get(id), &files);
+        seen.insert(
+            id.to_string(),
+            files
+                .into_iter()
+                .map(|f| (f.path, (f.mtime, f.size)))
+                .collect(),
+        );
+        if !moved.is_empty() {
+            runs::emit(EVENT, json!({ "id": id, "files": moved }));
+            changed.insert(id.to_string());
+        }
+    }
+    changed
+}
+
+// Stats the files of every given remote run, one SSH command per host, and
+// returns the ids whose files changed since the last poll.
+pub fn poll(active: &[ARCRun]) -> HashSet<String> {
+    let mut by_host: HashMap<String, (HostProfile, Vec<&ARCRun>)> = HashMap::new();
+    for run in active {
+        let (Some(host), Some(profile)) = (&run.host, runs::run_profile(&run.id)) else {
+            continue;
+        };
+        by_host
+            .entry(host.clone())
+            .or_insert_with(|| (profile, Vec::new()))
+            .1
+            .push(run);
+    }
+    by_host
+        .values()
+        .flat_map(|(profile, runs)| poll_host(profile, runs))
+        .collect()
+}
+
+// Whether the monitor should capture a remote run's pane this tick.
+pub fn capture_due(id: &str, changed: bool) -> bool {
+    let mut last = LAST_CAPTURE.lock().unwrap();
+    let due = changed
+        || last
+            .get(id)
+            .is_none_or(|at| at.elapsed() >= CAPTURE_FALLBACK);
+    if due {
+        last.insert(id.to_string(), Instant::now());
+    }
+    due
+}
+
+pub fn forget(id: &str) {
+    SEEN.lock().unwrap().remove(id);
+    LAST_CAPTURE.lock().unwrap().remove(id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, parse_stat, stat_command, FileStat, Stamps};
+    use crate::hostinfo::ToolFlavor;
+    use std::path::Path;
+
+    #[test]
+    fn stat_output_is_diffed_against_the_last_poll() {
+        let command = stat_command(&[Path::new("/scratch/u/CH4")], ToolFlavor::Gnu);
+        assert!(command.starts_with("stat -c '%Y|%s|%n' /scratch/u/CH4/arc.log "));
+        assert!(command.contains(" /scratch/u/CH4/*/restart.yml "));
+
+        let stats = parse_stat(
+            "1700000000|2048|/scratch/u/CH4/arc.log\n1700000100|0|/scratch/u/CH4/out|put\ngarbage\n",
+        );
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[1].path, "/scratch/u/CH4/out|put");
+
+        assert!(diff(None, &stats).is_empty());
+        let prev: Stamps = [("/scratch/u/CH4/arc.log".to_string(), (1700000000, 1024))].into();
+        let moved = diff(Some(&prev), &stats);
+        let paths: Vec<&str> = moved.iter().map(|f: &FileStat| f.path.as_str()).collect();
+        assert_eq!(paths, ["/scratch/u/CH4/arc.log", "/scratch/u/CH4/out|put"]);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::profilecheck::{self, CheckState};
+use crate::tmuxcmd::RemoteTmuxCommand;
+use crate::{
+    creds_from, diskusage, errors, hoststats, profiles, run_remote_cmd, runs, HostProfile,
+};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// each probe gets this long; a host that hangs on one still reports the rest
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProfileHealth {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub reachable: bool,
+    pub tmux: Option<String>, // `tmux -V`
+    pub sessions: Option<usize>,
+    pub load: Option<[f64; 3]>,
+    pub disk_free_bytes: Option<u64>, // where default_work_dir lives, or the home dir
+    pub errors: Vec<String>,          // "<probe>: <what went wrong>"
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub profiles: Vec<ProfileHealth>,
+    pub checked_at: String,
+}
+
+// Runs `probe` on its own thread and gives up on it after `timeout`. A probe
+// that is given up on still finishes in the background.
+pub fn with_timeout<T: Send + 'static>(
+    timeout: Duration,
+    probe: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(probe());
+    });
+    rx.recv_timeout(timeout)
+        .unwrap_or_else(|_| Err(format!("no answer within {timeout:?}")))
+}
+
+fn remote(profile: &HostProfile, command: &'static str) -> Result<String, String> {
+    let out = run_remote_cmd(&creds_from(profile), command.into())?;
+    if out.code != 0 {
+        return Err(out.stderr.trim().to_string());
+    }
+    Ok(out.stdout)
+}
+
+fn count_sessions(profile: &HostProfile) -> Result<usize, String> {
+    let list = RemoteTmuxCommand::new("list-sessions").args(&["-F", "#S"]);
+    let out = run_remote_cmd(&creds_from(profile), list.build())?;
+    if out.code != 0 {
+        if errors::no_server(&out.stderr) {
+            return Ok(0);
+        }
+        return Err(out.stderr.trim().to_string());
+    }
+    Ok(out.stdout.lines().filter(|l| !l.is_empty()).count())
+}
+
+fn probe_profile(id: String, name: String, profile: HostProfile) -> ProfileHealth {
+    let mut health = ProfileHealth {
+        id,
+        name,
+        host: runs::host_label(&profile),
+        ..ProfileHealth::default()
+    };
+    let tcp = profilecheck::tcp_check(&profile);
+    health.reachable = tcp.state == CheckState::Ok;
+    if !health.reachable {
+        health.errors.push(format!("reachable: {}", tcp.detail));
+        return health;
+    }
+
+    let spawn = |probe: fn(&HostProfile) -> Result<ProbeValue, String>| {
+        let profile = profile.clone();
+        thread::spawn(move || with_timeout(PROBE_TIMEOUT, move || probe(&profile)))
+    };
+    let probes = [
+        (
+            "tmux",
+            spawn(|p| remote(p, "tmux -V").map(ProbeValue::Tmux)),
+        ),
+        (
+            "sessions",
+            spawn(|p| count_sessions(p).map(ProbeValue::Sessions)),
+        ),
+        (
+            "load",
+            spawn(|p| {
+                let uptime = remote(p, "uptime")?;
+                hoststats::load_averages(&uptime)
+                    .map(ProbeValue::Load)
+                    .ok_or_else(|| format!("unexpected uptime output: {}", uptime.trim()))
+            }),
+        ),
+        (
+            "disk",
+            spawn(|p| {
+                // commands start in the home dir
+                let dir = p.overrides.default_work_dir.as_deref().unwrap_or(".");
+                diskusage::free_bytes(p, Path::new(dir)).map(ProbeValue::DiskFree)
+            }),
+        ),
+    ];
+    for (probe, handle) in probes {
+        match handle.join() {
+            Ok(Ok(ProbeValue::Tmux(version))) => health.tmux = Some(version.trim().to_string()),
+            Ok(Ok(ProbeValue::Sessions(count))) => health.sessions = Some(count),
+            Ok(Ok(ProbeValue::Load(load))) => health.load = Some(load),
+            Ok(Ok(ProbeValue::DiskFree(bytes))) => health.disk_free_bytes = Some(bytes),
+            Ok(Err(e)) => health.errors.push(format!("{probe}: {e}")),
+            Err(_) => health.errors.push(format!("{probe}: probe panicked")),
+        }
+    }
+    health
+}
+
+enum ProbeValue {
+    Tmux(String),
+    Sessions(usize),
+    Load([f64; 3]),
+    DiskFree(u64),
+}
+
+// Probes every enabled profile at once, so the overview takes as long as
+// its slowest host rather than the sum of them.
+pub fn profiles_health() -> HealthReport {
+    let handles: Vec<_> = profiles::list()
+        .into_iter()
+        .filter(|p| p.profile.enabled)
+        .map(|p| thread::spawn(move || probe_profile(p.id, p.name, p.profile)))
+        .collect();
+    let mut profiles: Vec<ProfileHealth> =
+        handles.into_iter().filter_map(|h| h.join().ok()).collect();
+    profiles.sort_by_key(|p| p.name.to_lowercase());
+    HealthReport {
+        profiles,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::with_timeout;
+    use std::time::Duration;
+
+    #[test]
+    fn probes_that_hang_time_out() {
+        let quick = with_timeout(Duration::from_secs(1), || Ok(3));
+        assert_eq!(quick, Ok(3));
+        let slow = with_timeout(Duration::from_millis(50), || {
+            std::thread::sleep(Duration::from_secs(1));
+            Ok(3)
+        });
+        assert_eq!(slow, Err("no answer within 50ms".into()));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::model::{ARCRun, RunStatus};
+use crate::runs;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryFilter {
+    pub status: Option<RunStatus>,
+    pub host: Option<String>,
+    pub name_contains: Option<String>,
+    pub since: Option<String>, // RFC 3339; runs that started before are skipped
+    pub until: Option<String>, // RFC 3339; runs that started after are skipped
+    #[serde(default)]
+    pub tags: Vec<String>, // runs must carry every one of these
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub input_name: String,
+    pub outcome: RunStatus,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub duration_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HostSummary {
+    pub host: String,
+    pub runs: usize,
+    pub mean_duration_secs: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct HistorySummary {
+    pub total: usize,
+    pub finished: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub success_rate: Option<f64>, // finished / total, None when there is nothing to rate
+    pub per_host: Vec<HostSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunHistory {
+    pub entries: Vec<HistoryEntry>,
+    pub summary: HistorySummary,
+}
+
+fn parse_ts(ts: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+pub fn duration_secs(run: &ARCRun) -> Option<i64> {
+    let start = parse_ts(run.started_at.as_deref()?)?;
+    let end = parse_ts(run.finished_at.as_deref()?)?;
+    Some((end - start).num_seconds().max(0))
+}
+
+fn run_host(run: &ARCRun) -> String {
+    run.host.clone().unwrap_or_else(|| "local".into())
+}
+
+fn is_done(status: &RunStatus) -> bool {
+    matches!(
+        status,
+        RunStatus::Finished | RunStatus::Failed | RunStatus::Cancelled
+    )
+}
+
+fn to_entry(run: &ARCRun) -> HistoryEntry {
+    HistoryEntry {
+        id: run.id.clone(),
+        name: run.name.clone(),
+        host: run_host(run),
+        input_name: run
+            .input_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        outcome: run.status.clone(),
+        started_at: run.started_at.clone(),
+        finished_at: run.finished_at.clone(),
+        duration_secs: duration_secs(run),
+    }
+}
+
+fn matches(run: &ARCRun, filter: &HistoryFilter) -> bool {
+    if filter.status.as_ref().is_some_and(|s| *s != run.status) {
+        return false;
+    }
+    if filter.host.as_ref().is_some_and(|h| *h != run_host(run)) {
+        return false;
+    }
+    if let Some(needle) = &filter.name_contains {
+        if !run.name.to_lowercase().contains(&needle.to_lowercase()) {
+            return false;
+        }
+    }
+    if !filter.tags.iter().all(|t| run.tags.contains(t)) {
+        return false;
+    }
+    let started = run.started_at.as_deref().and_then(parse_ts);
+    if let Some(since) = filter.since.as_deref().and_then(parse_ts) {
+        if started.is_none_or(|s| s < since) {
+            return false;
+        }
+    }
+    if let Some(until) = filter.until.as_deref().and_then(parse_ts) {
+        if started.is_none_or(|s| s > until) {
+            return false;
+        }
+    }
+    true
+}
+
+fn summarize(entries: &[HistoryEntry]) -> HistorySummary {
+    let count = |status: RunStatus| entries.iter().filter(|e| e.outcome == status).count();
+    let total = entries.len();
+    let finished = count(RunStatus::Finished);
+
+    let mut hosts: BTreeMap<&str, (usize, Vec<i64>)> = BTreeMap::new();
+    for entry in entries {
+        let slot = hosts.entry(&entry.host).or_default();
+        slot.0 += 1;
+        slot.1.extend(entry.duration_secs);
+    }
+    let per_host = hosts
+        .into_iter()
+        .map(|(host, (runs, durations))| HostSummary {
+            host: host.to_string(),
+            runs,
+            mean_duration_secs: (!durations.is_empty())
+                .then(|| durations.iter().sum::<i64>() as f64 / durations.len() as f64),
+        })
+        .collect();
+
+    HistorySummary {
+        total,
+        finished,
+        failed: count(RunStatus::Failed),
+        cancelled: count(RunStatus::Cancelled),
+        success_rate: (total > 0).then(|| finished as f64 / total as f64),
+        per_host,
+    }
+}
+
+pub fn history(filter: HistoryFilter) -> RunHistory {
+    let mut entries: Vec<HistoryEntry> = runs::list_runs()
+        .iter()
+        .filter(|r| is_done(&r.status) && matches(r, &filter))
+        .map(to_entry)
+        .collect();
+    // newest first for the dashboard
+    entries.reverse();
+    let summary = summarize(&entries);
+    RunHistory { entries, summary }
+}
+
+// Same filter as the history view, but over every run in the registry,
+// active ones included.
+pub fn search(filter: HistoryFilter) -> Vec<ARCRun> {
+    let mut found: Vec<ARCRun> = runs::list_runs()
+        .into_iter()
+        .filter(|r| matches(r, &filter))
+        .collect();
+    found.reverse();
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{matches, summarize, to_entry, HistoryFilter};
+    use crate::model::{ARCRun, RunStatus};
+
+    fn run(name: &str, status: RunStatus, start: &str, end: &str) -> ARCRun {
+        ARCRun {
+            name: name.into(),
+            started_at: Some(start.into()),
+            finished_at: Some(end.into()),
+            ..ARCRun::for_test(name, status)
+        }
+    }
+
+    #[test]
+    fn summary_computes_rates_and_host_means() {
+        let entries: Vec<_> = [
+            run(
+                "a",
+                RunStatus::Finished,
+                "2024-01-01T00:00:00Z",
+                "2024-01-01T01:00:00Z",
+            ),
+            run(
+                "b",
+                RunStatus::Failed,
+                "2024-01-02T00:00:00Z",
+                "2024-01-02T00:30:00Z",
+            ),
+        ]
+        .iter()
+        .map(to_entry)
+        .collect();
+        assert_eq!(entries[0].duration_secs, Some(3600));
+        assert_eq!(entries[0].input_name, "input.yml");
+
+        let summary = summarize(&entries);
+        assert_eq!(summary.total, 2);
+        assert_eq!(summary.success_rate, Some(0.5));
+        assert_eq!(summary.per_host.len(), 1);
+        assert_eq!(summary.per_host[0].mean_duration_secs, Some(2700.0));
+    }
+
+    #[test]
+    fn filter_applies_status_name_tags_and_window() {
+        let mut ch4 = run(
+            "CH4_opt",
+            RunStatus::Finished,
+            "2024-03-01T12:00:00Z",
+            "2024-03-01T13:00:00Z",
+        );
+        ch4.tags = vec!["benchmark".into()];
+        let filter = HistoryFilter {
+            status: Some(RunStatus::Finished),
+            name_contains: Some("ch4".into()),
+            since: Some("2024-02-01T00:00:00Z".into()),
+            tags: vec!["benchmark".into()],
+            ..HistoryFilter::default()
+        };
+        assert!(matches(&ch4, &filter));
+        let later = HistoryFilter {
+            since: Some("2024-04-01T00:00:00Z".into()),
+            ..HistoryFilter::default()
+        };
+        assert!(!matches(&ch4, &later));
+        let other_tag = HistoryFilter {
+            tags: vec!["paper-2".into()],
+            ..HistoryFilter::default()
+        };
+        assert!(!matches(&ch4, &other_tag));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::runs;
+use crate::{creds_from, run_remote_cmd, HostProfile};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const SEPARATOR: &str = "---";
+// Linux first, then the macOS/BSD fallbacks for each field.
+const PROBE: &str = r#"uname -srm
+echo ---
+if [ -r /etc/os-release ]; then (. /etc/os-release; echo "$PRETTY_NAME"); else sw_vers -productName 2>/dev/null; fi
+echo ---
+nproc 2>/dev/null || getconf _NPROCESSORS_ONLN 2>/dev/null || sysctl -n hw.ncpu
+echo ---
+if [ -r /proc/meminfo ]; then awk '/^MemTotal:/ {printf "%.0f\n", $2 * 1024}' /proc/meminfo; else sysctl -n hw.memsize; fi
+echo ---
+echo "$HOME"
+echo ---
+stat --version >/dev/null 2>&1 && echo gnu || echo bsd"#;
+
+// None of this changes while the app runs, so it is probed once per host.
+static CACHE: Lazy<Mutex<HashMap<String, HostInfo>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Which coreutils the host has; the BSD ones lack `stat -c`, `du -b` and
+// `df --output`.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolFlavor {
+    #[default]
+    Gnu,
+    Bsd,
+}
+
+impl ToolFlavor {
+    // `stat` printing `format`, written with the GNU %Y, %s and %n.
+    pub fn stat(self, format: &str) -> String {
+        match self {
+            ToolFlavor::Gnu => format!("stat -c '{format}'"),
+            ToolFlavor::Bsd => {
+                let format = format
+                    .replace("%Y", "%m")
+                    .replace("%s", "%z")
+                    .replace("%n", "%N");
+                format!("stat -f '{format}'")
+            }
+        }
+    }
+
+    // The command for a directory's size and the bytes per unit it prints.
+    pub fn du(self) -> (&'static str, u64) {
+        match self {
+            ToolFlavor::Gnu => ("du -sb", 1),
+            ToolFlavor::Bsd => ("du -sk", 1024),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HostInfo {
+    pub host: String,
+    pub uname: String,          // kernel name, release and machine
+    pub distro: Option<String>, // PRETTY_NAME, or the macOS product name
+    pub cpus: Option<u32>,
+    pub memory_bytes: Option<u64>,
+    pub home: Option<String>,
+    pub tools: ToolFlavor,
+}
+
+fn parse_info(host: String, stdout: &str) -> HostInfo {
+    let sections: Vec<&str> = stdout.split(&format!("{SEPARATOR}\n")).collect();
+    let section = |idx: usize| {
+        let text = sections.get(idx).copied().unwrap_or("").trim();
+        (!text.is_empty()).then(|| text.to_string())
+    };
+    HostInfo {
+        host,
+        uname: section(0).unwrap_or_default(),
+        distro: section(1),
+        cpus: section(2).and_then(|s| s.parse().ok()),
+        memory_bytes: section(3).and_then(|s| s.parse().ok()),
+        home: section(4),
+        tools: match section(5).as_deref() {
+            Some("bsd") => ToolFlavor::Bsd,
+            _ => ToolFlavor::Gnu,
+        },
+    }
+}
+
+pub fn remote_host_info(profile: &HostProfile) -> Result<HostInfo, String> {
+    let host = runs::host_label(profile);
+    if let Some(info) = CACHE.lock().unwrap().get(&host) {
+        return Ok(info.clone());
+    }
+    let out = run_remote_cmd(&creds_from(profile), PROBE.into())?;
+    if out.stdout.trim().is_empty() {
+        return Err(format!("no output from {host}: {}", out.stderr.trim()));
+    }
+    let info = parse_info(host.clone(), &out.stdout);
+    CACHE.lock().unwrap().insert(host, info.clone());
+    Ok(info)
+}
+
+// Hosts that can't be probed are assumed to be the usual Linux cluster.
+pub fn tool_flavor(profile: &HostProfile) -> ToolFlavor {
+    remote_host_info(profile)
+        .map(|info| info.tools)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_info, ToolFlavor};
+
+    #[test]
+    fn parses_probe_and_adapts_commands_to_bsd_tools() {
+        let linux = parse_info(
+            "u@hpc:22".into(),
+            "Linux 5.14.0-362.el9.x86_64 x86_64\n---\nRocky Linux 9.3 (Blue Onyx)\n---\n64\n---\n270014070784\n---\n/home/u\n---\ngnu\n",
+        );
+        assert_eq!(linux.distro.as_deref(), Some("Rocky Linux 9.3 (Blue Onyx)"));
+        assert_eq!(linux.cpus, Some(64));
+        assert_eq!(linux.memory_bytes, Some(270_014_070_784));
+        assert_eq!(linux.home.as_deref(), Some("/home/u"));
+        assert_eq!(linux.tools, ToolFlavor::Gnu);
+
+        let mac = parse_info(
+            "u@mac:22".into(),
+            "Darwin 23.4.0 arm64\n---\nmacOS\n---\n10\n---\n17179869184\n---\n/Users/u\n---\nbsd\n",
+        );
+        assert_eq!(mac.tools, ToolFlavor::Bsd);
+        assert_eq!(ToolFlavor::Bsd.stat("%Y|%s|%n"), "stat -f '%m|%z|%N'");
+        assert_eq!(ToolFlavor::Gnu.stat("%s"), "stat -c '%s'");
+    }
+}
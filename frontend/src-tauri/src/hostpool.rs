@@ -0,0 +1,212 @@
+use crate::hoststats;
+use crate::runs::{self, RunRegistry};
+use crate::{creds_from, run_remote_cmd, HostProfile};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const LOCAL: &str = "local";
+const LOAD_TTL: Duration = Duration::from_secs(60);
+const DEFAULT_MAX_LOAD: f64 = 1.0;
+// works on Linux and macOS; the last line is the core count
+const LOAD_PROBE: &str = "uptime; nproc 2>/dev/null || sysctl -n hw.ncpu";
+
+// Hosts that runs submitted without a fixed host can be placed on.
+static POOL: Lazy<Mutex<Vec<PoolHost>>> = Lazy::new(|| Mutex::new(Vec::new()));
+// host label -> (probed at, load per core); None when the probe failed
+static LOADS: Lazy<Mutex<HashMap<String, LoadSample>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+type LoadSample = (Instant, Option<f64>);
+
+#[derive(Clone, Deserialize)]
+pub struct PoolHost {
+    pub profile: Option<HostProfile>, // None is this machine
+    pub max_concurrent: u32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub max_load: Option<f64>, // 1-minute load per core above which the host is skipped
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolHostState {
+    pub host: String,
+    pub enabled: bool,
+    pub max_concurrent: u32,
+    pub active: usize,
+    pub load: Option<f64>,
+}
+
+// What placement needs to know about one host.
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    active: usize,
+    cap: u32,
+    load: Option<f64>,
+    max_load: f64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+pub fn label(host: &PoolHost) -> String {
+    host.profile
+        .as_ref()
+        .map(runs::host_label)
+        .unwrap_or_else(|| LOCAL.into())
+}
+
+// Load per core from `uptime` followed by the core count.
+fn parse_load(stdout: &str) -> Option<f64> {
+    let [one_minute, _, _] = hoststats::load_averages(stdout)?;
+    let cores: f64 = stdout.lines().last()?.trim().parse().ok()?;
+    (cores > 0.0).then(|| one_minute / cores)
+}
+
+fn probe_load(profile: Option<&HostProfile>) -> Option<f64> {
+    let stdout = match profile {
+        Some(profile) => {
+            let out = run_remote_cmd(&creds_from(profile), LOAD_PROBE.into()).ok()?;
+            (out.code == 0).then_some(out.stdout)?
+        }
+        None => {
+            let out = Command::new("sh").arg("-c").arg(LOAD_PROBE).output().ok()?;
+            String::from_utf8_lossy(&out.stdout).into_owned()
+        }
+    };
+    parse_load(&stdout)
+}
+
+fn cached_load(host: &PoolHost) -> Option<f64> {
+    let key = label(host);
+    if let Some((at, load)) = LOADS.lock().unwrap().get(&key) {
+        if at.elapsed() < LOAD_TTL {
+            return *load;
+        }
+    }
+    let load = probe_load(host.profile.as_ref());
+    LOADS.lock().unwrap().insert(key, (Instant::now(), load));
+    load
+}
+
+fn active_on(label: &str) -> usize {
+    RunRegistry::global()
+        .list()
+        .iter()
+        .filter(|r| runs::is_active(&r.status) && r.host.as_deref().unwrap_or(LOCAL) == label)
+        .count()
+}
+
+// Least busy host relative to its cap, then the lowest load; hosts that are
+// full, overloaded or could not be probed are left out.
+fn choose(candidates: &[Candidate]) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| {
+            c.active < c.cap.max(1) as usize && c.load.is_some_and(|load| load < c.max_load)
+        })
+        .min_by(|(_, a), (_, b)| {
+            let fill = |c: &Candidate| c.active as f64 / c.cap.max(1) as f64;
+            fill(a)
+                .total_cmp(&fill(b))
+                .then(a.load.unwrap_or(0.0).total_cmp(&b.load.unwrap_or(0.0)))
+        })
+        .map(|(idx, _)| idx)
+}
+
+// The host the next pooled run should go to, if any has room right now.
+pub fn place() -> Option<PoolHost> {
+    let hosts: Vec<PoolHost> = POOL
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|h| h.enabled)
+        .cloned()
+        .collect();
+    let candidates: Vec<Candidate> = hosts
+        .iter()
+        .map(|host| {
+            let active = active_on(&label(host));
+            Candidate {
+                active,
+                cap: host.max_concurrent,
+                // a full host is skipped anyway, no need to probe it
+                load: (active < host.max_concurrent.max(1) as usize)
+                    .then(|| cached_load(host))
+                    .flatten(),
+                max_load: host.max_load.unwrap_or(DEFAULT_MAX_LOAD),
+            }
+        })
+        .collect();
+    choose(&candidates).map(|idx| hosts[idx].clone())
+}
+
+pub fn has_hosts() -> bool {
+    POOL.lock().unwrap().iter().any(|h| h.enabled)
+}
+
+pub fn set_pool(hosts: Vec<PoolHost>) -> Result<(), String> {
+    if let Some(host) = hosts.iter().find(|h| h.max_concurrent == 0) {
+        return Err(format!(
+            "max_concurrent must be at least 1 for {}",
+            label(host)
+        ));
+    }
+    *POOL.lock().unwrap() = hosts;
+    LOADS.lock().unwrap().clear();
+    runs::schedule();
+    Ok(())
+}
+
+pub fn pool_state() -> Vec<PoolHostState> {
+    let hosts = POOL.lock().unwrap().clone();
+    let loads = LOADS.lock().unwrap();
+    hosts
+        .iter()
+        .map(|host| {
+            let label = label(host);
+            PoolHostState {
+                active: active_on(&label),
+                load: loads.get(&label).and_then(|(_, load)| *load),
+                host: label,
+                enabled: host.enabled,
+                max_concurrent: host.max_concurrent,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{choose, parse_load, Candidate};
+
+    #[test]
+    fn load_probe_and_placement() {
+        let linux = " 10:01:02 up 3 days,  2 users,  load average: 2.00, 1.50, 1.00\n8\n";
+        assert_eq!(parse_load(linux), Some(0.25));
+        let mac = "10:01  up 3 days, 2 users, load averages: 6.00 5.00 4.00\n4\n";
+        assert_eq!(parse_load(mac), Some(1.5));
+        assert_eq!(parse_load("ssh: connect to host"), None);
+
+        let host = |active, cap, load| Candidate {
+            active,
+            cap,
+            load,
+            max_load: 1.0,
+        };
+        // full, unreachable, overloaded, then two with room
+        let hosts = [
+            host(2, 2, Some(0.1)),
+            host(0, 4, None),
+            host(0, 4, Some(1.2)),
+            host(2, 4, Some(0.3)),
+            host(1, 4, Some(0.6)),
+        ];
+        assert_eq!(choose(&hosts), Some(4));
+        assert_eq!(choose(&hosts[..3]), None);
+    }
+}
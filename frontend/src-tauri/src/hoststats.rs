@@ -0,0 +1,226 @@
+use crate::runs;
+use crate::{creds_from, run_remote_cmd, HostProfile};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Long enough that flipping between hosts in the launch dialog does not
+// reconnect each time, short enough to follow a host getting busy.
+const CACHE_TTL: Duration = Duration::from_secs(15);
+const TOP_PROCESSES: usize = 5;
+const SEPARATOR: &str = "---";
+// exits 127 without output when nvidia-smi is not installed
+const GPU_QUERY: &str = "command -v nvidia-smi >/dev/null || exit 127; \
+    nvidia-smi --query-gpu=index,name,memory.total,memory.used,utilization.gpu \
+    --format=csv,noheader,nounits";
+
+static CACHE: Lazy<Mutex<HashMap<String, (HostStats, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MemoryStats {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: Option<u64>, // older `free` has no available column
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProcessStat {
+    pub pid: u32,
+    pub user: String,
+    pub cpu_percent: f64,
+    pub mem_percent: f64,
+    pub command: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HostStats {
+    pub host: String,
+    pub load: Option<[f64; 3]>, // 1, 5 and 15 minute load averages
+    pub cpus: Option<u32>,
+    pub memory: Option<MemoryStats>,
+    pub top: Vec<ProcessStat>, // busiest by CPU first
+    pub measured_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Gpu {
+    pub index: u32,
+    pub name: String,
+    pub memory_total_mib: Option<u64>,
+    pub memory_used_mib: Option<u64>,
+    pub utilization_percent: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GpuInfo {
+    pub host: String,
+    pub nvidia_smi: bool,      // false when the host has no nvidia-smi at all
+    pub gpus: Vec<Gpu>,        // empty as well when nvidia-smi could not reach the driver
+    pub error: Option<String>, // what nvidia-smi said when it failed
+}
+
+fn probe_command() -> String {
+    format!(
+        "uptime; echo {SEPARATOR}; nproc 2>/dev/null || getconf _NPROCESSORS_ONLN; \
+         echo {SEPARATOR}; free -b; echo {SEPARATOR}; \
+         ps -eo pid,user,pcpu,pmem,comm --sort=-pcpu | head -n {}",
+        TOP_PROCESSES + 1
+    )
+}
+
+// `uptime` prints "load average: 0.52, 0.58, 0.59" on Linux and
+// "load averages: 1.20 1.31 1.40" on macOS.
+pub fn load_averages(text: &str) -> Option<[f64; 3]> {
+    let (_, averages) = text
+        .lines()
+        .find_map(|line| line.split_once("load average"))?;
+    let mut values = averages
+        .trim_start_matches('s')
+        .trim_start_matches(':')
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<f64>().ok());
+    Some([values.next()??, values.next()??, values.next()??])
+}
+
+fn parse_memory(text: &str) -> Option<MemoryStats> {
+    let line = text.lines().find(|l| l.starts_with("Mem:"))?;
+    let columns: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .filter_map(|c| c.parse().ok())
+        .collect();
+    Some(MemoryStats {
+        total_bytes: *columns.first()?,
+        used_bytes: *columns.get(1)?,
+        available_bytes: columns.get(5).copied(),
+    })
+}
+
+fn parse_processes(text: &str) -> Vec<ProcessStat> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(ProcessStat {
+                pid: fields.next()?.parse().ok()?,
+                user: fields.next()?.to_string(),
+                cpu_percent: fields.next()?.parse().ok()?,
+                mem_percent: fields.next()?.parse().ok()?,
+                command: fields.collect::<Vec<_>>().join(" "),
+            })
+        })
+        .collect()
+}
+
+fn parse_stats(host: String, stdout: &str) -> HostStats {
+    let sections: Vec<&str> = stdout.split(&format!("{SEPARATOR}\n")).collect();
+    let section = |idx: usize| sections.get(idx).copied().unwrap_or("");
+    HostStats {
+        host,
+        load: load_averages(section(0)),
+        cpus: section(1).trim().parse().ok(),
+        memory: parse_memory(section(2)),
+        top: parse_processes(section(3)),
+        measured_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+// One GPU per line: "0, NVIDIA A100-SXM4-40GB, 40960, 1024, 3". Fields
+// nvidia-smi cannot read come back as "[N/A]".
+fn parse_gpus(stdout: &str) -> Vec<Gpu> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [index, name, total, used, utilization] = fields[..] else {
+                return None;
+            };
+            Some(Gpu {
+                index: index.parse().ok()?,
+                name: name.to_string(),
+                memory_total_mib: total.parse().ok(),
+                memory_used_mib: used.parse().ok(),
+                utilization_percent: utilization.parse().ok(),
+            })
+        })
+        .collect()
+}
+
+pub fn remote_gpu_info(profile: &HostProfile) -> Result<GpuInfo, String> {
+    let out = run_remote_cmd(&creds_from(profile), GPU_QUERY.into())?;
+    let nvidia_smi = out.code != 127;
+    let error = (nvidia_smi && out.code != 0).then(|| {
+        let said = if out.stderr.trim().is_empty() {
+            &out.stdout
+        } else {
+            &out.stderr
+        };
+        said.trim().to_string()
+    });
+    Ok(GpuInfo {
+        host: runs::host_label(profile),
+        nvidia_smi,
+        gpus: if out.code == 0 {
+            parse_gpus(&out.stdout)
+        } else {
+            Vec::new()
+        },
+        error,
+    })
+}
+
+pub fn remote_host_stats(profile: &HostProfile) -> Result<HostStats, String> {
+    let host = runs::host_label(profile);
+    if let Some((cached, at)) = CACHE.lock().unwrap().get(&host) {
+        if at.elapsed() < CACHE_TTL {
+            return Ok(cached.clone());
+        }
+    }
+    let out = run_remote_cmd(&creds_from(profile), probe_command())?;
+    if out.stdout.trim().is_empty() {
+        return Err(format!("no output from {host}: {}", out.stderr.trim()));
+    }
+    let stats = parse_stats(host.clone(), &out.stdout);
+    CACHE
+        .lock()
+        .unwrap()
+        .insert(host, (stats.clone(), Instant::now()));
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_averages, parse_gpus, parse_stats};
+
+    #[test]
+    fn parses_host_stats_and_gpu_queries() {
+        let stdout = " 10:01:02 up 3 days,  2 users,  load average: 2.00, 1.50, 1.00\n---\n8\n---\n               total        used        free      shared  buff/cache   available\nMem:     16000000000  6000000000  2000000000   100000000  8000000000  9500000000\nSwap:     2000000000           0  2000000000\n---\n    PID USER     %CPU %MEM COMMAND\n   4242 arc      99.5  3.1 g16\n    812 root      1.0  0.2 Web Content\n";
+        let stats = parse_stats("u@hpc:22".into(), stdout);
+        assert_eq!(stats.load, Some([2.0, 1.5, 1.0]));
+        assert_eq!(stats.cpus, Some(8));
+        let memory = stats.memory.unwrap();
+        assert_eq!(memory.total_bytes, 16_000_000_000);
+        assert_eq!(memory.available_bytes, Some(9_500_000_000));
+        assert_eq!(stats.top.len(), 2);
+        assert_eq!(stats.top[0].command, "g16");
+        assert_eq!(stats.top[1].command, "Web Content");
+
+        assert_eq!(
+            load_averages("load averages: 1.20 1.31 1.40"),
+            Some([1.2, 1.31, 1.4])
+        );
+        assert_eq!(parse_stats("h".into(), "ssh: timeout").cpus, None);
+
+        let gpus = parse_gpus(
+            "0, NVIDIA A100-SXM4-40GB, 40960, 1024, 3\n1, Tesla K80, [N/A], [N/A], [N/A]\n",
+        );
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].name, "NVIDIA A100-SXM4-40GB");
+        assert_eq!(gpus[0].memory_total_mib, Some(40960));
+        assert_eq!(gpus[1].utilization_percent, None);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::model::{BatchConfig, RunStatus};
+use crate::scheduler::{self, remote, JobScheduler};
+use crate::HostProfile;
+use std::path::Path;
+
+const SCRIPT_NAME: &str = "run_arc.sh";
+const SUBMIT_NAME: &str = "submit_arc.sub";
+
+// HTCondor takes its settings from the submit description, so the script
+// itself only runs ARC; extra_directives go into the description.
+const DEFAULT_TEMPLATE: &str = "#!/bin/bash -l
+cd {work_dir}
+{command}
+";
+
+pub struct HtCondor;
+
+fn submit_description(batch: &BatchConfig, name: &str, work_dir: &Path) -> String {
+    let dir = work_dir.to_string_lossy();
+    let mut lines = vec![
+        "universe = vanilla".to_string(),
+        format!("executable = {dir}/{SCRIPT_NAME}"),
+        format!("initialdir = {dir}"),
+        format!("batch_name = {name}"),
+        "output = condor.out".to_string(),
+        "error = condor.err".to_string(),
+        "log = condor.log".to_string(),
+        "getenv = True".to_string(),
+    ];
+    if let Some(cpus) = batch.cpus {
+        lines.push(format!("request_cpus = {cpus}"));
+    }
+    if let Some(memory) = &batch.memory {
+        lines.push(format!("request_memory = {memory}"));
+    }
+    lines.extend(batch.extra_directives.iter().cloned());
+    lines.push("queue".into());
+    lines.join("\n") + "\n"
+}
+
+// `condor_submit -terse` prints "<cluster>.<proc> - <cluster>.<proc>".
+fn parse_submit(stdout: &str) -> Option<String> {
+    let id = stdout.split_whitespace().next()?;
+    id.split('.')
+        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        .then(|| id.to_string())
+}
+
+// JobStatus codes from the job ClassAd; 4 needs the exit code to tell a
+// finished run from a failed one.
+fn map_status(code: &str, exit_code: Option<&str>) -> Option<RunStatus> {
+    match code {
+        "1" | "5" => Some(RunStatus::Starting),
+        "2" | "6" | "7" => Some(RunStatus::Running),
+        "3" => Some(RunStatus::Cancelled),
+        "4" if exit_code == Some("0") => Some(RunStatus::Finished),
+        "4" => Some(RunStatus::Failed),
+        _ => None,
+    }
+}
+
+fn parse_ad(stdout: &str) -> Option<RunStatus> {
+    let mut cols = stdout.lines().next()?.split_whitespace();
+    let code = cols.next()?;
+    map_status(code, cols.next())
+}
+
+impl JobScheduler for HtCondor {
+    fn submit(
+        &self,
+        profile: &HostProfile,
+        batch: &BatchConfig,
+        name: &str,
+        work_dir: &Path,
+        command: &str,
+    ) -> Result<String, String> {
+        let script = scheduler::render(batch, DEFAULT_TEMPLATE, "", name, work_dir, command);
+        let description = submit_description(batch, name, work_dir);
+        let stdout = scheduler::write_and_submit(
+            profile,
+            work_dir,
+            &[(SCRIPT_NAME, script), (SUBMIT_NAME, description)],
+            &format!("chmod +x {SCRIPT_NAME} && condor_submit -terse {SUBMIT_NAME}"),
+        )?;
+        parse_submit(&stdout)
+            .ok_or_else(|| format!("unexpected condor_submit output: {}", stdout.trim()))
+    }
+
+    // condor_q covers queued and running jobs, condor_history the rest.
+    fn status(&self, profile: &HostProfile, job_id: &str) -> Option<RunStatus> {
+        let job = shell_escape::escape(job_id.into());
+        let queued =
+            remote(profile, format!("condor_q {job} -af JobStatus ExitCode")).unwrap_or_default();
+        if let Some(status) = parse_ad(&queued) {
+            return Some(status);
+        }
+        let history = remote(
+            profile,
+            format!("condor_history {job} -limit 1 -af JobStatus ExitCode"),
+        )
+        .ok()?;
+        parse_ad(&history)
+    }
+
+    // condor_rm lets the job's own kill signal (SIGTERM by default) through,
+    // which is as graceful as HTCondor gets.
+    fn cancel(
+        &self,
+        profile: &HostProfile,
+        job_id: &str,
+        _signal: Option<&str>,
+    ) -> Result<(), String> {
+        let job = shell_escape::escape(job_id.into());
+        remote(profile, format!("condor_rm {job}")).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_ad, parse_submit, submit_description};
+    use crate::model::{BatchConfig, RunStatus};
+    use std::path::Path;
+
+    #[test]
+    fn submit_file_and_job_ads() {
+        let batch = BatchConfig {
+            cpus: Some(4),
+            ..BatchConfig::default()
+        };
+        let description = submit_description(&batch, "CH4", Path::new("/home/u/CH4"));
+        assert!(description.contains("executable = /home/u/CH4/run_arc.sh\n"));
+        assert!(description.contains("request_cpus = 4\n"));
+        assert!(description.ends_with("queue\n"));
+
+        assert_eq!(parse_submit("812.0 - 812.0\n"), Some("812.0".into()));
+        assert_eq!(parse_submit("ERROR: no such file"), None);
+        assert_eq!(parse_ad("2 undefined\n"), Some(RunStatus::Running));
+        assert_eq!(parse_ad("4 0\n"), Some(RunStatus::Finished));
+        assert_eq!(parse_ad("4 1\n"), Some(RunStatus::Failed));
+        assert_eq!(parse_ad(""), None);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::runs::{self, host_label};
+use crate::{creds_from, run_remote_cmd, ssh, HostProfile};
+use serde_yaml::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+// Extensions of the files an ARC input can point at: geometries, ESS
+// outputs to read a geometry from, and other yml files it pulls in.
+const REFERENCED: &[&str] = &[
+    "xyz", "yml", "yaml", "gjf", "log", "out", "inp", "mol", "sdf",
+];
+
+// A local file the input needs, and where it goes relative to the work dir.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputFile {
+    pub local: PathBuf,
+    pub relative: PathBuf,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct InputFiles {
+    pub local: Vec<InputFile>,
+    pub absolute: Vec<PathBuf>, // expected to exist on the host as written
+    pub missing: Vec<String>,   // "<path> (in <file>)"
+}
+
+fn is_reference(text: &str) -> bool {
+    !text.contains('\n')
+        && Path::new(text.trim())
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| REFERENCED.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+fn references(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(s) if is_reference(s) => out.push(s.trim().to_string()),
+        Value::Sequence(items) => items.iter().for_each(|v| references(v, out)),
+        Value::Mapping(map) => map.values().for_each(|v| references(v, out)),
+        Value::Tagged(tagged) => references(&tagged.value, out),
+        _ => {}
+    }
+}
+
+fn is_yaml(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yml" | "yaml")
+    )
+}
+
+// Relative references resolve against the file that mentions them and keep
+// their place relative to the input, so the input needs no rewriting. Yml
+// files it references are followed in turn.
+pub fn collect(input: &Path) -> Result<InputFiles, String> {
+    let base = input.parent().unwrap_or(Path::new(""));
+    let mut files = InputFiles::default();
+    let mut seen = HashSet::from([input.to_path_buf()]);
+    let mut pending = vec![(input.to_path_buf(), PathBuf::new())];
+    while let Some((file, relative_dir)) = pending.pop() {
+        let text =
+            std::fs::read_to_string(&file).map_err(|e| format!("read {}: {e}", file.display()))?;
+        let doc: Value =
+            serde_yaml::from_str(&text).map_err(|e| format!("parse {}: {e}", file.display()))?;
+        let mut found = Vec::new();
+        references(&doc, &mut found);
+        let from = file
+            .strip_prefix(base)
+            .unwrap_or(&file)
+            .display()
+            .to_string();
+        for reference in found {
+            let path = Path::new(&reference);
+            if path.is_absolute() {
+                files.absolute.push(path.to_path_buf());
+                continue;
+            }
+            let relative = relative_dir.join(path);
+            let local = base.join(&relative);
+            if !local.is_file() {
+                files.missing.push(format!("{reference} (in {from})"));
+                continue;
+            }
+            if !seen.insert(local.clone()) {
+                continue;
+            }
+            if is_yaml(&local) {
+                let dir = relative.parent().map(Path::to_path_buf).unwrap_or_default();
+                pending.push((local.clone(), dir));
+            }
+            files.local.push(InputFile { local, relative });
+        }
+    }
+    Ok(files)
+}
+
+// Absolute references the host does not have.
+fn missing_on_host(profile: &HostProfile, paths: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let list: Vec<String> = paths
+        .iter()
+        .map(|p| shell_escape::escape(p.to_string_lossy()).to_string())
+        .collect();
+    let command = format!(
+        "for f in {}; do [ -e \"$f\" ] || echo \"$f\"; done",
+        list.join(" ")
+    );
+    let out = run_remote_cmd(&creds_from(profile), command)?;
+    Ok(out.stdout.lines().map(PathBuf::from).collect())
+}
+
+// Checks everything the input references before touching the host, then
+// creates the work dir and uploads the input next to its files.
+pub fn upload(profile: &HostProfile, input: &Path, work_dir: &Path) -> Result<PathBuf, String> {
+    let file_name = input
+        .file_name()
+        .ok_or_else(|| format!("not a file: {}", input.display()))?;
+    let mut files = collect(input)?;
+    for path in missing_on_host(profile, &files.absolute)? {
+        files.missing.push(format!(
+            "{} (not on {})",
+            path.display(),
+            host_label(profile)
+        ));
+    }
+    if !files.missing.is_empty() {
+        return Err(format!(
+            "{} references files that are missing:\n  {}",
+            input.display(),
+            files.missing.join("\n  ")
+        ));
+    }
+
+    runs::create_work_dir(Some(profile), work_dir)?;
+    let creds = creds_from(profile);
+    for file in &files.local {
+        let remote = work_dir.join(&file.relative);
+        if let Some(dir) = remote.parent().filter(|d| *d != work_dir) {
+            runs::create_work_dir(Some(profile), dir)?;
+        }
+        ssh::upload(&creds, &file.local, &remote)?;
+    }
+    let remote = work_dir.join(file_name);
+    ssh::upload(&creds, input, &remote)?;
+    Ok(remote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect;
+    use std::path::PathBuf;
+
+    #[test]
+    fn collects_nested_references_and_reports_missing_ones() {
+        let base = std::env::temp_dir().join(format!("inputs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(base.join("geoms")).unwrap();
+        let input = base.join("input.yml");
+        std::fs::write(
+            &input,
+            "project: CH4\nlevel_of_theory: b3lyp/6-31g\nspecies:\n- label: CH4\n  xyz: geoms/CH4.xyz\n- label: OH\n  xyz: OH.log\n- label: H2O\n  xyz: /shared/H2O.xyz\ninclude: geoms/more.yml\n",
+        )
+        .unwrap();
+        std::fs::write(base.join("geoms/CH4.xyz"), "C 0 0 0\n").unwrap();
+        std::fs::write(
+            base.join("geoms/more.yml"),
+            "species:\n- xyz: H2.xyz\n- xyz: N2.xyz\n",
+        )
+        .unwrap();
+        std::fs::write(base.join("geoms/H2.xyz"), "H 0 0 0\n").unwrap();
+
+        let files = collect(&input).unwrap();
+        let relative: Vec<PathBuf> = files.local.iter().map(|f| f.relative.clone()).collect();
+        assert_eq!(
+            relative,
+            [
+                PathBuf::from("geoms/CH4.xyz"),
+                PathBuf::from("geoms/more.yml"),
+                PathBuf::from("geoms/H2.xyz"),
+            ]
+        );
+        assert_eq!(files.absolute, [PathBuf::from("/shared/H2O.xyz")]);
+        assert_eq!(
+            files.missing,
+            ["OH.log (in input.yml)", "N2.xyz (in geoms/more.yml)"]
+        );
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::progress::job_type;
+use crate::{creds_from, run_remote_cmd, runs, HostProfile};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_yaml::Value;
+use std::path::Path;
+
+static RUNNING_JOB: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bRunning\b.*?\bjob (\S+)(?: for (\S+))?").unwrap());
+static ENDING_JOB: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bEnding job (\S+)(?: for (\S+))?").unwrap());
+static FAILED_JOB: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b[Jj]ob (\S+)(?: for (\S+))?.*\b(errored|failed|crashed)\b").unwrap()
+});
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+// One electronic-structure job ARC launched inside a run.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EssJob {
+    pub name: String,          // e.g. "opt_a12"
+    pub label: Option<String>, // species or TS label
+    pub job_type: String,
+    pub server: Option<String>,
+    pub job_id: Option<String>,
+    pub state: JobState,
+}
+
+fn entry<'a>(jobs: &'a mut Vec<EssJob>, name: &str) -> &'a mut EssJob {
+    let idx = match jobs.iter().position(|j| j.name == name) {
+        Some(idx) => idx,
+        None => {
+            jobs.push(EssJob {
+                name: name.to_string(),
+                label: None,
+                job_type: job_type(name).to_string(),
+                server: None,
+                job_id: None,
+                state: JobState::Running,
+            });
+            jobs.len() - 1
+        }
+    };
+    &mut jobs[idx]
+}
+
+fn set_from_log(jobs: &mut Vec<EssJob>, caps: &regex::Captures, state: JobState) {
+    let job = entry(jobs, &caps[1]);
+    if let Some(label) = caps.get(2) {
+        job.label = Some(label.as_str().to_string());
+    }
+    job.state = state;
+}
+
+// Jobs in the order arc.log first mentions them.
+fn parse_log(log: &str) -> Vec<EssJob> {
+    let mut jobs = Vec::new();
+    for line in log.lines() {
+        if let Some(c) = FAILED_JOB.captures(line) {
+            set_from_log(&mut jobs, &c, JobState::Failed);
+        } else if let Some(c) = ENDING_JOB.captures(line) {
+            set_from_log(&mut jobs, &c, JobState::Done);
+        } else if let Some(c) = RUNNING_JOB.captures(line) {
+            set_from_log(&mut jobs, &c, JobState::Running);
+        }
+    }
+    jobs
+}
+
+fn scalar(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+// ARC keeps job_status as [server status, {status: ess status, ...}].
+fn map_status(value: Option<&Value>) -> Option<JobState> {
+    let status = match value? {
+        Value::Sequence(parts) => parts.first()?.as_str()?,
+        other => other.as_str()?,
+    };
+    match status {
+        "initializing" | "queued" | "pending" => Some(JobState::Queued),
+        "running" => Some(JobState::Running),
+        "done" => Some(JobState::Done),
+        "errored" | "failed" => Some(JobState::Failed),
+        _ => None,
+    }
+}
+
+// `running_jobs` in restart.yml: label -> job names (older ARC) or job
+// dicts carrying the server, scheduler job id and status.
+fn merge_restart(jobs: &mut Vec<EssJob>, restart: &Value) {
+    let Some(running) = restart.get("running_jobs").and_then(Value::as_mapping) else {
+        return;
+    };
+    for (label, listed) in running {
+        let label = label.as_str().map(str::to_string);
+        for item in listed.as_sequence().into_iter().flatten() {
+            let Some(name) = item
+                .as_str()
+                .map(str::to_string)
+                .or_else(|| scalar(item.get("job_name")))
+            else {
+                continue;
+            };
+            let job = entry(jobs, &name);
+            job.label = job.label.take().or_else(|| label.clone());
+            if let Some(kind) = scalar(item.get("job_type")) {
+                job.job_type = kind;
+            }
+            job.server = scalar(item.get("server"))
+                .or_else(|| scalar(item.get("job_server_name")))
+                .or(job.server.take());
+            job.job_id = scalar(item.get("job_id")).or(job.job_id.take());
+            // a job ARC still tracks has not ended, whatever the log says
+            job.state = map_status(item.get("job_status")).unwrap_or(JobState::Running);
+        }
+    }
+}
+
+pub fn parse_jobs(log: &str, restart: Option<&str>) -> Vec<EssJob> {
+    let mut jobs = parse_log(log);
+    if let Some(doc) = restart.and_then(|text| serde_yaml::from_str::<Value>(text).ok()) {
+        merge_restart(&mut jobs, &doc);
+    }
+    jobs
+}
+
+fn read_file(profile: Option<&HostProfile>, path: &Path) -> Option<String> {
+    match profile {
+        Some(profile) => {
+            let file = shell_escape::escape(path.to_string_lossy());
+            let out = run_remote_cmd(&creds_from(profile), format!("cat {file}")).ok()?;
+            (out.code == 0).then_some(out.stdout)
+        }
+        None => std::fs::read_to_string(path).ok(),
+    }
+}
+
+pub fn run_jobs(id: String) -> Result<Vec<EssJob>, String> {
+    let run = runs::get_run(id)?;
+    let profile = runs::run_profile(&run.id);
+    let profile = profile.as_ref();
+    let log = runs::locate_project_file(profile, &run.work_dir, "arc.log")
+        .ok_or_else(|| format!("no arc.log under {} yet", run.work_dir.display()))?;
+    let text = read_file(profile, &log).ok_or_else(|| format!("read {}", log.display()))?;
+    let restart = log
+        .parent()
+        .and_then(|dir| read_file(profile, &dir.join("restart.yml")));
+    Ok(parse_jobs(&text, restart.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_jobs, JobState};
+
+    #[test]
+    fn merges_log_and_restart_jobs() {
+        let log = "Considering species: CH4\n\
+            Running local job opt_a1 for CH4\n\
+            Ending job opt_a1 for CH4 (run time: 0:00:27)\n\
+            Running job freq_a2 for CH4\n\
+            Running job sp_a3 for OH\n\
+            Job sp_a3 for OH errored with a memory error\n\
+            Running job opt_a4 for OH\n";
+        let restart = "running_jobs:\n  CH4:\n  - job_name: freq_a2\n    job_id: 81234\n    server: server1\n    job_status: [running, {status: initializing}]\n  OH: [opt_a4]\n";
+
+        let jobs = parse_jobs(log, Some(restart));
+        let names: Vec<&str> = jobs.iter().map(|j| j.name.as_str()).collect();
+        assert_eq!(names, ["opt_a1", "freq_a2", "sp_a3", "opt_a4"]);
+        assert_eq!(jobs[0].state, JobState::Done);
+        assert_eq!(jobs[0].job_type, "opt");
+        assert_eq!(jobs[1].server.as_deref(), Some("server1"));
+        assert_eq!(jobs[1].job_id.as_deref(), Some("81234"));
+        assert_eq!(jobs[1].state, JobState::Running);
+        assert_eq!(jobs[2].state, JobState::Failed);
+        assert_eq!(jobs[3].label.as_deref(), Some("OH"));
+        assert_eq!(parse_jobs("", None), vec![]);
+    }
+}
@@ -1,3 +1,79 @@
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+// Everything but the Tauri commands: tmux, SSH, control mode, runs and the
+// types shared with the frontend. The binary in main.rs only wraps this in
+// commands.
+pub mod ansi;
+pub mod applog;
+pub mod arccheck;
+pub mod archive;
+pub mod backend;
+pub mod batch;
+pub mod captures;
+pub mod cleanup;
+pub mod coalesce;
+pub mod compress;
+pub mod conda;
+pub mod configcheck;
+pub mod context;
+pub mod control;
+pub mod diagnostics;
+pub mod diskusage;
+pub mod errors;
+pub mod eta;
+pub mod export;
+pub mod fetch;
+pub mod filepoll;
+pub mod health;
+pub mod history;
+pub mod hostinfo;
+pub mod hostpool;
+pub mod hoststats;
+pub mod htcondor;
+pub mod inputs;
+pub mod jobs;
+pub mod listcache;
+pub mod logstream;
+pub mod manifest;
 pub mod model;
-pub use model::{ARCRun, RunStatus}; // re-export for easier access
+pub mod names;
+pub mod notifications;
+pub mod oge;
+pub mod pbs;
+pub mod perf;
+pub mod prefetch;
+pub mod profilebundle;
+pub mod profilecheck;
+pub mod profiles;
+pub mod progress;
+pub mod pty;
+pub mod pyenvs;
+pub mod quota;
+pub mod recovery;
+pub mod requests;
+pub mod resources;
+pub mod results;
+pub mod runs;
+pub mod scheduler;
+pub mod scrollback;
+pub mod slurm;
+pub mod spill;
+pub mod ssh;
+pub mod standby;
+pub mod stats;
+pub mod timeline;
+pub mod tmux;
+pub mod tmuxcmd;
+pub mod tmuxpath;
+pub mod utf8;
+pub mod versions;
+pub mod watch;
+pub mod watchdog;
+pub mod wire;
+
+pub use model::{ARCRun, HostProfile, RunStatus}; // re-export for easier access
+pub use ssh::{auth_mode, creds_from, remote_shell_command, run_remote_cmd};
+pub use tmux::{
+    apply_window_names, build_tmux_send_keys_commands, ensure_window_ids,
+    format_remote_tmux_command, is_placeholder_name, local_capture, remote_capture_command,
+    remote_sessions, session_or_default, tmux_exec, Detail, Snapshot, TmuxCommand, TmuxSession,
+    TmuxWindow, WINDOW_NAMES_FORMAT,
+};
@@ -0,0 +1,111 @@
+use crate::{coalesce, runs, HostProfile};
+use once_cell::sync::Lazy;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// tmux subcommands that change what list-sessions or list-windows print
+const MUTATING: &[&str] = &[
+    "new-session",
+    "kill-session",
+    "rename-session",
+    "new-window",
+    "kill-window",
+    "rename-window",
+    "move-window",
+    "swap-window",
+    "kill-server",
+];
+
+// (host label or "local", session); no session is the session listing
+type Key = (String, Option<String>);
+type Entry = (Instant, Arc<dyn Any + Send + Sync>);
+
+static ENTRIES: Lazy<Mutex<HashMap<Key, Entry>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// a UI polling every second or so still gets most answers from here
+static TTL_MS: AtomicU64 = AtomicU64::new(2000);
+
+fn host(profile: Option<&HostProfile>) -> String {
+    profile
+        .map(runs::host_label)
+        .unwrap_or_else(|| "local".into())
+}
+
+// 0 turns the cache off.
+pub fn set_ttl(ttl_ms: u64) {
+    TTL_MS.store(ttl_ms, Ordering::Relaxed);
+    if ttl_ms == 0 {
+        ENTRIES.lock().unwrap().clear();
+    }
+}
+
+// Returns the listing cached for (profile, session) while it is fresh, and
+// loads and keeps it otherwise. Errors are not kept.
+pub fn cached<T: Clone + Send + Sync + 'static>(
+    profile: Option<&HostProfile>,
+    session: Option<&str>,
+    load: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let ttl = Duration::from_millis(TTL_MS.load(Ordering::Relaxed));
+    let key = (host(profile), session.map(str::to_string));
+    if let Some((at, value)) = ENTRIES.lock().unwrap().get(&key) {
+        if at.elapsed() < ttl {
+            if let Some(value) = value.downcast_ref::<T>() {
+                return Ok(value.clone());
+            }
+        }
+    }
+    // a burst of misses for the same listing runs it once
+    let label = format!("list {}", session.unwrap_or(""));
+    let value = coalesce::shared(profile, label, load)?;
+    if !ttl.is_zero() {
+        ENTRIES
+            .lock()
+            .unwrap()
+            .insert(key, (Instant::now(), Arc::new(value.clone())));
+    }
+    Ok(value)
+}
+
+// Drops every listing of the host. Window commands often only know a window
+// id, so a change anywhere on the host invalidates all of its sessions.
+pub fn invalidate(profile: Option<&HostProfile>) {
+    let host = host(profile);
+    ENTRIES.lock().unwrap().retain(|(h, _), _| *h != host);
+}
+
+pub fn invalidates(args: &[String]) -> bool {
+    args.first().is_some_and(|a| MUTATING.contains(&a.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cached, invalidate, invalidates};
+
+    #[test]
+    fn listings_are_reused_until_invalidated() {
+        let loads = std::cell::Cell::new(0);
+        let list = || {
+            cached(None, Some("arc-cache-test"), || {
+                loads.set(loads.get() + 1);
+                Ok(vec!["arc".to_string()])
+            })
+            .unwrap()
+        };
+        list();
+        list();
+        assert_eq!(loads.get(), 1);
+        invalidate(None);
+        list();
+        assert_eq!(loads.get(), 2);
+
+        assert!(invalidates(&[
+            "kill-window".into(),
+            "-t".into(),
+            "@1".into()
+        ]));
+        assert!(!invalidates(&["capture-pane".into()]));
+    }
+}
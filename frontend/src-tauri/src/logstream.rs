@@ -0,0 +1,401 @@
+use crate::ssh;
+use crate::{creds_from, HostProfile};
+use notify::{RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+static MANAGER: Lazy<LogStreamManager> = Lazy::new(LogStreamManager::new);
+// tells a stream's thread apart from a later stream under the same key
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(0);
+
+pub struct LogStreamManager {
+    inner: Mutex<HashMap<String, StreamHandle>>,
+}
+
+struct StreamHandle {
+    token: u64,
+    stop_tx: mpsc::Sender<()>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+// Follows a growing file from a byte offset and hands back complete lines.
+#[derive(Debug, Default)]
+pub struct TailReader {
+    offset: u64,
+    pending: Vec<u8>,
+}
+
+impl TailReader {
+    pub fn at_end(path: &Path) -> std::io::Result<Self> {
+        let offset = std::fs::metadata(path)?.len();
+        Ok(Self {
+            offset,
+            pending: Vec::new(),
+        })
+    }
+
+    pub fn read_lines(&mut self, path: &Path) -> std::io::Result<Vec<String>> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            // truncated or replaced: follow the new file from the top
+            self.offset = 0;
+            self.pending.clear();
+        }
+        if len == self.offset {
+            return Ok(Vec::new());
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        file.take(len - self.offset)
+            .read_to_end(&mut self.pending)?;
+        self.offset = len;
+        Ok(self.take_lines())
+    }
+
+    // Same line splitting for bytes that arrive some other way, e.g. from a
+    // remote `tail -F`.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(bytes);
+        self.take_lines()
+    }
+
+    fn take_lines(&mut self) -> Vec<String> {
+        let Some(last_newline) = self.pending.iter().rposition(|&b| b == b'\n') else {
+            return Vec::new();
+        };
+        let rest = self.pending.split_off(last_newline + 1);
+        let complete = std::mem::replace(&mut self.pending, rest);
+        String::from_utf8_lossy(&complete)
+            .lines()
+            .map(|l| l.trim_end_matches('\r').to_string())
+            .collect()
+    }
+}
+
+// Keeps at most `max_backlog` of the newest lines; returns how many were
+// dropped so the frontend can show a gap marker instead of freezing.
+fn bound_backlog(lines: &mut Vec<String>, max_backlog: usize) -> usize {
+    let dropped = lines.len().saturating_sub(max_backlog);
+    if dropped > 0 {
+        lines.drain(..dropped);
+    }
+    dropped
+}
+
+// `at` is when the lines were read, which is what orders them across runs
+// in the multiplexed view.
+fn emit_lines(app: &AppHandle, event: &str, id: &str, mut lines: Vec<String>) {
+    let mut dropped = bound_backlog(&mut lines, LogStreamManager::MAX_BACKLOG);
+    for batch in lines.chunks(LogStreamManager::MAX_BATCH) {
+        let payload = json!({
+            "id": id,
+            "at": chrono::Utc::now().to_rfc3339(),
+            "lines": batch,
+            "dropped": dropped,
+        });
+        let _ = app.emit(event, payload);
+        dropped = 0;
+        thread::sleep(LogStreamManager::BATCH_PAUSE);
+    }
+}
+
+// Sent once when a stream stops by itself: `error` is None when the log or
+// the remote tail simply ended.
+fn emit_end(app: &AppHandle, event: &str, id: &str, error: Option<String>) {
+    let payload = json!({ "id": id, "stream": event, "error": error });
+    let _ = app.emit(LogStreamManager::END_EVENT, payload);
+}
+
+impl LogStreamManager {
+    const EVENT: &'static str = "run-log-line";
+    const END_EVENT: &'static str = "run-log-end";
+    const MULTIPLEX_EVENT: &'static str = "runs-log-multiplex";
+    // multiplexed streams are keyed apart from a run's own stream
+    const MULTIPLEX_PREFIX: &'static str = "multiplex:";
+    const MAX_BATCH: usize = 500;
+    const MAX_BACKLOG: usize = 5000;
+    const BATCH_PAUSE: Duration = Duration::from_millis(50);
+    const POLL: Duration = Duration::from_millis(500);
+
+    fn new() -> Self {
+        Self {
+            inner: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn global() -> &'static Self {
+        &MANAGER
+    }
+
+    pub fn start(&self, app: AppHandle, run_id: String, log_path: PathBuf) -> Result<(), String> {
+        self.follow_local(app, run_id.clone(), run_id, log_path, Self::EVENT)
+    }
+
+    pub fn start_remote(
+        &self,
+        app: AppHandle,
+        run_id: String,
+        profile: HostProfile,
+        log_path: PathBuf,
+    ) -> Result<(), String> {
+        self.follow_remote(app, run_id.clone(), run_id, profile, log_path, Self::EVENT)
+    }
+
+    // Adds a run to the multiplexed stream; `profile` is None for local runs.
+    pub fn start_multiplexed(
+        &self,
+        app: AppHandle,
+        run_id: String,
+        profile: Option<HostProfile>,
+        log_path: PathBuf,
+    ) -> Result<(), String> {
+        let key = format!("{}{run_id}", Self::MULTIPLEX_PREFIX);
+        match profile {
+            Some(profile) => {
+                self.follow_remote(app, key, run_id, profile, log_path, Self::MULTIPLEX_EVENT)
+            }
+            None => self.follow_local(app, key, run_id, log_path, Self::MULTIPLEX_EVENT),
+        }
+    }
+
+    pub fn stop_multiplexed(&self) {
+        let keys: Vec<String> = self
+            .inner
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|k| k.starts_with(Self::MULTIPLEX_PREFIX))
+            .cloned()
+            .collect();
+        for key in keys {
+            let _ = self.stop(&key);
+        }
+    }
+
+    fn follow_local(
+        &self,
+        app: AppHandle,
+        key: String,
+        run_id: String,
+        log_path: PathBuf,
+        event: &'static str,
+    ) -> Result<(), String> {
+        let mut tail = TailReader::at_end(&log_path).map_err(|e| format!("open log: {e}"))?;
+
+        let (change_tx, change_rx) = mpsc::channel::<()>();
+        let mut watcher =
+            notify::recommended_watcher(move |_res: notify::Result<notify::Event>| {
+                let _ = change_tx.send(());
+            })
+            .map_err(|e| format!("watch log: {e}"))?;
+        watcher
+            .watch(&log_path, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("watch log: {e}"))?;
+
+        self.spawn(key, move |stop_rx| {
+            // the watcher stops delivering events once dropped
+            let _watcher = watcher;
+            loop {
+                if stop_rx.try_recv().is_ok() {
+                    break;
+                }
+                // a timeout doubles as a slow poll in case an event was missed
+                if let Err(mpsc::RecvTimeoutError::Disconnected) =
+                    change_rx.recv_timeout(LogStreamManager::POLL)
+                {
+                    break;
+                }
+                while change_rx.try_recv().is_ok() {}
+
+                match tail.read_lines(&log_path) {
+                    Ok(lines) => emit_lines(&app, event, &run_id, lines),
+                    // ARC moves arc.log aside when it restarts in the same
+                    // directory; the new one shows up shortly after
+                    Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    Err(e) => {
+                        emit_end(&app, event, &run_id, Some(format!("read log: {e}")));
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    // Remote logs are followed with `tail -F` on a dedicated connection so
+    // switching it to non-blocking mode does not affect the shared exec session.
+    fn follow_remote(
+        &self,
+        app: AppHandle,
+        key: String,
+        run_id: String,
+        profile: HostProfile,
+        log_path: PathBuf,
+        event: &'static str,
+    ) -> Result<(), String> {
+        let sess = ssh::connect_dedicated(&creds_from(&profile))?;
+        let mut channel = sess
+            .channel_session()
+            .map_err(|e| format!("channel: {e}"))?;
+        let cmd = format!(
+            "tail -n 0 -F {}",
+            shell_escape::escape(log_path.to_string_lossy())
+        );
+        channel.exec(&cmd).map_err(|e| format!("tail exec: {e}"))?;
+        sess.set_blocking(false);
+
+        self.spawn(key, move |stop_rx| {
+            let _sess = sess;
+            let mut tail = TailReader::default();
+            let mut buf = [0u8; 8192];
+            loop {
+                if stop_rx.try_recv().is_ok() {
+                    let _ = channel.close();
+                    break;
+                }
+                let mut lines = Vec::new();
+                let mut failed = None;
+                loop {
+                    match channel.read(&mut buf) {
+                        Ok(0) => break,
+                        Ok(n) => lines.extend(tail.feed(&buf[..n])),
+                        Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                        Err(e) => {
+                            failed = Some(format!("read tail: {e}"));
+                            break;
+                        }
+                    }
+                }
+                emit_lines(&app, event, &run_id, lines);
+                if failed.is_some() || channel.eof() {
+                    emit_end(&app, event, &run_id, failed);
+                    break;
+                }
+                thread::sleep(LogStreamManager::POLL);
+            }
+        })
+    }
+
+    // Runs `follow` on a thread of its own, registered under `key` until it
+    // is stopped or returns by itself. The check, the spawn and the insert
+    // share one lock, so two starts cannot both take the key and a thread
+    // that returns at once cannot clean up before it is registered.
+    fn spawn(
+        &self,
+        key: String,
+        follow: impl FnOnce(mpsc::Receiver<()>) + Send + 'static,
+    ) -> Result<(), String> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.contains_key(&key) {
+            return Err("log stream already running".into());
+        }
+        let token = NEXT_TOKEN.fetch_add(1, Ordering::Relaxed);
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let thread = {
+            let key = key.clone();
+            thread::spawn(move || {
+                follow(stop_rx);
+                LogStreamManager::global().finished(&key, token);
+            })
+        };
+        let handle = StreamHandle {
+            token,
+            stop_tx,
+            thread: Some(thread),
+        };
+        inner.insert(key, handle);
+        Ok(())
+    }
+
+    // Drops the entry of a stream whose thread ended by itself, unless stop()
+    // already took it or a new stream took over the key.
+    fn finished(&self, key: &str, token: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.get(key).is_some_and(|h| h.token == token) {
+            inner.remove(key);
+        }
+    }
+
+    pub fn stop(&self, run_id: &str) -> Result<(), String> {
+        let handle = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.remove(run_id)
+        };
+        match handle {
+            Some(mut handle) => {
+                let _ = handle.stop_tx.send(());
+                if let Some(thread) = handle.thread.take() {
+                    let _ = thread.join();
+                }
+                Ok(())
+            }
+            None => Err("log stream not running".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bound_backlog, LogStreamManager, TailReader};
+    use std::io::Write;
+
+    #[test]
+    fn tail_reader_returns_only_complete_new_lines() {
+        let path = std::env::temp_dir().join(format!("tail-{}.log", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "old line\n").unwrap();
+        let mut tail = TailReader::at_end(&path).unwrap();
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(file, "first\nsecond ha").unwrap();
+        assert_eq!(tail.read_lines(&path).unwrap(), vec!["first".to_string()]);
+        writeln!(file, "lf").unwrap();
+        assert_eq!(
+            tail.read_lines(&path).unwrap(),
+            vec!["second half".to_string()]
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn bound_backlog_keeps_newest_lines() {
+        let mut lines: Vec<String> = (0..10).map(|i| i.to_string()).collect();
+        assert_eq!(bound_backlog(&mut lines, 4), 6);
+        assert_eq!(lines, vec!["6", "7", "8", "9"]);
+    }
+
+    #[test]
+    fn a_key_streams_once_and_is_freed_when_its_stream_ends() {
+        let manager = LogStreamManager::global();
+        let key = format!("stream-{}", uuid::Uuid::new_v4());
+        let running = |m: &LogStreamManager| m.inner.lock().unwrap().contains_key(&key);
+
+        manager
+            .spawn(key.clone(), |stop_rx| {
+                let _ = stop_rx.recv();
+            })
+            .unwrap();
+        assert!(manager.spawn(key.clone(), |_stop_rx| {}).is_err());
+        assert!(manager.stop(&key).is_ok());
+
+        manager.spawn(key.clone(), |_stop_rx| {}).unwrap();
+        for _ in 0..100 {
+            if !running(manager) {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(!running(manager));
+        assert!(manager.stop(&key).is_err());
+    }
+}
@@ -2,1138 +2,1167 @@ use serde::Serialize;
 use serde_json::Value as JsonValue;
 use std::process::Command as PCommand;
 use tauri::Manager;
-use which::which;
 
-mod control;
-mod ssh;
-use ssh::{exec as ssh_exec, SshCreds};
+use frontend_lib::backend::{LocalBackend, SshBackend, TmuxBackend};
+use frontend_lib::errors::OrchestratorError;
+use frontend_lib::model::{ARCRun, AppConfig, EnvVersions, PythonEnv, WebhookConfig};
+use frontend_lib::requests::{
+    CaptureRequest, Remote, RenameSessionRequest, RenameWindowRequest, SendKeysRequest,
+    WindowOutcome, WindowRef,
+};
+use frontend_lib::ssh::exec as ssh_exec;
+use frontend_lib::tmuxcmd::RemoteTmuxCommand;
+use frontend_lib::{
+    ansi, applog, arccheck, archive, backend, captures, cleanup, coalesce, compress, conda,
+    configcheck, context, control, creds_from, diagnostics, diskusage, ensure_window_ids, errors,
+    export, fetch, health, history, hostinfo, hostpool, hoststats, jobs, listcache, local_capture,
+    logstream, manifest, names, notifications, perf, prefetch, profilebundle, profilecheck,
+    profiles, pty, pyenvs, quota, recovery, remote_capture_command, remote_sessions, resources,
+    results, run_remote_cmd, runs, scrollback, session_or_default, spill, standby, stats, timeline,
+    utf8, versions, watch, wire, Detail, HostProfile, Snapshot, TmuxSession, TmuxWindow,
+};
 
-// ---- types shared with frontend ----
-#[derive(serde::Deserialize)]
-struct HostProfile {
-    host: String,
-    port: Option<u16>,
-    user: String,
-    auth: Option<String>,     // "agent" | "key" | "password"
-    password: Option<String>, // only when auth == "password"
-    key_path: Option<String>,
-    key_pass: Option<String>,
-    use_agent: Option<bool>, // legacy switch; respected if auth not set
+// ----------------- LOCAL TMUX -----------------
+
+// Commands run their SSH and subprocess work on the blocking pool, so a slow
+// host holds up only its own invoke and the rest of the UI keeps going.
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| format!("command panicked: {e}"))
 }
 
-#[derive(Serialize)]
-struct TmuxWindow {
-    index: u32,
-    id: String,
-    name: String,
-    active: bool,
-    panes: u32,
+#[tauri::command]
+async fn tmux_list_sessions() -> Result<Vec<TmuxSession>, OrchestratorError> {
+    blocking(|| LocalBackend.list_sessions())
+        .await?
+        .map_err(OrchestratorError::from)
 }
 
-#[derive(Serialize)]
-struct TmuxSession {
-    name: String,
-    windows: u32,
-    attached: bool,
+#[tauri::command]
+async fn listing_cache_set_ttl(ttl_ms: u64) {
+    listcache::set_ttl(ttl_ms)
 }
 
-#[derive(Serialize)]
-struct Snapshot {
-    windows: Vec<TmuxWindow>,
-    pane: String,
-}
-
-fn is_placeholder_name(name: &str, index: u32) -> bool {
-    let trimmed = name.trim();
-    if trimmed.is_empty() {
-        return true;
-    }
-    trimmed.parse::<u32>().map(|n| n == index).unwrap_or(false)
-}
-
-fn tmux_target(session: &str, window: &TmuxWindow) -> String {
-    let id = window.id.trim();
-    if !id.is_empty() {
-        id.to_string()
-    } else {
-        format!("{}:{}", session, window.index)
-    }
-}
-
-fn hydrate_local_names(session: &str, windows: &mut [TmuxWindow]) -> Result<(), String> {
-    if windows.is_empty() {
-        return Ok(());
-    }
-    let tmux_path = which("tmux").map_err(|e| e.to_string())?;
-    for win in windows.iter_mut() {
-        if !is_placeholder_name(&win.name, win.index) {
-            continue;
-        }
-        let target = tmux_target(session, win);
-        let out = PCommand::new(&tmux_path)
-            .args([
-                "display-message",
-                "-p",
-                "-t",
-                &target,
-                "-F",
-                "#{window_name}",
-            ])
-            .output()
-            .map_err(|e| e.to_string())?;
-        if !out.status.success() {
-            continue;
-        }
-        let name = String::from_utf8_lossy(&out.stdout)
-            .trim_end_matches(['\r', '\n'])
-            .trim()
-            .to_string();
-        if !name.is_empty() {
-            win.name = name;
-        }
-    }
-    Ok(())
-}
-
-fn hydrate_remote_names(
-    session: &str,
-    windows: &mut [TmuxWindow],
-    creds: &SshCreds<'_>,
-) -> Result<(), String> {
-    if windows.is_empty() {
-        return Ok(());
-    }
-    for win in windows.iter_mut() {
-        if !is_placeholder_name(&win.name, win.index) {
-            continue;
-        }
-        let target = tmux_target(session, win);
-        let escaped = shell_escape::escape(target.into());
-        let cmd = format!(
-            "tmux display-message -p -t {} -F '#{{window_name}}'",
-            escaped
-        );
-        let out = ssh_exec(creds, &cmd)?;
-        if out.code != 0 {
-            continue;
-        }
-        let name = out.stdout.trim_end_matches(['\r', '\n']).trim().to_string();
-        if !name.is_empty() {
-            win.name = name;
-        }
-    }
-    Ok(())
+#[tauri::command]
+async fn remote_rate_limit_set(per_sec: u32) {
+    coalesce::set_rate(per_sec)
 }
 
-fn ensure_window_ids(session: &str, windows: &mut [TmuxWindow]) {
-    for win in windows.iter_mut() {
-        if win.id.trim().is_empty() {
-            win.id = format!("{}:{}", session, win.index);
-        }
-    }
+#[tauri::command]
+async fn capabilities() -> wire::Capabilities {
+    wire::capabilities()
 }
 
-fn run_remote_cmd(creds: &SshCreds<'_>, raw: String) -> Result<ssh::ExecOut, String> {
-    let prelude = "unset BASH_ENV TMUX PROMPT_COMMAND PS1; if [ -f /etc/profile ]; then source /etc/profile; fi";
-    let chained = format!("{}; {}", prelude, raw);
-    let wrapped = format!("bash -lc {}", shell_escape::escape(chained.into()));
-    ssh_exec(creds, &wrapped)
+#[tauri::command]
+async fn spill_release(path: String) -> Result<(), String> {
+    blocking(move || spill::release(&path)).await?
 }
 
-// ---- helper: build SshCreds from HostProfile (no slow fallbacks) ----
-fn creds_from(profile: &HostProfile) -> SshCreds<'_> {
-    use std::path::Path;
+#[tauri::command]
+async fn perf_stats() -> Result<Vec<perf::PerfStat>, String> {
+    blocking(perf::perf_stats).await
+}
 
-    // Resolve auth mode deterministically
-    let auth = profile.auth.as_deref().unwrap_or_else(|| {
-        // keep legacy behavior: default to agent unless told otherwise
-        if profile.use_agent.unwrap_or(true) {
-            "agent"
-        } else if profile.key_path.as_deref().is_some() {
-            "key"
-        } else {
-            "agent"
-        }
-    });
+// No kind sets the threshold for every kind without its own.
+#[tauri::command]
+async fn perf_set_threshold(kind: Option<String>, ms: u64) {
+    perf::set_threshold(kind, ms)
+}
 
-    let key_path = if auth == "key" {
-        profile.key_path.as_deref().and_then(|s| {
-            if s.trim().is_empty() {
-                None
-            } else {
-                Some(Path::new(s))
-            }
-        })
-    } else {
-        None
-    };
-
-    SshCreds {
-        host: &profile.host,
-        port: profile.port.unwrap_or(22),
-        user: &profile.user,
-        password: if auth == "password" {
-            profile.password.as_deref()
-        } else {
-            None
-        },
-        key_path,
-        key_pass: if auth == "key" {
-            profile.key_pass.as_deref()
-        } else {
-            None
-        },
-        use_agent: auth == "agent",
-    }
+#[tauri::command]
+async fn tmux_start_server() -> Result<(), OrchestratorError> {
+    blocking(|| LocalBackend.start_server())
+        .await?
+        .map_err(OrchestratorError::from)
 }
 
-// ----------------- LOCAL TMUX -----------------
+#[tauri::command]
+async fn tmux_kill_session(session: String) -> Result<(), OrchestratorError> {
+    blocking(move || LocalBackend.kill_session(&session))
+        .await?
+        .map_err(OrchestratorError::from)
+}
 
 #[tauri::command]
-fn tmux_list_sessions() -> Result<Vec<TmuxSession>, String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let out = PCommand::new(&path)
-        .args([
-            "list-sessions",
-            "-F",
-            "#S|#{session_windows}|#{?session_attached,1,0}",
-        ])
-        .output()
-        .map_err(|e| e.to_string())?;
-    if !out.status.success() {
-        let msg = String::from_utf8_lossy(&out.stderr).to_lowercase();
-        if msg.contains("no server running")
-            || msg.contains("failed to connect to server")
-            || msg.contains("no sessions")
-        {
-            return Ok(vec![]);
-        }
-        return Err(String::from_utf8_lossy(&out.stderr).to_string());
-    }
-    let stdout = String::from_utf8_lossy(&out.stdout);
-    let sessions = stdout
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|line| {
-            let mut it = line.split('|');
-            let name = it.next().unwrap_or("").to_string();
-            let windows = it.next().unwrap_or("0").parse().unwrap_or(0);
-            let attached = it.next().unwrap_or("0") == "1";
-            TmuxSession {
-                name,
-                windows,
-                attached,
-            }
-        })
-        .collect();
-    Ok(sessions)
-}
-
-#[tauri::command]
-fn tmux_start_server() -> Result<(), String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let out = PCommand::new(&path)
-        .args(["start-server"])
-        .output()
-        .map_err(|e| e.to_string())?;
-    if !out.status.success() {
-        return Err(String::from_utf8_lossy(&out.stderr).to_string());
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn tmux_kill_session(session: String) -> Result<(), String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let out = PCommand::new(&path)
-        .args(["kill-session", "-t", &session])
-        .output()
-        .map_err(|e| e.to_string())?;
-    if !out.status.success() {
-        return Err(String::from_utf8_lossy(&out.stderr).to_string());
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn tmux_new_session(session: String) -> Result<(), String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let out = PCommand::new(&path)
-        .args(["new-session", "-d", "-s", &session])
-        .output()
-        .map_err(|e| e.to_string())?;
-    if !out.status.success() {
-        return Err(String::from_utf8_lossy(&out.stderr).to_string());
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn tmux_rename_session(payload: JsonValue) -> Result<(), String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let session = payload
-        .get("session")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing session".to_string())?;
-    let new_name = payload
-        .get("new_name")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("newName").and_then(|v| v.as_str()))
-        .ok_or_else(|| "missing new_name/newName".to_string())?;
-    let out = PCommand::new(&path)
-        .args(["rename-session", "-t", session, new_name])
-        .output()
-        .map_err(|e| e.to_string())?;
-    if !out.status.success() {
-        return Err(String::from_utf8_lossy(&out.stderr).to_string());
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn tmux_list_windows(session: String) -> Result<Vec<TmuxWindow>, String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let out = PCommand::new(&path)
-        .args([
-            "list-windows",
-            "-t",
-            &session,
-            "-F",
-            "#{window_index}|#{window_id}|#{window_name}|#{?window_active,1,0}|#{window_panes}",
-        ])
-        .output()
-        .map_err(|e| e.to_string())?;
-
-    if !out.status.success() {
-        let msg = String::from_utf8_lossy(&out.stderr).to_lowercase();
-        if msg.contains("no server running") {
-            return Ok(vec![]);
-        }
-        return Err(String::from_utf8_lossy(&out.stderr).to_string());
-    }
-
-    let stdout = String::from_utf8_lossy(&out.stdout);
-    let mut windows: Vec<TmuxWindow> = stdout
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|line| {
-            let mut it = line.split('|'); // NOTE: '|' (not tab)
-            let index: u32 = it.next().unwrap_or("0").trim().parse().unwrap_or(0);
-            let id = it.next().unwrap_or("").trim().to_string();
-            let name = it
-                .next()
-                .unwrap_or("")
-                .trim_end_matches(['\r', '\n'])
-                .to_string();
-            let active = it.next().unwrap_or("0").trim() == "1";
-            let panes: u32 = it.next().unwrap_or("1").trim().parse().unwrap_or(1);
-            TmuxWindow {
-                index,
-                id,
-                name,
-                active,
-                panes,
-            }
-        })
-        .collect();
-    hydrate_local_names(&session, &mut windows)?;
-    ensure_window_ids(&session, &mut windows);
-    Ok(windows)
+async fn tmux_new_session(session: String) -> Result<(), OrchestratorError> {
+    let session = names::session(&session)?;
+    blocking(move || LocalBackend.new_session(&session))
+        .await?
+        .map_err(OrchestratorError::from)
 }
 
 #[tauri::command]
-fn tmux_new_window(
+async fn tmux_rename_session(payload: RenameSessionRequest) -> Result<(), OrchestratorError> {
+    let new_name = names::session(&payload.new_name)?;
+    blocking(move || LocalBackend.rename_session(&payload.session, &new_name))
+        .await?
+        .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn tmux_list_windows(
+    session: String,
+    detail: Option<Detail>,
+) -> Result<Vec<TmuxWindow>, OrchestratorError> {
+    blocking(move || LocalBackend.list_windows(&session, detail.unwrap_or_default()))
+        .await?
+        .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn tmux_new_window(
     session: String,
     name: Option<String>,
     cmd: Option<String>,
-) -> Result<(), String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let mut args = vec!["new-window", "-P", "-F", "#{window_id}", "-t", &session];
-    if let Some(ref n) = name {
-        args.push("-n");
-        args.push(n);
-    }
-    if let Some(c) = &cmd {
-        args.push(c);
-    }
-    let out = PCommand::new(&path)
-        .args(&args)
-        .output()
-        .map_err(|e| e.to_string())?;
-    if !out.status.success() {
-        return Err(String::from_utf8_lossy(&out.stderr).to_string());
-    }
-    if name.is_some() {
-        let id = String::from_utf8_lossy(&out.stdout).trim().to_string();
-        if !id.is_empty() {
-            let _ = PCommand::new(&path)
-                .args(["set-window-option", "-t", &id, "automatic-rename", "off"])
-                .output();
-        }
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn tmux_capture_pane(payload: JsonValue) -> Result<String, String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let session = payload
-        .get("session")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing session".to_string())?;
-    let idx = payload
-        .get("window_index")
-        .and_then(|v| v.as_u64())
-        .or_else(|| payload.get("windowIndex").and_then(|v| v.as_u64()))
-        .ok_or_else(|| "missing window_index/windowIndex".to_string())? as u32;
-    let window_id = payload
-        .get("window_id")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("windowId").and_then(|v| v.as_str()))
-        .map(|s| s.to_string());
-    let last = payload.get("lines").and_then(|v| v.as_u64()).unwrap_or(800) as u32;
-    let target = window_id.unwrap_or_else(|| format!("{}:{}", session, idx));
-    let out = PCommand::new(&path)
-        .args([
-            "capture-pane",
-            "-p",
-            "-t",
-            &target,
-            "-S",
-            &format!("-{}", last),
-            "-e",
-            "-J",
-        ])
-        .output()
-        .map_err(|e| e.to_string())?;
-    if !out.status.success() {
-        let msg = String::from_utf8_lossy(&out.stderr).to_lowercase();
-        if msg.contains("no server running") || msg.contains("failed to connect to server") {
-            return Ok(String::new());
+) -> Result<(), OrchestratorError> {
+    let name = name.as_deref().map(names::window).transpose()?;
+    blocking(move || LocalBackend.new_window(&session, name.as_deref(), cmd.as_deref()))
+        .await?
+        .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn tmux_capture_pane(
+    payload: CaptureRequest,
+) -> Result<tauri::ipc::Response, OrchestratorError> {
+    let (binary, spill_over) = (payload.binary, payload.spill_over);
+    let (text, stale_id) = blocking(move || -> Result<(String, bool), String> {
+        let session = payload.window.session.as_str();
+        let idx = payload.window.window_index;
+        let last = payload.lines;
+        let if_changed = payload.if_changed;
+        let compress = payload.compress_threshold();
+        let finish = |text| compress::pack(ansi::strip_if(text, payload.strip_ansi), compress);
+        if !if_changed {
+            if let Some(text) = prefetch::take(None, session, idx, last) {
+                prefetch::around(None, session.to_string(), idx, last);
+                return Ok((finish(text), false));
+            }
         }
-        return Err(String::from_utf8_lossy(&out.stderr).to_string());
-    }
-    Ok(String::from_utf8_lossy(&out.stdout).to_string())
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct TmuxCommand {
-    args: Vec<String>,
-}
-
-fn build_tmux_send_keys_commands(target: &str, keys: &str, with_enter: bool) -> Vec<TmuxCommand> {
-    let mut commands = vec![TmuxCommand {
-        args: vec![
-            "send-keys".into(),
-            "-t".into(),
-            target.to_string(),
-            "-l".into(),
-            keys.to_string(),
-        ],
-    }];
-    if with_enter {
-        commands.push(TmuxCommand {
-            args: vec![
-                "send-keys".into(),
-                "-t".into(),
-                target.to_string(),
-                "Enter".into(),
-            ],
+        let captured = payload.window.with_fallback(|target| {
+            let text = local_capture(target, last)?;
+            if if_changed {
+                return Ok(captures::local_if_changed(target, text));
+            }
+            Ok(text)
         });
-    }
-    commands
-}
-
-fn format_remote_tmux_command(command: &TmuxCommand) -> String {
-    use std::borrow::Cow;
-    let escaped: Vec<String> = command
-        .args
-        .iter()
-        .map(|arg| shell_escape::escape(Cow::from(arg.as_str())).to_string())
-        .collect();
-    format!("tmux {}", escaped.join(" "))
-}
-
-#[tauri::command]
-fn tmux_send_keys(payload: JsonValue) -> Result<(), String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let session = payload
-        .get("session")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing session".to_string())?;
-    let idx = payload
-        .get("window_index")
-        .and_then(|v| v.as_u64())
-        .or_else(|| payload.get("windowIndex").and_then(|v| v.as_u64()))
-        .ok_or_else(|| "missing window_index/windowIndex".to_string())? as u32;
-    let window_id = payload
-        .get("window_id")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("windowId").and_then(|v| v.as_str()))
-        .map(|s| s.to_string());
-    let keys = payload
-        .get("keys")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing keys".to_string())?;
-    let with_enter = payload
-        .get("with_enter")
-        .and_then(|v| v.as_bool())
-        .or_else(|| payload.get("withEnter").and_then(|v| v.as_bool()))
-        .unwrap_or(false);
-    let target = window_id.unwrap_or_else(|| format!("{}:{}", session, idx));
-    let commands = build_tmux_send_keys_commands(&target, keys, with_enter);
-    for command in commands {
-        let mut proc = PCommand::new(&path);
-        proc.args(&command.args);
-        let out = proc.output().map_err(|e| e.to_string())?;
-        if !out.status.success() {
-            return Err(String::from_utf8_lossy(&out.stderr).to_string());
+        prefetch::around(None, session.to_string(), idx, last);
+        captured.map(|(text, stale_id)| (finish(text), stale_id))
+    })
+    .await??;
+    let text = spill::spill(text, spill_over);
+    Ok(wire::text(captures::mark_stale(text, stale_id), binary))
+}
+
+#[tauri::command]
+async fn tmux_send_keys(payload: SendKeysRequest) -> Result<(), OrchestratorError> {
+    blocking(move || {
+        LocalBackend.send_keys(&payload.window.target(), &payload.keys, payload.with_enter)
+    })
+    .await?
+    .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn tmux_rename_window(
+    payload: RenameWindowRequest,
+) -> Result<WindowOutcome, OrchestratorError> {
+    let new_name = names::window(&payload.new_name)?;
+    blocking(move || {
+        let rename = |target: &str| LocalBackend.rename_window(target, &new_name);
+        payload
+            .window
+            .with_fallback(rename)
+            .map(|((), stale_id)| WindowOutcome { stale_id })
+    })
+    .await?
+    .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn tmux_kill_window(payload: WindowRef) -> Result<WindowOutcome, OrchestratorError> {
+    blocking(move || {
+        payload
+            .with_fallback(|target| LocalBackend.kill_window(target))
+            .map(|((), stale_id)| WindowOutcome { stale_id })
+    })
+    .await?
+    .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn app_config_validate(config: AppConfig) -> Result<configcheck::ConfigReport, String> {
+    blocking(move || configcheck::validate(&config)).await
+}
+
+#[tauri::command]
+async fn validate_python_executable(path: String) -> Result<String, String> {
+    blocking(move || {
+        use std::path::Path;
+        if !Path::new(&path).exists() {
+            return Err("File does not exist".into());
+        }
+        let output = PCommand::new(&path)
+            .args(["--version"])
+            .output()
+            .map_err(|e| format!("Failed to execute: {}", e))?;
+        if !output.status.success() {
+            return Err("Not a valid Python executable".into());
+        }
+        let v = if !output.stdout.is_empty() {
+            String::from_utf8_lossy(&output.stdout)
+        } else {
+            String::from_utf8_lossy(&output.stderr)
+        };
+        let line = v.lines().next().unwrap_or("").trim();
+        if line.starts_with("Python ") {
+            Ok(line.to_string())
+        } else {
+            Err("Invalid Python version output".into())
         }
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn tmux_rename_window(payload: JsonValue) -> Result<(), String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let session = payload
-        .get("session")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing session".to_string())?;
-    let idx = payload
-        .get("window_index")
-        .and_then(|v| v.as_u64())
-        .or_else(|| payload.get("windowIndex").and_then(|v| v.as_u64()))
-        .ok_or_else(|| "missing window_index/windowIndex".to_string())? as u32;
-    let new_name = payload
-        .get("new_name")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("name").and_then(|v| v.as_str()))
-        .ok_or_else(|| "missing new_name/name".to_string())?;
-    let target = format!("{}:{}", session, idx);
-    let out = PCommand::new(&path)
-        .args(["rename-window", "-t", &target, &new_name])
-        .output()
-        .map_err(|e| e.to_string())?;
-    if !out.status.success() {
-        return Err(String::from_utf8_lossy(&out.stderr).to_string());
-    }
-    let _ = PCommand::new(&path)
-        .args([
-            "set-window-option",
-            "-t",
-            &target,
-            "automatic-rename",
-            "off",
-        ])
-        .output();
-    Ok(())
-}
-
-#[tauri::command]
-fn tmux_kill_window(payload: JsonValue) -> Result<(), String> {
-    let path = which("tmux").map_err(|e| e.to_string())?;
-    let session = payload
-        .get("session")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing session".to_string())?;
-    let idx = payload
-        .get("window_index")
-        .and_then(|v| v.as_u64())
-        .or_else(|| payload.get("windowIndex").and_then(|v| v.as_u64()))
-        .ok_or_else(|| "missing window_index/windowIndex".to_string())? as u32;
-    let window_id = payload
-        .get("window_id")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("windowId").and_then(|v| v.as_str()))
-        .map(|s| s.to_string());
-    let target = window_id.unwrap_or_else(|| format!("{}:{}", session, idx));
-    let out = PCommand::new(&path)
-        .args(["kill-window", "-t", &target])
-        .output()
-        .map_err(|e| e.to_string())?;
-    if !out.status.success() {
-        return Err(String::from_utf8_lossy(&out.stderr).to_string());
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn validate_python_executable(path: String) -> Result<String, String> {
-    use std::path::Path;
-    if !Path::new(&path).exists() {
-        return Err("File does not exist".into());
-    }
-    let output = PCommand::new(&path)
-        .args(["--version"])
-        .output()
-        .map_err(|e| format!("Failed to execute: {}", e))?;
-    if !output.status.success() {
-        return Err("Not a valid Python executable".into());
-    }
-    let v = if !output.stdout.is_empty() {
-        String::from_utf8_lossy(&output.stdout)
-    } else {
-        String::from_utf8_lossy(&output.stderr)
-    };
-    let line = v.lines().next().unwrap_or("").trim();
-    if line.starts_with("Python ") {
-        Ok(line.to_string())
-    } else {
-        Err("Invalid Python version output".into())
-    }
+    })
+    .await?
 }
 
 // ----------------- REMOTE TMUX -----------------
 
 #[tauri::command]
-fn remote_tmux_list_sessions(profile: HostProfile) -> Result<Vec<TmuxSession>, String> {
-    let c = creds_from(&profile);
-    let cmd = r##"tmux list-sessions -F "#S|#{session_windows}|#{?session_attached,1,0}""##;
-    let out = run_remote_cmd(&c, cmd.to_string())?;
-    if out.code != 0 {
-        let msg = out.stderr.to_lowercase();
-        if msg.contains("no server running") || msg.contains("no sessions") {
-            return Ok(vec![]);
-        }
-        return Err(out.stderr);
-    }
-    let sessions = out
-        .stdout
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|line| {
-            let mut it = line.split('|');
-            let name = it.next().unwrap_or("").to_string();
-            let windows = it.next().unwrap_or("0").parse().unwrap_or(0);
-            let attached = it.next().unwrap_or("0") == "1";
-            TmuxSession {
-                name,
-                windows,
-                attached,
-            }
-        })
-        .collect();
-    Ok(sessions)
+async fn context_get() -> Result<context::AppContext, String> {
+    blocking(context::get).await
 }
 
 #[tauri::command]
-fn remote_tmux_list_windows(
-    profile: HostProfile,
+async fn context_set_profile(id: Option<String>) -> Result<context::AppContext, String> {
+    blocking(move || context::set_profile(id)).await?
+}
+
+#[tauri::command]
+async fn context_set_viewed(
+    profile: Option<String>,
     session: String,
-) -> Result<Vec<TmuxWindow>, String> {
-    let c = creds_from(&profile);
-
-    // robust: no newlines, single-quoted -F, escape tmux braces for Rust,
-    // and shell-escape the session name
-    let cmd = format!(
-    "tmux list-windows -t {} -F '#{{window_index}}|#{{window_id}}|#{{window_name}}|#{{?window_active,1,0}}|#{{window_panes}}'",
-    shell_escape::escape(session.clone().into())
-  );
-
-    let out = run_remote_cmd(&c, cmd.clone())?;
-    if out.code != 0 {
-        return Err(out.stderr);
-    }
-
-    println!(
-        "[remote_tmux_list_windows] cmd={} code={} stdout=<<{}>> stderr=<<{}>>",
-        cmd, out.code, out.stdout, out.stderr,
-    );
-
-    let mut windows: Vec<TmuxWindow> = out
-        .stdout
-        .lines()
-        .filter(|l| !l.trim().is_empty())
-        .map(|line| {
-            let mut it = line.split('|');
-            let index = it.next().unwrap_or("0").trim().parse().unwrap_or(0);
-            let id = it.next().unwrap_or("").trim().to_string();
-            let name = it
-                .next()
-                .unwrap_or("")
-                .trim_end_matches(['\r', '\n'])
-                .to_string();
-            let active = it.next().unwrap_or("0").trim() == "1";
-            let panes = it.next().unwrap_or("1").trim().parse().unwrap_or(1);
-            TmuxWindow {
-                index,
-                id,
-                name,
-                active,
-                panes,
-            }
-        })
-        .collect();
+    window: Option<String>,
+) -> Result<context::AppContext, String> {
+    blocking(move || context::set_viewed(profile, session, window)).await?
+}
+
+#[tauri::command]
+async fn profile_list() -> Result<Vec<profiles::StoredProfile>, String> {
+    blocking(profiles::list).await
+}
+
+#[tauri::command]
+async fn profile_validate(
+    name: String,
+    profile: JsonValue,
+) -> Result<Vec<profiles::FieldError>, String> {
+    blocking(move || {
+        profiles::parse_profile(&name, &profile)
+            .err()
+            .unwrap_or_default()
+    })
+    .await
+}
+
+#[tauri::command]
+async fn profile_create(
+    name: String,
+    profile: JsonValue,
+) -> Result<profiles::StoredProfile, profiles::ProfileError> {
+    blocking(move || profiles::create(name, profile)).await?
+}
+
+#[tauri::command]
+async fn profile_update(
+    id: String,
+    name: String,
+    profile: JsonValue,
+) -> Result<profiles::StoredProfile, profiles::ProfileError> {
+    blocking(move || profiles::update(id, name, profile)).await?
+}
+
+#[tauri::command]
+async fn profile_clone(
+    id: String,
+    overrides: JsonValue,
+) -> Result<profiles::StoredProfile, profiles::ProfileError> {
+    blocking(move || profiles::clone_profile(id, overrides)).await?
+}
 
-    hydrate_remote_names(&session, &mut windows, &c)?;
-    ensure_window_ids(&session, &mut windows);
-    Ok(windows)
+#[tauri::command]
+async fn profile_delete(id: String) -> Result<(), String> {
+    blocking(move || profiles::delete(id)).await?
+}
+
+#[tauri::command]
+async fn profile_export(
+    ids: Vec<String>,
+    path: String,
+    include_secrets: bool,
+    passphrase: Option<String>,
+) -> Result<usize, String> {
+    blocking(move || profilebundle::export(ids, path.as_ref(), include_secrets, passphrase)).await?
+}
+
+#[tauri::command]
+async fn profile_import(
+    path: String,
+    passphrase: Option<String>,
+    on_conflict: profilebundle::OnConflict,
+) -> Result<profilebundle::ImportReport, String> {
+    blocking(move || profilebundle::import(path.as_ref(), passphrase, on_conflict)).await?
 }
 
 #[tauri::command]
-fn remote_tmux_snapshot(
+async fn profile_diagnose(
     profile: HostProfile,
-    session: String,
+    config: Option<AppConfig>,
+) -> Result<profilecheck::Diagnosis, String> {
+    blocking(move || profilecheck::diagnose(&profile, config.as_ref())).await
+}
+
+#[tauri::command]
+async fn remote_host_info(profile: HostProfile) -> Result<hostinfo::HostInfo, String> {
+    blocking(move || hostinfo::remote_host_info(&profile)).await?
+}
+
+#[tauri::command]
+async fn profiles_health() -> Result<health::HealthReport, String> {
+    blocking(health::profiles_health).await
+}
+
+#[tauri::command]
+async fn remote_host_stats(profile: HostProfile) -> Result<hoststats::HostStats, String> {
+    blocking(move || hoststats::remote_host_stats(&profile)).await?
+}
+
+#[tauri::command]
+async fn remote_gpu_info(profile: HostProfile) -> Result<hoststats::GpuInfo, String> {
+    blocking(move || hoststats::remote_gpu_info(&profile)).await?
+}
+
+#[tauri::command]
+async fn remote_tmux_list_sessions(
+    profile: HostProfile,
+) -> Result<Vec<TmuxSession>, OrchestratorError> {
+    blocking(move || remote_sessions(&profile))
+        .await?
+        .map_err(OrchestratorError::from)
+}
+
+#[derive(Serialize)]
+struct HostSessions {
+    host: String,
+    sessions: Vec<TmuxSession>,
+    error: Option<OrchestratorError>,
+}
+
+// Lists every profile's sessions at once, giving each host `timeout_ms`
+// (default 10s). Hosts that fail or time out carry an error instead of
+// holding up the rest.
+#[tauri::command]
+async fn remote_tmux_list_sessions_many(
+    profiles: Vec<HostProfile>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<HostSessions>, String> {
+    blocking(move || {
+        let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(10_000));
+        let handles: Vec<_> = profiles
+            .into_iter()
+            .map(|profile| {
+                let host = runs::host_label(&profile);
+                let listing = std::thread::spawn(move || {
+                    health::with_timeout(timeout, move || remote_sessions(&profile))
+                });
+                (host, listing)
+            })
+            .collect();
+        let listings = handles
+            .into_iter()
+            .map(|(host, listing)| {
+                let listing = listing
+                    .join()
+                    .unwrap_or_else(|_| Err("listing panicked".into()));
+                match listing {
+                    Ok(sessions) => HostSessions {
+                        host,
+                        sessions,
+                        error: None,
+                    },
+                    Err(e) => HostSessions {
+                        host,
+                        sessions: vec![],
+                        error: Some(OrchestratorError::from(e)),
+                    },
+                }
+            })
+            .collect();
+        Ok(listings)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn remote_tmux_list_windows(
+    profile: HostProfile,
+    session: Option<String>,
+    detail: Option<Detail>,
+) -> Result<Vec<TmuxWindow>, OrchestratorError> {
+    blocking(move || {
+        let session = session_or_default(session, &profile)?;
+        SshBackend(profile).list_windows(&session, detail.unwrap_or_default())
+    })
+    .await?
+    .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn remote_tmux_snapshot(
+    profile: HostProfile,
+    session: Option<String>,
     window_index: Option<u32>,
     window_id: Option<String>,
     lines: Option<u32>,
-) -> Result<Snapshot, String> {
-    let c = creds_from(&profile);
-
-    // list-windows format
-    let fmt = "#{window_index}|#{window_id}|#{window_name}|#{?window_active,1,0}|#{window_panes}";
-    let delim = "__ARC_SPLIT__";
-
-    let escaped_session = shell_escape::escape(session.clone().into());
-
-    // pick a tmux target: if no index, use the active window via "session:"
-    let target = if let Some(ref id) = window_id {
-        id.clone()
-    } else if let Some(idx) = window_index {
-        format!("{}:{}", escaped_session, idx)
-    } else {
-        format!("{}:", escaped_session)
-    };
-
-    // one SSH exec
-    let cmd = format!(
-    "tmux list-windows -t {} -F '{}' && printf '\\n{}\\n' && tmux capture-pane -p -t {} -S -{} -e -J",
-    escaped_session,
-    fmt,
-    delim,
-    target,
-    lines.unwrap_or(200)
-  );
-
-    let out = run_remote_cmd(&c, cmd.clone())?;
-    if out.code != 0 {
-        return Err(out.stderr);
-    }
-
-    let delim_line = format!("\n{}\n", delim);
-    let (win_txt, pane_txt) = match out.stdout.split_once(&delim_line) {
-        Some((a, b)) => (a, b),
-        None => (out.stdout.as_str(), ""),
-    };
-
-    let mut windows = win_txt
-        .lines()
-        .filter(|l| !l.trim().is_empty())
-        .map(|line| {
-            let mut it = line.split('|');
-            let index = it.next().unwrap_or("0").trim().parse().unwrap_or(0);
-            let id = it.next().unwrap_or("").trim().to_string();
-            let name = it
-                .next()
-                .unwrap_or("")
-                .trim_end_matches(['\r', '\n'])
-                .to_string();
-            let active = it.next().unwrap_or("0").trim() == "1";
-            let panes = it.next().unwrap_or("1").trim().parse().unwrap_or(1);
-            TmuxWindow {
-                index,
-                id,
-                name,
-                active,
-                panes,
-            }
-        })
-        .collect::<Vec<_>>();
+    compress_over: Option<usize>,
+    binary: Option<bool>,
+    detail: Option<Detail>,
+    strip_ansi: Option<bool>,
+) -> Result<tauri::ipc::Response, OrchestratorError> {
+    let snapshot = blocking(move || -> Result<Snapshot, String> {
+        let session = session_or_default(session, &profile)?;
+        let c = creds_from(&profile);
+
+        let delim = "__ARC_SPLIT__";
+
+        // pick a tmux target: if no index, use the active window via "session:"
+        let target = if let Some(ref id) = window_id {
+            id.clone()
+        } else if let Some(idx) = window_index {
+            format!("{}:{}", session, idx)
+        } else {
+            format!("{}:", session)
+        };
+
+        // one SSH exec; the pane is gzipped on the host past compress_over
+        let capture = remote_capture_command(&target, lines.unwrap_or(200));
+        let capture = match compress_over {
+            Some(over) => compress::remote_command(&capture, over),
+            None => capture,
+        };
+        let list = RemoteTmuxCommand::new("list-windows")
+            .target(&session)
+            .args(&["-F", backend::WINDOWS_FORMAT])
+            .build();
+        let cmd = format!("{} && printf '\\n{}\\n' && {}", list, delim, capture);
+
+        let out = run_remote_cmd(&c, cmd.clone())?;
+        if out.code != 0 && errors::no_server(&out.stderr) {
+            return Ok(Snapshot {
+                windows: vec![],
+                pane: String::new(),
+            });
+        }
+        if out.code != 0 {
+            return Err(out.stderr);
+        }
+
+        let delim_line = format!("\n{}\n", delim);
+        let (win_txt, pane_txt) = match out.stdout.split_once(&delim_line) {
+            Some((a, b)) => (a, b),
+            None => (out.stdout.as_str(), ""),
+        };
+        let pane_txt = match compress_over {
+            Some(_) => compress::remote_output(pane_txt)?,
+            None => utf8::mark(pane_txt.to_string(), out.lossy),
+        };
 
-    hydrate_remote_names(&session, &mut windows, &c)?;
-    ensure_window_ids(&session, &mut windows);
+        let mut windows = backend::parse_windows(win_txt);
 
-    Ok(Snapshot {
-        windows,
-        pane: pane_txt.to_string(),
+        if detail.unwrap_or_default() == Detail::Full {
+            SshBackend(profile.clone()).hydrate_names(&session, &mut windows)?;
+        }
+        ensure_window_ids(&session, &mut windows);
+
+        Ok(Snapshot {
+            windows,
+            pane: compress::pack(
+                ansi::strip_if(pane_txt, strip_ansi.unwrap_or(false)),
+                compress_over,
+            ),
+        })
     })
+    .await??;
+    wire::snapshot(snapshot.windows, snapshot.pane, binary.unwrap_or(false))
+        .map_err(OrchestratorError::from)
 }
 
 #[tauri::command]
-fn remote_tmux_capture_pane(payload: JsonValue) -> Result<String, String> {
-    let profile: HostProfile = serde_json::from_value(
-        payload
-            .get("profile")
-            .cloned()
-            .ok_or_else(|| "missing profile".to_string())?,
-    )
-    .map_err(|e| format!("invalid profile: {}", e))?;
-    let session = payload
-        .get("session")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing session".to_string())?;
-    let idx = payload
-        .get("window_index")
-        .and_then(|v| v.as_u64())
-        .or_else(|| payload.get("windowIndex").and_then(|v| v.as_u64()))
-        .ok_or_else(|| "missing window_index/windowIndex".to_string())? as u32;
-    let window_id = payload
-        .get("window_id")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("windowId").and_then(|v| v.as_str()))
-        .map(|s| s.to_string());
-    let lines = payload.get("lines").and_then(|v| v.as_u64()).unwrap_or(800) as u32;
-    let c = creds_from(&profile);
-    let escaped_session = shell_escape::escape(session.into());
-    let target = window_id.unwrap_or_else(|| format!("{escaped_session}:{idx}"));
-    let cmd = format!(
-        r##"tmux capture-pane -p -t {} -S -{} -e -J"##,
-        target, lines
-    );
-    let out = run_remote_cmd(&c, cmd.clone())?;
-    if out.code == 0 {
-        Ok(out.stdout)
-    } else {
-        let msg = out.stderr.to_lowercase();
-        if msg.contains("no server running") {
-            return Ok(String::new());
+async fn remote_tmux_capture_pane(
+    payload: Remote<CaptureRequest>,
+) -> Result<tauri::ipc::Response, OrchestratorError> {
+    let Remote { profile, request } = payload;
+    let (binary, spill_over) = (request.binary, request.spill_over);
+    let (text, stale_id) = blocking(move || -> Result<(String, bool), String> {
+        let session = request.window.session.as_str();
+        let idx = request.window.window_index;
+        let lines = request.lines;
+        let c = creds_from(&profile);
+        let if_changed = request.if_changed;
+        let compress = request.compress_threshold();
+        let finish = |text| compress::pack(ansi::strip_if(text, request.strip_ansi), compress);
+        if !if_changed {
+            if let Some(text) = prefetch::take(Some(&profile), session, idx, lines) {
+                prefetch::around(Some(profile.clone()), session.to_string(), idx, lines);
+                return Ok((finish(text), false));
+            }
         }
-        Err(out.stderr)
-    }
+        let captured = request.window.with_fallback(|target| {
+            let cmd = remote_capture_command(target, lines);
+            let cmd = if if_changed {
+                captures::remote_command(&profile, target, &cmd)
+            } else {
+                cmd
+            };
+            // gzip on the host as well, so big captures are small over SSH too
+            let cmd = match compress {
+                Some(over) => compress::remote_command(&cmd, over),
+                None => cmd,
+            };
+            // the command spells out the whole request, so it is the key too
+            coalesce::shared(Some(&profile), cmd.clone(), || {
+                let mut out = run_remote_cmd(&c, cmd.clone())?;
+                if out.code == 0 && compress.is_some() {
+                    out.stdout = compress::remote_output(&out.stdout)?;
+                }
+                out.stdout = utf8::mark(out.stdout, out.lossy);
+                if out.code == 0 && if_changed {
+                    Ok(captures::remote_if_changed(&profile, target, &out.stdout))
+                } else if out.code == 0 {
+                    Ok(out.stdout)
+                } else if errors::no_server(&out.stderr) {
+                    Ok(String::new())
+                } else {
+                    Err(out.stderr)
+                }
+            })
+        });
+        prefetch::around(Some(profile.clone()), session.to_string(), idx, lines);
+        captured.map(|(text, stale_id)| (finish(text), stale_id))
+    })
+    .await??;
+    let text = spill::spill(text, spill_over);
+    Ok(wire::text(captures::mark_stale(text, stale_id), binary))
 }
 
 #[tauri::command]
-fn remote_tmux_select_window(
+async fn remote_tmux_select_window(
     profile: HostProfile,
     session: String,
     target: String,
-) -> Result<(), String> {
-    control::send_command(profile, session, format!("select-window -t {}", target))
+) -> Result<(), OrchestratorError> {
+    blocking(move || {
+        control::send_command(profile, session, format!("select-window -t {}", target))
+    })
+    .await?
+    .map_err(OrchestratorError::from)
 }
 
 #[tauri::command]
-fn remote_tmux_control_start(
+async fn remote_tmux_control_start(
     app_handle: tauri::AppHandle,
     profile: HostProfile,
     session: String,
-) -> Result<(), String> {
-    control::start_control(app_handle, profile, session)
+) -> Result<(), OrchestratorError> {
+    blocking(move || control::start_control(app_handle, profile, session))
+        .await?
+        .map_err(OrchestratorError::from)
 }
 
 #[tauri::command]
-fn remote_tmux_control_stop(profile: HostProfile, session: String) -> Result<(), String> {
-    control::stop_control(profile, session)
+async fn remote_tmux_control_stop(
+    profile: HostProfile,
+    session: String,
+) -> Result<(), OrchestratorError> {
+    blocking(move || control::stop_control(profile, session))
+        .await?
+        .map_err(OrchestratorError::from)
 }
 
 #[tauri::command]
-fn remote_tmux_control_send(
+async fn remote_tmux_control_send(
     profile: HostProfile,
     session: String,
     command: String,
-) -> Result<(), String> {
-    control::send_command(profile, session, command)
+) -> Result<(), OrchestratorError> {
+    blocking(move || control::send_command(profile, session, command))
+        .await?
+        .map_err(OrchestratorError::from)
 }
 
 #[tauri::command]
-fn remote_tmux_send_keys(payload: JsonValue) -> Result<(), String> {
-    let profile: HostProfile = serde_json::from_value(
-        payload
-            .get("profile")
-            .cloned()
-            .ok_or_else(|| "missing profile".to_string())?,
-    )
-    .map_err(|e| format!("invalid profile: {}", e))?;
-    let c = creds_from(&profile);
-    let session = payload
-        .get("session")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing session".to_string())?;
-    let idx = payload
-        .get("window_index")
-        .and_then(|v| v.as_u64())
-        .or_else(|| payload.get("windowIndex").and_then(|v| v.as_u64()))
-        .ok_or_else(|| "missing window_index/windowIndex".to_string())? as u32;
-    let window_id = payload
-        .get("window_id")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("windowId").and_then(|v| v.as_str()))
-        .map(|s| s.to_string());
-    let keys = payload
-        .get("keys")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing keys".to_string())?;
-    let with_enter = payload
-        .get("with_enter")
-        .and_then(|v| v.as_bool())
-        .or_else(|| payload.get("withEnter").and_then(|v| v.as_bool()))
-        .unwrap_or(false);
-    let target = window_id.unwrap_or_else(|| format!("{}:{}", session, idx));
-    let commands = build_tmux_send_keys_commands(&target, keys, with_enter);
-    for command in commands {
-        let formatted = format_remote_tmux_command(&command);
-        let out = run_remote_cmd(&c, formatted)?;
-        if out.code != 0 {
-            return Err(out.stderr);
-        }
-    }
-    Ok(())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::{
-        build_tmux_send_keys_commands,
-        format_remote_tmux_command,
-        TmuxCommand,
-    };
-
-    #[test]
-    fn build_commands_include_enter_when_requested() {
-        let commands = build_tmux_send_keys_commands("arc:0", "ls -la", true);
-        assert_eq!(
-            commands,
-            vec![
-                TmuxCommand {
-                    args: vec![
-                        "send-keys".into(),
-                        "-t".into(),
-                        "arc:0".into(),
-                        "-l".into(),
-                        "ls -la".into(),
-                    ],
-                },
-                TmuxCommand {
-                    args: vec![
-                        "send-keys".into(),
-                        "-t".into(),
-                        "arc:0".into(),
-                        "Enter".into(),
-                    ],
-                },
-            ]
-        );
-    }
-
-    #[test]
-    fn build_commands_omit_enter_when_not_requested() {
-        let commands = build_tmux_send_keys_commands("arc:1", "whoami", false);
-        assert_eq!(
-            commands,
-            vec![TmuxCommand {
-                args: vec![
-                    "send-keys".into(),
-                    "-t".into(),
-                    "arc:1".into(),
-                    "-l".into(),
-                    "whoami".into(),
-                ],
-            }]
-        );
-    }
-
-    #[test]
-    fn remote_format_escapes_arguments() {
-        let commands = build_tmux_send_keys_commands("pane @1", "echo 'hi'", true);
-        let literal = format_remote_tmux_command(&commands[0]);
-        let enter = format_remote_tmux_command(&commands[1]);
-        assert_eq!(
-            literal,
-            "tmux send-keys -t 'pane @1' -l 'echo '"'"'hi'"'"''"
-        );
-        assert_eq!(enter, "tmux send-keys -t 'pane @1' Enter");
-    }
-}
-
-#[tauri::command]
-fn remote_tmux_new_window(
+async fn remote_tmux_set_client_size(
+    profile: HostProfile,
+    session: String,
+    cols: u32,
+    rows: u32,
+) -> Result<(), OrchestratorError> {
+    blocking(move || control::set_client_size(profile, session, cols, rows))
+        .await?
+        .map_err(OrchestratorError::from)
+}
+
+// ----------------- RUNS -----------------
+
+#[tauri::command]
+async fn arc_detect_version(
+    python_path: String,
+    arc_path: String,
+    conda_env: Option<String>,
+) -> Result<EnvVersions, String> {
+    blocking(move || versions::detect_local(&python_path, &arc_path, conda_env.as_deref())).await?
+}
+
+#[tauri::command]
+async fn pyenv_list() -> Result<Vec<PythonEnv>, String> {
+    blocking(pyenvs::list).await
+}
+
+#[tauri::command]
+async fn pyenv_save(env: PythonEnv) -> Result<PythonEnv, String> {
+    blocking(move || pyenvs::save_env(env)).await?
+}
+
+#[tauri::command]
+async fn pyenv_delete(name: String) -> Result<(), String> {
+    blocking(move || pyenvs::delete(&name)).await?
+}
+
+#[tauri::command]
+async fn pyenv_probe(name: String, profile: Option<HostProfile>) -> Result<PythonEnv, String> {
+    blocking(move || pyenvs::probe(&name, profile.as_ref())).await?
+}
+
+#[tauri::command]
+async fn conda_list_envs() -> Result<Vec<conda::CondaEnv>, String> {
+    blocking(conda::list_local_envs).await?
+}
+
+#[tauri::command]
+async fn remote_conda_list_envs(profile: HostProfile) -> Result<Vec<conda::CondaEnv>, String> {
+    blocking(move || conda::list_remote_envs(&profile)).await?
+}
+
+#[tauri::command]
+async fn remote_arc_detect_version(
+    profile: HostProfile,
+    python_path: String,
+    arc_path: String,
+    conda_env: Option<String>,
+) -> Result<EnvVersions, String> {
+    blocking(move || {
+        versions::detect_remote(&profile, &python_path, &arc_path, conda_env.as_deref())
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn remote_check_arc(
+    profile: HostProfile,
+    arc_path: String,
+    conda_env: Option<String>,
+) -> Result<arccheck::ArcCheck, String> {
+    blocking(move || arccheck::remote_check_arc(&profile, &arc_path, conda_env.as_deref())).await?
+}
+
+#[tauri::command]
+async fn arc_run_start(
+    config: AppConfig,
+    input_path: String,
+    name: String,
+    work_dir: Option<String>,
+    priority: Option<i32>,
+    profile: Option<HostProfile>,
+    auto_host: Option<bool>,
+) -> Result<ARCRun, String> {
+    blocking(move || {
+        runs::start_run(
+            config,
+            input_path,
+            name,
+            work_dir,
+            priority,
+            profile,
+            auto_host.unwrap_or(false),
+        )
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn host_pool_set(hosts: Vec<hostpool::PoolHost>) -> Result<(), String> {
+    blocking(move || hostpool::set_pool(hosts)).await?
+}
+
+#[tauri::command]
+async fn host_pool_state() -> Result<Vec<hostpool::PoolHostState>, String> {
+    blocking(hostpool::pool_state).await
+}
+
+#[tauri::command]
+async fn runs_import(
+    config: AppConfig,
+    manifest_path: String,
+) -> Result<manifest::ImportReport, String> {
+    blocking(move || manifest::import_runs(config, manifest_path)).await?
+}
+
+#[tauri::command]
+async fn run_adopt(
+    profile: Option<HostProfile>,
+    session: String,
+    window_id: String,
+    name: String,
+    work_dir: String,
+) -> Result<ARCRun, String> {
+    blocking(move || runs::adopt_run(profile, session, window_id, name, work_dir)).await?
+}
+
+#[tauri::command]
+async fn runs_discover(profile: Option<HostProfile>) -> Result<Vec<runs::DiscoveredRun>, String> {
+    blocking(move || runs::discover_runs(profile)).await?
+}
+
+#[tauri::command]
+async fn runs_list() -> Result<Vec<ARCRun>, String> {
+    blocking(runs::list_runs).await
+}
+
+#[tauri::command]
+async fn run_get(id: String) -> Result<ARCRun, String> {
+    blocking(move || runs::get_run(id)).await?
+}
+
+#[tauri::command]
+async fn run_set_priority(id: String, priority: i32) -> Result<(), String> {
+    blocking(move || runs::set_priority(id, priority)).await?
+}
+
+#[tauri::command]
+async fn run_stop(id: String, mode: String) -> Result<(), String> {
+    blocking(move || runs::stop_run(id, mode)).await?
+}
+
+#[tauri::command]
+async fn run_restart(id: String) -> Result<ARCRun, String> {
+    blocking(move || runs::restart_run(id)).await?
+}
+
+#[tauri::command]
+async fn run_archive(
+    id: String,
+    dest_path: String,
+    delete_original: Option<bool>,
+    force: Option<bool>,
+) -> Result<ARCRun, String> {
+    blocking(move || {
+        archive::archive_run(
+            id,
+            dest_path,
+            delete_original.unwrap_or(false),
+            force.unwrap_or(false),
+        )
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn run_fetch_results(id: String, dest: String) -> Result<ARCRun, String> {
+    blocking(move || fetch::fetch_results(id, dest)).await?
+}
+
+#[tauri::command]
+async fn run_cleanup(
+    id: String,
+    scope: String,
+    force: Option<bool>,
+) -> Result<cleanup::CleanupReport, String> {
+    blocking(move || cleanup::cleanup_run(id, scope, force.unwrap_or_default())).await?
+}
+
+#[tauri::command]
+async fn runs_reconcile() -> Result<recovery::ReconcileReport, String> {
+    blocking(recovery::reconcile).await
+}
+
+#[tauri::command]
+async fn run_results(id: String) -> Result<results::RunResults, String> {
+    blocking(move || results::run_results(id)).await?
+}
+
+#[tauri::command]
+async fn run_jobs(id: String) -> Result<Vec<jobs::EssJob>, String> {
+    blocking(move || jobs::run_jobs(id)).await?
+}
+
+#[tauri::command]
+async fn run_diagnostics(id: String) -> Result<String, String> {
+    blocking(move || diagnostics::collect(id).map(|dir| dir.to_string_lossy().into_owned())).await?
+}
+
+#[tauri::command]
+async fn run_disk_usage(id: String) -> Result<diskusage::DiskUsage, String> {
+    blocking(move || diskusage::run_disk_usage(id)).await?
+}
+
+#[tauri::command]
+async fn workdir_quota_status() -> Result<Vec<quota::QuotaState>, String> {
+    blocking(quota::status).await
+}
+
+#[tauri::command]
+async fn workdirs_disk_usage() -> Result<diskusage::WorkdirsUsage, String> {
+    blocking(diskusage::workdirs_disk_usage).await
+}
+
+#[tauri::command]
+async fn runs_export(ids: Vec<String>, format: String, dest: String) -> Result<usize, String> {
+    blocking(move || export::export_runs(ids, format, dest)).await?
+}
+
+#[tauri::command]
+async fn run_resources(id: String) -> Result<Vec<resources::ResourceSample>, String> {
+    blocking(move || {
+        runs::get_run(id.clone())?;
+        Ok(resources::samples(&id))
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn run_log_stream_start(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
+    blocking(move || runs::start_log_stream(app_handle, id)).await?
+}
+
+#[tauri::command]
+async fn run_log_stream_stop(id: String) -> Result<(), String> {
+    blocking(move || runs::stop_log_stream(id)).await?
+}
+
+#[tauri::command]
+async fn runs_log_multiplex(
+    app_handle: tauri::AppHandle,
+    ids: Vec<String>,
+) -> Result<runs::MultiplexReport, String> {
+    blocking(move || runs::start_log_multiplex(app_handle, ids)).await
+}
+
+#[tauri::command]
+async fn runs_log_multiplex_stop() -> Result<(), String> {
+    blocking(move || logstream::LogStreamManager::global().stop_multiplexed()).await
+}
+
+#[tauri::command]
+async fn runs_history(
+    filter: Option<history::HistoryFilter>,
+) -> Result<history::RunHistory, String> {
+    blocking(move || history::history(filter.unwrap_or_default())).await
+}
+
+#[tauri::command]
+async fn runs_stats(range: Option<String>) -> Result<stats::RunStats, String> {
+    blocking(move || stats::runs_stats(range)).await?
+}
+
+#[tauri::command]
+async fn runs_timeline(range: Option<String>) -> Result<Vec<timeline::TimelineRow>, String> {
+    blocking(move || timeline::runs_timeline(range)).await?
+}
+
+#[tauri::command]
+async fn runs_search(query: Option<history::HistoryFilter>) -> Result<Vec<ARCRun>, String> {
+    blocking(move || history::search(query.unwrap_or_default())).await
+}
+
+#[tauri::command]
+async fn run_tag_add(id: String, tag: String) -> Result<ARCRun, String> {
+    blocking(move || runs::add_tag(id, tag)).await?
+}
+
+#[tauri::command]
+async fn run_tag_remove(id: String, tag: String) -> Result<ARCRun, String> {
+    blocking(move || runs::remove_tag(id, tag)).await?
+}
+
+#[tauri::command]
+async fn run_annotate(
+    id: String,
+    text: String,
+    timestamp: Option<String>,
+) -> Result<ARCRun, String> {
+    blocking(move || runs::annotate(id, text, timestamp)).await?
+}
+
+#[tauri::command]
+async fn runs_queue_state() -> Result<Vec<runs::QueueEntry>, String> {
+    blocking(runs::queue_state).await
+}
+
+#[tauri::command]
+async fn scheduler_state() -> Result<runs::SchedulerState, String> {
+    blocking(runs::scheduler_state).await
+}
+
+#[tauri::command]
+async fn notification_test(url: String, slack: Option<bool>) -> Result<(), String> {
+    blocking(move || {
+        notifications::test_webhook(WebhookConfig {
+            url,
+            slack: slack.unwrap_or(false),
+            ..WebhookConfig::default()
+        })
+    })
+    .await?
+}
+
+// ----------------- WATCHERS -----------------
+
+#[tauri::command]
+async fn watch_activity_start(
+    app_handle: tauri::AppHandle,
+    profile: Option<HostProfile>,
+    session: String,
+    interval_secs: Option<u64>,
+    silence_minutes: Option<u64>,
+) -> Result<String, String> {
+    blocking(move || {
+        watch::start_activity(app_handle, profile, session, interval_secs, silence_minutes)
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn watch_patterns_start(
+    app_handle: tauri::AppHandle,
+    profile: Option<HostProfile>,
+    target: String,
+    patterns: Vec<String>,
+    context_lines: Option<usize>,
+    interval_secs: Option<u64>,
+) -> Result<String, String> {
+    blocking(move || {
+        watch::start_patterns(
+            app_handle,
+            profile,
+            target,
+            patterns,
+            context_lines,
+            interval_secs,
+        )
+    })
+    .await?
+}
+
+#[tauri::command]
+async fn watch_start(
+    app_handle: tauri::AppHandle,
+    profile: Option<HostProfile>,
+    target: String,
+    interval_ms: Option<u64>,
+    lines: Option<u32>,
+) -> Result<String, String> {
+    blocking(move || watch::start_pane(app_handle, profile, target, interval_ms, lines)).await?
+}
+
+#[tauri::command]
+async fn capture_stream(
+    app_handle: tauri::AppHandle,
+    profile: Option<HostProfile>,
+    target: String,
+    lines: Option<u32>,
+    chunk_kb: Option<usize>,
+) -> Result<String, String> {
+    blocking(move || scrollback::start(app_handle, profile, target, lines, chunk_kb)).await?
+}
+
+#[tauri::command]
+async fn tmux_capture_page(
+    profile: Option<HostProfile>,
+    target: String,
+    before_line: Option<i64>,
+    count: Option<u32>,
+) -> Result<scrollback::CapturePage, OrchestratorError> {
+    blocking(move || scrollback::page(profile.as_ref(), &target, before_line, count))
+        .await?
+        .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn capture_stream_cancel(id: String) -> Result<(), String> {
+    blocking(move || scrollback::cancel(id)).await
+}
+
+#[tauri::command]
+async fn watch_stop(id: String) -> Result<(), String> {
+    blocking(move || watch::stop_watch(id)).await?
+}
+
+// ----------------- INTERACTIVE TERMINALS -----------------
+
+#[tauri::command]
+async fn terminal_open(
+    app_handle: tauri::AppHandle,
+    profile: HostProfile,
+    session: Option<String>,
+    cols: u32,
+    rows: u32,
+) -> Result<String, String> {
+    blocking(move || pty::open_terminal(app_handle, profile, session, cols, rows)).await?
+}
+
+#[tauri::command]
+async fn terminal_input(id: String, data: String) -> Result<(), String> {
+    blocking(move || pty::send_input(id, data)).await?
+}
+
+#[tauri::command]
+async fn terminal_write(id: String, bytes: Vec<u8>) -> Result<(), String> {
+    blocking(move || pty::write_bytes(id, bytes)).await?
+}
+
+#[tauri::command]
+async fn terminal_resize(id: String, cols: u32, rows: u32) -> Result<(), String> {
+    blocking(move || pty::resize_terminal(id, cols, rows)).await?
+}
+
+#[tauri::command]
+async fn terminal_close(id: String) -> Result<(), String> {
+    blocking(move || pty::close_terminal(id)).await?
+}
+
+#[tauri::command]
+async fn terminal_list() -> Result<Vec<String>, String> {
+    blocking(pty::list_terminals).await
+}
+
+// ----------------- LOGS -----------------
+
+// The last `lines` (default 500) lines of the app's log, for the log viewer.
+#[tauri::command]
+async fn logs_get_recent(lines: Option<usize>) -> Result<Vec<String>, String> {
+    blocking(move || applog::recent(lines.unwrap_or(500))).await?
+}
+
+#[tauri::command]
+async fn remote_tmux_send_keys(payload: Remote<SendKeysRequest>) -> Result<(), OrchestratorError> {
+    let Remote { profile, request } = payload;
+    blocking(move || {
+        SshBackend(profile).send_keys(&request.window.target(), &request.keys, request.with_enter)
+    })
+    .await?
+    .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn remote_tmux_new_window(
     profile: HostProfile,
     session: String,
     name: Option<String>,
     cmd: Option<String>,
-) -> Result<(), String> {
-    let c = creds_from(&profile);
-    let mut args = format!(
-        "tmux new-window -P -F '#{{window_id}}' -t {}",
-        shell_escape::escape(session.clone().into())
-    );
-    if let Some(ref n) = name {
-        args.push_str(&format!(" -n {}", shell_escape::escape(n.into())));
-    }
-    if let Some(command) = cmd {
-        args.push(' ');
-        args.push_str(&command);
-    }
-    let out = run_remote_cmd(&c, args.clone())?;
-    if out.code != 0 {
-        return Err(out.stderr);
-    }
-    if name.is_some() {
-        let id = out.stdout.trim();
-        if !id.is_empty() {
-            let _ = run_remote_cmd(
-                &c,
-                format!("tmux set-window-option -t {} automatic-rename off", id),
-            );
-        }
-    }
-    Ok(())
+) -> Result<(), OrchestratorError> {
+    let name = name.as_deref().map(names::window).transpose()?;
+    blocking(move || SshBackend(profile).new_window(&session, name.as_deref(), cmd.as_deref()))
+        .await?
+        .map_err(OrchestratorError::from)
 }
 
 #[tauri::command]
-fn remote_tmux_kill_window(payload: JsonValue) -> Result<(), String> {
-    let profile: HostProfile = serde_json::from_value(
-        payload
-            .get("profile")
-            .cloned()
-            .ok_or_else(|| "missing profile".to_string())?,
-    )
-    .map_err(|e| format!("invalid profile: {}", e))?;
-    let c = creds_from(&profile);
-    let session = payload
-        .get("session")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing session".to_string())?;
-    let idx = payload
-        .get("window_index")
-        .and_then(|v| v.as_u64())
-        .or_else(|| payload.get("windowIndex").and_then(|v| v.as_u64()))
-        .ok_or_else(|| "missing window_index/windowIndex".to_string())? as u32;
-    let window_id = payload
-        .get("window_id")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("windowId").and_then(|v| v.as_str()))
-        .map(|s| s.to_string());
-    let escaped_session = shell_escape::escape(session.into());
-    let target = window_id.unwrap_or_else(|| format!("{}:{}", escaped_session, idx));
-    let out = ssh_exec(&c, &format!("tmux kill-window -t {}", target))?;
-    if out.code != 0 {
-        return Err(out.stderr);
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn remote_tmux_rename_window(payload: JsonValue) -> Result<(), String> {
-    let profile: HostProfile = serde_json::from_value(
-        payload
-            .get("profile")
-            .cloned()
-            .ok_or_else(|| "missing profile".to_string())?,
-    )
-    .map_err(|e| format!("invalid profile: {}", e))?;
-    let c = creds_from(&profile);
-    let session = payload
-        .get("session")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing session".to_string())?;
-    let idx = payload
-        .get("window_index")
-        .and_then(|v| v.as_u64())
-        .or_else(|| payload.get("windowIndex").and_then(|v| v.as_u64()))
-        .ok_or_else(|| "missing window_index/windowIndex".to_string())? as u32;
-    let window_id = payload
-        .get("window_id")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("windowId").and_then(|v| v.as_str()))
-        .map(|s| s.to_string());
-    let new_name = payload
-        .get("new_name")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("name").and_then(|v| v.as_str()))
-        .ok_or_else(|| "missing new_name/name".to_string())?;
-    let escaped_session = shell_escape::escape(session.into());
-    let target = window_id.unwrap_or_else(|| format!("{}:{}", escaped_session, idx));
-    let cmd = format!(
-        "tmux rename-window -t {} {}",
-        target,
-        shell_escape::escape(new_name.into())
-    );
-    let out = ssh_exec(&c, &cmd)?;
-    if out.code != 0 {
-        return Err(out.stderr);
-    }
-    let _ = ssh_exec(
-        &c,
-        &format!("tmux set-window-option -t {} automatic-rename off", target),
-    );
-    Ok(())
-}
-
-#[tauri::command]
-fn remote_tmux_start_server(profile: HostProfile) -> Result<(), String> {
-    let c = creds_from(&profile);
-    let out = ssh_exec(&c, "tmux start-server")?;
-    if out.code != 0 {
-        return Err(out.stderr);
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn remote_tmux_new_session(profile: HostProfile, session: String) -> Result<(), String> {
-    let c = creds_from(&profile);
-    let out = ssh_exec(
-        &c,
-        &format!(
-            "tmux new-session -d -s {}",
-            shell_escape::escape(session.into())
-        ),
-    )?;
-    if out.code != 0 {
-        return Err(out.stderr);
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn remote_tmux_rename_session(payload: JsonValue) -> Result<(), String> {
-    let profile: HostProfile = serde_json::from_value(
-        payload
-            .get("profile")
-            .cloned()
-            .ok_or_else(|| "missing profile".to_string())?,
-    )
-    .map_err(|e| format!("invalid profile: {}", e))?;
-    let c = creds_from(&profile);
-    let session = payload
-        .get("session")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "missing session".to_string())?;
-    let new_name = payload
-        .get("new_name")
-        .and_then(|v| v.as_str())
-        .or_else(|| payload.get("newName").and_then(|v| v.as_str()))
-        .ok_or_else(|| "missing new_name/newName".to_string())?;
-    let out = ssh_exec(
-        &c,
-        &format!(
-            "tmux rename-session -t {} {}",
-            shell_escape::escape(session.into()),
-            shell_escape::escape(new_name.into())
-        ),
-    )?;
-    if out.code != 0 {
-        return Err(out.stderr);
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn remote_tmux_kill_session(profile: HostProfile, session: String) -> Result<(), String> {
-    let c = creds_from(&profile);
-    let out = ssh_exec(
-        &c,
-        &format!(
-            "tmux kill-session -t {}",
-            shell_escape::escape(session.into())
-        ),
-    )?;
-    if out.code != 0 {
-        return Err(out.stderr);
-    }
-    Ok(())
-}
-
-#[tauri::command]
-fn remote_ping(profile: HostProfile) -> Result<String, String> {
-    let c = creds_from(&profile);
-    let out = ssh_exec(&c, "whoami && tmux -V || true")?;
-    if out.code == 0 {
-        Ok(out.stdout.trim().to_string())
-    } else {
-        Err(out.stderr)
-    }
+async fn remote_tmux_kill_window(
+    payload: Remote<WindowRef>,
+) -> Result<WindowOutcome, OrchestratorError> {
+    let Remote { profile, request } = payload;
+    blocking(move || {
+        let backend = SshBackend(profile);
+        request
+            .with_fallback(|target| backend.kill_window(target))
+            .map(|((), stale_id)| WindowOutcome { stale_id })
+    })
+    .await?
+    .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn remote_tmux_rename_window(
+    payload: Remote<RenameWindowRequest>,
+) -> Result<WindowOutcome, OrchestratorError> {
+    let Remote { profile, request } = payload;
+    let new_name = names::window(&request.new_name)?;
+    blocking(move || {
+        let backend = SshBackend(profile);
+        let rename = |target: &str| backend.rename_window(target, &new_name);
+        request
+            .window
+            .with_fallback(rename)
+            .map(|((), stale_id)| WindowOutcome { stale_id })
+    })
+    .await?
+    .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn remote_tmux_start_server(profile: HostProfile) -> Result<(), OrchestratorError> {
+    blocking(move || SshBackend(profile).start_server())
+        .await?
+        .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn remote_tmux_new_session(
+    profile: HostProfile,
+    session: String,
+) -> Result<(), OrchestratorError> {
+    let session = names::session(&session)?;
+    blocking(move || SshBackend(profile).new_session(&session))
+        .await?
+        .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn remote_tmux_rename_session(
+    payload: Remote<RenameSessionRequest>,
+) -> Result<(), OrchestratorError> {
+    let Remote { profile, request } = payload;
+    let new_name = names::session(&request.new_name)?;
+    blocking(move || SshBackend(profile).rename_session(&request.session, &new_name))
+        .await?
+        .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn remote_tmux_kill_session(
+    profile: HostProfile,
+    session: String,
+) -> Result<(), OrchestratorError> {
+    blocking(move || SshBackend(profile).kill_session(&session))
+        .await?
+        .map_err(OrchestratorError::from)
+}
+
+#[tauri::command]
+async fn remote_ping(profile: HostProfile) -> Result<String, OrchestratorError> {
+    blocking(move || {
+        let c = creds_from(&profile);
+        let out = ssh_exec(&c, "whoami && tmux -V || true")?;
+        if out.code == 0 {
+            Ok(out.stdout.trim().to_string())
+        } else {
+            Err(out.stderr)
+        }
+    })
+    .await?
+    .map_err(OrchestratorError::from)
 }
 
 fn main() {
@@ -1143,13 +1172,29 @@ fn main() {
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             if let Some(_win) = app.get_webview_window("main") { /* keep restored size/pos */ }
+            applog::init(app.handle());
+            profiles::init(app.handle());
+            context::init(app.handle());
+            pyenvs::init(app.handle());
+            spill::init(app.handle());
+            recovery::init(app.handle().clone());
+            quota::start();
+            perf::start();
+            standby::start();
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(applog::traced(tauri::generate_handler![
             // local
             tmux_list_sessions,
+            listing_cache_set_ttl,
+            remote_rate_limit_set,
+            capabilities,
+            spill_release,
+            perf_stats,
+            perf_set_threshold,
             tmux_start_server,
             tmux_kill_session,
             tmux_new_session,
@@ -1161,11 +1206,29 @@ fn main() {
             tmux_rename_window,
             tmux_kill_window,
             validate_python_executable,
+            app_config_validate,
             // remote
             remote_ping,
             remote_tmux_snapshot,
             remote_tmux_start_server,
+            context_get,
+            context_set_profile,
+            context_set_viewed,
+            profile_list,
+            profile_validate,
+            profile_create,
+            profile_update,
+            profile_clone,
+            profile_delete,
+            profile_export,
+            profile_import,
+            profile_diagnose,
+            remote_host_info,
+            remote_host_stats,
+            profiles_health,
+            remote_gpu_info,
             remote_tmux_list_sessions,
+            remote_tmux_list_sessions_many,
             remote_tmux_list_windows,
             remote_tmux_capture_pane,
             remote_tmux_send_keys,
@@ -1179,7 +1242,72 @@ fn main() {
             remote_tmux_control_start,
             remote_tmux_control_stop,
             remote_tmux_control_send,
-        ])
+            remote_tmux_set_client_size,
+            // runs
+            arc_detect_version,
+            remote_arc_detect_version,
+            remote_check_arc,
+            pyenv_list,
+            pyenv_save,
+            pyenv_delete,
+            pyenv_probe,
+            conda_list_envs,
+            remote_conda_list_envs,
+            arc_run_start,
+            host_pool_set,
+            host_pool_state,
+            runs_import,
+            run_adopt,
+            runs_discover,
+            runs_list,
+            run_get,
+            run_set_priority,
+            run_stop,
+            run_restart,
+            run_archive,
+            run_fetch_results,
+            run_cleanup,
+            run_results,
+            run_jobs,
+            run_diagnostics,
+            run_disk_usage,
+            workdirs_disk_usage,
+            workdir_quota_status,
+            runs_export,
+            run_resources,
+            run_log_stream_start,
+            run_log_stream_stop,
+            runs_log_multiplex,
+            runs_log_multiplex_stop,
+            runs_history,
+            runs_search,
+            runs_stats,
+            runs_timeline,
+            run_tag_add,
+            run_tag_remove,
+            run_annotate,
+            runs_queue_state,
+            scheduler_state,
+            runs_reconcile,
+            notification_test,
+            // watchers
+            watch_activity_start,
+            watch_patterns_start,
+            watch_start,
+            capture_stream,
+            capture_stream_cancel,
+            tmux_capture_page,
+            watch_stop,
+            // terminals
+            terminal_open,
+            terminal_input,
+            terminal_write,
+            terminal_resize,
+            terminal_close,
+            terminal_list,
+            // logs
+            logs_get_recent,
+        ]))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
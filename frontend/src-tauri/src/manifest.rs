@@ -0,0 +1,233 @@
+use crate::model::{ARCRun, AppConfig};
+use crate::{runs, HostProfile};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+// One manifest entry expands to a run per input file matching `inputs`.
+#[derive(Clone, Deserialize)]
+pub struct ManifestEntry {
+    pub inputs: String, // glob, relative to the manifest's directory
+    // run name; `{stem}` is the input file name without extension and
+    // `{index}` its 1-based position among the entry's matches
+    #[serde(default = "default_name_template")]
+    pub name_template: String,
+    pub work_dir: Option<String>, // parent directory, each run gets <work_dir>/<name>
+    pub priority: Option<i32>,
+    pub host: Option<HostProfile>, // None runs locally
+    #[serde(default)]
+    pub auto_host: bool, // place each run on a host from the pool instead
+    pub conda_env: Option<String>, // overrides the config's env for these runs
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct Manifest {
+    pub runs: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EntryError {
+    pub entry: usize, // 0-based index into `runs`
+    pub input: Option<PathBuf>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    pub queued: Vec<ARCRun>,
+    pub errors: Vec<EntryError>,
+}
+
+// A run the manifest asks for, checked but not yet submitted.
+struct Planned {
+    entry: usize,
+    input: PathBuf,
+    name: String,
+}
+
+fn default_name_template() -> String {
+    "{stem}".into()
+}
+
+fn parse_manifest(path: &Path, text: &str) -> Result<Manifest, String> {
+    let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    } else {
+        serde_yaml::from_str(text).map_err(|e| e.to_string())
+    };
+    parsed.map_err(|e| format!("invalid manifest {}: {e}", path.display()))
+}
+
+fn render_name(template: &str, input: &Path, index: usize) -> String {
+    let stem = input
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    template
+        .replace("{stem}", &stem)
+        .replace("{index}", &index.to_string())
+        .trim()
+        .to_string()
+}
+
+fn expand(base: &Path, entry: &ManifestEntry) -> Result<Vec<PathBuf>, String> {
+    let pattern = if Path::new(&entry.inputs).is_absolute() {
+        entry.inputs.clone()
+    } else {
+        base.join(&entry.inputs).to_string_lossy().into_owned()
+    };
+    let mut inputs: Vec<PathBuf> = glob::glob(&pattern)
+        .map_err(|e| format!("bad glob {}: {e}", entry.inputs))?
+        .filter_map(|p| p.ok())
+        .filter(|p| p.is_file())
+        .collect();
+    inputs.sort();
+    if inputs.is_empty() {
+        return Err(format!("no input files match {}", entry.inputs));
+    }
+    Ok(inputs)
+}
+
+// Checks every entry up front so that a manifest with mistakes queues
+// nothing rather than half a batch.
+fn plan(base: &Path, manifest: &Manifest) -> (Vec<Planned>, Vec<EntryError>) {
+    let mut planned = Vec::new();
+    let mut errors = Vec::new();
+    let mut names = HashSet::new();
+    for (i, entry) in manifest.runs.iter().enumerate() {
+        if entry.auto_host && entry.host.is_some() {
+            errors.push(EntryError {
+                entry: i,
+                input: None,
+                message: "an entry either has a host or uses auto_host".into(),
+            });
+            continue;
+        }
+        let inputs = match expand(base, entry) {
+            Ok(inputs) => inputs,
+            Err(message) => {
+                errors.push(EntryError {
+                    entry: i,
+                    input: None,
+                    message,
+                });
+                continue;
+            }
+        };
+        for (n, input) in inputs.into_iter().enumerate() {
+            let name = render_name(&entry.name_template, &input, n + 1);
+            let problem = if name.is_empty() {
+                Some("name template produced an empty run name".to_string())
+            } else if !names.insert(name.clone()) {
+                Some(format!("duplicate run name: {name}"))
+            } else {
+                None
+            };
+            match problem {
+                Some(message) => errors.push(EntryError {
+                    entry: i,
+                    input: Some(input),
+                    message,
+                }),
+                None => planned.push(Planned {
+                    entry: i,
+                    input,
+                    name,
+                }),
+            }
+        }
+    }
+    (planned, errors)
+}
+
+pub fn import_runs(config: AppConfig, manifest_path: String) -> Result<ImportReport, String> {
+    let path = PathBuf::from(&manifest_path);
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("read {manifest_path}: {e}"))?;
+    let manifest = parse_manifest(&path, &text)?;
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    let (planned, errors) = plan(base, &manifest);
+    let mut report = ImportReport {
+        errors,
+        ..ImportReport::default()
+    };
+    if !report.errors.is_empty() {
+        return Ok(report);
+    }
+    // the queue applies the concurrency cap, so everything can be submitted
+    // at once
+    for run in planned {
+        let entry = &manifest.runs[run.entry];
+        let work_dir = entry.work_dir.as_ref().map(|dir| {
+            Path::new(dir)
+                .join(&run.name)
+                .to_string_lossy()
+                .into_owned()
+        });
+        let mut config = config.clone();
+        if let Some(env) = &entry.conda_env {
+            if entry.host.is_some() || entry.auto_host {
+                config.remote_conda_env = Some(env.clone());
+            }
+            if entry.host.is_none() {
+                config.conda_env = Some(env.clone());
+            }
+        }
+        let started = runs::start_run(
+            config,
+            run.input.to_string_lossy().into_owned(),
+            run.name,
+            work_dir,
+            entry.priority,
+            entry.host.clone(),
+            entry.auto_host,
+        );
+        let queued = started.and_then(|queued| {
+            entry
+                .tags
+                .iter()
+                .try_fold(queued, |queued, tag| runs::add_tag(queued.id, tag.clone()))
+        });
+        match queued {
+            Ok(queued) => report.queued.push(queued),
+            Err(message) => report.errors.push(EntryError {
+                entry: run.entry,
+                input: Some(run.input),
+                message,
+            }),
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_manifest, plan};
+    use std::path::Path;
+
+    #[test]
+    fn plan_expands_globs_and_reports_bad_entries() {
+        let dir = std::env::temp_dir().join(format!("manifest-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("inputs")).unwrap();
+        for name in ["CH4.yml", "OH.yml", "notes.txt"] {
+            std::fs::write(dir.join("inputs").join(name), "").unwrap();
+        }
+        let manifest = parse_manifest(
+            Path::new("batch.yml"),
+            "runs:\n  - inputs: inputs/*.yml\n    name_template: 'screen-{stem}'\n    tags: [screen]\n  - inputs: missing/*.yml\n  - inputs: inputs/CH4.yml\n    name_template: 'screen-{stem}'\n",
+        )
+        .unwrap();
+
+        let (planned, errors) = plan(&dir, &manifest);
+        let names: Vec<&str> = planned.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["screen-CH4", "screen-OH"]);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].entry, 1);
+        assert!(errors[0].message.contains("no input files"));
+        assert_eq!(errors[1].entry, 2);
+        assert!(errors[1].message.contains("duplicate run name"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -4,24 +4,139 @@ use std::path::PathBuf;
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum RunStatus {
     Idle,
+    Queued,
     Starting,
     Running,
     Finished,
     Failed,
+    Cancelled,
+    Stalled,
+    Orphaned, // its tmux window was gone when the app came back up
+    Unknown,  // its host could not be checked when the app came back up
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ARCRun {
-    pub id: String,                  // unique id of the run
-    pub name: String,                // name of the run e.g. "rmg_rxn_1"
-    pub session: String,             // tmux session id
-    pub input_path: PathBuf,         // path to the input file
-    pub work_dir: PathBuf,           // working directory for the run
-    pub started_at: Option<String>,  // timestamp when the run started
-    pub finished_at: Option<String>, // timestamp when the run finished
-    pub status: RunStatus,           // current status of the run
-    pub last_stdout: Option<String>, // last stdout line
-    pub last_stderr: Option<String>, // last stderr line
+    pub id: String,                     // unique id of the run
+    pub name: String,                   // name of the run e.g. "rmg_rxn_1"
+    pub session: String,                // tmux session id
+    pub window_id: Option<String>,      // tmux window id the run lives in, e.g. "@3"
+    pub input_path: PathBuf,            // path to the input file
+    pub work_dir: PathBuf,              // working directory for the run
+    pub started_at: Option<String>,     // timestamp when the run started
+    pub finished_at: Option<String>,    // timestamp when the run finished
+    pub status: RunStatus,              // current status of the run
+    pub last_stdout: Option<String>,    // last stdout line
+    pub last_stderr: Option<String>,    // last stderr line
+    pub restarted_from: Option<String>, // id of the run this one restarts
+    pub progress: Option<RunProgress>,  // parsed from arc.log while the run is live
+    pub archive_path: Option<PathBuf>,  // tarball the work_dir was archived to
+    pub host: Option<String>,           // "user@host:port" for remote runs, None when local
+    #[serde(default)]
+    pub tags: Vec<String>, // free-form labels, e.g. "benchmark" or "paper-2"
+    #[serde(default)]
+    pub notes: Vec<RunNote>, // oldest first
+    #[serde(default)]
+    pub diagnostics_path: Option<PathBuf>, // logs collected when the run failed
+    #[serde(default)]
+    pub versions: Option<EnvVersions>, // detected in the run's environment at launch
+    #[serde(default)]
+    pub batch_job_id: Option<String>, // scheduler job id for batch submissions
+    #[serde(default)]
+    pub core_secs: f64, // CPU time of the run's pane processes, summed from samples
+    #[serde(default)]
+    pub results_path: Option<PathBuf>, // local copy of a remote run's output directory
+    #[serde(default)]
+    pub queued_at: Option<String>, // when the run was submitted to the queue
+}
+
+impl ARCRun {
+    // A record under a new id with nothing seen of the run yet.
+    pub fn new(
+        name: String,
+        session: String,
+        input_path: PathBuf,
+        work_dir: PathBuf,
+        status: RunStatus,
+    ) -> Self {
+        ARCRun {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            session,
+            window_id: None,
+            input_path,
+            work_dir,
+            started_at: None,
+            finished_at: None,
+            status,
+            last_stdout: None,
+            last_stderr: None,
+            restarted_from: None,
+            progress: None,
+            archive_path: None,
+            host: None,
+            tags: Vec::new(),
+            notes: Vec::new(),
+            diagnostics_path: None,
+            versions: None,
+            batch_job_id: None,
+            core_secs: 0.0,
+            results_path: None,
+            queued_at: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn for_test(id: &str, status: RunStatus) -> Self {
+        ARCRun {
+            id: id.into(),
+            ..ARCRun::new(
+                "CH4".into(),
+                "arc".into(),
+                "/work/input.yml".into(),
+                "/work".into(),
+                status,
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EnvVersions {
+    pub arc: Option<String>,
+    pub arc_commit: Option<String>, // short hash of the ARC checkout
+    pub rmg_py: Option<String>,
+    pub rmg_py_commit: Option<String>,
+    pub rmg_database: Option<String>,
+    pub rmg_database_commit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunNote {
+    pub at: String, // RFC 3339
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RunProgress {
+    pub species_total: u32,        // species ARC announced it is considering
+    pub species_converged: u32,    // species whose jobs all converged
+    pub species_failed: u32,       // species ARC gave up on
+    pub jobs_completed: u32,       // ESS jobs that have ended
+    pub running_jobs: Vec<String>, // names of ESS jobs currently running
+    pub job_types: Vec<String>,    // distinct job types among running jobs
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub eta_secs: Option<i64>, // rough time left, from progress and similar past runs
+    #[serde(default)]
+    pub phases: Vec<PhaseSpan>, // in the order they first showed up in arc.log
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PhaseSpan {
+    pub phase: String, // "conformers", "opt", "sp" or "post-processing"
+    pub start: String, // RFC 3339, when arc.log first showed the phase
+    pub end: String,   // RFC 3339, when arc.log last showed the phase
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -30,6 +145,34 @@ pub struct AppConfig {
     pub arc_path: String,         // path to the ARC root directory  - so like /home/user/ARC/ARC.py
     pub default_work_dir: String, // default working directory for runs
     pub concurrency_cap: u32,     // max number of concurrent runs
+    // conda env activated before launching ARC locally, in place of python_path
+    pub conda_env: Option<String>,
+    // conda env activated before launching ARC on a remote host
+    pub remote_conda_env: Option<String>,
+    // ARC.py on remote hosts; falls back to arc_path
+    pub remote_arc_path: Option<String>,
+    // minutes without log or job activity before a run is marked Stalled
+    pub stall_timeout_minutes: Option<u64>,
+    // which run transitions raise a desktop notification
+    #[serde(default)]
+    pub notifications: NotificationToggles,
+    // endpoints POSTed to on run transitions, e.g. a Slack incoming webhook
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    // warn once default_work_dir grows past this many GB
+    pub disk_warning_gb: Option<u64>,
+    // submit remote runs to the cluster's scheduler instead of a tmux pane
+    pub batch: Option<BatchConfig>,
+    // refuse remote launches when the work dir's filesystem has less free space
+    pub remote_min_free_gb: Option<u64>,
+    // launch anyway and only raise a disk-space-low event
+    #[serde(default)]
+    pub remote_low_space_warn_only: bool,
+    // name of a registered PythonEnv, used in place of python_path/conda_env
+    pub python_env: Option<String>,
+    // how full default_work_dir may get before warning or refusing launches
+    #[serde(default)]
+    pub workdir_quota: Option<WorkdirQuota>,
 }
 
 impl Default for AppConfig {
@@ -39,6 +182,136 @@ impl Default for AppConfig {
             arc_path: "/path/to/ARC/ARC.py".into(),
             default_work_dir: "/path/to/arc_work_dir".into(),
             concurrency_cap: 2,
+            conda_env: None,
+            remote_conda_env: None,
+            remote_arc_path: None,
+            stall_timeout_minutes: None,
+            notifications: NotificationToggles::default(),
+            webhooks: Vec::new(),
+            disk_warning_gb: None,
+            batch: None,
+            remote_min_free_gb: None,
+            remote_low_space_warn_only: false,
+            python_env: None,
+            workdir_quota: None,
         }
     }
 }
+
+// An SSH host the app runs on; passwords and key passphrases are never
+// written out.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct HostProfile {
+    pub host: String,
+    pub port: Option<u16>,
+    pub user: String,
+    pub auth: Option<String>, // "agent" | "key" | "password"
+    #[serde(skip_serializing)]
+    pub password: Option<String>, // only when auth == "password"; never saved
+    pub key_path: Option<String>,
+    #[serde(skip_serializing)]
+    pub key_pass: Option<String>,
+    // Deprecated legacy switch, only read when auth is not set. Never written
+    // back; dropped once profiles saved before auth existed have migrated.
+    #[serde(default, skip_serializing)]
+    pub use_agent: Option<bool>,
+    pub scheduler: Option<String>, // "slurm" | "pbs" | "htcondor" | "oge"; batch runs only
+    pub env_preset: Option<String>, // module loads, exports etc. run before commands on the host
+    pub max_concurrent_ops: Option<u32>, // for hosts that throttle SSH sessions
+    pub session_template: Option<String>, // e.g. "arc-{project}-{date}", see runs::session_name
+    pub default_session: Option<String>, // used when list/snapshot commands get no session
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool, // disabled profiles are left out of profiles_health
+    #[serde(default)]
+    pub keep_warm: bool, // connected at start-up and kept alive, see standby
+    #[serde(default)]
+    pub overrides: ConfigOverrides,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+// Settings a host profile can carry in place of the global AppConfig's.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ConfigOverrides {
+    pub python_path: Option<String>,
+    pub arc_path: Option<String>, // ARC.py on the host
+    pub default_work_dir: Option<String>,
+    pub concurrency_cap: Option<u32>, // counts only the runs on the host
+    pub python_env: Option<String>,   // registered PythonEnv, with its path on the host
+    pub workdir_quota: Option<WorkdirQuota>,
+}
+
+// Thresholds on the space used where a work dir lives: the user's quota
+// when the host reports one, the filesystem otherwise.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct WorkdirQuota {
+    pub warn_gb: Option<u64>,
+    pub hard_gb: Option<u64>, // new launches are refused past this
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PythonKind {
+    #[default]
+    System,
+    Conda, // path is the env's name or prefix, activated with `conda activate`
+    Venv,  // path is the venv's python
+}
+
+// A named python that configs and profiles pick by name. version and
+// missing are filled in by probing it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PythonEnv {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub kind: PythonKind,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub missing: Vec<String>, // ARC dependencies it could not import
+    #[serde(default)]
+    pub checked_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BatchConfig {
+    pub partition: Option<String>,
+    pub time_limit: Option<String>, // e.g. "2-00:00:00"
+    pub cpus: Option<u32>,
+    pub memory: Option<String>,        // e.g. "16G"
+    pub extra_directives: Vec<String>, // raw lines such as "#SBATCH --account=grp"
+    // whole script; {name}, {work_dir}, {command} and {directives} are filled in
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationToggles {
+    pub finished: bool,
+    pub failed: bool,
+    pub stalled: bool,
+}
+
+impl Default for NotificationToggles {
+    fn default() -> Self {
+        NotificationToggles {
+            finished: true,
+            failed: true,
+            stalled: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub slack: bool, // send Slack's {"text": ...} shape instead of the raw event
+    #[serde(default)]
+    pub statuses: Vec<RunStatus>, // empty means Finished and Failed
+}
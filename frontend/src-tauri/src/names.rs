@@ -0,0 +1,57 @@
+use crate::errors::OrchestratorError;
+
+// Session and window names as tmux can take them. tmux reads `:` and `.` in
+// a target as the window and pane separators, so a name holding either one
+// can never be addressed again. Surrounding whitespace is dropped and control
+// characters become spaces; what is still unusable is refused before
+// anything reaches tmux.
+fn normalize(kind: &'static str, name: &str) -> Result<String, OrchestratorError> {
+    let invalid = |reason: &str| OrchestratorError::InvalidName {
+        kind,
+        name: name.to_string(),
+        reason: reason.to_string(),
+    };
+    let normalized: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    if normalized.is_empty() {
+        return Err(invalid("name is empty"));
+    }
+    if let Some(c) = normalized.chars().find(|c| matches!(c, ':' | '.')) {
+        return Err(invalid(&format!("name contains '{c}'")));
+    }
+    Ok(normalized)
+}
+
+pub fn session(name: &str) -> Result<String, OrchestratorError> {
+    normalize("session", name)
+}
+
+pub fn window(name: &str) -> Result<String, OrchestratorError> {
+    normalize("window", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{session, window};
+    use serde_json::json;
+
+    #[test]
+    fn names_are_trimmed_and_separators_refused() {
+        assert_eq!(session("  arc run\t").unwrap(), "arc run");
+        assert_eq!(window("opt\nfreq").unwrap(), "opt freq");
+        let err = session("arc:1").unwrap_err();
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            json!({
+                "code": "invalid_name",
+                "message": "invalid session name \"arc:1\": name contains ':'",
+                "context": { "kind": "session", "name": "arc:1", "reason": "name contains ':'" },
+            })
+        );
+        assert!(window("v1.2").is_err());
+        assert_eq!(window(" \n").unwrap_err().code(), "invalid_name");
+    }
+}
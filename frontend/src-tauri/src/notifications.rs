@@ -0,0 +1,162 @@
+use crate::history;
+use crate::model::{ARCRun, NotificationToggles, RunStatus, WebhookConfig};
+use serde_json::{json, Value as JsonValue};
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const WEBHOOK_ATTEMPTS: u32 = 3;
+
+fn format_duration(secs: i64) -> String {
+    let (hours, minutes) = (secs / 3600, (secs % 3600) / 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m")
+    } else if minutes > 0 {
+        format!("{minutes}m")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+fn title(status: &RunStatus, toggles: &NotificationToggles) -> Option<&'static str> {
+    match status {
+        RunStatus::Finished if toggles.finished => Some("ARC run finished"),
+        RunStatus::Failed if toggles.failed => Some("ARC run failed"),
+        RunStatus::Stalled if toggles.stalled => Some("ARC run stalled"),
+        _ => None,
+    }
+}
+
+fn body(run: &ARCRun) -> String {
+    let host = run.host.as_deref().unwrap_or("local");
+    let mut body = format!("{} on {host}", run.name);
+    if let Some(secs) = history::duration_secs(run) {
+        body.push_str(&format!(" after {}", format_duration(secs)));
+    }
+    if run.status == RunStatus::Failed {
+        if let Some(err) = &run.last_stderr {
+            body.push_str(&format!("\n{err}"));
+        }
+    }
+    body
+}
+
+// Shows a native notification when `run` just entered a status the user
+// asked to hear about.
+pub fn notify_transition(app: &AppHandle, run: &ARCRun, toggles: &NotificationToggles) {
+    let Some(title) = title(&run.status, toggles) else {
+        return;
+    };
+    let _ = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(body(run))
+        .show();
+}
+
+fn wants(hook: &WebhookConfig, status: &RunStatus) -> bool {
+    if hook.statuses.is_empty() {
+        matches!(status, RunStatus::Finished | RunStatus::Failed)
+    } else {
+        hook.statuses.contains(status)
+    }
+}
+
+fn webhook_payload(hook: &WebhookConfig, event: &str, run: &ARCRun) -> JsonValue {
+    if hook.slack {
+        json!({ "text": format!("{}: {}", event, body(run)) })
+    } else {
+        json!({ "event": event, "run": run })
+    }
+}
+
+fn post(url: &str, payload: &JsonValue) -> Result<(), String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(WEBHOOK_TIMEOUT))
+        .build()
+        .into();
+    agent
+        .post(url)
+        .send_json(payload)
+        .map(|_| ())
+        .map_err(|e| format!("POST {url}: {e}"))
+}
+
+// Retries with a growing pause so a brief outage on the receiving side
+// doesn't lose the event.
+fn post_with_retries(url: &str, payload: &JsonValue) -> Result<(), String> {
+    let mut last_err = String::new();
+    for attempt in 0..WEBHOOK_ATTEMPTS {
+        if attempt > 0 {
+            thread::sleep(Duration::from_secs(2u64.pow(attempt)));
+        }
+        match post(url, payload) {
+            Ok(()) => return Ok(()),
+            Err(e) => last_err = e,
+        }
+    }
+    Err(last_err)
+}
+
+// Fires the matching webhooks in the background; the monitor must never wait
+// on a slow endpoint.
+pub fn send_webhooks(hooks: &[WebhookConfig], event: &str, run: &ARCRun) {
+    for hook in hooks.iter().filter(|h| wants(h, &run.status)) {
+        let url = hook.url.clone();
+        let payload = webhook_payload(hook, event, run);
+        thread::spawn(move || {
+            if let Err(e) = post_with_retries(&url, &payload) {
+                tracing::warn!("webhook failed: {e}");
+            }
+        });
+    }
+}
+
+pub fn test_webhook(hook: WebhookConfig) -> Result<(), String> {
+    let payload = if hook.slack {
+        json!({ "text": "Test notification from the ARC orchestrator" })
+    } else {
+        json!({ "event": "notification-test", "run": null })
+    };
+    post(&hook.url, &payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_duration, title, wants};
+    use crate::model::{NotificationToggles, RunStatus, WebhookConfig};
+
+    #[test]
+    fn toggles_gate_titles_and_durations_read_naturally() {
+        let toggles = NotificationToggles {
+            stalled: false,
+            ..NotificationToggles::default()
+        };
+        assert_eq!(title(&RunStatus::Failed, &toggles), Some("ARC run failed"));
+        assert_eq!(title(&RunStatus::Stalled, &toggles), None);
+        assert_eq!(title(&RunStatus::Running, &toggles), None);
+
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(600), "10m");
+        assert_eq!(format_duration(3 * 3600 + 5 * 60), "3h 05m");
+    }
+
+    #[test]
+    fn webhooks_default_to_terminal_outcomes() {
+        let hook = WebhookConfig {
+            url: "https://hooks.example.org/x".into(),
+            ..WebhookConfig::default()
+        };
+        assert!(wants(&hook, &RunStatus::Failed));
+        assert!(!wants(&hook, &RunStatus::Stalled));
+        let stalled_only = WebhookConfig {
+            statuses: vec![RunStatus::Stalled],
+            ..hook
+        };
+        assert!(wants(&stalled_only, &RunStatus::Stalled));
+        assert!(!wants(&stalled_only, &RunStatus::Finished));
+    }
+}
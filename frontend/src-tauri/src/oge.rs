@@ -0,0 +1,147 @@
+use crate::model::{BatchConfig, RunStatus};
+use crate::scheduler::{self, remote, JobScheduler};
+use crate::HostProfile;
+use std::path::Path;
+
+const SCRIPT_NAME: &str = "submit_arc.sge";
+
+const DEFAULT_TEMPLATE: &str = "#!/bin/bash -l
+#$ -N {name}
+#$ -o {work_dir}/sge.out
+#$ -e {work_dir}/sge.err
+#$ -S /bin/bash
+{directives}
+cd {work_dir}
+{command}
+";
+
+// Open Grid Engine and its SGE relatives.
+pub struct Oge;
+
+fn directives(batch: &BatchConfig) -> String {
+    let mut lines = Vec::new();
+    if let Some(queue) = &batch.partition {
+        lines.push(format!("#$ -q {queue}"));
+    }
+    if let Some(time) = &batch.time_limit {
+        lines.push(format!("#$ -l h_rt={time}"));
+    }
+    if let Some(cpus) = batch.cpus {
+        lines.push(format!("#$ -pe smp {cpus}"));
+    }
+    if let Some(memory) = &batch.memory {
+        lines.push(format!("#$ -l h_vmem={memory}"));
+    }
+    lines.extend(batch.extra_directives.iter().cloned());
+    lines.join("\n")
+}
+
+// State column of the job's row in plain `qstat` output.
+fn parse_qstat(stdout: &str, job_id: &str) -> Option<RunStatus> {
+    let state = stdout.lines().find_map(|line| {
+        let cols: Vec<&str> = line.split_whitespace().collect();
+        (cols.first() == Some(&job_id)).then(|| cols.get(4).copied())?
+    })?;
+    if state.contains('E') {
+        Some(RunStatus::Failed)
+    } else if state.contains('r') || state.contains('t') {
+        Some(RunStatus::Running)
+    } else if state.contains("qw") || state.contains('h') {
+        Some(RunStatus::Starting)
+    } else {
+        None
+    }
+}
+
+// `qacct -j` once the job has left the queue.
+fn parse_qacct(stdout: &str) -> Option<RunStatus> {
+    let field = |key: &str| {
+        stdout.lines().find_map(|line| {
+            let (k, v) = line.split_once(char::is_whitespace)?;
+            (k == key).then(|| v.trim().to_string())
+        })
+    };
+    let failed = field("failed")?;
+    let exit = field("exit_status")?;
+    if failed.starts_with('0') && exit == "0" {
+        Some(RunStatus::Finished)
+    } else {
+        Some(RunStatus::Failed)
+    }
+}
+
+impl JobScheduler for Oge {
+    fn submit(
+        &self,
+        profile: &HostProfile,
+        batch: &BatchConfig,
+        name: &str,
+        work_dir: &Path,
+        command: &str,
+    ) -> Result<String, String> {
+        let script = scheduler::render(
+            batch,
+            DEFAULT_TEMPLATE,
+            &directives(batch),
+            name,
+            work_dir,
+            command,
+        );
+        let stdout = scheduler::write_and_submit(
+            profile,
+            work_dir,
+            &[(SCRIPT_NAME, script)],
+            &format!("qsub -terse {SCRIPT_NAME}"),
+        )?;
+        let id = stdout.trim();
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("unexpected qsub output: {id}"));
+        }
+        Ok(id.to_string())
+    }
+
+    fn status(&self, profile: &HostProfile, job_id: &str) -> Option<RunStatus> {
+        let listing = remote(profile, "qstat".into()).ok()?;
+        if let Some(status) = parse_qstat(&listing, job_id) {
+            return Some(status);
+        }
+        let job = shell_escape::escape(job_id.into());
+        let accounted = remote(profile, format!("qacct -j {job}")).ok()?;
+        parse_qacct(&accounted)
+    }
+
+    // Grid Engine can't deliver an arbitrary signal, so a graceful stop is
+    // a plain qdel as well.
+    fn cancel(
+        &self,
+        profile: &HostProfile,
+        job_id: &str,
+        _signal: Option<&str>,
+    ) -> Result<(), String> {
+        let job = shell_escape::escape(job_id.into());
+        remote(profile, format!("qdel {job}")).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_qacct, parse_qstat};
+    use crate::model::RunStatus;
+
+    #[test]
+    fn qstat_and_qacct_map_to_run_states() {
+        let listing = "job-ID  prior   name  user  state submit/start at     queue  slots\n\
+            -----------------------------------------------------------------\n\
+            4711 0.55500 CH4   u     r     03/01/2024 12:00:00 all.q@n1  8\n\
+            4712 0.00000 OH    u     qw    03/01/2024 12:01:00           8\n";
+        assert_eq!(parse_qstat(listing, "4711"), Some(RunStatus::Running));
+        assert_eq!(parse_qstat(listing, "4712"), Some(RunStatus::Starting));
+        assert_eq!(parse_qstat(listing, "9999"), None);
+
+        let done = "jobnumber    4711\nfailed       0    \nexit_status  0\n";
+        assert_eq!(parse_qacct(done), Some(RunStatus::Finished));
+        let killed =
+            "jobnumber    4711\nfailed       100 : assumedly after job\nexit_status  137\n";
+        assert_eq!(parse_qacct(killed), Some(RunStatus::Failed));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::model::{BatchConfig, RunStatus};
+use crate::scheduler::{self, remote, JobScheduler};
+use crate::HostProfile;
+use std::path::Path;
+
+const SCRIPT_NAME: &str = "submit_arc.pbs";
+
+const DEFAULT_TEMPLATE: &str = "#!/bin/bash -l
+#PBS -N {name}
+#PBS -o {work_dir}/pbs.out
+#PBS -e {work_dir}/pbs.err
+{directives}
+cd {work_dir}
+{command}
+";
+
+// PBS Pro and Torque.
+pub struct Pbs;
+
+fn directives(batch: &BatchConfig) -> String {
+    let mut lines = Vec::new();
+    if let Some(queue) = &batch.partition {
+        lines.push(format!("#PBS -q {queue}"));
+    }
+    if let Some(time) = &batch.time_limit {
+        lines.push(format!("#PBS -l walltime={time}"));
+    }
+    if let Some(cpus) = batch.cpus {
+        lines.push(format!("#PBS -l nodes=1:ppn={cpus}"));
+    }
+    if let Some(memory) = &batch.memory {
+        lines.push(format!("#PBS -l mem={memory}"));
+    }
+    lines.extend(batch.extra_directives.iter().cloned());
+    lines.join("\n")
+}
+
+// Reads `job_state` and the exit status out of `qstat -f`.
+fn parse_qstat(stdout: &str) -> Option<RunStatus> {
+    let field = |key: &str| {
+        stdout.lines().find_map(|line| {
+            let (k, v) = line.split_once('=')?;
+            k.trim()
+                .eq_ignore_ascii_case(key)
+                .then(|| v.trim().to_string())
+        })
+    };
+    match field("job_state")?.as_str() {
+        "Q" | "H" | "W" | "T" | "S" => Some(RunStatus::Starting),
+        "R" | "E" | "B" => Some(RunStatus::Running),
+        "C" | "F" => match field("exit_status").as_deref() {
+            Some("0") => Some(RunStatus::Finished),
+            _ => Some(RunStatus::Failed),
+        },
+        _ => None,
+    }
+}
+
+impl JobScheduler for Pbs {
+    fn submit(
+        &self,
+        profile: &HostProfile,
+        batch: &BatchConfig,
+        name: &str,
+        work_dir: &Path,
+        command: &str,
+    ) -> Result<String, String> {
+        let script = scheduler::render(
+            batch,
+            DEFAULT_TEMPLATE,
+            &directives(batch),
+            name,
+            work_dir,
+            command,
+        );
+        let stdout = scheduler::write_and_submit(
+            profile,
+            work_dir,
+            &[(SCRIPT_NAME, script)],
+            &format!("qsub {SCRIPT_NAME}"),
+        )?;
+        // e.g. "1234.pbs-server"
+        let id = stdout.trim();
+        if id.is_empty() {
+            return Err("qsub printed no job id".into());
+        }
+        Ok(id.to_string())
+    }
+
+    // `-x` keeps finished jobs visible on PBS Pro; Torque shows them for
+    // keep_completed seconds.
+    fn status(&self, profile: &HostProfile, job_id: &str) -> Option<RunStatus> {
+        let job = shell_escape::escape(job_id.into());
+        let stdout = remote(
+            profile,
+            format!("qstat -x -f {job} 2>/dev/null || qstat -f {job}"),
+        )
+        .ok()?;
+        parse_qstat(&stdout)
+    }
+
+    fn cancel(
+        &self,
+        profile: &HostProfile,
+        job_id: &str,
+        signal: Option<&str>,
+    ) -> Result<(), String> {
+        let job = shell_escape::escape(job_id.into());
+        let command = match signal {
+            Some(signal) => format!("qsig -s SIG{signal} {job}"),
+            None => format!("qdel {job}"),
+        };
+        remote(profile, command).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_qstat;
+    use crate::model::RunStatus;
+
+    #[test]
+    fn qstat_states_map_to_run_states() {
+        let running = "Job Id: 1234.server\n    Job_Name = CH4\n    job_state = R\n";
+        assert_eq!(parse_qstat(running), Some(RunStatus::Running));
+        let done = "Job Id: 1234.server\n    job_state = F\n    Exit_status = 0\n";
+        assert_eq!(parse_qstat(done), Some(RunStatus::Finished));
+        let failed = "Job Id: 1234.server\n    job_state = C\n    exit_status = 271\n";
+        assert_eq!(parse_qstat(failed), Some(RunStatus::Failed));
+        assert_eq!(parse_qstat("qstat: Unknown Job Id"), None);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::runs;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const SLOW_EVENT: &str = "slow-command";
+// the offending command is cut to this many characters in the event
+const COMMAND_PREVIEW: usize = 300;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PerfStat {
+    pub kind: String, // "tmux capture-pane", "remote tmux list-windows", "remote uptime", ...
+    pub count: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    pub last_ms: u64,
+    pub slow: u64, // how many went over the threshold
+}
+
+static STATS: Lazy<Mutex<HashMap<String, PerfStat>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// per-kind slow thresholds; kinds without one use DEFAULT_SLOW_MS
+static THRESHOLDS: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static DEFAULT_SLOW_MS: AtomicU64 = AtomicU64::new(3000);
+// set by start(); before that there is no UI to warn
+static WARNINGS: OnceCell<mpsc::Sender<JsonValue>> = OnceCell::new();
+
+// What a command counts as: its program plus the tmux subcommand when it
+// runs one, with "remote " in front for commands run over SSH.
+fn kind(command: &str, remote: bool) -> String {
+    let words: Vec<&str> = command
+        .split(|c: char| c.is_whitespace() || matches!(c, '(' | '$' | ';'))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let tmux = words
+        .iter()
+        .position(|w| *w == "tmux" || w.ends_with("/tmux"));
+    let kind = match tmux {
+        Some(i) => match words
+            .get(i + 1)
+            .filter(|sub| sub.starts_with(|c: char| c.is_ascii_lowercase()))
+        {
+            Some(sub) => format!("tmux {sub}"),
+            None => "tmux".to_string(),
+        },
+        None => words.first().unwrap_or(&"?").to_string(),
+    };
+    if remote {
+        format!("remote {kind}")
+    } else {
+        kind
+    }
+}
+
+fn threshold_ms(kind: &str) -> u64 {
+    THRESHOLDS
+        .lock()
+        .unwrap()
+        .get(kind)
+        .copied()
+        .unwrap_or_else(|| DEFAULT_SLOW_MS.load(Ordering::Relaxed))
+}
+
+// Sets the slow threshold for one kind, or for every kind without its own.
+pub fn set_threshold(kind: Option<String>, ms: u64) {
+    match kind {
+        Some(kind) => {
+            THRESHOLDS.lock().unwrap().insert(kind, ms);
+        }
+        None => DEFAULT_SLOW_MS.store(ms, Ordering::Relaxed),
+    }
+}
+
+// Adds one run of `command` to its kind's stats and warns with
+// SLOW_EVENT when it took longer than the kind's threshold.
+pub fn record(command: &str, remote: bool, elapsed: Duration) {
+    let kind = kind(command, remote);
+    let ms = elapsed.as_millis() as u64;
+    let threshold = threshold_ms(&kind);
+    let slow = ms > threshold;
+    add(&kind, ms, slow);
+    if slow {
+        if let Some(warnings) = WARNINGS.get() {
+            let preview: String = command.chars().take(COMMAND_PREVIEW).collect();
+            let _ = warnings.send(json!({
+                "kind": kind,
+                "command": preview,
+                "elapsed_ms": ms,
+                "threshold_ms": threshold,
+            }));
+        }
+    }
+}
+
+// Emits slow-command warnings from a thread of their own, so the command
+// that was slow does not also wait on the UI.
+pub fn start() {
+    let (tx, rx) = mpsc::channel();
+    if WARNINGS.set(tx).is_ok() {
+        thread::spawn(move || {
+            for warning in rx {
+                runs::emit(SLOW_EVENT, warning);
+            }
+        });
+    }
+}
+
+fn add(kind: &str, ms: u64, slow: bool) {
+    let mut stats = STATS.lock().unwrap();
+    let stat = stats.entry(kind.to_string()).or_insert_with(|| PerfStat {
+        kind: kind.to_string(),
+        ..PerfStat::default()
+    });
+    stat.count += 1;
+    stat.total_ms += ms;
+    stat.max_ms = stat.max_ms.max(ms);
+    stat.last_ms = ms;
+    stat.slow += slow as u64;
+}
+
+pub fn perf_stats() -> Vec<PerfStat> {
+    let mut stats: Vec<PerfStat> = STATS.lock().unwrap().values().cloned().collect();
+    stats.sort_by(|a, b| a.kind.cmp(&b.kind));
+    stats
+}
+
+// `Command::output` that records how long the subprocess took.
+pub trait TimedOutput {
+    fn timed_output(&mut self) -> io::Result<Output>;
+}
+
+impl TimedOutput for Command {
+    fn timed_output(&mut self) -> io::Result<Output> {
+        let program = Path::new(self.get_program())
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let args: Vec<String> = self
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        let started = Instant::now();
+        let out = self.output();
+        record(
+            &format!("{program} {}", args.join(" ")),
+            false,
+            started.elapsed(),
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add, kind, perf_stats};
+
+    #[test]
+    fn commands_are_grouped_by_kind() {
+        assert_eq!(
+            kind("tmux capture-pane -p -t @1", false),
+            "tmux capture-pane"
+        );
+        assert_eq!(
+            kind("(out=$(tmux capture-pane -p -t @1 && echo x)", true),
+            "remote tmux capture-pane"
+        );
+        assert_eq!(kind("uptime", true), "remote uptime");
+        assert_eq!(kind("tmux -V", true), "remote tmux");
+
+        add("perftest", 40, true);
+        add("perftest", 10, false);
+        let stat = perf_stats()
+            .into_iter()
+            .find(|s| s.kind == "perftest")
+            .unwrap();
+        assert_eq!((stat.count, stat.total_ms, stat.max_ms), (2, 50, 40));
+        assert_eq!((stat.last_ms, stat.slow), (10, 1));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    coalesce, creds_from, local_capture, remote_capture_command, run_remote_cmd, runs, utf8,
+    HostProfile,
+};
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// a prefetched pane older than this is captured again instead
+const FRESH: Duration = Duration::from_secs(5);
+
+// (host label or "local", session, window index, lines)
+type Key = (String, String, u32, u32);
+
+static READY: Lazy<Mutex<HashMap<Key, (Instant, String)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static PENDING: Lazy<Mutex<HashSet<Key>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn key(profile: Option<&HostProfile>, session: &str, index: u32, lines: u32) -> Key {
+    let host = profile
+        .map(runs::host_label)
+        .unwrap_or_else(|| "local".into());
+    (host, session.to_string(), index, lines)
+}
+
+fn neighbours(index: u32) -> Vec<u32> {
+    index
+        .checked_sub(1)
+        .into_iter()
+        .chain(index.checked_add(1))
+        .collect()
+}
+
+// Hands out a fresh prefetched capture once; the next ask captures live.
+pub fn take(
+    profile: Option<&HostProfile>,
+    session: &str,
+    index: u32,
+    lines: u32,
+) -> Option<String> {
+    let (at, text) = READY
+        .lock()
+        .unwrap()
+        .remove(&key(profile, session, index, lines))?;
+    (at.elapsed() < FRESH).then_some(text)
+}
+
+// Same command and coalescing key as a plain remote_tmux_capture_pane, so a
+// viewer switching mid-prefetch waits for it rather than capturing twice,
+// and both count against the host's rate limit.
+fn capture(
+    profile: Option<&HostProfile>,
+    session: &str,
+    index: u32,
+    lines: u32,
+) -> Result<String, String> {
+    let Some(profile) = profile else {
+        return local_capture(&format!("{session}:{index}"), lines);
+    };
+    let cmd = remote_capture_command(&format!("{session}:{index}"), lines);
+    coalesce::shared(Some(profile), cmd.clone(), || {
+        let out = run_remote_cmd(&creds_from(profile), cmd)?;
+        if out.code != 0 {
+            return Err(out.stderr);
+        }
+        Ok(utf8::mark(out.stdout, out.lossy))
+    })
+}
+
+// Captures the windows either side of the one being viewed in the
+// background, so switching to them can be answered from here.
+pub fn around(profile: Option<HostProfile>, session: String, index: u32, lines: u32) {
+    READY
+        .lock()
+        .unwrap()
+        .retain(|_, (at, _)| at.elapsed() < FRESH);
+    for index in neighbours(index) {
+        let key = key(profile.as_ref(), &session, index, lines);
+        if READY.lock().unwrap().contains_key(&key) || !PENDING.lock().unwrap().insert(key.clone())
+        {
+            continue;
+        }
+        let (profile, session) = (profile.clone(), session.clone());
+        thread::spawn(move || {
+            // a window that is not there or not reachable is simply not kept
+            if let Ok(text) = capture(profile.as_ref(), &session, index, lines) {
+                if !text.is_empty() {
+                    READY
+                        .lock()
+                        .unwrap()
+                        .insert(key.clone(), (Instant::now(), text));
+                }
+            }
+            PENDING.lock().unwrap().remove(&key);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key, neighbours, take, FRESH, READY};
+    use std::time::Instant;
+
+    #[test]
+    fn prefetched_panes_are_taken_once_while_fresh() {
+        assert_eq!(neighbours(0), vec![1]);
+        assert_eq!(neighbours(3), vec![2, 4]);
+
+        let fresh = key(None, "prefetch-test", 1, 200);
+        let stale = key(None, "prefetch-test", 2, 200);
+        READY
+            .lock()
+            .unwrap()
+            .insert(fresh, (Instant::now(), "$ ls\n".into()));
+        READY
+            .lock()
+            .unwrap()
+            .insert(stale, (Instant::now() - FRESH, "$ pwd\n".into()));
+        assert_eq!(take(None, "prefetch-test", 1, 200), Some("$ ls\n".into()));
+        assert_eq!(take(None, "prefetch-test", 1, 200), None);
+        assert_eq!(take(None, "prefetch-test", 2, 200), None);
+    }
+}
@@ -0,0 +1,304 @@
+use crate::profiles::{self, StoredProfile};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const FORMAT: &str = "arc-orchestrator-profiles";
+const VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    Skip,
+    Replace, // keeps the existing id so runs still point at it
+    Rename,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Sealed {
+    kdf: String, // always "argon2id" for now
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+// Either `profiles` or `sealed` is set, depending on whether a passphrase
+// was given on export.
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    format: String,
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    profiles: Option<Vec<StoredProfile>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed: Option<Sealed>,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct ImportReport {
+    pub added: Vec<String>, // names as stored, so renamed profiles show their new name
+    pub replaced: Vec<String>,
+    pub skipped: Vec<String>, // "name: reason"
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("derive key: {e}"))?;
+    Ok(key)
+}
+
+fn seal(plain: &[u8], passphrase: &str) -> Result<Sealed, String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let data = cipher
+        .encrypt(&nonce, plain)
+        .map_err(|_| "encrypt profiles failed".to_string())?;
+    Ok(Sealed {
+        kdf: "argon2id".into(),
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        data: STANDARD.encode(data),
+    })
+}
+
+fn open(sealed: &Sealed, passphrase: &str) -> Result<Vec<u8>, String> {
+    if sealed.kdf != "argon2id" {
+        return Err(format!("unsupported key derivation: {}", sealed.kdf));
+    }
+    let decode = |s: &str| {
+        STANDARD
+            .decode(s)
+            .map_err(|e| format!("corrupt bundle: {e}"))
+    };
+    let salt = decode(&sealed.salt)?;
+    let nonce = decode(&sealed.nonce)?;
+    if nonce.len() != NONCE_LEN {
+        return Err("corrupt bundle: bad nonce".into());
+    }
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), decode(&sealed.data)?.as_slice())
+        .map_err(|_| "wrong passphrase or corrupt bundle".to_string())
+}
+
+// Stored profiles never hold passwords or key passphrases, so the only
+// secret left to strip is the private key location. Key auth then falls
+// back to the agent, which is where a shared key usually lives anyway.
+fn strip_secrets(mut stored: StoredProfile) -> StoredProfile {
+    if stored.profile.auth.as_deref() == Some("key") {
+        stored.profile.auth = Some("agent".into());
+    }
+    stored.profile.key_path = None;
+    stored.profile.password = None;
+    stored.profile.key_pass = None;
+    stored
+}
+
+fn encode(profiles: Vec<StoredProfile>, passphrase: Option<&str>) -> Result<String, String> {
+    let bundle = match passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => {
+            let plain = serde_json::to_vec(&profiles).map_err(|e| e.to_string())?;
+            Bundle {
+                format: FORMAT.into(),
+                version: VERSION,
+                profiles: None,
+                sealed: Some(seal(&plain, passphrase)?),
+            }
+        }
+        None => Bundle {
+            format: FORMAT.into(),
+            version: VERSION,
+            profiles: Some(profiles),
+            sealed: None,
+        },
+    };
+    serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())
+}
+
+fn decode(text: &str, passphrase: Option<&str>) -> Result<Vec<StoredProfile>, String> {
+    let bundle: Bundle =
+        serde_json::from_str(text).map_err(|e| format!("not a profile bundle: {e}"))?;
+    if bundle.format != FORMAT {
+        return Err(format!("not a profile bundle: format {:?}", bundle.format));
+    }
+    if bundle.version > VERSION {
+        return Err(format!(
+            "bundle version {} is newer than this app supports",
+            bundle.version
+        ));
+    }
+    match (bundle.profiles, bundle.sealed) {
+        (Some(profiles), _) => Ok(profiles),
+        (None, Some(sealed)) => {
+            let passphrase = passphrase
+                .filter(|p| !p.is_empty())
+                .ok_or("this bundle is encrypted, a passphrase is needed")?;
+            serde_json::from_slice(&open(&sealed, passphrase)?)
+                .map_err(|e| format!("corrupt bundle: {e}"))
+        }
+        (None, None) => Err("bundle holds no profiles".into()),
+    }
+}
+
+// A profile conflicts with an existing one sharing its id or, failing
+// that, its name.
+fn merge(
+    existing: &mut Vec<StoredProfile>,
+    incoming: Vec<StoredProfile>,
+    on_conflict: OnConflict,
+) -> ImportReport {
+    let mut report = ImportReport::default();
+    for mut stored in incoming {
+        if let Err(e) = profiles::validate(&stored.name, &stored.profile) {
+            report.skipped.push(format!("{}: {e}", stored.name));
+            continue;
+        }
+        stored.name = stored.name.trim().to_string();
+        let clash = existing.iter().position(|p| p.id == stored.id).or_else(|| {
+            existing
+                .iter()
+                .position(|p| p.name.eq_ignore_ascii_case(&stored.name))
+        });
+        match (clash, on_conflict) {
+            (None, _) => {
+                report.added.push(stored.name.clone());
+                existing.push(stored);
+            }
+            (Some(idx), OnConflict::Skip) => {
+                report.skipped.push(format!(
+                    "{}: conflicts with {:?}",
+                    stored.name, existing[idx].name
+                ));
+            }
+            (Some(idx), OnConflict::Replace) => {
+                let taken = existing
+                    .iter()
+                    .enumerate()
+                    .any(|(i, p)| i != idx && p.name.eq_ignore_ascii_case(&stored.name));
+                if taken {
+                    report
+                        .skipped
+                        .push(format!("{}: name is used by another profile", stored.name));
+                    continue;
+                }
+                existing[idx].name = stored.name.clone();
+                existing[idx].profile = stored.profile;
+                report.replaced.push(stored.name);
+            }
+            (Some(_), OnConflict::Rename) => {
+                stored.id = uuid::Uuid::new_v4().to_string();
+                if existing
+                    .iter()
+                    .any(|p| p.name.eq_ignore_ascii_case(&stored.name))
+                {
+                    stored.name = profiles::free_name(existing, &stored.name);
+                }
+                report.added.push(stored.name.clone());
+                existing.push(stored);
+            }
+        }
+    }
+    report
+}
+
+// Exports the given profiles, or all of them when `ids` is empty.
+pub fn export(
+    ids: Vec<String>,
+    path: &Path,
+    include_secrets: bool,
+    passphrase: Option<String>,
+) -> Result<usize, String> {
+    let all = profiles::list();
+    for id in &ids {
+        if !all.iter().any(|p| &p.id == id) {
+            return Err(format!("unknown profile: {id}"));
+        }
+    }
+    let selected: Vec<StoredProfile> = all
+        .into_iter()
+        .filter(|p| ids.is_empty() || ids.contains(&p.id))
+        .map(|p| if include_secrets { p } else { strip_secrets(p) })
+        .collect();
+    let count = selected.len();
+    let text = encode(selected, passphrase.as_deref())?;
+    std::fs::write(path, text).map_err(|e| format!("write {}: {e}", path.display()))?;
+    Ok(count)
+}
+
+pub fn import(
+    path: &Path,
+    passphrase: Option<String>,
+    on_conflict: OnConflict,
+) -> Result<ImportReport, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|e| format!("read {}: {e}", path.display()))?;
+    let incoming = decode(&text, passphrase.as_deref())?;
+    profiles::modify(|existing| merge(existing, incoming, on_conflict))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, merge, strip_secrets, OnConflict};
+    use crate::profiles::StoredProfile;
+    use serde_json::json;
+
+    fn stored(id: &str, name: &str) -> StoredProfile {
+        serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "host": "hpc.example.org",
+            "user": "arc",
+            "auth": "key",
+            "key_path": "~/.ssh/id_ed25519",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn bundles_roundtrip_and_merge_on_conflict() {
+        let text = encode(vec![stored("a", "hpc")], Some("hunter2")).unwrap();
+        assert!(!text.contains("hpc.example.org"));
+        assert_eq!(decode(&text, Some("hunter2")).unwrap()[0].name, "hpc");
+        assert!(decode(&text, Some("wrong")).is_err());
+        assert!(decode(&text, None).is_err());
+        let plain = encode(vec![strip_secrets(stored("a", "hpc"))], None).unwrap();
+        let shared = decode(&plain, None).unwrap();
+        assert_eq!(shared[0].profile.key_path, None);
+        assert_eq!(shared[0].profile.auth.as_deref(), Some("agent"));
+
+        let incoming = || vec![stored("a", "hpc"), stored("b", "HPC"), stored("c", "new")];
+        let mut existing = vec![stored("a", "hpc")];
+        let report = merge(&mut existing, incoming(), OnConflict::Skip);
+        assert_eq!(report.added, ["new"]);
+        assert_eq!(report.skipped.len(), 2);
+
+        let mut existing = vec![stored("a", "hpc")];
+        let report = merge(&mut existing, incoming(), OnConflict::Rename);
+        assert_eq!(report.added, ["hpc (2)", "HPC (3)", "new"]);
+        assert_eq!(existing.len(), 4);
+        assert!(existing[1..].iter().all(|p| p.id != "a"));
+
+        let mut existing = vec![stored("a", "hpc")];
+        let report = merge(&mut existing, incoming(), OnConflict::Replace);
+        assert_eq!(report.replaced, ["hpc", "HPC"]);
+        assert_eq!(existing[0].id, "a");
+        assert_eq!(existing[0].name, "HPC");
+
+        let mut broken = stored("d", "broken");
+        broken.profile.key_path = None;
+        let report = merge(&mut existing, vec![broken], OnConflict::Skip);
+        assert_eq!(report.skipped, ["broken: auth=key requires key_path"]);
+    }
+}
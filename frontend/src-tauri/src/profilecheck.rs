@@ -0,0 +1,229 @@
+use crate::model::AppConfig;
+use crate::runs::{self, conda_env};
+use crate::ssh::{self, ExecOut};
+use crate::{creds_from, run_remote_cmd, HostProfile};
+use serde::Serialize;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+const TCP_TIMEOUT: Duration = Duration::from_secs(5);
+const PRELUDE_MARKER: &str = "__arc_prelude_ok__";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckState {
+    Ok,
+    Warn,
+    Fail,
+    Skipped, // an earlier check failed, or there was no config to check against
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Check {
+    pub name: String,
+    pub state: CheckState,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnosis {
+    pub host: String,
+    pub ok: bool, // no check failed
+    pub checks: Vec<Check>,
+}
+
+pub fn check(name: &str, state: CheckState, detail: impl Into<String>) -> Check {
+    Check {
+        name: name.to_string(),
+        state,
+        detail: detail.into(),
+    }
+}
+
+pub fn tcp_check(profile: &HostProfile) -> Check {
+    let target = (profile.host.as_str(), profile.port.unwrap_or(22));
+    let addr = match target.to_socket_addrs().map(|mut a| a.next()) {
+        Ok(Some(addr)) => addr,
+        Ok(None) => return check("tcp", CheckState::Fail, "host did not resolve"),
+        Err(e) => return check("tcp", CheckState::Fail, format!("resolve: {e}")),
+    };
+    match TcpStream::connect_timeout(&addr, TCP_TIMEOUT) {
+        Ok(_) => check("tcp", CheckState::Ok, format!("{addr} reachable")),
+        Err(e) => check("tcp", CheckState::Fail, format!("{addr}: {e}")),
+    }
+}
+
+// Login scripts that print or fail show up as stderr around the marker;
+// a missing marker means the prelude kills the shell.
+fn prelude_check(out: &ExecOut) -> Check {
+    if !out.stdout.contains(PRELUDE_MARKER) {
+        return check("shell prelude", CheckState::Fail, out.stderr.trim());
+    }
+    if out.stderr.trim().is_empty() {
+        check("shell prelude", CheckState::Ok, "login shell is clean")
+    } else {
+        check("shell prelude", CheckState::Warn, out.stderr.trim())
+    }
+}
+
+fn tool_check(name: &str, out: Result<ExecOut, String>, missing: &str) -> Check {
+    match out {
+        Ok(out) if out.code == 0 => {
+            let said = format!("{}{}", out.stdout, out.stderr);
+            check(
+                name,
+                CheckState::Ok,
+                said.lines().next().unwrap_or("").trim(),
+            )
+        }
+        Ok(out) => check(
+            name,
+            CheckState::Fail,
+            format!("{missing}: {}", out.stderr.trim()),
+        ),
+        Err(e) => check(name, CheckState::Fail, e),
+    }
+}
+
+fn config_checks(profile: &HostProfile, config: &AppConfig) -> Vec<Check> {
+    let creds = creds_from(profile);
+    let sh = |command: String| run_remote_cmd(&creds, command);
+    let mut checks = Vec::new();
+
+    let python = match conda_env(config, true) {
+        Some(env) => {
+            let env = shell_escape::escape(env.into());
+            sh(format!("conda run -n {env} python --version"))
+        }
+        None => {
+            let python = shell_escape::escape(config.python_path.as_str().into());
+            sh(format!("{python} --version"))
+        }
+    };
+    checks.push(tool_check("python", python, "python not runnable"));
+
+    let arc_path = config
+        .remote_arc_path
+        .as_deref()
+        .unwrap_or(&config.arc_path);
+    let arc = shell_escape::escape(arc_path.into());
+    checks.push(match sh(format!("test -f {arc}")) {
+        Ok(out) if out.code == 0 => check("arc path", CheckState::Ok, arc_path),
+        Ok(_) => check(
+            "arc path",
+            CheckState::Fail,
+            format!("{arc_path} not found"),
+        ),
+        Err(e) => check("arc path", CheckState::Fail, e),
+    });
+
+    // the work dir may not exist yet, so its nearest existing parent counts
+    let dir = shell_escape::escape(config.default_work_dir.as_str().into());
+    let writable = format!(
+        "d={dir}; while [ ! -e \"$d\" ]; do d=$(dirname \"$d\"); done; test -d \"$d\" -a -w \"$d\""
+    );
+    checks.push(match sh(writable) {
+        Ok(out) if out.code == 0 => check("work dir", CheckState::Ok, &config.default_work_dir),
+        Ok(_) => check(
+            "work dir",
+            CheckState::Fail,
+            format!("{} is not writable", config.default_work_dir),
+        ),
+        Err(e) => check("work dir", CheckState::Fail, e),
+    });
+    checks
+}
+
+const CONFIG_CHECKS: &[&str] = &["python", "arc path", "work dir"];
+
+// Runs every check in order; once the host can't be reached or logged into,
+// the rest are reported as skipped rather than failing the same way.
+pub fn diagnose(profile: &HostProfile, config: Option<&AppConfig>) -> Diagnosis {
+    let mut checks = vec![tcp_check(profile)];
+    let mut reachable = checks[0].state == CheckState::Ok;
+    if reachable {
+        let auth = match ssh::exec(&creds_from(profile), "true") {
+            Ok(_) => check(
+                "auth",
+                CheckState::Ok,
+                format!("logged in as {}", profile.user),
+            ),
+            Err(e) => check("auth", CheckState::Fail, e),
+        };
+        reachable = auth.state == CheckState::Ok;
+        checks.push(auth);
+    }
+    if reachable {
+        let creds = creds_from(profile);
+        let prelude = run_remote_cmd(&creds, format!("echo {PRELUDE_MARKER}"));
+        checks.push(match prelude {
+            Ok(out) => prelude_check(&out),
+            Err(e) => check("shell prelude", CheckState::Fail, e),
+        });
+        let tmux = run_remote_cmd(&creds, "tmux -V".into());
+        checks.push(tool_check("tmux", tmux, "tmux not found"));
+        match config {
+            Some(config) => checks.extend(config_checks(profile, config)),
+            None => checks.extend(
+                CONFIG_CHECKS
+                    .iter()
+                    .map(|name| check(name, CheckState::Skipped, "no config given")),
+            ),
+        }
+    } else {
+        let rest = ["auth", "shell prelude", "tmux"]
+            .iter()
+            .chain(CONFIG_CHECKS);
+        for name in rest {
+            if !checks.iter().any(|c| c.name == *name) {
+                checks.push(check(name, CheckState::Skipped, "host not reachable"));
+            }
+        }
+    }
+    Diagnosis {
+        host: runs::host_label(profile),
+        ok: checks.iter().all(|c| c.state != CheckState::Fail),
+        checks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prelude_check, tool_check, CheckState, PRELUDE_MARKER};
+    use crate::ssh::ExecOut;
+
+    fn out(code: i32, stdout: &str, stderr: &str) -> ExecOut {
+        ExecOut {
+            code,
+            stdout: stdout.into(),
+            stderr: stderr.into(),
+            lossy: false,
+        }
+    }
+
+    #[test]
+    fn classifies_prelude_and_tool_output() {
+        let clean = prelude_check(&out(0, &format!("{PRELUDE_MARKER}\n"), ""));
+        assert_eq!(clean.state, CheckState::Ok);
+        let noisy = prelude_check(&out(
+            0,
+            &format!("{PRELUDE_MARKER}\n"),
+            "module: command not found\n",
+        ));
+        assert_eq!(noisy.state, CheckState::Warn);
+        assert_eq!(noisy.detail, "module: command not found");
+        assert_eq!(prelude_check(&out(1, "", "")).state, CheckState::Fail);
+
+        let tmux = tool_check("tmux", Ok(out(0, "tmux 3.3a\n", "")), "tmux not found");
+        assert_eq!(tmux.detail, "tmux 3.3a");
+        // python 2 prints its version on stderr
+        let python = tool_check("python", Ok(out(0, "", "Python 2.7.18\n")), "");
+        assert_eq!(python.detail, "Python 2.7.18");
+        let missing = tool_check(
+            "tmux",
+            Ok(out(127, "", "bash: tmux: command not found")),
+            "tmux not found",
+        );
+        assert_eq!(missing.state, CheckState::Fail);
+    }
+}
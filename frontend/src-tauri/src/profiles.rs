@@ -0,0 +1,566 @@
+use crate::model::{ConfigOverrides, WorkdirQuota};
+use crate::{auth_mode, context, pyenvs, runs, scheduler, HostProfile};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const STORE_FILE: &str = "profiles.json";
+// the frontend's tauri-plugin-store file, which held the one remote profile
+const LEGACY_STORE_FILE: &str = ".settings.json";
+const AUTH_MODES: &[&str] = &["agent", "key", "password"];
+
+static STORE: OnceCell<PathBuf> = OnceCell::new();
+static PROFILES: Lazy<Mutex<Vec<StoredProfile>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+// Secrets are skipped when HostProfile serializes, so password hosts ask
+// again after a restart.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoredProfile {
+    pub id: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub profile: HostProfile,
+}
+
+// One problem with a profile, tied to the field the form should highlight.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+// What the profile commands fail with: the fields to fix, or anything else
+// (an unknown id, a failed save) as a plain message.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ProfileError {
+    Invalid { errors: Vec<FieldError> },
+    Failed { message: String },
+}
+
+impl From<String> for ProfileError {
+    fn from(message: String) -> Self {
+        ProfileError::Failed { message }
+    }
+}
+
+impl From<Vec<FieldError>> for ProfileError {
+    fn from(errors: Vec<FieldError>) -> Self {
+        ProfileError::Invalid { errors }
+    }
+}
+
+fn field_error(field: &str, message: impl Into<String>) -> FieldError {
+    FieldError {
+        field: field.to_string(),
+        message: message.into(),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Text,
+    RequiredText,
+    Port,
+    Count,
+    Flag,
+    Object,
+}
+
+const FIELDS: &[(&str, Kind)] = &[
+    ("host", Kind::RequiredText),
+    ("port", Kind::Port),
+    ("user", Kind::RequiredText),
+    ("auth", Kind::Text),
+    ("password", Kind::Text),
+    ("key_path", Kind::Text),
+    ("key_pass", Kind::Text),
+    ("use_agent", Kind::Flag),
+    ("scheduler", Kind::Text),
+    ("env_preset", Kind::Text),
+    ("max_concurrent_ops", Kind::Count),
+    ("session_template", Kind::Text),
+    ("default_session", Kind::Text),
+    ("enabled", Kind::Flag),
+    ("keep_warm", Kind::Flag),
+    ("overrides", Kind::Object),
+];
+
+fn type_error(field: &str, kind: Kind, value: Option<&Value>) -> Option<FieldError> {
+    let value = match value {
+        None | Some(Value::Null) if kind == Kind::RequiredText => {
+            return Some(field_error(field, format!("{field} is required")));
+        }
+        None | Some(Value::Null) => return None,
+        Some(value) => value,
+    };
+    let message = match kind {
+        Kind::Text | Kind::RequiredText if !value.is_string() => format!("{field} must be text"),
+        Kind::Port if !value.as_u64().is_some_and(|p| (1..=65535).contains(&p)) => {
+            "port must be 1-65535".into()
+        }
+        Kind::Count
+            if !value
+                .as_u64()
+                .is_some_and(|n| (1..=u32::MAX as u64).contains(&n)) =>
+        {
+            format!("{field} must be a whole number of at least 1")
+        }
+        Kind::Flag if !value.is_boolean() => format!("{field} must be true or false"),
+        Kind::Object if !value.is_object() => format!("{field} must be an object"),
+        Kind::Object => match serde_json::from_value::<ConfigOverrides>(value.clone()) {
+            Ok(_) => return None,
+            Err(e) => format!("{field}: {e}"),
+        },
+        _ => return None,
+    };
+    Some(field_error(field, message))
+}
+
+// Rules that hold once every field has the right type.
+fn check(name: &str, profile: &HostProfile) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if name.trim().is_empty() {
+        errors.push(field_error("name", "name must not be empty"));
+    }
+    if profile.host.trim().is_empty() || profile.host.contains(char::is_whitespace) {
+        errors.push(field_error(
+            "host",
+            "host must not be empty or contain spaces",
+        ));
+    }
+    if profile.user.trim().is_empty() {
+        errors.push(field_error("user", "user must not be empty"));
+    }
+    if profile.port == Some(0) {
+        errors.push(field_error("port", "port must be 1-65535"));
+    }
+    if profile.max_concurrent_ops == Some(0) {
+        errors.push(field_error(
+            "max_concurrent_ops",
+            "max_concurrent_ops must be at least 1",
+        ));
+    }
+    let key_path = profile.key_path.as_deref().filter(|k| !k.trim().is_empty());
+    let key_pass = profile.key_pass.as_deref().filter(|p| !p.is_empty());
+    match profile.auth.as_deref() {
+        None => {}
+        Some(auth) if !AUTH_MODES.contains(&auth) => {
+            errors.push(field_error("auth", "auth must be agent, key or password"));
+        }
+        Some("key") if key_path.is_none() => {
+            errors.push(field_error("key_path", "auth=key requires key_path"));
+        }
+        Some(auth) if auth != "key" && key_pass.is_some() => {
+            errors.push(field_error("key_pass", "key_pass only applies to auth=key"));
+        }
+        Some(_) => {}
+    }
+    if let Some(WorkdirQuota {
+        warn_gb: Some(warn),
+        hard_gb: Some(hard),
+    }) = profile.overrides.workdir_quota
+    {
+        if warn > hard {
+            errors.push(field_error(
+                "overrides",
+                "workdir_quota warn_gb must not exceed hard_gb",
+            ));
+        }
+    }
+    if let Err(e) = pyenvs::check_known(profile.overrides.python_env.as_deref()) {
+        errors.push(field_error("overrides", e));
+    }
+    let template = profile.session_template.as_deref().unwrap_or("");
+    if let Some(name) = runs::unknown_placeholder(template) {
+        errors.push(field_error(
+            "session_template",
+            format!("unknown placeholder {{{name}}}, expected project, date, user or host"),
+        ));
+    }
+    if profile.scheduler.is_some() {
+        if let Err(e) = scheduler::for_profile(profile) {
+            errors.push(field_error("scheduler", e));
+        }
+    }
+    errors
+}
+
+// Checks each field's type before serde sees the payload, so the UI gets
+// "port must be 1-65535" rather than "invalid type: string, expected u16".
+pub fn parse_profile(name: &str, value: &Value) -> Result<HostProfile, Vec<FieldError>> {
+    let Some(fields) = value.as_object() else {
+        return Err(vec![field_error("profile", "profile must be an object")]);
+    };
+    let errors: Vec<FieldError> = FIELDS
+        .iter()
+        .filter_map(|(field, kind)| type_error(field, *kind, fields.get(*field)))
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let profile: HostProfile = serde_json::from_value(value.clone())
+        .map_err(|e| vec![field_error("profile", e.to_string())])?;
+    let errors = check(name, &profile);
+    if errors.is_empty() {
+        Ok(profile)
+    } else {
+        Err(errors)
+    }
+}
+
+pub fn validate(name: &str, profile: &HostProfile) -> Result<(), String> {
+    let messages: Vec<String> = check(name, profile)
+        .into_iter()
+        .map(|e| e.message)
+        .collect();
+    if messages.is_empty() {
+        Ok(())
+    } else {
+        Err(messages.join("; "))
+    }
+}
+
+fn check_unique(
+    profiles: &[StoredProfile],
+    name: &str,
+    except: Option<&str>,
+) -> Result<(), FieldError> {
+    let taken = profiles
+        .iter()
+        .any(|p| Some(p.id.as_str()) != except && p.name.eq_ignore_ascii_case(name.trim()));
+    if taken {
+        return Err(field_error(
+            "name",
+            format!("a profile named {:?} already exists", name.trim()),
+        ));
+    }
+    Ok(())
+}
+
+// "name (2)", "name (3)"... whichever no profile uses yet.
+pub fn free_name(existing: &[StoredProfile], name: &str) -> String {
+    (2..)
+        .map(|n| format!("{name} ({n})"))
+        .find(|candidate| {
+            !existing
+                .iter()
+                .any(|p| p.name.eq_ignore_ascii_case(candidate))
+        })
+        .unwrap()
+}
+
+fn load(path: &Path) -> Result<Option<Vec<StoredProfile>>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| format!("parse {}: {e}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("read {}: {e}", path.display())),
+    }
+}
+
+fn write(path: &Path, profiles: &[StoredProfile]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    }
+    let text = serde_json::to_string_pretty(profiles).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, text).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("replace {}: {e}", path.display()))
+}
+
+fn save(profiles: &[StoredProfile]) -> Result<(), String> {
+    match STORE.get() {
+        Some(path) => write(path, profiles),
+        None => Ok(()),
+    }
+}
+
+// Pins a profile that relies on the legacy use_agent switch to the auth mode
+// it was being connected with, and says what changed.
+fn explicit_auth(stored: &mut StoredProfile) -> Option<String> {
+    if stored.profile.auth.is_some() {
+        stored.profile.use_agent = None;
+        return None;
+    }
+    let auth = auth_mode(&stored.profile).to_string();
+    let change = format!(
+        "{}: use_agent={:?} -> auth={auth}",
+        stored.name, stored.profile.use_agent
+    );
+    stored.profile.auth = Some(auth);
+    stored.profile.use_agent = None;
+    Some(change)
+}
+
+// The settings store kept a single profile under config.remote.
+fn legacy_profiles(settings: &Value) -> Vec<StoredProfile> {
+    let Some(remote) = settings.pointer("/config/remote") else {
+        return Vec::new();
+    };
+    let Ok(profile) = serde_json::from_value::<HostProfile>(remote.clone()) else {
+        return Vec::new();
+    };
+    if validate(&profile.host, &profile).is_err() {
+        return Vec::new();
+    }
+    vec![StoredProfile {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: format!("{}@{}", profile.user, profile.host),
+        profile,
+    }]
+}
+
+fn migrate(dir: &Path) -> Vec<StoredProfile> {
+    std::fs::read_to_string(dir.join(LEGACY_STORE_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+        .map(|settings| legacy_profiles(&settings))
+        .unwrap_or_default()
+}
+
+// Loads profiles.json, or on first start seeds it from the frontend store.
+// Profiles still on use_agent are rewritten to an explicit auth.
+pub fn init(app: &AppHandle) {
+    let dir = match app.path().app_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            tracing::warn!("no app data dir, profiles will not be kept: {e}");
+            return;
+        }
+    };
+    let path = dir.join(STORE_FILE);
+    let (mut profiles, mut dirty) = match load(&path) {
+        Ok(Some(profiles)) => (profiles, false),
+        Ok(None) => (migrate(&dir), true),
+        Err(e) => {
+            tracing::warn!("loading profiles failed: {e}");
+            (Vec::new(), false)
+        }
+    };
+    for change in profiles.iter_mut().filter_map(explicit_auth) {
+        tracing::info!("profile migrated to explicit auth: {change}");
+        dirty = true;
+    }
+    if dirty {
+        if let Err(e) = write(&path, &profiles) {
+            tracing::warn!("saving profiles failed: {e}");
+        }
+    }
+    *PROFILES.lock().unwrap() = profiles;
+    let _ = STORE.set(path);
+}
+
+pub fn list() -> Vec<StoredProfile> {
+    PROFILES.lock().unwrap().clone()
+}
+
+// Applies `f` to the profile list under the lock and saves the result.
+pub fn modify<T>(f: impl FnOnce(&mut Vec<StoredProfile>) -> T) -> Result<T, String> {
+    let mut profiles = PROFILES.lock().unwrap();
+    let out = f(&mut profiles);
+    save(&profiles)?;
+    Ok(out)
+}
+
+pub fn create(name: String, profile: Value) -> Result<StoredProfile, ProfileError> {
+    let profile = parse_profile(&name, &profile)?;
+    let mut profiles = PROFILES.lock().unwrap();
+    check_unique(&profiles, &name, None).map_err(|e| vec![e])?;
+    let mut stored = StoredProfile {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.trim().to_string(),
+        profile,
+    };
+    explicit_auth(&mut stored);
+    profiles.push(stored.clone());
+    save(&profiles)?;
+    Ok(stored)
+}
+
+pub fn update(id: String, name: String, profile: Value) -> Result<StoredProfile, ProfileError> {
+    let profile = parse_profile(&name, &profile)?;
+    let mut profiles = PROFILES.lock().unwrap();
+    check_unique(&profiles, &name, Some(&id)).map_err(|e| vec![e])?;
+    let slot = profiles
+        .iter_mut()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("unknown profile: {id}"))?;
+    slot.name = name.trim().to_string();
+    slot.profile = profile;
+    explicit_auth(slot);
+    let updated = slot.clone();
+    save(&profiles)?;
+    Ok(updated)
+}
+
+// Overrides are merged into the profile's JSON, so nested objects such as
+// `overrides` only replace the keys they name.
+fn merge_json(base: &mut Value, overrides: &Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(key) {
+                    Some(slot) if slot.is_object() && value.is_object() => merge_json(slot, value),
+                    _ => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides.clone(),
+    }
+}
+
+// Copies a profile under a new id, e.g. one per node of a cluster. Secrets
+// never serialize, so the copy asks for its own. Without a name in
+// `overrides` it is called "<name> (2)" and so on.
+pub fn clone_profile(id: String, overrides: Value) -> Result<StoredProfile, ProfileError> {
+    let mut profiles = PROFILES.lock().unwrap();
+    let source = profiles
+        .iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("unknown profile: {id}"))?;
+    let mut value = serde_json::to_value(source).map_err(|e| e.to_string())?;
+    let name = free_name(&profiles, &source.name);
+    merge_json(&mut value, &json!({ "name": name }));
+    merge_json(&mut value, &overrides);
+    let Some(name) = value
+        .get("name")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+    else {
+        return Err(vec![field_error("name", "name must be text")].into());
+    };
+    let profile = parse_profile(&name, &value)?;
+    check_unique(&profiles, &name, None).map_err(|e| vec![e])?;
+    let mut stored = StoredProfile {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.trim().to_string(),
+        profile,
+    };
+    explicit_auth(&mut stored);
+    profiles.push(stored.clone());
+    save(&profiles)?;
+    Ok(stored)
+}
+
+pub fn delete(id: String) -> Result<(), String> {
+    let mut profiles = PROFILES.lock().unwrap();
+    let before = profiles.len();
+    profiles.retain(|p| p.id != id);
+    if profiles.len() == before {
+        return Err(format!("unknown profile: {id}"));
+    }
+    save(&profiles)?;
+    drop(profiles);
+    context::forget_profile(&id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_unique, explicit_auth, legacy_profiles, merge_json, parse_profile, validate,
+    };
+    use crate::HostProfile;
+    use serde_json::json;
+
+    fn profile(auth: Option<&str>, key_path: Option<&str>) -> HostProfile {
+        serde_json::from_value(json!({
+            "host": "hpc.example.org",
+            "port": 22,
+            "user": "arc",
+            "auth": auth,
+            "key_path": key_path,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn validates_profiles_and_migrates_the_settings_store() {
+        assert!(validate("hpc", &profile(Some("agent"), None)).is_ok());
+        assert!(validate("hpc", &profile(Some("key"), Some("~/.ssh/id_ed25519"))).is_ok());
+        assert!(validate("hpc", &profile(Some("key"), None)).is_err());
+        assert!(validate("hpc", &profile(Some("kerberos"), None)).is_err());
+        assert!(validate(" ", &profile(None, None)).is_err());
+        let mut bad_port = profile(None, None);
+        bad_port.port = Some(0);
+        assert!(validate("hpc", &bad_port).is_err());
+
+        let settings = json!({ "config": { "python_path": "python", "remote": {
+            "host": "hpc.example.org", "port": 22, "user": "arc", "auth": "agent",
+            "key_path": "", "use_agent": true
+        }}});
+        let migrated = legacy_profiles(&settings);
+        assert_eq!(migrated.len(), 1);
+        assert_eq!(migrated[0].name, "arc@hpc.example.org");
+        assert!(check_unique(&migrated, "ARC@hpc.example.org", None).is_err());
+        assert!(check_unique(&migrated, "ARC@hpc.example.org", Some(&migrated[0].id)).is_ok());
+        assert!(legacy_profiles(&json!({ "config": {} })).is_empty());
+
+        let mut legacy = migrated[0].clone();
+        legacy.profile.auth = None;
+        legacy.profile.use_agent = Some(false);
+        legacy.profile.key_path = Some("~/.ssh/id_rsa".into());
+        assert_eq!(
+            explicit_auth(&mut legacy).as_deref(),
+            Some("arc@hpc.example.org: use_agent=Some(false) -> auth=key")
+        );
+        assert_eq!(legacy.profile.auth.as_deref(), Some("key"));
+        assert!(explicit_auth(&mut legacy).is_none());
+        let saved = serde_json::to_value(&legacy).unwrap();
+        assert!(saved.get("use_agent").is_none());
+    }
+
+    #[test]
+    fn clone_overrides_merge_into_nested_fields() {
+        let mut value = json!({
+            "name": "hpc", "host": "login.hpc", "user": "arc",
+            "overrides": { "python_path": "/opt/py", "concurrency_cap": 4 }
+        });
+        merge_json(
+            &mut value,
+            &json!({ "name": "node02", "host": "node02.hpc", "overrides": { "concurrency_cap": 2 } }),
+        );
+        assert_eq!(value["host"], "node02.hpc");
+        assert_eq!(value["overrides"]["python_path"], "/opt/py");
+        assert_eq!(value["overrides"]["concurrency_cap"], 2);
+    }
+
+    #[test]
+    fn parse_profile_names_each_bad_field() {
+        let fields = |value: serde_json::Value| -> Vec<(String, String)> {
+            parse_profile("hpc", &value)
+                .err()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| (e.field, e.message))
+                .collect()
+        };
+        let wrong_types = fields(json!({ "host": "hpc", "port": "22", "max_concurrent_ops": 0 }));
+        assert_eq!(
+            wrong_types,
+            [
+                ("port".into(), "port must be 1-65535".into()),
+                ("user".into(), "user is required".into()),
+                (
+                    "max_concurrent_ops".into(),
+                    "max_concurrent_ops must be a whole number of at least 1".into()
+                ),
+            ]
+        );
+        let rules = fields(json!({ "host": "hpc", "user": "arc", "auth": "key", "port": 70000 }));
+        assert_eq!(rules, [("port".into(), "port must be 1-65535".into())]);
+        let rules = fields(json!({ "host": "hpc", "user": "arc", "auth": "key" }));
+        assert_eq!(
+            rules,
+            [("key_path".into(), "auth=key requires key_path".into())]
+        );
+        assert!(parse_profile("hpc", &json!({ "host": "hpc", "user": "arc" })).is_ok());
+    }
+}
@@ -0,0 +1,266 @@
+use crate::model::{PhaseSpan, RunProgress};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+
+static PARSERS: Lazy<Mutex<HashMap<String, ProgressParser>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static CONSIDERING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"Considering species:\s*(\S+)").unwrap());
+static CONVERGED: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"All jobs for species (\S+) successfully converged").unwrap());
+static NOT_CONVERGED: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[Ss]pecies (\S+) did not converge").unwrap());
+static RUNNING_JOB: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bRunning\b.*?\bjob (\S+)").unwrap());
+static ENDING_JOB: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bEnding job (\S+)").unwrap());
+// ARC hands the converged species over to Arkane once its ESS jobs are done
+static PROCESSING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(post-processing|processing species|arkane)\b").unwrap());
+
+// ARC names ESS jobs `<type>_a<counter>`, e.g. `opt_a12` or `conformer_a3`.
+pub fn job_type(job_name: &str) -> &str {
+    match job_name.rfind("_a") {
+        Some(idx) if job_name[idx + 2..].chars().all(|c| c.is_ascii_digit()) => &job_name[..idx],
+        _ => job_name,
+    }
+}
+
+// The timeline phase an ESS job belongs to; "opt" covers every geometry job
+// that is neither a conformer search nor a single point.
+fn phase_of(job_type: &str) -> &'static str {
+    match job_type {
+        t if t.starts_with("conformer") || t == "tsg" || t == "gen_confs" => "conformers",
+        "sp" => "sp",
+        _ => "opt",
+    }
+}
+
+// Consumes arc.log incrementally so a long log is only ever read once.
+#[derive(Debug, Default)]
+pub struct ProgressParser {
+    offset: u64,
+    pending: String,
+    species: BTreeSet<String>,
+    converged: BTreeSet<String>,
+    failed: BTreeSet<String>,
+    running: BTreeSet<String>,
+    jobs_completed: u32,
+    phases: Vec<PhaseSpan>,
+    // set until the first read after a restart, which re-reads lines whose
+    // phases were already stamped by the previous session
+    resumed: bool,
+}
+
+impl ProgressParser {
+    // A parser picking up a run whose spans were stamped before a restart.
+    fn resume(stamped: &[PhaseSpan]) -> ProgressParser {
+        ProgressParser {
+            phases: stamped.to_vec(),
+            resumed: !stamped.is_empty(),
+            ..ProgressParser::default()
+        }
+    }
+
+    // Lines are stamped when they are read, so phase spans are only as fine
+    // as the monitor's polling. Re-read lines leave stamped spans alone.
+    fn mark_phase(&mut self, phase: &str, at: &str) {
+        match self.phases.iter_mut().find(|p| p.phase == phase) {
+            Some(_) if self.resumed => {}
+            Some(span) => span.end = at.to_string(),
+            None => self.phases.push(PhaseSpan {
+                phase: phase.to_string(),
+                start: at.to_string(),
+                end: at.to_string(),
+            }),
+        }
+    }
+
+    fn feed_line(&mut self, line: &str, at: &str) {
+        if let Some(c) = ENDING_JOB
+            .captures(line)
+            .or_else(|| RUNNING_JOB.captures(line))
+        {
+            self.mark_phase(phase_of(job_type(&c[1])), at);
+        } else if PROCESSING.is_match(line) {
+            self.mark_phase("post-processing", at);
+        }
+        if let Some(c) = CONSIDERING.captures(line) {
+            self.species.insert(c[1].to_string());
+        } else if let Some(c) = CONVERGED.captures(line) {
+            self.converged.insert(c[1].to_string());
+        } else if let Some(c) = NOT_CONVERGED.captures(line) {
+            self.failed.insert(c[1].to_string());
+        } else if let Some(c) = ENDING_JOB.captures(line) {
+            self.running.remove(&c[1]);
+            self.jobs_completed += 1;
+        } else if let Some(c) = RUNNING_JOB.captures(line) {
+            self.running.insert(c[1].to_string());
+        }
+    }
+
+    pub fn feed(&mut self, text: &str) {
+        self.feed_at(text, &chrono::Utc::now().to_rfc3339());
+    }
+
+    fn feed_at(&mut self, text: &str, at: &str) {
+        self.pending.push_str(text);
+        while let Some(idx) = self.pending.find('\n') {
+            let line: String = self.pending.drain(..=idx).collect();
+            self.feed_line(line.trim_end(), at);
+        }
+        self.resumed = false;
+    }
+
+    pub fn snapshot(&self) -> RunProgress {
+        let running_jobs: Vec<String> = self.running.iter().cloned().collect();
+        let job_types: BTreeSet<String> = running_jobs
+            .iter()
+            .map(|j| job_type(j).to_string())
+            .collect();
+        RunProgress {
+            species_total: self.species.len() as u32,
+            species_converged: self.converged.len() as u32,
+            species_failed: self.failed.len() as u32,
+            jobs_completed: self.jobs_completed,
+            running_jobs,
+            job_types: job_types.into_iter().collect(),
+            updated_at: Some(chrono::Utc::now().to_rfc3339()),
+            eta_secs: None,
+            phases: self.phases.clone(),
+        }
+    }
+
+    // Reads whatever was appended to `path` since the last call; a shrunken
+    // file means ARC started a fresh log, so parsing starts over.
+    fn read_from(&mut self, path: &Path) -> std::io::Result<bool> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            *self = ProgressParser::default();
+        }
+        if len == self.offset {
+            return Ok(false);
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut buf)?;
+        self.offset = len;
+        self.feed(&String::from_utf8_lossy(&buf));
+        Ok(true)
+    }
+}
+
+// Returns fresh progress for `run_id` when its log grew since the last call.
+// `stamped` are the spans saved for the run, kept when its log is re-read
+// from the start after a restart.
+pub fn refresh(run_id: &str, log_path: &Path, stamped: &[PhaseSpan]) -> Option<RunProgress> {
+    let mut parsers = PARSERS.lock().unwrap();
+    let parser = parsers
+        .entry(run_id.to_string())
+        .or_insert_with(|| ProgressParser::resume(stamped));
+    match parser.read_from(log_path) {
+        Ok(true) => Some(parser.snapshot()),
+        _ => None,
+    }
+}
+
+// Species ARC reported as fully converged anywhere in the log.
+pub fn converged_species(log_path: &Path) -> std::io::Result<Vec<String>> {
+    let mut parser = ProgressParser::default();
+    parser.read_from(log_path)?;
+    Ok(parser.converged.into_iter().collect())
+}
+
+// The same for a log read some other way, e.g. from a remote host.
+pub fn converged_in(log: &str) -> Vec<String> {
+    let mut parser = ProgressParser::default();
+    parser.feed(log);
+    parser.feed("\n");
+    parser.converged.into_iter().collect()
+}
+
+pub fn forget(run_id: &str) {
+    PARSERS.lock().unwrap().remove(run_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{job_type, ProgressParser};
+    use crate::model::PhaseSpan;
+
+    #[test]
+    fn job_type_strips_arc_counter() {
+        assert_eq!(job_type("opt_a12"), "opt");
+        assert_eq!(job_type("conformer_a3"), "conformer");
+        assert_eq!(job_type("custom"), "custom");
+    }
+
+    #[test]
+    fn parser_tracks_species_and_jobs_across_chunks() {
+        let mut parser = ProgressParser::default();
+        parser.feed("Considering species: CH4\nConsidering species: OH\n");
+        parser
+            .feed("Running local job opt_a1 using gaussian for CH4\nRunning job freq_a2 for OH\n");
+        parser.feed("Ending job opt_a1 for CH4 (run time: 0:01:02)\nAll jobs for spec");
+        parser.feed("ies CH4 successfully converged. Run time: 0:05:00\n");
+        let progress = parser.snapshot();
+        assert_eq!(progress.species_total, 2);
+        assert_eq!(progress.species_converged, 1);
+        assert_eq!(progress.jobs_completed, 1);
+        assert_eq!(progress.running_jobs, vec!["freq_a2".to_string()]);
+        assert_eq!(progress.job_types, vec!["freq".to_string()]);
+    }
+
+    #[test]
+    fn parser_spans_phases_from_first_to_last_sighting() {
+        let mut parser = ProgressParser::default();
+        parser.feed_at("Running job conformer_a1 for CH4\n", "t1");
+        parser.feed_at(
+            "Ending job conformer_a1 for CH4\nRunning job opt_a2 for CH4\n",
+            "t2",
+        );
+        parser.feed_at(
+            "Running job sp_a3 for CH4\nEnding job freq_a4 for CH4\n",
+            "t3",
+        );
+        parser.feed_at("Processing species CH4\n", "t4");
+        let span = |phase: &str, start: &str, end: &str| PhaseSpan {
+            phase: phase.into(),
+            start: start.into(),
+            end: end.into(),
+        };
+        assert_eq!(
+            parser.snapshot().phases,
+            [
+                span("conformers", "t1", "t2"),
+                span("opt", "t2", "t3"),
+                span("sp", "t3", "t3"),
+                span("post-processing", "t4", "t4"),
+            ]
+        );
+    }
+
+    #[test]
+    fn resumed_parser_keeps_spans_stamped_before_a_restart() {
+        let span = |phase: &str, start: &str, end: &str| PhaseSpan {
+            phase: phase.into(),
+            start: start.into(),
+            end: end.into(),
+        };
+        let mut parser = ProgressParser::resume(&[span("conformers", "t1", "t2")]);
+        parser.feed_at(
+            "Running job conformer_a1 for CH4\nRunning job opt_a2 for CH4\n",
+            "t9",
+        );
+        parser.feed_at("Ending job opt_a2 for CH4\n", "t10");
+        assert_eq!(
+            parser.snapshot().phases,
+            [span("conformers", "t1", "t2"), span("opt", "t9", "t10")]
+        );
+    }
+}
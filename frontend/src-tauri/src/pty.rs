@@ -0,0 +1,457 @@
+use crate::tmuxcmd::RemoteTmuxCommand;
+use crate::{control, ssh};
+use crate::{creds_from, HostProfile};
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::io::{ErrorKind, Read, Write};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+static MANAGER: Lazy<TerminalManager> = Lazy::new(TerminalManager::new);
+
+pub struct TerminalManager {
+    inner: Mutex<HashMap<String, TerminalHandle>>,
+}
+
+enum TerminalInput {
+    Data(Vec<u8>),
+    Resize(u32, u32),
+}
+
+// Token bucket over input bytes; a runaway paste or a stuck key repeat
+// should not be able to flood the remote side.
+struct InputBudget {
+    available: f64,
+    last_refill: Instant,
+}
+
+impl InputBudget {
+    const BYTES_PER_SEC: f64 = 256.0 * 1024.0;
+    const BURST: f64 = 64.0 * 1024.0;
+
+    fn new() -> Self {
+        Self {
+            available: Self::BURST,
+            last_refill: Instant::now(),
+        }
+    }
+
+    // How many of `wanted` bytes may go out now; at most BURST.
+    fn grant(&mut self, wanted: usize, now: Instant) -> usize {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.available = (self.available + elapsed * Self::BYTES_PER_SEC).min(Self::BURST);
+        self.last_refill = now;
+        let granted = wanted.min(self.available as usize);
+        self.available -= granted as f64;
+        granted
+    }
+}
+
+// Input waiting to be written, in the order it came in. Data goes out as
+// fast as the budget allows, so a big paste is slowed down, not dropped; a
+// resize waits behind the data sent before it.
+struct InputQueue {
+    pending: VecDeque<TerminalInput>,
+    budget: InputBudget,
+}
+
+impl InputQueue {
+    fn new() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            budget: InputBudget::new(),
+        }
+    }
+
+    fn push(&mut self, input: TerminalInput) {
+        self.pending.push_back(input);
+    }
+
+    // The next input to apply at `now`, or None until the budget refills.
+    fn next(&mut self, now: Instant) -> Option<TerminalInput> {
+        match self.pending.front_mut()? {
+            TerminalInput::Data(bytes) => {
+                let granted = self.budget.grant(bytes.len(), now);
+                if granted == 0 {
+                    return None;
+                }
+                if granted == bytes.len() {
+                    return self.pending.pop_front();
+                }
+                let rest = bytes.split_off(granted);
+                Some(TerminalInput::Data(std::mem::replace(bytes, rest)))
+            }
+            TerminalInput::Resize(..) => self.pending.pop_front(),
+        }
+    }
+}
+
+struct TerminalHandle {
+    profile: HostProfile,
+    session: Option<String>,
+    input_tx: mpsc::Sender<TerminalInput>,
+    stop_tx: mpsc::Sender<()>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+// Splits off a trailing partial UTF-8 sequence so it can be completed by the
+// next read instead of being replaced with U+FFFD.
+fn take_utf8(pending: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(pending) {
+        Ok(_) => pending.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => pending.len(),
+    };
+    let rest = pending.split_off(valid);
+    let text = String::from_utf8_lossy(pending).into_owned();
+    *pending = rest;
+    text
+}
+
+fn write_all_nonblocking(channel: &mut ssh2::Channel, mut data: &[u8]) -> std::io::Result<()> {
+    while !data.is_empty() {
+        match channel.write(data) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => data = &data[n..],
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(5));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    loop {
+        match channel.flush() {
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(5)),
+            other => return other,
+        }
+    }
+}
+
+fn request_pty_size_nonblocking(
+    channel: &mut ssh2::Channel,
+    cols: u32,
+    rows: u32,
+) -> std::io::Result<()> {
+    loop {
+        match channel.request_pty_size(cols, rows, None, None) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                let err = std::io::Error::from(e);
+                if err.kind() != ErrorKind::WouldBlock {
+                    return Err(err);
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+    }
+}
+
+impl TerminalManager {
+    const EVENT: &'static str = "terminal-event";
+
+    fn new() -> Self {
+        Self {
+            inner: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn global() -> &'static Self {
+        &MANAGER
+    }
+
+    pub fn open(
+        &self,
+        app: AppHandle,
+        profile: HostProfile,
+        session: Option<String>,
+        cols: u32,
+        rows: u32,
+    ) -> Result<String, String> {
+        let sess = ssh::connect_dedicated(&creds_from(&profile))?;
+        let mut channel = sess
+            .channel_session()
+            .map_err(|e| format!("channel: {e}"))?;
+        channel
+            .request_pty(
+                "xterm-256color",
+                None,
+                Some((cols.max(1), rows.max(1), 0, 0)),
+            )
+            .map_err(|e| format!("request pty: {e}"))?;
+        match session {
+            Some(ref name) => {
+                let cmd = RemoteTmuxCommand::new("attach-session")
+                    .target(name)
+                    .build();
+                channel
+                    .exec(&cmd)
+                    .map_err(|e| format!("tmux attach exec: {e}"))?;
+            }
+            None => channel.shell().map_err(|e| format!("shell: {e}"))?,
+        }
+        sess.set_blocking(false);
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let (input_tx, input_rx) = mpsc::channel::<TerminalInput>();
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let handle_id = id.clone();
+
+        // held across the spawn, so a channel that ends at once cannot have
+        // its thread forget the handle before it is inserted
+        let mut inner = self.inner.lock().unwrap();
+        let reader_thread = thread::spawn(move || {
+            // keep the dedicated session alive for as long as the channel
+            let _sess = sess;
+            let mut channel = channel;
+            let send_event = |kind: &str, data: Option<String>| {
+                let payload = json!({
+                    "id": handle_id,
+                    "kind": kind,
+                    "data": data,
+                });
+                let _ = app.emit(TerminalManager::EVENT, payload);
+            };
+
+            send_event("started", None);
+            let mut buf = [0u8; 8192];
+            let mut pending: Vec<u8> = Vec::new();
+            let mut queue = InputQueue::new();
+
+            loop {
+                if stop_rx.try_recv().is_ok() {
+                    let _ = channel.close();
+                    send_event("closed", None);
+                    break;
+                }
+
+                while let Ok(input) = input_rx.try_recv() {
+                    queue.push(input);
+                }
+                while let Some(input) = queue.next(Instant::now()) {
+                    let result = match input {
+                        TerminalInput::Data(bytes) => write_all_nonblocking(&mut channel, &bytes),
+                        TerminalInput::Resize(cols, rows) => {
+                            request_pty_size_nonblocking(&mut channel, cols, rows)
+                        }
+                    };
+                    if let Err(e) = result {
+                        send_event("error", Some(format!("write failed: {e}")));
+                        let _ = channel.close();
+                        send_event("closed", None);
+                        TerminalManager::global().forget(&handle_id);
+                        return;
+                    }
+                }
+
+                match channel.read(&mut buf) {
+                    Ok(0) => {
+                        if channel.eof() {
+                            if !pending.is_empty() {
+                                send_event("output", Some(take_utf8(&mut pending)));
+                            }
+                            send_event("closed", None);
+                            TerminalManager::global().forget(&handle_id);
+                            break;
+                        }
+                        thread::sleep(Duration::from_millis(10));
+                    }
+                    Ok(n) => {
+                        pending.extend_from_slice(&buf[..n]);
+                        let text = take_utf8(&mut pending);
+                        if !text.is_empty() {
+                            send_event("output", Some(text));
+                        }
+                    }
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(10));
+                    }
+                    Err(err) => {
+                        send_event("error", Some(format!("read failed: {err}")));
+                        let _ = channel.close();
+                        send_event("closed", None);
+                        TerminalManager::global().forget(&handle_id);
+                        break;
+                    }
+                }
+            }
+        });
+
+        let handle = TerminalHandle {
+            profile,
+            session,
+            input_tx,
+            stop_tx,
+            thread: Some(reader_thread),
+        };
+        inner.insert(id.clone(), handle);
+        Ok(id)
+    }
+
+    // Drops the handle of a terminal whose remote side went away on its own.
+    fn forget(&self, id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.remove(id);
+    }
+
+    pub fn input(&self, id: &str, bytes: Vec<u8>) -> Result<(), String> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let inner = self.inner.lock().unwrap();
+        match inner.get(id) {
+            Some(handle) => handle
+                .input_tx
+                .send(TerminalInput::Data(bytes))
+                .map_err(|e| format!("{e}")),
+            None => Err("terminal not open".into()),
+        }
+    }
+
+    pub fn resize(&self, id: &str, cols: u32, rows: u32) -> Result<(), String> {
+        if cols == 0 || rows == 0 {
+            return Err("terminal size must be non-zero".into());
+        }
+        let attached = {
+            let inner = self.inner.lock().unwrap();
+            let handle = inner
+                .get(id)
+                .ok_or_else(|| "terminal not open".to_string())?;
+            handle
+                .input_tx
+                .send(TerminalInput::Resize(cols, rows))
+                .map_err(|e| format!("{e}"))?;
+            handle
+                .session
+                .clone()
+                .map(|session| (handle.profile.clone(), session))
+        };
+        // A control-mode client on the same session would otherwise keep
+        // constraining the window to its own, older size.
+        if let Some((profile, session)) = attached {
+            let _ = control::set_client_size(profile, session, cols, rows);
+        }
+        Ok(())
+    }
+
+    pub fn close(&self, id: &str) -> Result<(), String> {
+        let handle = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.remove(id)
+        };
+        match handle {
+            Some(mut handle) => {
+                let _ = handle.stop_tx.send(());
+                if let Some(thread) = handle.thread.take() {
+                    let _ = thread.join();
+                }
+                Ok(())
+            }
+            None => Err("terminal not open".into()),
+        }
+    }
+
+    pub fn list(&self) -> Vec<String> {
+        let inner = self.inner.lock().unwrap();
+        inner.keys().cloned().collect()
+    }
+}
+
+pub fn open_terminal(
+    app: AppHandle,
+    profile: HostProfile,
+    session: Option<String>,
+    cols: u32,
+    rows: u32,
+) -> Result<String, String> {
+    TerminalManager::global().open(app, profile, session, cols, rows)
+}
+
+pub fn send_input(id: String, data: String) -> Result<(), String> {
+    TerminalManager::global().input(&id, data.into_bytes())
+}
+
+pub fn write_bytes(id: String, bytes: Vec<u8>) -> Result<(), String> {
+    TerminalManager::global().input(&id, bytes)
+}
+
+pub fn resize_terminal(id: String, cols: u32, rows: u32) -> Result<(), String> {
+    TerminalManager::global().resize(&id, cols, rows)
+}
+
+pub fn close_terminal(id: String) -> Result<(), String> {
+    TerminalManager::global().close(&id)
+}
+
+pub fn list_terminals() -> Vec<String> {
+    TerminalManager::global().list()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{take_utf8, InputBudget, InputQueue, TerminalInput};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn take_utf8_keeps_split_multibyte_sequence() {
+        let mut pending = "ab\u{00e9}".as_bytes().to_vec();
+        let last = pending.pop().unwrap();
+        assert_eq!(take_utf8(&mut pending), "ab");
+        pending.push(last);
+        assert_eq!(take_utf8(&mut pending), "\u{00e9}");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn take_utf8_replaces_invalid_bytes() {
+        let mut pending = vec![b'a', 0xff, b'b'];
+        assert_eq!(take_utf8(&mut pending), "a\u{fffd}b");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn input_budget_caps_bursts_and_refills() {
+        let start = Instant::now();
+        let mut budget = InputBudget::new();
+        assert_eq!(budget.grant(48 * 1024, start), 48 * 1024);
+        assert_eq!(budget.grant(32 * 1024, start), 16 * 1024);
+        assert_eq!(
+            budget.grant(32 * 1024, start + Duration::from_millis(100)),
+            26_214 // 100ms at 256 KiB/s
+        );
+    }
+
+    #[test]
+    fn input_over_the_burst_is_paced_not_dropped() {
+        let start = Instant::now();
+        let paste: Vec<u8> = (0..200 * 1024).map(|i| (i % 251) as u8).collect();
+        let mut queue = InputQueue::new();
+        queue.push(TerminalInput::Data(paste.clone()));
+        queue.push(TerminalInput::Resize(80, 24));
+
+        let mut written = Vec::new();
+        let mut now = start;
+        let mut resized = false;
+        while !resized {
+            while let Some(input) = queue.next(now) {
+                match input {
+                    TerminalInput::Data(bytes) => {
+                        assert!(bytes.len() <= 64 * 1024);
+                        written.extend(bytes);
+                    }
+                    TerminalInput::Resize(..) => resized = true,
+                }
+            }
+            if now == start {
+                assert_eq!(written.len(), 64 * 1024);
+            }
+            now += Duration::from_millis(50);
+            assert!(now < start + Duration::from_secs(2), "paste never drained");
+        }
+        assert_eq!(written, paste);
+    }
+}
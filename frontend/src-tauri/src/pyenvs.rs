@@ -0,0 +1,219 @@
+use crate::model::{AppConfig, PythonEnv, PythonKind};
+use crate::{conda, HostProfile};
+use once_cell::sync::{Lazy, OnceCell};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const STORE_FILE: &str = "python_envs.json";
+
+static STORE: OnceCell<PathBuf> = OnceCell::new();
+static ENVS: Lazy<Mutex<Vec<PythonEnv>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+fn load(path: &Path) -> Result<Vec<PythonEnv>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => {
+            serde_json::from_str(&text).map_err(|e| format!("parse {}: {e}", path.display()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("read {}: {e}", path.display())),
+    }
+}
+
+fn save(envs: &[PythonEnv]) -> Result<(), String> {
+    let Some(path) = STORE.get() else {
+        return Ok(());
+    };
+    let text = serde_json::to_string_pretty(envs).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, text).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("replace {}: {e}", path.display()))
+}
+
+pub fn init(app: &AppHandle) {
+    let path = match app.path().app_data_dir() {
+        Ok(dir) => dir.join(STORE_FILE),
+        Err(e) => {
+            tracing::warn!("no app data dir, python envs will not be kept: {e}");
+            return;
+        }
+    };
+    match load(&path) {
+        Ok(envs) => *ENVS.lock().unwrap() = envs,
+        Err(e) => tracing::warn!("loading python envs failed: {e}"),
+    }
+    let _ = STORE.set(path);
+}
+
+pub fn list() -> Vec<PythonEnv> {
+    ENVS.lock().unwrap().clone()
+}
+
+pub fn get(name: &str) -> Option<PythonEnv> {
+    ENVS.lock()
+        .unwrap()
+        .iter()
+        .find(|e| e.name == name)
+        .cloned()
+}
+
+// Adds the env, or replaces the one with the same name. A changed path or
+// kind drops the old probe results.
+pub fn save_env(mut env: PythonEnv) -> Result<PythonEnv, String> {
+    env.name = env.name.trim().to_string();
+    if env.name.is_empty() {
+        return Err("python env name must not be empty".into());
+    }
+    if env.path.trim().is_empty() {
+        return Err("python env path must not be empty".into());
+    }
+    let mut envs = ENVS.lock().unwrap();
+    match envs.iter_mut().find(|e| e.name == env.name) {
+        Some(slot) => {
+            if slot.path != env.path || slot.kind != env.kind {
+                env.version = None;
+                env.missing.clear();
+                env.checked_at = None;
+            }
+            *slot = env.clone();
+        }
+        None => envs.push(env.clone()),
+    }
+    save(&envs)?;
+    Ok(env)
+}
+
+pub fn delete(name: &str) -> Result<(), String> {
+    let mut envs = ENVS.lock().unwrap();
+    let before = envs.len();
+    envs.retain(|e| e.name != name);
+    if envs.len() == before {
+        return Err(format!("unknown python env: {name}"));
+    }
+    save(&envs)
+}
+
+// Points a config at the env: a conda env replaces both conda settings,
+// anything else becomes python_path with conda turned off.
+fn apply_env(config: AppConfig, env: &PythonEnv) -> AppConfig {
+    match env.kind {
+        PythonKind::Conda => AppConfig {
+            conda_env: Some(env.path.clone()),
+            remote_conda_env: Some(env.path.clone()),
+            ..config
+        },
+        PythonKind::System | PythonKind::Venv => AppConfig {
+            python_path: env.path.clone(),
+            conda_env: None,
+            remote_conda_env: None,
+            ..config
+        },
+    }
+}
+
+// Resolves config.python_env against the registry; start_run has already
+// refused names that are not registered.
+pub fn apply(config: AppConfig) -> AppConfig {
+    match config.python_env.as_deref().and_then(get) {
+        Some(env) => apply_env(config, &env),
+        None => config,
+    }
+}
+
+pub fn check_known(name: Option<&str>) -> Result<(), String> {
+    match name {
+        Some(name) if get(name).is_none() => Err(format!("unknown python env: {name}")),
+        _ => Ok(()),
+    }
+}
+
+// Prints the version on the first line, then the modules that fail to import.
+fn probe_command(env: &PythonEnv) -> String {
+    let script = format!(
+        "import sys\nprint(sys.version.split()[0])\n{}",
+        conda::import_script()
+    );
+    let script = shell_escape::escape(Cow::from(script));
+    match env.kind {
+        PythonKind::Conda => format!(
+            "conda activate {} && python -c {script}",
+            shell_escape::escape(Cow::from(env.path.as_str()))
+        ),
+        PythonKind::System | PythonKind::Venv => format!(
+            "{} -c {script}",
+            shell_escape::escape(Cow::from(env.path.as_str()))
+        ),
+    }
+}
+
+fn parse_probe(stdout: &str) -> (Option<String>, Vec<String>) {
+    let mut lines = stdout.lines().map(str::trim).filter(|l| !l.is_empty());
+    let version = lines.next().map(str::to_string);
+    (version, lines.map(str::to_string).collect())
+}
+
+// Runs the env's python locally, or on `profile` for envs that live on a
+// host, and records what it found.
+pub fn probe(name: &str, profile: Option<&HostProfile>) -> Result<PythonEnv, String> {
+    let mut env = get(name).ok_or_else(|| format!("unknown python env: {name}"))?;
+    let command = probe_command(&env);
+    let (code, stdout) = match profile {
+        Some(profile) => conda::remote_sh(profile, &command)?,
+        None => conda::local_sh(&command)?,
+    };
+    if code != 0 {
+        return Err(format!("{name}: python did not run (exit {code})"));
+    }
+    let (version, missing) = parse_probe(&stdout);
+    env.version = version;
+    env.missing = missing;
+    env.checked_at = Some(chrono::Utc::now().to_rfc3339());
+    let mut envs = ENVS.lock().unwrap();
+    if let Some(slot) = envs.iter_mut().find(|e| e.name == env.name) {
+        *slot = env.clone();
+    }
+    save(&envs)?;
+    Ok(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_env, parse_probe, probe_command};
+    use crate::model::{AppConfig, PythonEnv, PythonKind};
+
+    #[test]
+    fn envs_point_configs_at_their_python() {
+        let conda = PythonEnv {
+            name: "arc".into(),
+            path: "/opt/conda/envs/arc_env".into(),
+            kind: PythonKind::Conda,
+            ..PythonEnv::default()
+        };
+        let config = apply_env(AppConfig::default(), &conda);
+        assert_eq!(config.conda_env.as_deref(), Some("/opt/conda/envs/arc_env"));
+        assert_eq!(
+            config.remote_conda_env.as_deref(),
+            Some("/opt/conda/envs/arc_env")
+        );
+        assert!(probe_command(&conda).starts_with("conda activate /opt/conda/envs/arc_env && "));
+
+        let venv = PythonEnv {
+            name: "venv".into(),
+            path: "/home/u/venv/bin/python".into(),
+            kind: PythonKind::Venv,
+            ..PythonEnv::default()
+        };
+        let config = apply_env(config, &venv);
+        assert_eq!(config.python_path, "/home/u/venv/bin/python");
+        assert_eq!(config.conda_env, None);
+
+        assert_eq!(
+            parse_probe("3.7.12\nrdkit\nopenbabel\n"),
+            (
+                Some("3.7.12".into()),
+                vec!["rdkit".to_string(), "openbabel".to_string()]
+            )
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use crate::model::{AppConfig, WorkdirQuota};
+use crate::{creds_from, profiles, run_remote_cmd, runs, HostProfile};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+const EVENT: &str = "workdir-quota-warning";
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const GB: u64 = 1024 * 1024 * 1024;
+
+// last check per (host label or "local", work dir)
+static STATES: Lazy<Mutex<HashMap<(String, PathBuf), QuotaState>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, PartialOrd)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaLevel {
+    Ok,
+    Warn,
+    Hard,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Usage {
+    pub used_bytes: u64,
+    pub limit_bytes: Option<u64>, // quota hard limit, or the filesystem size
+    pub source: String,           // statvfs, df or quota
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaState {
+    pub host: String,
+    pub path: PathBuf,
+    pub usage: Usage,
+    pub quota: WorkdirQuota,
+    pub level: QuotaLevel,
+    pub checked_at: String,
+}
+
+fn level(used: u64, quota: &WorkdirQuota) -> QuotaLevel {
+    if quota.hard_gb.is_some_and(|gb| used >= gb * GB) {
+        QuotaLevel::Hard
+    } else if quota.warn_gb.is_some_and(|gb| used >= gb * GB) {
+        QuotaLevel::Warn
+    } else {
+        QuotaLevel::Ok
+    }
+}
+
+// A work dir that does not exist yet lands on its nearest parent's
+// filesystem.
+#[cfg(unix)]
+fn local_usage(path: &Path) -> Result<Usage, String> {
+    use std::os::unix::ffi::OsStrExt;
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| format!("{} is not reachable", path.display()))?;
+    let c_path = std::ffi::CString::new(existing.as_os_str().as_bytes())
+        .map_err(|e| format!("{}: {e}", existing.display()))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(format!(
+            "statvfs {}: {}",
+            existing.display(),
+            std::io::Error::last_os_error()
+        ));
+    }
+    let block = stat.f_frsize as u64;
+    Ok(Usage {
+        used_bytes: (stat.f_blocks as u64 - stat.f_bfree as u64) * block,
+        limit_bytes: Some(stat.f_blocks as u64 * block),
+        source: "statvfs".into(),
+    })
+}
+
+#[cfg(not(unix))]
+fn local_usage(path: &Path) -> Result<Usage, String> {
+    Err(format!(
+        "{}: quota checks need statvfs, which this platform lacks",
+        path.display()
+    ))
+}
+
+// `df -kP` prints a header, then "<fs> <KiB blocks> <KiB used> ...".
+fn parse_df(stdout: &str) -> Option<(String, Usage)> {
+    let columns: Vec<&str> = stdout.lines().nth(1)?.split_whitespace().collect();
+    let [fs, blocks, used, ..] = columns[..] else {
+        return None;
+    };
+    let usage = Usage {
+        used_bytes: used.parse::<u64>().ok()? * 1024,
+        limit_bytes: Some(blocks.parse::<u64>().ok()? * 1024),
+        source: "df".into(),
+    };
+    Some((fs.to_string(), usage))
+}
+
+// `quota -w` lists "<fs> <KiB used>[*] <soft> <hard> ..." per filesystem the
+// user has a quota on; a 0 limit means none.
+fn parse_quota(stdout: &str, fs: &str) -> Option<Usage> {
+    let line = stdout
+        .lines()
+        .find(|l| l.split_whitespace().next() == Some(fs))?;
+    let columns: Vec<&str> = line.split_whitespace().collect();
+    let [_, used, soft, hard, ..] = columns[..] else {
+        return None;
+    };
+    let kib = |s: &str| s.trim_end_matches('*').parse::<u64>().ok();
+    let limit = [kib(hard)?, kib(soft)?].into_iter().find(|&l| l > 0)?;
+    Some(Usage {
+        used_bytes: kib(used)? * 1024,
+        limit_bytes: Some(limit * 1024),
+        source: "quota".into(),
+    })
+}
+
+// Prefers the user's quota on the work dir's filesystem over the
+// filesystem's own fill level, which on shared scratch says little.
+fn remote_usage(profile: &HostProfile, path: &Path) -> Result<Usage, String> {
+    let dir = shell_escape::escape(path.to_string_lossy());
+    let command = format!("df -kP {dir}; echo ---; quota -w 2>/dev/null");
+    let out = run_remote_cmd(&creds_from(profile), command)?;
+    let (df, quota) = out.stdout.split_once("---\n").unwrap_or((&out.stdout, ""));
+    let (fs, usage) =
+        parse_df(df).ok_or_else(|| format!("df {}: {}", path.display(), out.stderr.trim()))?;
+    Ok(parse_quota(quota, &fs).unwrap_or(usage))
+}
+
+fn host(profile: Option<&HostProfile>) -> String {
+    profile
+        .map(runs::host_label)
+        .unwrap_or_else(|| "local".into())
+}
+
+// Measures and records the work dir's usage. The warning event goes out
+// when the level rises, not on every check.
+pub fn check(
+    profile: Option<&HostProfile>,
+    path: &Path,
+    quota: WorkdirQuota,
+) -> Result<QuotaState, String> {
+    let usage = match profile {
+        Some(profile) => remote_usage(profile, path)?,
+        None => local_usage(path)?,
+    };
+    let state = QuotaState {
+        host: host(profile),
+        path: path.to_path_buf(),
+        level: level(usage.used_bytes, &quota),
+        usage,
+        quota,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let key = (state.host.clone(), state.path.clone());
+    let before = STATES
+        .lock()
+        .unwrap()
+        .insert(key, state.clone())
+        .map_or(QuotaLevel::Ok, |s| s.level);
+    if state.level > before {
+        runs::emit(EVENT, state.clone());
+    }
+    Ok(state)
+}
+
+// Refuses a launch once the hard threshold is reached. A host that cannot
+// report its usage is not held back.
+pub fn check_launch(
+    profile: Option<&HostProfile>,
+    work_dir: &Path,
+    config: &AppConfig,
+) -> Result<(), String> {
+    let Some(quota) = config.workdir_quota else {
+        return Ok(());
+    };
+    let Ok(state) = check(profile, work_dir, quota) else {
+        return Ok(());
+    };
+    if state.level < QuotaLevel::Hard {
+        return Ok(());
+    }
+    Err(format!(
+        "{} on {} uses {:.1} GB, past its {} GB quota",
+        work_dir.display(),
+        state.host,
+        state.usage.used_bytes as f64 / GB as f64,
+        quota.hard_gb.unwrap_or_default()
+    ))
+}
+
+// Work dirs with a quota: the roots runs were launched into, and the
+// default_work_dir of every profile whose overrides set both.
+fn targets() -> Vec<(Option<HostProfile>, PathBuf, WorkdirQuota)> {
+    let mut found: HashMap<(String, PathBuf), (Option<HostProfile>, WorkdirQuota)> = HashMap::new();
+    for run in runs::list_runs() {
+        let Some(config) = runs::launch_config(&run.id) else {
+            continue;
+        };
+        let Some(quota) = config.workdir_quota else {
+            continue;
+        };
+        let profile = runs::run_profile(&run.id);
+        let key = (host(profile.as_ref()), config.default_work_dir.into());
+        found.entry(key).or_insert((profile, quota));
+    }
+    for stored in profiles::list() {
+        let overrides = &stored.profile.overrides;
+        if let (Some(dir), Some(quota)) = (&overrides.default_work_dir, overrides.workdir_quota) {
+            let key = (host(Some(&stored.profile)), dir.into());
+            found.insert(key, (Some(stored.profile), quota));
+        }
+    }
+    found
+        .into_iter()
+        .map(|((_, path), (profile, quota))| (profile, path, quota))
+        .collect()
+}
+
+pub fn start() {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    thread::spawn(|| loop {
+        for (profile, path, quota) in targets() {
+            if let Err(e) = check(profile.as_ref(), &path, quota) {
+                tracing::warn!("quota check of {} failed: {e}", path.display());
+            }
+        }
+        thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+pub fn status() -> Vec<QuotaState> {
+    let mut states: Vec<QuotaState> = STATES.lock().unwrap().values().cloned().collect();
+    states.sort_by(|a, b| (&a.host, &a.path).cmp(&(&b.host, &b.path)));
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{level, parse_df, parse_quota, QuotaLevel, GB};
+    use crate::model::WorkdirQuota;
+
+    #[test]
+    fn reads_df_and_quota_and_grades_usage() {
+        let (fs, usage) = parse_df(
+            "Filesystem 1024-blocks Used Available Capacity Mounted on\n\
+             nfs:/home 104857600 52428800 52428800 50% /home\n",
+        )
+        .unwrap();
+        assert_eq!(fs, "nfs:/home");
+        assert_eq!(usage.used_bytes, 50 * GB);
+        assert_eq!(usage.limit_bytes, Some(100 * GB));
+
+        let quota = "Disk quotas for user u (uid 1000):\n\
+             Filesystem blocks quota limit grace files quota limit grace\n\
+             nfs:/home 20971520* 10485760 31457280 6days 100 0 0\n";
+        let usage = parse_quota(quota, "nfs:/home").unwrap();
+        assert_eq!(usage.used_bytes, 20 * GB);
+        assert_eq!(usage.limit_bytes, Some(30 * GB));
+        assert_eq!(parse_quota(quota, "/dev/sda1"), None);
+        assert_eq!(
+            parse_quota("Disk quotas for user u (uid 1000): none\n", "nfs:/home"),
+            None
+        );
+
+        let quota = WorkdirQuota {
+            warn_gb: Some(10),
+            hard_gb: Some(20),
+        };
+        assert_eq!(level(5 * GB, &quota), QuotaLevel::Ok);
+        assert_eq!(level(15 * GB, &quota), QuotaLevel::Warn);
+        assert_eq!(level(20 * GB, &quota), QuotaLevel::Hard);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::model::{ARCRun, AppConfig, RunStatus};
+use crate::runs::{self, RunRegistry};
+use crate::{tmux_exec, HostProfile};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use tauri::{AppHandle, Manager};
+
+const STORE_FILE: &str = "runs.json";
+
+static STORE: OnceCell<PathBuf> = OnceCell::new();
+// set whenever the registry or queue changes; the monitor saves on its next tick
+static DIRTY: AtomicBool = AtomicBool::new(false);
+
+// Everything needed to pick runs up again after the app restarts. Profile
+// secrets are not written, so password hosts need a fresh login.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SavedState {
+    pub runs: Vec<ARCRun>,
+    pub profiles: HashMap<String, HostProfile>,
+    pub configs: HashMap<String, AppConfig>,
+    pub queue: Vec<SavedQueueEntry>, // in queue order
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedQueueEntry {
+    pub id: String,
+    pub priority: i32,
+    pub pooled: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconcileReport {
+    pub rebound: Vec<String>,
+    pub orphaned: Vec<String>,
+    pub unknown: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Outcome {
+    Rebound,
+    Orphaned,
+    Unknown,
+}
+
+pub fn touch() {
+    DIRTY.store(true, Ordering::SeqCst);
+}
+
+fn load(path: &Path) -> Result<SavedState, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => {
+            serde_json::from_str(&text).map_err(|e| format!("parse {}: {e}", path.display()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SavedState::default()),
+        Err(e) => Err(format!("read {}: {e}", path.display())),
+    }
+}
+
+// Written next to the target and renamed over it so a crash mid-write never
+// leaves a truncated store.
+fn write(path: &Path, state: &SavedState) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    }
+    let text = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, text).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("replace {}: {e}", path.display()))
+}
+
+pub fn save() {
+    let Some(path) = STORE.get() else {
+        return;
+    };
+    if !DIRTY.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    if let Err(e) = write(path, &runs::saved_state()) {
+        tracing::warn!("saving runs failed: {e}");
+        touch();
+    }
+}
+
+// "session|window id" of every window on the host; None when the host could
+// not be asked. A tmux without a server has no windows rather than failing.
+fn live_windows(profile: Option<&HostProfile>) -> Option<HashSet<String>> {
+    let args: Vec<String> = ["list-windows", "-a", "-F", "#{session_name}|#{window_id}"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+    let out = tmux_exec(profile, &args).ok()?;
+    if out.code != 0 {
+        return Some(HashSet::new());
+    }
+    Some(out.stdout.lines().map(|l| l.trim().to_string()).collect())
+}
+
+fn decide(run: &ARCRun, live: Option<&HashSet<String>>) -> Outcome {
+    // the monitor asks the scheduler about batch jobs directly
+    if run.batch_job_id.is_some() {
+        return Outcome::Rebound;
+    }
+    let Some(window_id) = run.window_id.as_deref() else {
+        return Outcome::Orphaned;
+    };
+    match live {
+        None => Outcome::Unknown,
+        Some(windows) if windows.contains(&format!("{}|{window_id}", run.session)) => {
+            Outcome::Rebound
+        }
+        Some(_) => Outcome::Orphaned,
+    }
+}
+
+// Checks runs that were live (or could not be checked last time) against
+// tmux on their hosts. Rebound runs go back to Running and the monitor takes
+// it from there.
+pub fn reconcile() -> ReconcileReport {
+    let mut report = ReconcileReport::default();
+    let mut windows: HashMap<Option<String>, Option<HashSet<String>>> = HashMap::new();
+    for run in RunRegistry::global().list() {
+        if !runs::is_active(&run.status) && run.status != RunStatus::Unknown {
+            continue;
+        }
+        let profile = runs::run_profile(&run.id);
+        // a remote run whose profile was not saved can't be reached
+        let live = if run.host.is_some() && profile.is_none() {
+            None
+        } else {
+            windows
+                .entry(run.host.clone())
+                .or_insert_with(|| live_windows(profile.as_ref()))
+                .as_ref()
+        };
+        let outcome = decide(&run, live);
+        let status = match outcome {
+            Outcome::Rebound => {
+                report.rebound.push(run.id.clone());
+                if run.status != RunStatus::Unknown {
+                    continue;
+                }
+                RunStatus::Running
+            }
+            Outcome::Orphaned => {
+                report.orphaned.push(run.id.clone());
+                RunStatus::Orphaned
+            }
+            Outcome::Unknown => {
+                report.unknown.push(run.id.clone());
+                RunStatus::Unknown
+            }
+        };
+        if let Some(updated) = RunRegistry::global().update(&run.id, |r| r.status = status) {
+            runs::emit_status(Some(&run.status), &updated);
+        }
+    }
+    report
+}
+
+// Restores the last session's runs right away so the UI can list them, then
+// checks them against tmux before the monitor starts polling; otherwise a
+// vanished window would be reported as a failed run.
+pub fn init(app: AppHandle) {
+    runs::attach_app(app.clone());
+    match app.path().app_data_dir() {
+        Ok(dir) => {
+            let path = dir.join(STORE_FILE);
+            match load(&path) {
+                Ok(state) => runs::restore(state),
+                Err(e) => tracing::warn!("loading runs failed: {e}"),
+            }
+            let _ = STORE.set(path);
+        }
+        Err(e) => tracing::warn!("no app data dir, runs will not be kept: {e}"),
+    }
+    thread::spawn(move || {
+        reconcile();
+        runs::start_monitor(app);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decide, load, write, Outcome, SavedState};
+    use crate::model::{ARCRun, RunStatus};
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    fn run(window_id: Option<&str>, batch_job_id: Option<&str>) -> ARCRun {
+        ARCRun {
+            window_id: window_id.map(String::from),
+            input_path: PathBuf::from("/w/input.yml"),
+            work_dir: PathBuf::from("/w"),
+            batch_job_id: batch_job_id.map(String::from),
+            ..ARCRun::for_test("r1", RunStatus::Running)
+        }
+    }
+
+    #[test]
+    fn reconcile_decisions_and_store_roundtrip() {
+        let live: HashSet<String> = ["arc|@3".to_string()].into();
+        assert_eq!(
+            decide(&run(Some("@3"), None), Some(&live)),
+            Outcome::Rebound
+        );
+        assert_eq!(
+            decide(&run(Some("@4"), None), Some(&live)),
+            Outcome::Orphaned
+        );
+        assert_eq!(decide(&run(Some("@3"), None), None), Outcome::Unknown);
+        assert_eq!(decide(&run(None, Some("42")), None), Outcome::Rebound);
+        assert_eq!(decide(&run(None, None), Some(&live)), Outcome::Orphaned);
+
+        let dir = std::env::temp_dir().join(format!("recovery-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("runs.json");
+        assert!(load(&path).unwrap().runs.is_empty());
+        let state = SavedState {
+            runs: vec![run(Some("@3"), None)],
+            ..SavedState::default()
+        };
+        write(&path, &state).unwrap();
+        assert_eq!(load(&path).unwrap().runs, state.runs);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
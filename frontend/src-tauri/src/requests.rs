@@ -0,0 +1,193 @@
+use crate::{compress, errors, HostProfile};
+use serde::{Deserialize, Serialize};
+
+// Request bodies of the window and session commands. Fields are camelCase
+// on the wire; the snake_case spellings older frontend code sends are
+// accepted as aliases.
+
+// A window of a session: by id when the frontend has one, by index
+// otherwise.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowRef {
+    pub session: String,
+    #[serde(alias = "window_index")]
+    pub window_index: u32,
+    #[serde(default, alias = "window_id")]
+    pub window_id: Option<String>,
+}
+
+impl WindowRef {
+    pub fn target(&self) -> String {
+        self.window_id
+            .clone()
+            .unwrap_or_else(|| self.index_target())
+    }
+
+    pub fn index_target(&self) -> String {
+        format!("{}:{}", self.session, self.window_index)
+    }
+
+    // Runs `op` on target(). A window id goes stale when its window is
+    // closed and recreated; if tmux no longer finds the id, `op` runs again
+    // on session:index. The flag says whether that happened.
+    pub fn with_fallback<T>(
+        &self,
+        mut op: impl FnMut(&str) -> Result<T, String>,
+    ) -> Result<(T, bool), String> {
+        match op(&self.target()) {
+            Err(e) if self.window_id.is_some() && errors::window_missing(&e) => {
+                op(&self.index_target()).map(|out| (out, true))
+            }
+            other => other.map(|out| (out, false)),
+        }
+    }
+}
+
+// What kill and rename answer with. `staleId` tells the frontend the window
+// id it sent is gone and its window list needs refreshing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowOutcome {
+    pub stale_id: bool,
+}
+
+fn default_lines() -> u32 {
+    800
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureRequest {
+    #[serde(flatten)]
+    pub window: WindowRef,
+    #[serde(default = "default_lines")]
+    pub lines: u32,
+    // only return the text when it changed since the last such capture
+    #[serde(default, alias = "if_changed")]
+    pub if_changed: bool,
+    // true compresses over compress::DEFAULT_THRESHOLD
+    #[serde(default)]
+    pub compress: bool,
+    #[serde(default, alias = "compress_over")]
+    pub compress_over: Option<usize>,
+    #[serde(default, alias = "spill_over")]
+    pub spill_over: Option<usize>,
+    // drop terminal escape sequences, for views that want plain text
+    #[serde(default, alias = "strip_ansi")]
+    pub strip_ansi: bool,
+    #[serde(default)]
+    pub binary: bool,
+}
+
+impl CaptureRequest {
+    pub fn compress_threshold(&self) -> Option<usize> {
+        self.compress_over
+            .or(self.compress.then_some(compress::DEFAULT_THRESHOLD))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendKeysRequest {
+    #[serde(flatten)]
+    pub window: WindowRef,
+    pub keys: String,
+    #[serde(default, alias = "with_enter")]
+    pub with_enter: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameWindowRequest {
+    #[serde(flatten)]
+    pub window: WindowRef,
+    #[serde(alias = "new_name", alias = "name")]
+    pub new_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameSessionRequest {
+    pub session: String,
+    #[serde(alias = "new_name")]
+    pub new_name: String,
+}
+
+// Any of the above for a remote host.
+#[derive(Clone, Deserialize)]
+pub struct Remote<T> {
+    pub profile: HostProfile,
+    #[serde(flatten)]
+    pub request: T,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CaptureRequest, RenameWindowRequest, WindowRef};
+    use serde_json::json;
+
+    #[test]
+    fn both_spellings_deserialize_and_missing_fields_are_caught() {
+        let capture: CaptureRequest = serde_json::from_value(json!({
+            "session": "arc",
+            "window_index": 2,
+            "windowId": "@7",
+            "if_changed": true,
+            "compress": true,
+        }))
+        .unwrap();
+        assert_eq!(
+            capture.window,
+            WindowRef {
+                session: "arc".into(),
+                window_index: 2,
+                window_id: Some("@7".into()),
+            }
+        );
+        assert_eq!(capture.lines, 800);
+        assert!(capture.if_changed);
+        assert_eq!(capture.compress_threshold(), Some(64 * 1024));
+        assert_eq!(capture.window.target(), "@7");
+
+        let rename: RenameWindowRequest =
+            serde_json::from_value(json!({ "session": "a b", "windowIndex": 0, "name": "x" }))
+                .unwrap();
+        assert_eq!(rename.new_name, "x");
+        assert_eq!(rename.window.target(), "a b:0");
+
+        let err = serde_json::from_value::<CaptureRequest>(json!({ "session": "arc" }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("windowIndex"), "{err}");
+    }
+
+    #[test]
+    fn a_stale_window_id_falls_back_to_the_index() {
+        let window = WindowRef {
+            session: "arc".into(),
+            window_index: 3,
+            window_id: Some("@9".into()),
+        };
+        let mut tried = vec![];
+        let out = window.with_fallback(|target| {
+            tried.push(target.to_string());
+            match target {
+                "@9" => Err("can't find window: @9".to_string()),
+                _ => Ok(target.len()),
+            }
+        });
+        assert_eq!(out, Ok((5, true)));
+        assert_eq!(tried, ["@9", "arc:3"]);
+
+        // other failures, and refs without an id, are not retried
+        let out = window.with_fallback(|_| Err::<(), _>("can't find session: arc".to_string()));
+        assert_eq!(out, Err("can't find session: arc".to_string()));
+        let by_index = WindowRef {
+            window_id: None,
+            ..window
+        };
+        let out = by_index.with_fallback(|_| Err::<(), _>("can't find window: 3".to_string()));
+        assert!(out.is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use crate::runs::RunRegistry;
+use crate::{creds_from, run_remote_cmd, tmux_exec, HostProfile};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::process::Command as PCommand;
+use std::sync::Mutex;
+
+static SERIES: Lazy<Mutex<HashMap<String, VecDeque<ResourceSample>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// at the monitor's 5s interval this covers the last ten minutes
+const MAX_SAMPLES: usize = 120;
+const PS_ARGS: &str = "-e -o pid=,ppid=,pcpu=,rss=";
+// longer gaps (host unreachable, app asleep) are not counted as CPU time
+const MAX_SAMPLE_GAP_SECS: f64 = 30.0;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ResourceSample {
+    pub at: String,
+    pub cpu_percent: f32, // summed over the pane's process tree
+    pub rss_kb: u64,
+    pub processes: u32, // descendants of the pane's shell
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PsRow {
+    pid: u32,
+    ppid: u32,
+    cpu: f32,
+    rss_kb: u64,
+}
+
+fn parse_ps(stdout: &str) -> Vec<PsRow> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut cols = line.split_whitespace();
+            Some(PsRow {
+                pid: cols.next()?.parse().ok()?,
+                ppid: cols.next()?.parse().ok()?,
+                cpu: cols.next()?.parse().ok()?,
+                rss_kb: cols.next()?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// Sums usage over `root` and everything below it.
+fn tree_usage(rows: &[PsRow], root: u32) -> Option<(f32, u64, u32)> {
+    let root_row = rows.iter().find(|r| r.pid == root)?;
+    let mut children: HashMap<u32, Vec<&PsRow>> = HashMap::new();
+    for row in rows {
+        children.entry(row.ppid).or_default().push(row);
+    }
+    let (mut cpu, mut rss, mut count) = (root_row.cpu, root_row.rss_kb, 0);
+    let mut stack = vec![root];
+    while let Some(pid) = stack.pop() {
+        for child in children.get(&pid).into_iter().flatten() {
+            cpu += child.cpu;
+            rss += child.rss_kb;
+            count += 1;
+            stack.push(child.pid);
+        }
+    }
+    Some((cpu, rss, count))
+}
+
+fn pane_pid(profile: Option<&HostProfile>, window_id: &str) -> Option<u32> {
+    let args: Vec<String> = ["display-message", "-p", "-t", window_id, "#{pane_pid}"]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+    let out = tmux_exec(profile, &args).ok()?;
+    if out.code != 0 {
+        return None;
+    }
+    out.stdout.trim().parse().ok()
+}
+
+fn process_table(profile: Option<&HostProfile>) -> Option<Vec<PsRow>> {
+    let stdout = match profile {
+        Some(profile) => {
+            let out = run_remote_cmd(&creds_from(profile), format!("ps {PS_ARGS}")).ok()?;
+            (out.code == 0).then_some(out.stdout)?
+        }
+        None => {
+            let out = PCommand::new("ps").args(PS_ARGS.split(' ')).output().ok()?;
+            String::from_utf8_lossy(&out.stdout).to_string()
+        }
+    };
+    Some(parse_ps(&stdout))
+}
+
+fn since(at: &str, now: chrono::DateTime<chrono::Utc>) -> Option<f64> {
+    let at = chrono::DateTime::parse_from_rfc3339(at).ok()?;
+    Some((now - at.with_timezone(&chrono::Utc)).num_milliseconds() as f64 / 1000.0)
+}
+
+// Core-seconds used over `secs` at `cpu_percent` (100 per busy core).
+fn core_secs(cpu_percent: f32, secs: f64) -> f64 {
+    if !(0.0..=MAX_SAMPLE_GAP_SECS).contains(&secs) {
+        return 0.0;
+    }
+    cpu_percent as f64 / 100.0 * secs
+}
+
+// Records one sample for the run's pane; quietly skips when the window or
+// `ps` can't be reached so a flaky host doesn't disturb the monitor.
+pub fn sample(run_id: &str, profile: Option<&HostProfile>, window_id: &str) {
+    let Some(root) = pane_pid(profile, window_id) else {
+        return;
+    };
+    let Some((cpu, rss_kb, processes)) =
+        process_table(profile).and_then(|rows| tree_usage(&rows, root))
+    else {
+        return;
+    };
+    let now = chrono::Utc::now();
+    let sample = ResourceSample {
+        at: now.to_rfc3339(),
+        cpu_percent: cpu,
+        rss_kb,
+        processes,
+    };
+    let mut series = SERIES.lock().unwrap();
+    let samples = series.entry(run_id.to_string()).or_default();
+    if let Some(secs) = samples.back().and_then(|prev| since(&prev.at, now)) {
+        RunRegistry::global().update(run_id, |r| r.core_secs += core_secs(cpu, secs));
+    }
+    if samples.len() == MAX_SAMPLES {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+pub fn samples(run_id: &str) -> Vec<ResourceSample> {
+    SERIES
+        .lock()
+        .unwrap()
+        .get(run_id)
+        .map(|s| s.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{core_secs, parse_ps, tree_usage};
+
+    #[test]
+    fn tree_usage_covers_pane_process_tree() {
+        let rows = parse_ps(
+            "  100     1  0.0  4000\n  200   100 95.5 812000\n  201   200 50.0 20000\n  300     1 99.0 1000\n",
+        );
+        assert_eq!(rows.len(), 4);
+        assert_eq!(tree_usage(&rows, 100), Some((145.5, 836000, 2)));
+        assert_eq!(tree_usage(&rows, 999), None);
+
+        assert_eq!(core_secs(400.0, 5.0), 20.0);
+        assert_eq!(core_secs(400.0, 600.0), 0.0);
+    }
+}
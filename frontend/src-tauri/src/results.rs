@@ -0,0 +1,342 @@
+use crate::runs;
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub units: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct Thermo {
+    pub h298: Option<Quantity>,
+    pub s298: Option<Quantity>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct Arrhenius {
+    pub a: Option<Quantity>,
+    pub n: Option<f64>,
+    pub ea: Option<Quantity>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SpeciesResult {
+    pub label: String,
+    pub converged: Option<bool>,
+    pub job_types: BTreeMap<String, bool>, // job type -> whether it converged
+    pub errors: Option<String>,
+    pub warnings: Option<String>,
+    pub thermo: Option<Thermo>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ReactionResult {
+    pub label: String,
+    pub kinetics: Arrhenius,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FailedJob {
+    pub label: String,
+    pub job_type: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RunResults {
+    pub project_dir: PathBuf,
+    pub species: Vec<SpeciesResult>,
+    pub reactions: Vec<ReactionResult>,
+    pub failed_jobs: Vec<FailedJob>,
+}
+
+fn read_yaml(path: &Path) -> Option<Value> {
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_yaml::from_str(&text).ok()
+}
+
+fn non_empty_str(map: &Mapping, key: &str) -> Option<String> {
+    map.get(key)
+        .and_then(Value::as_str)
+        .filter(|s| !s.trim().is_empty())
+        .map(str::to_string)
+}
+
+// Arkane writes quantities as `{class: ScalarQuantity, value: x, units: u}`,
+// but older files use `[x, u]` or a bare number.
+fn quantity(value: &Value) -> Option<Quantity> {
+    match value {
+        Value::Number(n) => Some(Quantity {
+            value: n.as_f64()?,
+            units: None,
+        }),
+        Value::Sequence(seq) => Some(Quantity {
+            value: seq.first()?.as_f64()?,
+            units: seq.get(1).and_then(Value::as_str).map(str::to_string),
+        }),
+        Value::Mapping(map) => Some(Quantity {
+            value: map.get("value")?.as_f64()?,
+            units: map.get("units").and_then(Value::as_str).map(str::to_string),
+        }),
+        _ => None,
+    }
+}
+
+// Depth-first lookup of the first mapping entry named `key`.
+fn find_key<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Mapping(map) => map
+            .get(key)
+            .or_else(|| map.values().find_map(|v| find_key(v, key))),
+        Value::Sequence(seq) => seq.iter().find_map(|v| find_key(v, key)),
+        _ => None,
+    }
+}
+
+fn parse_thermo(doc: &Value) -> Option<Thermo> {
+    let thermo = Thermo {
+        h298: find_key(doc, "H298").and_then(quantity),
+        s298: find_key(doc, "S298").and_then(quantity),
+    };
+    (thermo != Thermo::default()).then_some(thermo)
+}
+
+fn parse_kinetics(doc: &Value) -> Option<Arrhenius> {
+    let kinetics = Arrhenius {
+        a: find_key(doc, "A").and_then(quantity),
+        n: find_key(doc, "n").and_then(quantity).map(|q| q.value),
+        ea: find_key(doc, "Ea").and_then(quantity),
+    };
+    (kinetics.a.is_some() && kinetics.ea.is_some()).then_some(kinetics)
+}
+
+// ARC's output dictionary: label -> {convergence, job_types, errors, ...}.
+fn parse_status(output: &Mapping) -> Vec<SpeciesResult> {
+    output
+        .iter()
+        .filter_map(|(label, entry)| {
+            let entry = entry.as_mapping()?;
+            let job_types = entry
+                .get("job_types")
+                .and_then(Value::as_mapping)
+                .map(|jobs| {
+                    jobs.iter()
+                        .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v.as_bool()?)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(SpeciesResult {
+                label: label.as_str()?.to_string(),
+                converged: entry.get("convergence").and_then(Value::as_bool),
+                job_types,
+                errors: non_empty_str(entry, "errors"),
+                warnings: non_empty_str(entry, "warnings"),
+                thermo: None,
+            })
+        })
+        .collect()
+}
+
+fn yaml_files(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            yaml_files(&path, found);
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "yml" || ext == "yaml")
+        {
+            found.push(path);
+        }
+    }
+}
+
+fn first_in_dir<T>(dir: &Path, parse: impl Fn(&Value) -> Option<T>) -> Option<T> {
+    let mut files = Vec::new();
+    yaml_files(dir, &mut files);
+    files
+        .iter()
+        .filter_map(|f| read_yaml(f))
+        .find_map(|doc| parse(&doc))
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+fn dir_label(dir: &Path) -> String {
+    dir.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+pub fn parse_project(project: &Path) -> Result<RunResults, String> {
+    let output_dir = project.join("output");
+    if !output_dir.is_dir() {
+        return Err(format!("no output directory under {}", project.display()));
+    }
+
+    // newer ARC versions write the status dictionary to output/status.yml;
+    // otherwise it lives under `output:` in restart.yml
+    let status = read_yaml(&output_dir.join("status.yml")).or_else(|| {
+        read_yaml(&project.join("restart.yml")).and_then(|doc| doc.get("output").cloned())
+    });
+    let mut species = status
+        .as_ref()
+        .and_then(Value::as_mapping)
+        .map(parse_status)
+        .unwrap_or_default();
+
+    for dir in subdirs(&output_dir.join("Species")) {
+        let label = dir_label(&dir);
+        let thermo = first_in_dir(&dir, parse_thermo);
+        match species.iter_mut().find(|s| s.label == label) {
+            Some(entry) => entry.thermo = thermo,
+            None => species.push(SpeciesResult {
+                label,
+                thermo,
+                ..SpeciesResult::default()
+            }),
+        }
+    }
+
+    let reactions = subdirs(&output_dir.join("rxns"))
+        .iter()
+        .filter_map(|dir| {
+            Some(ReactionResult {
+                label: dir_label(dir),
+                kinetics: first_in_dir(dir, parse_kinetics)?,
+            })
+        })
+        .collect();
+
+    let failed_jobs = species
+        .iter()
+        .flat_map(|s| {
+            s.job_types
+                .iter()
+                .filter(|(_, ok)| !**ok)
+                .map(|(job_type, _)| FailedJob {
+                    label: s.label.clone(),
+                    job_type: job_type.clone(),
+                })
+        })
+        .collect();
+
+    Ok(RunResults {
+        project_dir: project.to_path_buf(),
+        species,
+        reactions,
+        failed_jobs,
+    })
+}
+
+// A remote run's results are read from the copy fetch_results brought back.
+pub fn run_results(id: String) -> Result<RunResults, String> {
+    let run = runs::get_run(id)?;
+    if runs::is_active(&run.status) {
+        return Err("results are available once the run has finished".into());
+    }
+    if let Some(output) = &run.results_path {
+        let project = output
+            .parent()
+            .ok_or_else(|| format!("no project around {}", output.display()))?;
+        return parse_project(project);
+    }
+    if run.host.is_some() {
+        return Err("results of a remote run are not fetched yet; fetch results first".into());
+    }
+    let project = runs::find_project_file(&run.work_dir, "arc.log")
+        .or_else(|| runs::find_restart_file(&run.work_dir))
+        .and_then(|f| f.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| run.work_dir.clone());
+    parse_project(&project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_project, run_results};
+    use crate::model::{ARCRun, RunStatus};
+    use crate::runs::RunRegistry;
+
+    #[test]
+    fn parses_status_thermo_and_kinetics() {
+        let project = std::env::temp_dir().join(format!("results-{}", uuid::Uuid::new_v4()));
+        let species_dir = project.join("output/Species/CH4/arkane");
+        let rxn_dir = project.join("output/rxns/rxn1");
+        std::fs::create_dir_all(&species_dir).unwrap();
+        std::fs::create_dir_all(&rxn_dir).unwrap();
+        std::fs::write(
+            project.join("restart.yml"),
+            "output:\n  CH4:\n    convergence: true\n    errors: ''\n    job_types: {opt: true, freq: true}\n  OH:\n    convergence: false\n    errors: 'opt failed'\n    job_types: {opt: false, freq: true}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            species_dir.join("CH4.yml"),
+            "thermo_data:\n  H298: {class: ScalarQuantity, value: -74.6, units: kJ/mol}\n  S298: [186.3, J/(mol*K)]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            rxn_dir.join("kinetics.yml"),
+            "kinetics:\n  A: {value: 1.2e+10, units: cm^3/(mol*s)}\n  n: 0.5\n  Ea: {value: 42.0, units: kJ/mol}\n",
+        )
+        .unwrap();
+
+        let results = parse_project(&project).unwrap();
+        let ch4 = results.species.iter().find(|s| s.label == "CH4").unwrap();
+        assert_eq!(ch4.converged, Some(true));
+        let thermo = ch4.thermo.as_ref().unwrap();
+        assert_eq!(thermo.h298.as_ref().unwrap().value, -74.6);
+        assert_eq!(
+            thermo.s298.as_ref().unwrap().units.as_deref(),
+            Some("J/(mol*K)")
+        );
+
+        let oh = results.species.iter().find(|s| s.label == "OH").unwrap();
+        assert_eq!(oh.errors.as_deref(), Some("opt failed"));
+        assert_eq!(results.failed_jobs.len(), 1);
+        assert_eq!(results.failed_jobs[0].job_type, "opt");
+
+        assert_eq!(results.reactions.len(), 1);
+        assert_eq!(results.reactions[0].kinetics.n, Some(0.5));
+        let _ = std::fs::remove_dir_all(&project);
+    }
+
+    #[test]
+    fn remote_runs_read_their_fetched_copy() {
+        let fetched = std::env::temp_dir().join(format!("results-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(fetched.join("output/Species/CH4")).unwrap();
+        let remote = ARCRun {
+            host: Some("u@hpc:22".into()),
+            ..ARCRun::for_test(&uuid::Uuid::new_v4().to_string(), RunStatus::Finished)
+        };
+        RunRegistry::global().insert(remote.clone());
+        let err = run_results(remote.id.clone()).unwrap_err();
+        assert!(err.contains("fetch results first"), "{err}");
+
+        RunRegistry::global().update(&remote.id, |r| {
+            r.results_path = Some(fetched.join("output"));
+        });
+        let results = run_results(remote.id).unwrap();
+        assert_eq!(results.project_dir, fetched);
+        assert_eq!(results.species[0].label, "CH4");
+        let _ = std::fs::remove_dir_all(&fetched);
+    }
+}
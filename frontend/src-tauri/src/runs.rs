@@ -0,0 +1,2068 @@
+use crate::batch::{var, RemoteBatch};
+use crate::model::{ARCRun, AppConfig, RunNote, RunStatus};
+use crate::{
+    build_tmux_send_keys_commands, creds_from, errors, run_remote_cmd, tmux_exec, HostProfile,
+};
+use crate::{
+    diagnostics, diskusage, eta, filepoll, hostpool, inputs, logstream, notifications, progress,
+    pyenvs, quota, recovery, resources, scheduler, versions, watchdog,
+};
+use once_cell::sync::{Lazy, OnceCell};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::json;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+static REGISTRY: Lazy<RunRegistry> = Lazy::new(RunRegistry::new);
+
+static QUEUE: Lazy<Mutex<VecDeque<QueuedRun>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+// held by the one scheduling pass running at a time; RESCHEDULE asks it for
+// another pass once it is done with the current one
+static SCHEDULING: Mutex<()> = Mutex::new(());
+static RESCHEDULE: AtomicBool = AtomicBool::new(false);
+// runs with a stop in progress; the monitor leaves them alone so the
+// KeyboardInterrupt traceback is not reported as a failure
+static STOPPING: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+// config each run was launched with, so it can be restarted the same way
+static CONFIGS: Lazy<Mutex<HashMap<String, AppConfig>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// host profile of every remote run; runs missing here live on this machine
+static PROFILES: Lazy<Mutex<HashMap<String, HostProfile>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static PROJECT_LINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"Starting project\s+(\S+)").unwrap());
+static MONITOR_STARTED: AtomicBool = AtomicBool::new(false);
+static APP: OnceCell<AppHandle> = OnceCell::new();
+// last scheduler state sent to the UI, so unchanged ticks stay quiet
+static LAST_SCHEDULER_STATE: Lazy<Mutex<Option<serde_json::Value>>> =
+    Lazy::new(|| Mutex::new(None));
+
+// tmux session that app-launched runs are opened in, unless the profile
+// has a session_template
+const RUN_SESSION: &str = "arc";
+const SESSION_PLACEHOLDERS: &[&str] = &["project", "date", "user", "host"];
+const MONITOR_INTERVAL: Duration = Duration::from_secs(5);
+const STATUS_EVENT: &str = "run-status-changed";
+const CREATED_EVENT: &str = "run-created";
+const QUEUED_EVENT: &str = "run-queued";
+const STARTED_EVENT: &str = "run-started";
+const FINISHED_EVENT: &str = "run-finished";
+const FAILED_EVENT: &str = "run-failed";
+const QUEUE_EVENT: &str = "run-queue-position";
+const SCHEDULER_EVENT: &str = "scheduler-state-changed";
+const PROGRESS_EVENT: &str = "run-progress";
+const GRACEFUL_TIMEOUT: Duration = Duration::from_secs(120);
+const STALLED_EVENT: &str = "run-stalled";
+const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+const STALL_CONTEXT_LINES: usize = 20;
+
+// Runs change state from background threads, so events go through the
+// handle captured when the monitor starts rather than a per-command one.
+pub fn emit<S: Serialize + Clone>(event: &str, payload: S) {
+    if let Some(app) = APP.get() {
+        let _ = app.emit(event, payload);
+    }
+}
+
+// Every change goes out as STATUS_EVENT; the transitions the UI reacts to
+// also get their own lifecycle event carrying the run.
+fn lifecycle_event(prev: Option<&RunStatus>, next: &RunStatus) -> Option<&'static str> {
+    match next {
+        RunStatus::Queued => Some(QUEUED_EVENT),
+        RunStatus::Starting | RunStatus::Running if !prev.is_some_and(is_active) => {
+            Some(STARTED_EVENT)
+        }
+        RunStatus::Finished => Some(FINISHED_EVENT),
+        RunStatus::Failed => Some(FAILED_EVENT),
+        _ => None,
+    }
+}
+
+pub fn emit_status(prev: Option<&RunStatus>, run: &ARCRun) {
+    if prev == Some(&run.status) {
+        return;
+    }
+    emit(STATUS_EVENT, run);
+    let event = lifecycle_event(prev, &run.status);
+    if let Some(event) = event {
+        emit(event, run);
+    }
+    let config = launch_config(&run.id);
+    if let Some(app) = APP.get() {
+        let toggles = config
+            .as_ref()
+            .map(|c| c.notifications.clone())
+            .unwrap_or_default();
+        notifications::notify_transition(app, run, &toggles);
+    }
+    if run.status == RunStatus::Failed {
+        let id = run.id.clone();
+        thread::spawn(move || {
+            if let Err(e) = diagnostics::collect(id) {
+                tracing::warn!("diagnostics failed: {e}");
+            }
+        });
+    }
+    if let Some(config) = &config {
+        let event = match run.status {
+            RunStatus::Stalled => STALLED_EVENT,
+            _ => event.unwrap_or(STATUS_EVENT),
+        };
+        notifications::send_webhooks(&config.webhooks, event, run);
+    }
+}
+
+fn register(run: &ARCRun) {
+    RunRegistry::global().insert(run.clone());
+    emit(CREATED_EVENT, run);
+    emit_status(None, run);
+}
+
+pub struct RunRegistry {
+    inner: Mutex<HashMap<String, ARCRun>>,
+}
+
+impl RunRegistry {
+    fn new() -> Self {
+        Self {
+            inner: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn global() -> &'static Self {
+        &REGISTRY
+    }
+
+    pub fn insert(&self, run: ARCRun) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.insert(run.id.clone(), run);
+        recovery::touch();
+    }
+
+    pub fn get(&self, id: &str) -> Option<ARCRun> {
+        let inner = self.inner.lock().unwrap();
+        inner.get(id).cloned()
+    }
+
+    // Applies `f` to the stored run and returns the updated copy.
+    pub fn update<F: FnOnce(&mut ARCRun)>(&self, id: &str, f: F) -> Option<ARCRun> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.get_mut(id).map(|run| {
+            f(run);
+            recovery::touch();
+            run.clone()
+        })
+    }
+
+    pub fn list(&self) -> Vec<ARCRun> {
+        let inner = self.inner.lock().unwrap();
+        let mut runs: Vec<ARCRun> = inner.values().cloned().collect();
+        runs.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        runs
+    }
+}
+
+fn tmux(profile: Option<&HostProfile>, args: &[&str]) -> Result<crate::ssh::ExecOut, String> {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    tmux_exec(profile, &args)
+}
+
+// Runs a plain shell command on the profile's host.
+fn remote_sh(profile: &HostProfile, command: String) -> Result<crate::ssh::ExecOut, String> {
+    run_remote_cmd(&creds_from(profile), command)
+}
+
+pub fn host_label(profile: &HostProfile) -> String {
+    format!(
+        "{}@{}:{}",
+        profile.user,
+        profile.host,
+        profile.port.unwrap_or(22)
+    )
+}
+
+pub fn run_profile(id: &str) -> Option<HostProfile> {
+    PROFILES.lock().unwrap().get(id).cloned()
+}
+
+// The profile of a remote run's host; None for a local run.
+pub fn remote_profile(run: &ARCRun) -> Result<Option<HostProfile>, String> {
+    match run.host.as_deref() {
+        Some(host) => run_profile(&run.id)
+            .map(Some)
+            .ok_or_else(|| format!("no saved login for {host}, reconnect")),
+        None => Ok(None),
+    }
+}
+
+// The config a run on `profile` launches with: the profile's overrides
+// where it has them, the global config elsewhere.
+pub fn resolve_config(config: AppConfig, profile: Option<&HostProfile>) -> AppConfig {
+    let Some(overrides) = profile.map(|p| &p.overrides) else {
+        return pyenvs::apply(config);
+    };
+    // a profile's own python_path beats the global python env
+    let python_env = match (&overrides.python_env, &overrides.python_path) {
+        (Some(env), _) => Some(env.clone()),
+        (None, Some(_)) => None,
+        (None, None) => config.python_env.clone(),
+    };
+    pyenvs::apply(AppConfig {
+        python_path: overrides.python_path.clone().unwrap_or(config.python_path),
+        // remote launches read remote_arc_path first
+        remote_arc_path: overrides.arc_path.clone().or(config.remote_arc_path),
+        default_work_dir: overrides
+            .default_work_dir
+            .clone()
+            .unwrap_or(config.default_work_dir),
+        concurrency_cap: overrides.concurrency_cap.unwrap_or(config.concurrency_cap),
+        python_env,
+        workdir_quota: overrides.workdir_quota.or(config.workdir_quota),
+        ..config
+    })
+}
+
+// Active runs that count against a queued run's cap: those on its host
+// when its profile sets its own cap, all of them otherwise.
+fn cap_usage(id: &str, runs: &[ARCRun]) -> usize {
+    let host = run_profile(id)
+        .filter(|p| p.overrides.concurrency_cap.is_some())
+        .map(|p| host_label(&p));
+    runs.iter()
+        .filter(|r| is_active(&r.status))
+        .filter(|r| host.is_none() || r.host == host)
+        .count()
+}
+
+pub fn conda_env(config: &AppConfig, remote: bool) -> Option<&str> {
+    if remote {
+        config.remote_conda_env.as_deref()
+    } else {
+        config.conda_env.as_deref()
+    }
+}
+
+fn build_arc_command(config: &AppConfig, input_path: &Path, remote: bool) -> String {
+    let input = input_path.to_string_lossy();
+    let env = conda_env(config, remote);
+    // an activated env puts its own python first on PATH
+    let python = if env.is_some() {
+        "python"
+    } else {
+        config.python_path.as_str()
+    };
+    let arc_path = match &config.remote_arc_path {
+        Some(path) if remote => path.as_str(),
+        _ => config.arc_path.as_str(),
+    };
+    let command = format!(
+        "{} {} {}",
+        shell_escape::escape(Cow::from(python)),
+        shell_escape::escape(Cow::from(arc_path)),
+        shell_escape::escape(input)
+    );
+    match env {
+        Some(env) => format!(
+            "conda activate {} && {}",
+            shell_escape::escape(Cow::from(env)),
+            command
+        ),
+        None => command,
+    }
+}
+
+// The profile's env preset goes in front of ARC in the run window and the
+// batch script. send-keys types one line, so its lines are joined with `;`.
+fn with_env_preset(profile: Option<&HostProfile>, command: String) -> String {
+    let Some(preset) = profile.and_then(|p| p.env_preset.as_deref()) else {
+        return command;
+    };
+    let mut lines: Vec<&str> = preset
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .collect();
+    if lines.is_empty() {
+        return command;
+    }
+    lines.push(&command);
+    lines.join("; ")
+}
+
+// The first `{name}` in a session template that session_name can't fill.
+pub fn unknown_placeholder(template: &str) -> Option<&str> {
+    template
+        .split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+        .find(|name| !SESSION_PLACEHOLDERS.contains(name))
+}
+
+fn expand_session(template: &str, project: &str, date: &str, profile: &HostProfile) -> String {
+    template
+        .replace("{project}", project)
+        .replace("{date}", date)
+        .replace("{user}", &profile.user)
+        .replace("{host}", &profile.host)
+        .chars()
+        // tmux reads `.` and `:` in a target as window and pane separators
+        .map(|c| match c {
+            '.' | ':' => '-',
+            c if c.is_whitespace() => '-',
+            c => c,
+        })
+        .collect()
+}
+
+// Session a run on `profile` is opened in, from its session_template.
+pub fn session_name(profile: Option<&HostProfile>, project: &str) -> String {
+    let template = profile.and_then(|p| Some((p, p.session_template.as_deref()?)));
+    match template {
+        Some((profile, template)) if !template.trim().is_empty() => {
+            let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+            expand_session(template, project, &date, profile)
+        }
+        _ => RUN_SESSION.to_string(),
+    }
+}
+
+fn ensure_session(
+    profile: Option<&HostProfile>,
+    session: &str,
+    work_dir: &str,
+) -> Result<(), String> {
+    if tmux(profile, &["has-session", "-t", session])?.code == 0 {
+        return Ok(());
+    }
+    let out = tmux(
+        profile,
+        &["new-session", "-d", "-s", session, "-c", work_dir],
+    )?;
+    if out.code != 0 {
+        return Err(out.stderr);
+    }
+    Ok(())
+}
+
+fn open_run_window(
+    profile: Option<&HostProfile>,
+    session: &str,
+    name: &str,
+    work_dir: &str,
+) -> Result<String, String> {
+    let target = format!("{}:", session);
+    let out = tmux(
+        profile,
+        &[
+            "new-window",
+            "-d",
+            "-P",
+            "-F",
+            "#{window_id}",
+            "-t",
+            &target,
+            "-n",
+            name,
+            "-c",
+            work_dir,
+        ],
+    )?;
+    if out.code != 0 {
+        return Err(out.stderr);
+    }
+    let id = out.stdout.trim().to_string();
+    if id.is_empty() {
+        return Err("tmux did not report the new window id".into());
+    }
+    let _ = tmux(
+        profile,
+        &["set-window-option", "-t", &id, "automatic-rename", "off"],
+    );
+    Ok(id)
+}
+
+// open_run_window and the send-keys after it as one exec on the host.
+fn launch_remote_window(
+    profile: &HostProfile,
+    run: &ARCRun,
+    work_dir: &str,
+    command: &str,
+) -> Result<String, String> {
+    let target = format!("{}:", run.session);
+    let mut batch = RemoteBatch::new();
+    batch
+        .tmux_into(
+            "id",
+            &[
+                "new-window",
+                "-d",
+                "-P",
+                "-F",
+                "#{window_id}",
+                "-t",
+                &target,
+                "-n",
+                &run.name,
+                "-c",
+                work_dir,
+            ],
+        )
+        .tmux_optional(&[
+            "set-window-option",
+            "-t",
+            &var("id"),
+            "automatic-rename",
+            "off",
+        ]);
+    for cmd in build_tmux_send_keys_commands(&var("id"), command, true) {
+        batch.tmux(&cmd.args);
+    }
+    let steps = batch.run(profile)?.check()?;
+    let id = steps[0].stdout.trim().to_string();
+    if id.is_empty() {
+        return Err("tmux did not report the new window id".into());
+    }
+    Ok(id)
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct PaneClassification {
+    status: Option<RunStatus>,
+    last_stdout: Option<String>,
+    last_stderr: Option<String>,
+}
+
+// ARC prints a banner when it starts and a closing line when it exits; an
+// uncaught exception leaves a Python traceback instead of (or after) it.
+fn classify_output(lines: &[String]) -> PaneClassification {
+    let last_index = |needle: &str| lines.iter().rposition(|l| l.contains(needle));
+    let initiated = last_index("ARC execution initiated");
+    let terminated = last_index("ARC execution terminated");
+    let traceback = last_index("Traceback (most recent call last)");
+
+    let last_stdout = lines
+        .iter()
+        .rev()
+        .find(|l| !l.trim().is_empty())
+        .map(|l| l.trim().to_string());
+    let last_stderr = traceback.and_then(|start| {
+        lines[start + 1..]
+            .iter()
+            .find(|l| !l.trim().is_empty() && !l.starts_with(char::is_whitespace))
+            .map(|l| l.trim().to_string())
+    });
+
+    let status = match (initiated, terminated, traceback) {
+        (_, _, Some(tb)) if terminated.is_none_or(|t| tb > t) => Some(RunStatus::Failed),
+        (_, Some(_), _) => Some(RunStatus::Finished),
+        (Some(_), None, None) => Some(RunStatus::Running),
+        _ => None,
+    };
+    PaneClassification {
+        status,
+        last_stdout,
+        last_stderr,
+    }
+}
+
+// A stalled run still has a live process and keeps its slot until it
+// recovers or is stopped.
+pub fn is_active(status: &RunStatus) -> bool {
+    matches!(
+        status,
+        RunStatus::Starting | RunStatus::Running | RunStatus::Stalled
+    )
+}
+
+pub fn capture_window(
+    profile: Option<&HostProfile>,
+    window_id: &str,
+) -> Result<crate::ssh::ExecOut, String> {
+    tmux(
+        profile,
+        &["capture-pane", "-p", "-J", "-t", window_id, "-S", "-200"],
+    )
+}
+
+fn poll_run(run: &ARCRun) {
+    let Some(window_id) = run.window_id.as_deref() else {
+        return;
+    };
+    if STOPPING.lock().unwrap().contains(&run.id) {
+        return;
+    }
+    let profile = run_profile(&run.id);
+    let out = match capture_window(profile.as_ref(), window_id) {
+        Ok(out) => out,
+        Err(_) => return,
+    };
+    let now = chrono::Utc::now().to_rfc3339();
+    let updated = if out.code != 0 {
+        if !out.stderr.to_lowercase().contains("can't find window") {
+            return;
+        }
+        RunRegistry::global().update(&run.id, |r| {
+            r.status = RunStatus::Failed;
+            r.finished_at = Some(now.clone());
+            r.last_stderr = Some("run window no longer exists".into());
+        })
+    } else {
+        let lines: Vec<String> = out.stdout.lines().map(|l| l.to_string()).collect();
+        let found = classify_output(&lines);
+        RunRegistry::global().update(&run.id, |r| {
+            if found.last_stdout.is_some() {
+                r.last_stdout = found.last_stdout.clone();
+            }
+            if found.last_stderr.is_some() {
+                r.last_stderr = found.last_stderr.clone();
+            }
+            if let Some(status) = found.status.clone() {
+                // only the watchdog moves a run out of Stalled while it is alive
+                if r.status == RunStatus::Stalled && status == RunStatus::Running {
+                    return;
+                }
+                if !is_active(&status) && r.finished_at.is_none() {
+                    r.finished_at = Some(now.clone());
+                }
+                r.status = status;
+            }
+        })
+    };
+    if let Some(updated) = updated {
+        emit_status(Some(&run.status), &updated);
+    }
+}
+
+// Batch runs have no pane to read, so their status comes from the scheduler.
+fn poll_batch_run(run: &ARCRun, job_id: &str) {
+    if STOPPING.lock().unwrap().contains(&run.id) {
+        return;
+    }
+    let Some(profile) = run_profile(&run.id) else {
+        return;
+    };
+    let Ok(scheduler) = scheduler::for_profile(&profile) else {
+        return;
+    };
+    let Some(status) = scheduler.status(&profile, job_id) else {
+        return;
+    };
+    // only the watchdog moves a run out of Stalled while it is alive
+    if status == run.status || (run.status == RunStatus::Stalled && is_active(&status)) {
+        return;
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    let updated = RunRegistry::global().update(&run.id, |r| {
+        if !is_active(&status) && r.finished_at.is_none() {
+            r.finished_at = Some(now);
+        }
+        if status == RunStatus::Failed {
+            r.last_stderr = Some(format!("batch job {job_id} failed"));
+        }
+        r.status = status;
+    });
+    if let Some(updated) = updated {
+        emit_status(Some(&run.status), &updated);
+    }
+}
+
+fn refresh_progress(run: &ARCRun) {
+    let Some(log) = find_project_file(&run.work_dir, "arc.log") else {
+        return;
+    };
+    let stamped = run
+        .progress
+        .as_ref()
+        .map(|p| p.phases.as_slice())
+        .unwrap_or_default();
+    let Some(mut found) = progress::refresh(&run.id, &log, stamped) else {
+        return;
+    };
+    found.eta_secs = eta::estimate_for(run, &found);
+    let updated = RunRegistry::global().update(&run.id, |r| r.progress = Some(found.clone()));
+    if updated.is_some() {
+        emit(PROGRESS_EVENT, json!({ "id": run.id, "progress": found }));
+    }
+}
+
+fn stall_timeout(id: &str) -> Duration {
+    launch_config(id)
+        .and_then(|c| c.stall_timeout_minutes)
+        .map(|m| Duration::from_secs(m * 60))
+        .unwrap_or(DEFAULT_STALL_TIMEOUT)
+}
+
+// Flags a running run as Stalled once neither arc.log nor its ESS jobs have
+// moved for the stall timeout, and clears the flag when they move again.
+fn check_stall(id: &str) {
+    // re-read: the poll just before may already have finished the run
+    let Some(run) = RunRegistry::global().get(id) else {
+        return;
+    };
+    if !matches!(run.status, RunStatus::Running | RunStatus::Stalled) {
+        return;
+    }
+    let profile = run_profile(&run.id);
+    let progress = run.progress.clone().unwrap_or_default();
+    let activity = watchdog::Activity {
+        log_size: watchdog::log_size(profile.as_ref(), &run.work_dir),
+        running_jobs: progress.running_jobs,
+        jobs_completed: progress.jobs_completed,
+    };
+    let quiet = watchdog::quiet_for(&run.id, activity);
+    let stalled = quiet >= stall_timeout(&run.id);
+    let next = match (&run.status, stalled) {
+        (RunStatus::Running, true) => RunStatus::Stalled,
+        (RunStatus::Stalled, false) => RunStatus::Running,
+        _ => return,
+    };
+    let Some(updated) = RunRegistry::global().update(&run.id, |r| r.status = next) else {
+        return;
+    };
+    emit_status(Some(&run.status), &updated);
+    if updated.status == RunStatus::Stalled {
+        let last_lines =
+            watchdog::last_log_lines(profile.as_ref(), &run.work_dir, STALL_CONTEXT_LINES);
+        emit(
+            STALLED_EVENT,
+            json!({
+                "id": run.id,
+                "quiet_minutes": quiet.as_secs() / 60,
+                "run": updated,
+                "last_lines": last_lines,
+            }),
+        );
+    }
+}
+
+pub fn attach_app(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+// Polls every Starting/Running run for the lifetime of the app and starts
+// queued runs as slots free up.
+pub fn start_monitor(app: AppHandle) {
+    if MONITOR_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    attach_app(app);
+    thread::spawn(move || loop {
+        let all = RunRegistry::global().list();
+        let active: Vec<ARCRun> = all
+            .iter()
+            .filter(|r| is_active(&r.status))
+            .cloned()
+            .collect();
+        // a stat per host is far cheaper than capturing every remote pane
+        let changed = filepoll::poll(&active);
+        for run in all {
+            if is_active(&run.status) {
+                match run.batch_job_id.as_deref() {
+                    Some(job_id) => poll_batch_run(&run, job_id),
+                    None if run.host.is_none()
+                        || filepoll::capture_due(&run.id, changed.contains(&run.id)) =>
+                    {
+                        poll_run(&run)
+                    }
+                    None => {}
+                }
+                if let Some(window_id) = run.window_id.as_deref() {
+                    resources::sample(&run.id, run_profile(&run.id).as_ref(), window_id);
+                }
+                // arc.log of a remote run is not on this disk
+                if run.host.is_none() {
+                    refresh_progress(&run);
+                }
+                check_stall(&run.id);
+            } else {
+                progress::forget(&run.id);
+                watchdog::forget(&run.id);
+                filepoll::forget(&run.id);
+            }
+        }
+        schedule();
+        recovery::save();
+        thread::sleep(MONITOR_INTERVAL);
+    });
+}
+
+struct QueuedRun {
+    id: String,
+    priority: i32,
+    config: AppConfig,
+    pooled: bool, // placed on a host from the pool when it starts
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueEntry {
+    pub id: String,
+    pub name: String,
+    pub priority: i32,
+    pub position: usize,
+    pub pooled: bool,
+    pub blocked_by: Option<String>, // why it has not started yet
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HostRuns {
+    pub host: String,
+    pub running: Vec<String>, // ids of the active runs on the host
+    pub cap: Option<u32>,     // pool hosts only
+    pub free_slots: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulerState {
+    pub active: usize,
+    pub concurrency_cap: Option<u32>, // of the first queued run not placed by the pool
+    pub free_slots: Option<usize>,    // under that cap
+    pub hosts: Vec<HostRuns>,
+    pub queued: Vec<QueueEntry>,
+}
+
+// Keeps the queue ordered by descending priority; equal priorities stay
+// first-come first-served, and running runs are never preempted.
+fn enqueue(queue: &mut VecDeque<QueuedRun>, entry: QueuedRun) {
+    let at = queue
+        .iter()
+        .position(|q| q.priority < entry.priority)
+        .unwrap_or(queue.len());
+    queue.insert(at, entry);
+}
+
+pub fn create_work_dir(profile: Option<&HostProfile>, work_dir: &Path) -> Result<(), String> {
+    match profile {
+        Some(profile) => {
+            let dir = work_dir.to_string_lossy();
+            let out = remote_sh(profile, format!("mkdir -p {}", shell_escape::escape(dir)))?;
+            if out.code != 0 {
+                return Err(format!("create work dir: {}", out.stderr.trim()));
+            }
+            Ok(())
+        }
+        None => std::fs::create_dir_all(work_dir)
+            .map_err(|e| format!("create work dir {}: {e}", work_dir.display())),
+    }
+}
+
+enum Launched {
+    Window(String),   // tmux window id
+    BatchJob(String), // scheduler job id
+}
+
+fn launch(run: &ARCRun, config: &AppConfig) -> Result<Launched, String> {
+    let profile = run_profile(&run.id);
+    let profile = profile.as_ref();
+    create_work_dir(profile, &run.work_dir)?;
+    quota::check_launch(profile, &run.work_dir, config)?;
+    if let Some(profile) = profile {
+        diskusage::check_free_space(profile, &run.work_dir, config)?;
+    }
+    if let (Some(batch), Some(profile)) = (&config.batch, profile) {
+        let command = build_arc_command(config, &run.input_path, true);
+        let command = with_env_preset(Some(profile), command);
+        let scheduler = scheduler::for_profile(profile)?;
+        let job_id = scheduler.submit(profile, batch, &run.name, &run.work_dir, &command)?;
+        return Ok(Launched::BatchJob(job_id));
+    }
+    let work_dir = run.work_dir.to_string_lossy().to_string();
+
+    ensure_session(profile, &run.session, &work_dir)?;
+    let command = build_arc_command(config, &run.input_path, profile.is_some());
+    let command = with_env_preset(profile, command);
+    if let Some(profile) = profile {
+        let window_id = launch_remote_window(profile, run, &work_dir, &command)?;
+        return Ok(Launched::Window(window_id));
+    }
+
+    let window_id = open_run_window(profile, &run.session, &run.name, &work_dir)?;
+    for cmd in build_tmux_send_keys_commands(&window_id, &command, true) {
+        let out = tmux_exec(profile, &cmd.args)?;
+        if out.code != 0 {
+            return Err(out.stderr);
+        }
+    }
+    Ok(Launched::Window(window_id))
+}
+
+// Probing the environment takes a few seconds of python start-up, so it
+// runs beside the launch rather than holding up the queue.
+fn record_versions(id: String, config: AppConfig) {
+    thread::spawn(move || {
+        let found = match run_profile(&id) {
+            Some(profile) => versions::detect_remote(
+                &profile,
+                &config.python_path,
+                config
+                    .remote_arc_path
+                    .as_deref()
+                    .unwrap_or(&config.arc_path),
+                conda_env(&config, true),
+            ),
+            None => versions::detect_local(
+                &config.python_path,
+                &config.arc_path,
+                conda_env(&config, false),
+            ),
+        };
+        if let Ok(found) = found {
+            RunRegistry::global().update(&id, |r| r.versions = Some(found));
+        }
+    });
+}
+
+fn free_slots(active: usize, cap: u32) -> usize {
+    (cap.max(1) as usize).saturating_sub(active)
+}
+
+// Binds a pooled run to the host it was placed on, uploading its input there.
+fn bind_host(run: ARCRun, host: hostpool::PoolHost) -> Result<ARCRun, String> {
+    let Some(profile) = host.profile else {
+        return Ok(run);
+    };
+    let input_path = stage_remote_input(&profile, &run.input_path, &run.work_dir)?;
+    let label = host_label(&profile);
+    let session = session_name(Some(&profile), &run.name);
+    PROFILES.lock().unwrap().insert(run.id.clone(), profile);
+    Ok(RunRegistry::global()
+        .update(&run.id, |r| {
+            r.host = Some(label);
+            r.input_path = input_path;
+            r.session = session;
+        })
+        .unwrap_or(run))
+}
+
+// Starts queued runs in queue order while the concurrency cap allows it, then
+// tells the frontend where the remaining ones stand. Pooled runs are bound by
+// their hosts' caps instead of the global one. A call made while a pass is
+// running leaves the work to that pass instead of waiting for it.
+pub fn schedule() {
+    RESCHEDULE.store(true, Ordering::SeqCst);
+    loop {
+        let Ok(pass) = SCHEDULING.try_lock() else {
+            return;
+        };
+        while RESCHEDULE.swap(false, Ordering::SeqCst) {
+            schedule_pass();
+        }
+        drop(pass);
+        // a request that came in between the last pass and the unlock
+        if !RESCHEDULE.load(Ordering::SeqCst) {
+            return;
+        }
+    }
+}
+
+// The queue is only locked to look at its head and to pop it; placing on a
+// host, launching and the status events happen without it, so a slow remote
+// launch does not hold up enqueues, stops or the scheduler state.
+fn schedule_pass() {
+    loop {
+        let head = QUEUE
+            .lock()
+            .unwrap()
+            .front()
+            .map(|q| (q.id.clone(), q.pooled, q.config.concurrency_cap));
+        let Some((id, pooled, cap)) = head else {
+            break;
+        };
+        let placed = if pooled {
+            match hostpool::place() {
+                Some(host) => Some(host),
+                None => break,
+            }
+        } else {
+            let active = cap_usage(&id, &RunRegistry::global().list());
+            if free_slots(active, cap) == 0 {
+                break;
+            }
+            None
+        };
+        let next = {
+            let mut queue = QUEUE.lock().unwrap();
+            // stopped or overtaken while the host was picked: look again
+            if queue.front().map(|q| q.id.as_str()) != Some(id.as_str()) {
+                continue;
+            }
+            queue.pop_front().unwrap()
+        };
+        start_queued(next, placed);
+    }
+    let positions: Vec<serde_json::Value> = QUEUE
+        .lock()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .map(|(position, queued)| {
+            json!({ "id": queued.id, "position": position + 1, "priority": queued.priority })
+        })
+        .collect();
+    for position in positions {
+        emit(QUEUE_EVENT, position);
+    }
+    emit_scheduler_state();
+}
+
+fn start_queued(next: QueuedRun, placed: Option<hostpool::PoolHost>) {
+    let Some(run) = RunRegistry::global().get(&next.id) else {
+        return;
+    };
+    let prev = run.status.clone();
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut config = next.config.clone();
+    let run = match placed {
+        Some(host) => {
+            // a pooled run only learns its host's overrides now
+            config = resolve_config(config, host.profile.as_ref());
+            CONFIGS
+                .lock()
+                .unwrap()
+                .insert(next.id.clone(), config.clone());
+            bind_host(run, host)
+        }
+        None => Ok(run),
+    };
+    let launched = run.and_then(|run| launch(&run, &config).map(|l| (run, l)));
+    // stop_run may have cancelled the run while it launched; the record is
+    // only touched while it is still Queued
+    let mut cancelled = false;
+    let updated = match launched {
+        Ok((run, launched)) => {
+            let updated = RunRegistry::global().update(&run.id, |r| {
+                if r.status != RunStatus::Queued {
+                    cancelled = true;
+                    return;
+                }
+                match &launched {
+                    Launched::Window(window_id) => r.window_id = Some(window_id.clone()),
+                    Launched::BatchJob(job_id) => r.batch_job_id = Some(job_id.clone()),
+                }
+                r.started_at = Some(now);
+                r.status = RunStatus::Starting;
+            });
+            if cancelled {
+                take_down(&run, launched);
+                return;
+            }
+            record_versions(run.id.clone(), config);
+            updated
+        }
+        Err(e) => RunRegistry::global().update(&next.id, |r| {
+            if r.status != RunStatus::Queued {
+                cancelled = true;
+                return;
+            }
+            r.finished_at = Some(now);
+            r.status = RunStatus::Failed;
+            r.last_stderr = Some(e);
+        }),
+    };
+    if let Some(updated) = updated.filter(|_| !cancelled) {
+        emit_status(Some(&prev), &updated);
+    }
+}
+
+// Kills what start_queued launched for a run that was cancelled meanwhile.
+fn take_down(run: &ARCRun, launched: Launched) {
+    let profile = run_profile(&run.id);
+    match launched {
+        Launched::Window(window_id) => {
+            let _ = tmux(profile.as_ref(), &["kill-window", "-t", &window_id]);
+        }
+        Launched::BatchJob(job_id) => {
+            if let Some(profile) = profile {
+                if let Ok(scheduler) = scheduler::for_profile(&profile) {
+                    let _ = scheduler.cancel(&profile, &job_id, None);
+                }
+            }
+        }
+    }
+}
+
+// The queue is strictly ordered, so only its head waits on capacity; every
+// other run waits on the head.
+fn blocked_by(
+    position: usize,
+    head: &str,
+    pooled: bool,
+    active: usize,
+    cap: u32,
+    pool: &[hostpool::PoolHostState],
+) -> Option<String> {
+    if position > 0 {
+        return Some(format!("queued behind {head}"));
+    }
+    if !pooled {
+        return (free_slots(active, cap) == 0)
+            .then(|| format!("concurrency cap reached ({active}/{cap})"));
+    }
+    let enabled: Vec<_> = pool.iter().filter(|h| h.enabled).collect();
+    Some(if enabled.is_empty() {
+        "no enabled pool hosts".into()
+    } else if enabled
+        .iter()
+        .all(|h| h.active >= h.max_concurrent.max(1) as usize)
+    {
+        "every pool host is at its cap".into()
+    } else {
+        "pool hosts with free slots are overloaded or unreachable".into()
+    })
+}
+
+pub fn scheduler_state() -> SchedulerState {
+    let runs = RunRegistry::global().list();
+    let active: Vec<&ARCRun> = runs.iter().filter(|r| is_active(&r.status)).collect();
+    let pool = hostpool::pool_state();
+    let queue = QUEUE.lock().unwrap();
+    let capped = queue
+        .iter()
+        .find(|q| !q.pooled)
+        .map(|q| (q.config.concurrency_cap, cap_usage(&q.id, &runs)));
+    let name_of = |id: &str| {
+        RunRegistry::global()
+            .get(id)
+            .map(|r| r.name)
+            .unwrap_or_default()
+    };
+    let head = queue.front().map(|q| name_of(&q.id)).unwrap_or_default();
+    let queued = queue
+        .iter()
+        .enumerate()
+        .map(|(position, queued)| QueueEntry {
+            id: queued.id.clone(),
+            name: name_of(&queued.id),
+            priority: queued.priority,
+            position: position + 1,
+            pooled: queued.pooled,
+            blocked_by: blocked_by(
+                position,
+                &head,
+                queued.pooled,
+                cap_usage(&queued.id, &runs),
+                queued.config.concurrency_cap,
+                &pool,
+            ),
+        })
+        .collect();
+    drop(queue);
+
+    let mut hosts: Vec<HostRuns> = pool
+        .iter()
+        .map(|h| HostRuns {
+            host: h.host.clone(),
+            running: Vec::new(),
+            cap: Some(h.max_concurrent),
+            free_slots: Some(free_slots(h.active, h.max_concurrent)),
+        })
+        .collect();
+    for run in &active {
+        let label = run.host.as_deref().unwrap_or("local");
+        match hosts.iter_mut().find(|h| h.host == label) {
+            Some(host) => host.running.push(run.id.clone()),
+            None => hosts.push(HostRuns {
+                host: label.to_string(),
+                running: vec![run.id.clone()],
+                cap: None,
+                free_slots: None,
+            }),
+        }
+    }
+    SchedulerState {
+        active: active.len(),
+        concurrency_cap: capped.map(|(cap, _)| cap),
+        free_slots: capped.map(|(cap, used)| free_slots(used, cap)),
+        hosts,
+        queued,
+    }
+}
+
+fn emit_scheduler_state() {
+    let Ok(state) = serde_json::to_value(scheduler_state()) else {
+        return;
+    };
+    let mut last = LAST_SCHEDULER_STATE.lock().unwrap();
+    if last.as_ref() != Some(&state) {
+        emit(SCHEDULER_EVENT, state.clone());
+        *last = Some(state);
+    }
+}
+
+pub fn set_priority(id: String, priority: i32) -> Result<(), String> {
+    {
+        let mut queue = QUEUE.lock().unwrap();
+        let at = queue
+            .iter()
+            .position(|q| q.id == id)
+            .ok_or_else(|| format!("run is not queued: {id}"))?;
+        let mut entry = queue.remove(at).unwrap();
+        entry.priority = priority;
+        enqueue(&mut queue, entry);
+    }
+    recovery::touch();
+    schedule();
+    Ok(())
+}
+
+pub fn queue_state() -> Vec<QueueEntry> {
+    scheduler_state().queued
+}
+
+pub fn saved_state() -> recovery::SavedState {
+    recovery::SavedState {
+        runs: RunRegistry::global().list(),
+        profiles: PROFILES.lock().unwrap().clone(),
+        configs: CONFIGS.lock().unwrap().clone(),
+        queue: QUEUE
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|q| recovery::SavedQueueEntry {
+                id: q.id.clone(),
+                priority: q.priority,
+                pooled: q.pooled,
+            })
+            .collect(),
+    }
+}
+
+// Loads what the last session saved; queued runs go back into the queue in
+// their old order.
+pub fn restore(state: recovery::SavedState) {
+    for run in state.runs {
+        RunRegistry::global().insert(run);
+    }
+    PROFILES.lock().unwrap().extend(state.profiles);
+    CONFIGS.lock().unwrap().extend(state.configs.clone());
+    let mut queue = QUEUE.lock().unwrap();
+    for entry in state.queue {
+        let Some(config) = state.configs.get(&entry.id).cloned() else {
+            continue;
+        };
+        if RunRegistry::global()
+            .get(&entry.id)
+            .is_some_and(|r| r.status == RunStatus::Queued)
+        {
+            queue.push_back(QueuedRun {
+                id: entry.id,
+                priority: entry.priority,
+                config,
+                pooled: entry.pooled,
+            });
+        }
+    }
+}
+
+// A local input file is copied into the remote work dir along with the
+// files it references; a path that only exists on the host is used as is.
+fn stage_remote_input(
+    profile: &HostProfile,
+    input: &Path,
+    work_dir: &Path,
+) -> Result<PathBuf, String> {
+    if input.is_file() {
+        return inputs::upload(profile, input, work_dir);
+    }
+    let out = remote_sh(
+        profile,
+        format!("test -f {}", shell_escape::escape(input.to_string_lossy())),
+    )?;
+    if out.code != 0 {
+        return Err(format!(
+            "input file not found locally or on {}: {}",
+            host_label(profile),
+            input.display()
+        ));
+    }
+    Ok(input.to_path_buf())
+}
+
+pub fn start_run(
+    config: AppConfig,
+    input_path: String,
+    name: String,
+    work_dir: Option<String>,
+    priority: Option<i32>,
+    profile: Option<HostProfile>,
+    auto_host: bool,
+) -> Result<ARCRun, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("run name must not be empty".into());
+    }
+    let python_env = profile
+        .as_ref()
+        .and_then(|p| p.overrides.python_env.as_deref())
+        .or(config.python_env.as_deref());
+    pyenvs::check_known(python_env)?;
+    let config = resolve_config(config, profile.as_ref());
+    let work_dir = match work_dir.filter(|w| !w.trim().is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(&config.default_work_dir).join(&name),
+    };
+    let input_path = PathBuf::from(input_path);
+    if auto_host && profile.is_some() {
+        return Err("a run either has a host or is placed from the pool".into());
+    }
+    if auto_host && !hostpool::has_hosts() {
+        return Err("no hosts are enabled in the pool".into());
+    }
+    // a pooled run's input is uploaded once it has a host
+    let input_path = match &profile {
+        Some(profile) => stage_remote_input(profile, &input_path, &work_dir)?,
+        None if input_path.is_file() => input_path,
+        None => return Err(format!("input file not found: {}", input_path.display())),
+    };
+
+    let session = session_name(profile.as_ref(), &name);
+    let run = ARCRun {
+        queued_at: Some(chrono::Utc::now().to_rfc3339()),
+        host: profile.as_ref().map(host_label),
+        ..ARCRun::new(name, session, input_path, work_dir, RunStatus::Queued)
+    };
+    Ok(submit(
+        run,
+        config,
+        priority.unwrap_or(0),
+        profile,
+        auto_host,
+    ))
+}
+
+fn submit(
+    run: ARCRun,
+    config: AppConfig,
+    priority: i32,
+    profile: Option<HostProfile>,
+    pooled: bool,
+) -> ARCRun {
+    if let Some(profile) = profile {
+        PROFILES.lock().unwrap().insert(run.id.clone(), profile);
+    }
+    register(&run);
+    CONFIGS
+        .lock()
+        .unwrap()
+        .insert(run.id.clone(), config.clone());
+    enqueue(
+        &mut QUEUE.lock().unwrap(),
+        QueuedRun {
+            id: run.id.clone(),
+            priority,
+            config,
+            pooled,
+        },
+    );
+    schedule();
+    RunRegistry::global().get(&run.id).unwrap_or(run)
+}
+
+// ARC keeps arc.log and restart.yml in the project directory, which is
+// either the run's work_dir itself or a folder directly below it.
+pub fn find_project_file(work_dir: &Path, file_name: &str) -> Option<PathBuf> {
+    let direct = work_dir.join(file_name);
+    if direct.is_file() {
+        return Some(direct);
+    }
+    std::fs::read_dir(work_dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path().join(file_name))
+        .find(|p| p.is_file())
+}
+
+pub fn find_restart_file(work_dir: &Path) -> Option<PathBuf> {
+    find_project_file(work_dir, "restart.yml")
+}
+
+fn find_remote_project_file(
+    profile: &HostProfile,
+    work_dir: &Path,
+    file_name: &str,
+) -> Option<PathBuf> {
+    let dir = shell_escape::escape(work_dir.to_string_lossy()).to_string();
+    let file = shell_escape::escape(Cow::from(file_name)).to_string();
+    let out = remote_sh(
+        profile,
+        format!("ls -1d {dir}/{file} {dir}/*/{file} 2>/dev/null | head -n 1"),
+    )
+    .ok()?;
+    let found = out.stdout.trim();
+    (!found.is_empty()).then(|| PathBuf::from(found))
+}
+
+pub fn locate_project_file(
+    profile: Option<&HostProfile>,
+    work_dir: &Path,
+    file_name: &str,
+) -> Option<PathBuf> {
+    match profile {
+        Some(profile) => find_remote_project_file(profile, work_dir, file_name),
+        None => find_project_file(work_dir, file_name),
+    }
+}
+
+fn restart_written_since(
+    profile: Option<&HostProfile>,
+    work_dir: &Path,
+    since: SystemTime,
+) -> bool {
+    let Some(profile) = profile else {
+        return find_restart_file(work_dir)
+            .and_then(|p| std::fs::metadata(p).ok())
+            .and_then(|m| m.modified().ok())
+            .is_some_and(|modified| modified >= since);
+    };
+    let epoch = since
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let dir = shell_escape::escape(work_dir.to_string_lossy());
+    remote_sh(
+        profile,
+        format!("find {dir} -maxdepth 2 -name restart.yml -newermt @{epoch} | head -n 1"),
+    )
+    .is_ok_and(|out| !out.stdout.trim().is_empty())
+}
+
+fn python_still_running(profile: Option<&HostProfile>, window_id: &str) -> bool {
+    match tmux(
+        profile,
+        &[
+            "display-message",
+            "-p",
+            "-t",
+            window_id,
+            "#{pane_current_command}",
+        ],
+    ) {
+        Ok(out) if out.code == 0 => out.stdout.trim().starts_with("python"),
+        _ => false,
+    }
+}
+
+// Cancels a run only while it is still Queued, in one registry update so
+// start_queued cannot mark it Starting in between.
+fn cancel_launching(id: &str) -> bool {
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut cancelled = false;
+    let updated = RunRegistry::global().update(id, |r| {
+        if r.status == RunStatus::Queued {
+            r.status = RunStatus::Cancelled;
+            r.finished_at = Some(now);
+            cancelled = true;
+        }
+    });
+    if let Some(updated) = updated.filter(|_| cancelled) {
+        emit_status(Some(&RunStatus::Queued), &updated);
+    }
+    cancelled
+}
+
+fn finish_stop(id: &str, note: Option<&str>) {
+    let prev = RunRegistry::global().get(id).map(|r| r.status);
+    let now = chrono::Utc::now().to_rfc3339();
+    let updated = RunRegistry::global().update(id, |r| {
+        r.status = RunStatus::Cancelled;
+        r.finished_at = Some(now);
+        if let Some(note) = note {
+            r.last_stdout = Some(note.to_string());
+        }
+    });
+    STOPPING.lock().unwrap().remove(id);
+    if let Some(updated) = updated {
+        emit_status(prev.as_ref(), &updated);
+    }
+    schedule();
+}
+
+// "graceful" interrupts ARC so it can save restart.yml on the way out and
+// only kills the window if python is still alive after GRACEFUL_TIMEOUT;
+// "kill" skips straight to killing the window and its process group.
+pub fn stop_run(id: String, mode: String) -> Result<(), String> {
+    let graceful = match mode.as_str() {
+        "graceful" => true,
+        "kill" => false,
+        other => return Err(format!("unknown stop mode: {other}")),
+    };
+    let run = RunRegistry::global()
+        .get(&id)
+        .ok_or_else(|| format!("unknown run: {id}"))?;
+
+    if run.status == RunStatus::Queued {
+        let dequeued = {
+            let mut queue = QUEUE.lock().unwrap();
+            let before = queue.len();
+            queue.retain(|q| q.id != id);
+            queue.len() < before
+        };
+        if dequeued {
+            finish_stop(&id, None);
+            return Ok(());
+        }
+        // a scheduling pass popped it first: while it is still launching,
+        // start_queued sees the cancel and takes down what it launched;
+        // once launched, it is stopped like any other run
+        if cancel_launching(&id) {
+            return Ok(());
+        }
+        return stop_run(id, mode);
+    }
+    if !is_active(&run.status) {
+        return Err("run is not active".into());
+    }
+    if let Some(job_id) = run.batch_job_id.clone() {
+        return stop_batch_run(run, job_id, graceful);
+    }
+    let window_id = run
+        .window_id
+        .clone()
+        .ok_or_else(|| "run has no window".to_string())?;
+    if !STOPPING.lock().unwrap().insert(id.clone()) {
+        return Err("run is already stopping".into());
+    }
+    let profile = run_profile(&id);
+
+    if !graceful {
+        let out = tmux(profile.as_ref(), &["kill-window", "-t", &window_id])?;
+        if out.code != 0 {
+            STOPPING.lock().unwrap().remove(&id);
+            return Err(out.stderr);
+        }
+        finish_stop(&id, None);
+        return Ok(());
+    }
+
+    let requested = SystemTime::now();
+    let out = tmux(profile.as_ref(), &["send-keys", "-t", &window_id, "C-c"])?;
+    if out.code != 0 {
+        STOPPING.lock().unwrap().remove(&id);
+        return Err(out.stderr);
+    }
+    thread::spawn(move || {
+        let deadline = Instant::now() + GRACEFUL_TIMEOUT;
+        let mut exited = false;
+        while Instant::now() < deadline {
+            thread::sleep(Duration::from_secs(2));
+            if !python_still_running(profile.as_ref(), &window_id) {
+                exited = true;
+                break;
+            }
+        }
+        if !exited {
+            let _ = tmux(profile.as_ref(), &["kill-window", "-t", &window_id]);
+        }
+        let note = if restart_written_since(profile.as_ref(), &run.work_dir, requested) {
+            "stopped; restart.yml saved"
+        } else {
+            "stopped; restart.yml not updated"
+        };
+        finish_stop(&run.id, Some(note));
+    });
+    Ok(())
+}
+
+fn stop_batch_run(run: ARCRun, job_id: String, graceful: bool) -> Result<(), String> {
+    let profile = run_profile(&run.id).ok_or_else(|| "batch run has no host".to_string())?;
+    let scheduler = scheduler::for_profile(&profile)?;
+    if !STOPPING.lock().unwrap().insert(run.id.clone()) {
+        return Err("run is already stopping".into());
+    }
+    let signal = graceful.then_some("INT");
+    if let Err(e) = scheduler.cancel(&profile, &job_id, signal) {
+        STOPPING.lock().unwrap().remove(&run.id);
+        return Err(e);
+    }
+    if !graceful {
+        finish_stop(&run.id, None);
+        return Ok(());
+    }
+    let requested = SystemTime::now();
+    thread::spawn(move || {
+        let deadline = Instant::now() + GRACEFUL_TIMEOUT;
+        while Instant::now() < deadline && scheduler.is_queued(&profile, &job_id) {
+            thread::sleep(Duration::from_secs(5));
+        }
+        if scheduler.is_queued(&profile, &job_id) {
+            let _ = scheduler.cancel(&profile, &job_id, None);
+        }
+        let note = if restart_written_since(Some(&profile), &run.work_dir, requested) {
+            "stopped; restart.yml saved"
+        } else {
+            "stopped; restart.yml not updated"
+        };
+        finish_stop(&run.id, Some(note));
+    });
+    Ok(())
+}
+
+pub fn launch_config(id: &str) -> Option<AppConfig> {
+    CONFIGS.lock().unwrap().get(id).cloned()
+}
+
+// Queues `python ARC.py restart.yml` in the project directory as a new run
+// that points back at the one it continues.
+pub fn restart_run(id: String) -> Result<ARCRun, String> {
+    let original = RunRegistry::global()
+        .get(&id)
+        .ok_or_else(|| format!("unknown run: {id}"))?;
+    if original.status == RunStatus::Queued || is_active(&original.status) {
+        return Err("run is still queued or active".into());
+    }
+    let profile = run_profile(&id);
+    let restart = locate_project_file(profile.as_ref(), &original.work_dir, "restart.yml")
+        .ok_or_else(|| format!("no restart.yml found under {}", original.work_dir.display()))?;
+    let config =
+        launch_config(&id).ok_or_else(|| "launch config for this run is unknown".to_string())?;
+    let work_dir = restart
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| original.work_dir.clone());
+
+    let run = ARCRun {
+        restarted_from: Some(original.id.clone()),
+        queued_at: Some(chrono::Utc::now().to_rfc3339()),
+        host: original.host.clone(),
+        tags: original.tags.clone(),
+        ..ARCRun::new(
+            format!("{}-restart", original.name),
+            original.session.clone(),
+            restart,
+            work_dir,
+            RunStatus::Queued,
+        )
+    };
+    Ok(submit(run, config, 0, profile, false))
+}
+
+// Binds a window the user started ARC in by hand to a new run record; the
+// monitor then treats it like any app-launched run. With a profile, the
+// window and work dir are looked up on that host.
+pub fn adopt_run(
+    profile: Option<HostProfile>,
+    session: String,
+    window_id: String,
+    name: String,
+    work_dir: String,
+) -> Result<ARCRun, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("run name must not be empty".into());
+    }
+    let work_dir = PathBuf::from(work_dir);
+    let found_dir = match &profile {
+        Some(profile) => {
+            let dir = shell_escape::escape(work_dir.to_string_lossy());
+            remote_sh(profile, format!("test -d {dir}"))?.code == 0
+        }
+        None => work_dir.is_dir(),
+    };
+    if !found_dir {
+        return Err(format!("work dir not found: {}", work_dir.display()));
+    }
+    let host = profile.as_ref().map(host_label);
+    let owner = tmux(
+        profile.as_ref(),
+        &["display-message", "-p", "-t", &window_id, "#{session_name}"],
+    )?;
+    if owner.code != 0 {
+        return Err(owner.stderr.trim().to_string());
+    }
+    if owner.stdout.trim() != session {
+        return Err(format!("window {window_id} is not in session {session}"));
+    }
+    let taken = RunRegistry::global().list().into_iter().any(|r| {
+        r.host == host
+            && r.session == session
+            && r.window_id.as_deref() == Some(window_id.as_str())
+            && (r.status == RunStatus::Queued || is_active(&r.status))
+    });
+    if taken {
+        return Err(format!("window {window_id} already belongs to a run"));
+    }
+
+    let out = capture_window(profile.as_ref(), &window_id)?;
+    let lines: Vec<String> = out.stdout.lines().map(|l| l.to_string()).collect();
+    let found = classify_output(&lines);
+    // no banner yet usually means ARC is still loading, so keep watching it
+    let status = found.status.unwrap_or(RunStatus::Running);
+    let now = chrono::Utc::now().to_rfc3339();
+    let input_path =
+        locate_project_file(profile.as_ref(), &work_dir, "input.yml").unwrap_or_default();
+    let run = ARCRun {
+        window_id: Some(window_id),
+        finished_at: (!is_active(&status)).then(|| now.clone()),
+        started_at: Some(now),
+        last_stdout: found.last_stdout,
+        last_stderr: found.last_stderr,
+        host,
+        ..ARCRun::new(name, session, input_path, work_dir, status)
+    };
+    if let Some(profile) = profile {
+        PROFILES.lock().unwrap().insert(run.id.clone(), profile);
+    }
+    register(&run);
+    Ok(run)
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DiscoveredRun {
+    pub session: String,
+    pub window_id: String,
+    pub window_name: String,
+    pub pane_id: String,
+    pub command: String,
+    pub project: String, // from ARC's "Starting project" line, else the window name
+    pub work_dir: String, // the pane's current path
+    pub status: Option<RunStatus>,
+    pub managed: bool, // already bound to a run in the registry
+}
+
+const PANE_FORMAT: &str = "#{session_name}|#{window_id}|#{window_name}|#{pane_id}|#{pane_current_command}|#{pane_current_path}";
+
+fn parse_pane_list(stdout: &str) -> Vec<DiscoveredRun> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(6, '|');
+            let session = parts.next()?.to_string();
+            let window_id = parts.next()?.to_string();
+            let window_name = parts.next()?.to_string();
+            let pane_id = parts.next()?.to_string();
+            let command = parts.next()?.to_string();
+            let work_dir = parts.next()?.to_string();
+            Some(DiscoveredRun {
+                session,
+                window_id,
+                project: window_name.clone(),
+                window_name,
+                pane_id,
+                command,
+                work_dir,
+                status: None,
+                managed: false,
+            })
+        })
+        .collect()
+}
+
+// A pane looks like ARC when python is running ARC.py in it, or when its
+// scrollback still shows ARC's banners from a run that already exited.
+fn looks_like_arc(command: &str, lines: &[String]) -> bool {
+    let banner = lines
+        .iter()
+        .any(|l| l.contains("ARC execution initiated") || l.contains("Starting project"));
+    let python = command.starts_with("python") && lines.iter().any(|l| l.contains("ARC.py"));
+    banner || python
+}
+
+pub fn discover_runs(profile: Option<HostProfile>) -> Result<Vec<DiscoveredRun>, String> {
+    let profile = profile.as_ref();
+    let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+    let out = tmux_exec(profile, &args(&["list-panes", "-a", "-F", PANE_FORMAT]))?;
+    if out.code != 0 {
+        // no server running means there is simply nothing to discover
+        if errors::no_server(&out.stderr) {
+            return Ok(Vec::new());
+        }
+        return Err(out.stderr.trim().to_string());
+    }
+    let host = profile.map(host_label);
+    let known = RunRegistry::global().list();
+    let mut found = Vec::new();
+    for mut pane in parse_pane_list(&out.stdout) {
+        let capture = tmux_exec(
+            profile,
+            &args(&[
+                "capture-pane",
+                "-p",
+                "-J",
+                "-t",
+                &pane.pane_id,
+                "-S",
+                "-400",
+            ]),
+        )?;
+        if capture.code != 0 {
+            continue;
+        }
+        let lines: Vec<String> = capture.stdout.lines().map(|l| l.to_string()).collect();
+        if !looks_like_arc(&pane.command, &lines) {
+            continue;
+        }
+        if let Some(c) = lines.iter().rev().find_map(|l| PROJECT_LINE.captures(l)) {
+            pane.project = c[1].to_string();
+        }
+        pane.status = classify_output(&lines).status;
+        pane.managed = known.iter().any(|r| {
+            r.host == host
+                && r.session == pane.session
+                && r.window_id.as_deref() == Some(pane.window_id.as_str())
+        });
+        found.push(pane);
+    }
+    Ok(found)
+}
+
+pub fn start_log_stream(app: AppHandle, id: String) -> Result<(), String> {
+    let run = RunRegistry::global()
+        .get(&id)
+        .ok_or_else(|| format!("unknown run: {id}"))?;
+    let profile = run_profile(&id);
+    let log = locate_project_file(profile.as_ref(), &run.work_dir, "arc.log")
+        .ok_or_else(|| format!("no arc.log under {} yet", run.work_dir.display()))?;
+    match profile {
+        Some(profile) => logstream::LogStreamManager::global().start_remote(app, id, profile, log),
+        None => logstream::LogStreamManager::global().start(app, id, log),
+    }
+}
+
+pub fn stop_log_stream(id: String) -> Result<(), String> {
+    logstream::LogStreamManager::global().stop(&id)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MultiplexReport {
+    pub streaming: Vec<String>,
+    pub errors: Vec<String>, // "<id>: <reason>" for runs that could not be followed
+}
+
+// Replaces the multiplexed stream with one following `ids`; runs without an
+// arc.log yet are reported rather than failing the rest.
+pub fn start_log_multiplex(app: AppHandle, ids: Vec<String>) -> MultiplexReport {
+    let manager = logstream::LogStreamManager::global();
+    manager.stop_multiplexed();
+    let mut report = MultiplexReport::default();
+    for id in ids {
+        let started = get_run(id.clone()).and_then(|run| {
+            let profile = run_profile(&id);
+            let log = locate_project_file(profile.as_ref(), &run.work_dir, "arc.log")
+                .ok_or_else(|| format!("no arc.log under {} yet", run.work_dir.display()))?;
+            manager.start_multiplexed(app.clone(), id.clone(), profile, log)
+        });
+        match started {
+            Ok(()) => report.streaming.push(id),
+            Err(e) => report.errors.push(format!("{id}: {e}")),
+        }
+    }
+    report
+}
+
+pub fn list_runs() -> Vec<ARCRun> {
+    RunRegistry::global().list()
+}
+
+pub fn get_run(id: String) -> Result<ARCRun, String> {
+    RunRegistry::global()
+        .get(&id)
+        .ok_or_else(|| format!("unknown run: {id}"))
+}
+
+pub fn add_tag(id: String, tag: String) -> Result<ARCRun, String> {
+    let tag = tag.trim().to_string();
+    if tag.is_empty() {
+        return Err("tag must not be empty".into());
+    }
+    RunRegistry::global()
+        .update(&id, |run| {
+            if !run.tags.contains(&tag) {
+                run.tags.push(tag);
+            }
+        })
+        .ok_or_else(|| format!("unknown run: {id}"))
+}
+
+pub fn remove_tag(id: String, tag: String) -> Result<ARCRun, String> {
+    let tag = tag.trim();
+    RunRegistry::global()
+        .update(&id, |run| run.tags.retain(|t| t != tag))
+        .ok_or_else(|| format!("unknown run: {id}"))
+}
+
+// Notes stay in time order, so one stamped in the past lands where it
+// belongs rather than at the end.
+pub fn annotate(id: String, text: String, timestamp: Option<String>) -> Result<ARCRun, String> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("note must not be empty".into());
+    }
+    let at = match timestamp {
+        Some(ts) => chrono::DateTime::parse_from_rfc3339(&ts)
+            .map_err(|e| format!("invalid timestamp {ts}: {e}"))?
+            .with_timezone(&chrono::Utc),
+        None => chrono::Utc::now(),
+    };
+    RunRegistry::global()
+        .update(&id, |run| {
+            let pos = run.notes.partition_point(|n| {
+                chrono::DateTime::parse_from_rfc3339(&n.at).is_ok_and(|t| t <= at)
+            });
+            let at = at.to_rfc3339();
+            run.notes.insert(pos, RunNote { at, text });
+        })
+        .ok_or_else(|| format!("unknown run: {id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        blocked_by, build_arc_command, cancel_launching, classify_output, enqueue, expand_session,
+        free_slots, lifecycle_event, looks_like_arc, parse_pane_list, resolve_config,
+        unknown_placeholder, with_env_preset, QueuedRun, FAILED_EVENT, QUEUED_EVENT, STARTED_EVENT,
+    };
+    use crate::hostpool::PoolHostState;
+    use crate::model::{ARCRun, AppConfig, RunStatus};
+    use crate::runs::RunRegistry;
+    use std::collections::VecDeque;
+    use std::path::Path;
+
+    #[test]
+    fn arc_command_escapes_paths() {
+        let config = AppConfig {
+            python_path: "/opt/conda/envs/arc env/bin/python".into(),
+            arc_path: "/home/u/ARC/ARC.py".into(),
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            build_arc_command(&config, Path::new("input.yml"), false),
+            "'/opt/conda/envs/arc env/bin/python' /home/u/ARC/ARC.py input.yml"
+        );
+    }
+
+    #[test]
+    fn remote_arc_command_activates_conda_env() {
+        let config = AppConfig {
+            arc_path: "/home/u/ARC/ARC.py".into(),
+            remote_conda_env: Some("arc_env".into()),
+            remote_arc_path: Some("/scratch/u/ARC/ARC.py".into()),
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            build_arc_command(&config, Path::new("/scratch/u/run1/input.yml"), true),
+            "conda activate arc_env && python /scratch/u/ARC/ARC.py /scratch/u/run1/input.yml"
+        );
+        // remote settings never leak into local launches
+        assert_eq!(
+            build_arc_command(&config, Path::new("input.yml"), false),
+            "python3 /home/u/ARC/ARC.py input.yml"
+        );
+        let local_env = AppConfig {
+            conda_env: Some("arc_env".into()),
+            ..config
+        };
+        assert_eq!(
+            build_arc_command(&local_env, Path::new("input.yml"), false),
+            "conda activate arc_env && python /home/u/ARC/ARC.py input.yml"
+        );
+    }
+
+    #[test]
+    fn session_template_expands_placeholders() {
+        let profile: crate::HostProfile = serde_json::from_value(serde_json::json!({
+            "host": "login.hpc.example.org",
+            "user": "u",
+            "session_template": "arc-{project}-{date}",
+        }))
+        .unwrap();
+        assert_eq!(
+            expand_session("arc-{project}-{date}", "CH4 v2", "2025-01-01", &profile),
+            "arc-CH4-v2-2025-01-01"
+        );
+        assert_eq!(
+            expand_session("{user}@{host}", "p", "d", &profile),
+            "u@login-hpc-example-org"
+        );
+        assert_eq!(unknown_placeholder("arc-{project}-{date}"), None);
+        assert_eq!(unknown_placeholder("arc-{projet}"), Some("projet"));
+    }
+
+    #[test]
+    fn env_preset_runs_before_the_command() {
+        let profile: crate::HostProfile = serde_json::from_value(serde_json::json!({
+            "host": "hpc",
+            "user": "u",
+            "env_preset": "module load gaussian/16\n# scratch\nexport GAUSS_SCRDIR=/tmp\n",
+        }))
+        .unwrap();
+        assert_eq!(
+            with_env_preset(Some(&profile), "python ARC.py input.yml".into()),
+            "module load gaussian/16; export GAUSS_SCRDIR=/tmp; python ARC.py input.yml"
+        );
+        assert_eq!(with_env_preset(None, "python".into()), "python");
+    }
+
+    #[test]
+    fn profile_overrides_replace_global_settings() {
+        let config = AppConfig {
+            remote_arc_path: Some("/shared/ARC/ARC.py".into()),
+            ..AppConfig::default()
+        };
+        let profile: crate::HostProfile = serde_json::from_value(serde_json::json!({
+            "host": "hpc",
+            "port": 22,
+            "user": "u",
+            "overrides": { "arc_path": "/home/u/ARC/ARC.py", "concurrency_cap": 8 }
+        }))
+        .unwrap();
+        let resolved = resolve_config(config.clone(), Some(&profile));
+        assert_eq!(
+            resolved.remote_arc_path.as_deref(),
+            Some("/home/u/ARC/ARC.py")
+        );
+        assert_eq!(resolved.concurrency_cap, 8);
+        assert_eq!(resolved.python_path, config.python_path);
+        assert_eq!(resolve_config(config.clone(), None), config);
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn classify_detects_lifecycle_banners() {
+        let running = classify_output(&lines(
+            "ARC execution initiated on Mon\nspawning job opt_a1",
+        ));
+        assert_eq!(running.status, Some(RunStatus::Running));
+        assert_eq!(running.last_stdout.as_deref(), Some("spawning job opt_a1"));
+
+        let done = classify_output(&lines(
+            "ARC execution initiated on Mon\nARC execution terminated on Tue\n",
+        ));
+        assert_eq!(done.status, Some(RunStatus::Finished));
+        assert_eq!(classify_output(&lines("$ ls")).status, None);
+    }
+
+    #[test]
+    fn classify_reports_traceback_as_failure() {
+        let failed = classify_output(&lines(
+            "ARC execution initiated on Mon\n\
+             Traceback (most recent call last):\n  File \"ARC.py\", line 1\n\
+             InputError: bad species\n$ ",
+        ));
+        assert_eq!(failed.status, Some(RunStatus::Failed));
+        assert_eq!(
+            failed.last_stderr.as_deref(),
+            Some("InputError: bad species")
+        );
+    }
+
+    #[test]
+    fn free_slots_respects_cap() {
+        assert_eq!(free_slots(0, 2), 2);
+        assert_eq!(free_slots(2, 2), 0);
+        assert_eq!(free_slots(3, 2), 0);
+        assert_eq!(free_slots(0, 0), 1);
+    }
+
+    #[test]
+    fn enqueue_orders_by_priority_then_arrival() {
+        let mut queue = VecDeque::new();
+        for (id, priority) in [("a", 0), ("b", 5), ("c", 0), ("d", 5), ("e", 9)] {
+            let entry = QueuedRun {
+                id: id.into(),
+                priority,
+                config: AppConfig::default(),
+                pooled: false,
+            };
+            enqueue(&mut queue, entry);
+        }
+        let order: Vec<&str> = queue.iter().map(|q| q.id.as_str()).collect();
+        assert_eq!(order, vec!["e", "b", "d", "a", "c"]);
+    }
+
+    #[test]
+    fn lifecycle_events_fire_on_transitions() {
+        assert_eq!(
+            lifecycle_event(None, &RunStatus::Queued),
+            Some(QUEUED_EVENT)
+        );
+        assert_eq!(
+            lifecycle_event(Some(&RunStatus::Queued), &RunStatus::Starting),
+            Some(STARTED_EVENT)
+        );
+        assert_eq!(
+            lifecycle_event(Some(&RunStatus::Starting), &RunStatus::Running),
+            None
+        );
+        assert_eq!(
+            lifecycle_event(Some(&RunStatus::Running), &RunStatus::Failed),
+            Some(FAILED_EVENT)
+        );
+        assert_eq!(
+            lifecycle_event(Some(&RunStatus::Running), &RunStatus::Cancelled),
+            None
+        );
+    }
+
+    #[test]
+    fn discovery_parses_panes_and_spots_arc() {
+        let panes = parse_pane_list(
+            "arc|@3|rxn_1|%5|python|/home/u/runs/rxn 1\nmain|@1|zsh|%1|zsh|/home/u\n",
+        );
+        assert_eq!(panes.len(), 2);
+        assert_eq!(panes[0].window_id, "@3");
+        assert_eq!(panes[0].work_dir, "/home/u/runs/rxn 1");
+        assert_eq!(panes[0].project, "rxn_1");
+
+        assert!(looks_like_arc(
+            "python",
+            &lines("$ python ARC.py input.yml\nloading")
+        ));
+        assert!(looks_like_arc("zsh", &lines("Starting project rxn_1\n$")));
+        assert!(!looks_like_arc("python", &lines(">>> import numpy")));
+    }
+
+    #[test]
+    fn queue_head_reports_what_it_waits_on() {
+        let host = |active, enabled| PoolHostState {
+            host: "u@hpc:22".into(),
+            enabled,
+            max_concurrent: 2,
+            active,
+            load: None,
+        };
+        assert_eq!(blocked_by(0, "CH4", false, 1, 2, &[]), None);
+        assert_eq!(
+            blocked_by(0, "CH4", false, 2, 2, &[]).as_deref(),
+            Some("concurrency cap reached (2/2)")
+        );
+        assert_eq!(
+            blocked_by(3, "CH4", false, 0, 2, &[]).as_deref(),
+            Some("queued behind CH4")
+        );
+        assert_eq!(
+            blocked_by(0, "CH4", true, 0, 2, &[host(0, false)]).as_deref(),
+            Some("no enabled pool hosts")
+        );
+        assert_eq!(
+            blocked_by(0, "CH4", true, 0, 2, &[host(2, true)]).as_deref(),
+            Some("every pool host is at its cap")
+        );
+    }
+
+    #[test]
+    fn a_run_is_only_cancelled_mid_launch_while_still_queued() {
+        let run = ARCRun::for_test(&uuid::Uuid::new_v4().to_string(), RunStatus::Queued);
+        RunRegistry::global().insert(run.clone());
+        assert!(cancel_launching(&run.id));
+        assert_eq!(
+            RunRegistry::global().get(&run.id).unwrap().status,
+            RunStatus::Cancelled
+        );
+
+        // start_queued got there first: the run is stopped like a launched one
+        let run = ARCRun::for_test(&uuid::Uuid::new_v4().to_string(), RunStatus::Starting);
+        RunRegistry::global().insert(run.clone());
+        assert!(!cancel_launching(&run.id));
+        assert_eq!(
+            RunRegistry::global().get(&run.id).unwrap().status,
+            RunStatus::Starting
+        );
+    }
+}
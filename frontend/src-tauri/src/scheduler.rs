@@ -0,0 +1,97 @@
+use crate::htcondor::HtCondor;
+use crate::model::{BatchConfig, RunStatus};
+use crate::oge::Oge;
+use crate::pbs::Pbs;
+use crate::slurm::Slurm;
+use crate::{creds_from, run_remote_cmd, runs, HostProfile};
+use std::path::Path;
+
+// A cluster batch system ARC runs can be handed to instead of a tmux pane.
+pub trait JobScheduler: Sync {
+    // Writes whatever the scheduler needs into `work_dir` and submits it;
+    // returns the job id.
+    fn submit(
+        &self,
+        profile: &HostProfile,
+        batch: &BatchConfig,
+        name: &str,
+        work_dir: &Path,
+        command: &str,
+    ) -> Result<String, String>;
+
+    // None when the scheduler can't say, e.g. it was unreachable.
+    fn status(&self, profile: &HostProfile, job_id: &str) -> Option<RunStatus>;
+
+    // With `signal` the job is asked to stop (INT lets ARC save restart.yml)
+    // where the scheduler supports that; otherwise it is removed outright.
+    fn cancel(
+        &self,
+        profile: &HostProfile,
+        job_id: &str,
+        signal: Option<&str>,
+    ) -> Result<(), String>;
+
+    fn is_queued(&self, profile: &HostProfile, job_id: &str) -> bool {
+        self.status(profile, job_id)
+            .is_some_and(|s| runs::is_active(&s))
+    }
+}
+
+// Profiles name their batch system in `scheduler`; SLURM when unset.
+pub fn for_profile(profile: &HostProfile) -> Result<&'static dyn JobScheduler, String> {
+    match profile.scheduler.as_deref().unwrap_or("slurm") {
+        "slurm" => Ok(&Slurm),
+        "pbs" | "torque" => Ok(&Pbs),
+        "htcondor" | "condor" => Ok(&HtCondor),
+        "oge" | "sge" => Ok(&Oge),
+        other => Err(format!("unknown scheduler: {other}")),
+    }
+}
+
+pub fn remote(profile: &HostProfile, command: String) -> Result<String, String> {
+    let out = run_remote_cmd(&creds_from(profile), command)?;
+    if out.code != 0 {
+        return Err(out.stderr.trim().to_string());
+    }
+    Ok(out.stdout)
+}
+
+// Fills a script template; `batch.template` replaces `default` when set.
+pub fn render(
+    batch: &BatchConfig,
+    default: &str,
+    directives: &str,
+    name: &str,
+    work_dir: &Path,
+    command: &str,
+) -> String {
+    batch
+        .template
+        .as_deref()
+        .unwrap_or(default)
+        .replace("{directives}", directives)
+        .replace("{name}", name)
+        .replace("{work_dir}", &work_dir.to_string_lossy())
+        .replace("{command}", command)
+}
+
+// Writes `files` into `work_dir` on the host, then runs `submit` there.
+pub fn write_and_submit(
+    profile: &HostProfile,
+    work_dir: &Path,
+    files: &[(&str, String)],
+    submit: &str,
+) -> Result<String, String> {
+    let mut steps = vec![format!(
+        "cd {}",
+        shell_escape::escape(work_dir.to_string_lossy())
+    )];
+    for (name, content) in files {
+        steps.push(format!(
+            "printf '%s' {} > {name}",
+            shell_escape::escape(content.clone().into())
+        ));
+    }
+    steps.push(submit.to_string());
+    remote(profile, steps.join(" && "))
+}
@@ -0,0 +1,302 @@
+use crate::{
+    creds_from, errors, format_remote_tmux_command, remote_shell_command, ssh, tmux_exec, tmuxpath,
+    utf8, HostProfile, TmuxCommand,
+};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::io::Read;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::thread;
+use tauri::{AppHandle, Emitter};
+
+const CHUNK_EVENT: &str = "capture-chunk";
+const DONE_EVENT: &str = "capture-done";
+const DEFAULT_CHUNK_KB: usize = 256;
+const DEFAULT_PAGE_LINES: u32 = 500;
+
+// streams the UI gave up on; their reader stops at the next chunk
+static CANCELLED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+// Splits off as much of `pending` as is valid UTF-8. A character cut in half
+// by the chunk boundary waits for the rest, unless nothing more is coming.
+fn take_text(pending: &mut Vec<u8>, last: bool) -> String {
+    let valid = match std::str::from_utf8(pending) {
+        Ok(_) => pending.len(),
+        Err(e) if e.error_len().is_none() && !last => e.valid_up_to(),
+        Err(_) => pending.len(),
+    };
+    let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+    pending.drain(..valid);
+    text
+}
+
+// Reads `reader` in chunk-sized pieces and hands each to `emit`; returns the
+// number of chunks and bytes, or None when the stream was cancelled.
+fn pump(
+    mut reader: impl Read,
+    chunk_bytes: usize,
+    mut emit: impl FnMut(u64, String),
+    cancelled: impl Fn() -> bool,
+) -> Result<Option<(u64, u64)>, String> {
+    let mut buf = vec![0u8; chunk_bytes];
+    let mut pending = Vec::new();
+    let (mut seq, mut bytes) = (0u64, 0u64);
+    loop {
+        if cancelled() {
+            return Ok(None);
+        }
+        let n = reader.read(&mut buf).map_err(|e| e.to_string())?;
+        pending.extend_from_slice(&buf[..n]);
+        bytes += n as u64;
+        if pending.len() >= chunk_bytes || (n == 0 && !pending.is_empty()) {
+            emit(seq, take_text(&mut pending, n == 0));
+            seq += 1;
+        }
+        if n == 0 {
+            return Ok(Some((seq, bytes)));
+        }
+    }
+}
+
+fn capture_args(target: &str, lines: Option<u32>) -> Vec<String> {
+    let start = lines.map_or("-".to_string(), |n| format!("-{n}"));
+    ["capture-pane", "-p", "-e", "-J", "-t", target, "-S", &start]
+        .iter()
+        .map(|a| a.to_string())
+        .collect()
+}
+
+fn stream_local(
+    args: &[String],
+    chunk_bytes: usize,
+    emit: impl FnMut(u64, String),
+    cancelled: impl Fn() -> bool,
+) -> Result<Option<(u64, u64)>, String> {
+    let mut child = tmuxpath::local_command()?
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(tmuxpath::spawn_error)?;
+    let stdout = child.stdout.take().ok_or("tmux gave no stdout")?;
+    let streamed = pump(stdout, chunk_bytes, emit, cancelled);
+    if matches!(streamed, Ok(None)) {
+        let _ = child.kill();
+    }
+    let out = child.wait_with_output().map_err(|e| e.to_string())?;
+    if streamed.is_ok() && !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr).trim().to_string());
+    }
+    streamed
+}
+
+fn stream_remote(
+    profile: &HostProfile,
+    args: Vec<String>,
+    chunk_bytes: usize,
+    emit: impl FnMut(u64, String),
+    cancelled: impl Fn() -> bool,
+) -> Result<Option<(u64, u64)>, String> {
+    let creds = creds_from(profile);
+    let command = format_remote_tmux_command(&TmuxCommand { args });
+    let mut channel = ssh::open_channel(&creds)?;
+    channel
+        .exec(&remote_shell_command(&creds, &command))
+        .map_err(|e| format!("exec: {e}"))?;
+    let streamed = pump(&mut channel, chunk_bytes, emit, cancelled)?;
+    if streamed.is_none() {
+        let _ = channel.close();
+        return Ok(None);
+    }
+    let mut stderr = String::new();
+    let _ = channel.stderr().read_to_string(&mut stderr);
+    let _ = channel.wait_close();
+    if channel.exit_status().unwrap_or(1) != 0 {
+        return Err(stderr.trim().to_string());
+    }
+    Ok(streamed)
+}
+
+// Captures `lines` of scrollback, or all of it, and sends it as numbered
+// capture-chunk events followed by one capture-done, so a huge history
+// never sits in memory or crosses IPC in one piece. Returns the stream id.
+pub fn start(
+    app: AppHandle,
+    profile: Option<HostProfile>,
+    target: String,
+    lines: Option<u32>,
+    chunk_kb: Option<usize>,
+) -> Result<String, String> {
+    let chunk_bytes = chunk_kb.unwrap_or(DEFAULT_CHUNK_KB).clamp(4, 4096) * 1024;
+    let id = uuid::Uuid::new_v4().to_string();
+    let stream_id = id.clone();
+    thread::spawn(move || {
+        let args = capture_args(&target, lines);
+        let emit = |seq: u64, data: String| {
+            let payload = json!({ "id": stream_id, "seq": seq, "data": data });
+            let _ = app.emit(CHUNK_EVENT, payload);
+        };
+        let cancelled = || CANCELLED.lock().unwrap().contains(&stream_id);
+        let streamed = match &profile {
+            Some(profile) => stream_remote(profile, args, chunk_bytes, emit, cancelled),
+            None => stream_local(&args, chunk_bytes, emit, cancelled),
+        };
+        let done = match streamed {
+            Ok(Some((chunks, bytes))) => json!({
+                "id": stream_id, "target": target, "chunks": chunks, "bytes": bytes,
+            }),
+            Ok(None) => json!({ "id": stream_id, "target": target, "cancelled": true }),
+            Err(e) => json!({ "id": stream_id, "target": target, "error": e }),
+        };
+        CANCELLED.lock().unwrap().remove(&stream_id);
+        let _ = app.emit(DONE_EVENT, done);
+    });
+    Ok(id)
+}
+
+pub fn cancel(id: String) {
+    CANCELLED.lock().unwrap().insert(id);
+}
+
+// One page of history. Line numbers are tmux's: 0 is the top of the
+// visible pane and the history runs back to -history_size.
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturePage {
+    pub text: String,
+    pub start: i64,
+    pub end: i64,
+    pub history_size: i64,
+    pub has_more: bool, // older lines remain; ask again with before_line = start
+}
+
+// The lines of a page of `count` ending just above `before_line`, or at the
+// bottom of the pane; None once the history is used up.
+fn page_range(
+    history: i64,
+    height: i64,
+    before_line: Option<i64>,
+    count: u32,
+) -> Option<(i64, i64)> {
+    let end = before_line.map_or(height - 1, |b| b - 1).min(height - 1);
+    if end < -history {
+        return None;
+    }
+    Some(((end - count as i64 + 1).max(-history), end))
+}
+
+fn tmux(profile: Option<&HostProfile>, args: &[&str]) -> Result<String, String> {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    let out = tmux_exec(profile, &args)?;
+    if out.code != 0 {
+        return Err(out.stderr.trim().to_string());
+    }
+    Ok(utf8::mark(out.stdout, out.lossy))
+}
+
+// Fetches history a page at a time going backwards, so a viewer can scroll
+// up indefinitely without ever asking for the whole buffer.
+pub fn page(
+    profile: Option<&HostProfile>,
+    target: &str,
+    before_line: Option<i64>,
+    count: Option<u32>,
+) -> Result<CapturePage, String> {
+    let count = count.unwrap_or(DEFAULT_PAGE_LINES).clamp(1, 10_000);
+    let sizes = match tmux(
+        profile,
+        &[
+            "display-message",
+            "-p",
+            "-t",
+            target,
+            "#{history_size} #{pane_height}",
+        ],
+    ) {
+        Ok(sizes) => sizes,
+        // no server, no history: the empty last page
+        Err(e) if errors::no_server(&e) => String::new(),
+        Err(e) => return Err(e),
+    };
+    let mut sizes = sizes
+        .split_whitespace()
+        .map(|n| n.parse::<i64>().unwrap_or(0));
+    let (history, height) = (sizes.next().unwrap_or(0), sizes.next().unwrap_or(0));
+    let Some((start, end)) = page_range(history, height, before_line, count) else {
+        return Ok(CapturePage {
+            text: String::new(),
+            start: -history,
+            end: -history,
+            history_size: history,
+            has_more: false,
+        });
+    };
+    let (first, last) = (start.to_string(), end.to_string());
+    let text = tmux(
+        profile,
+        &[
+            "capture-pane",
+            "-p",
+            "-e",
+            "-J",
+            "-t",
+            target,
+            "-S",
+            &first,
+            "-E",
+            &last,
+        ],
+    )?;
+    Ok(CapturePage {
+        text,
+        start,
+        end,
+        history_size: history,
+        has_more: start > -history,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{page_range, pump, take_text};
+
+    #[test]
+    fn streams_in_chunks_without_splitting_characters() {
+        let mut pending = "é".as_bytes()[..1].to_vec();
+        assert_eq!(take_text(&mut pending, false), "");
+        pending.push("é".as_bytes()[1]);
+        assert_eq!(take_text(&mut pending, false), "é");
+        assert!(pending.is_empty());
+
+        let text = "ΔE = 12.3 kJ/mol\n".repeat(10);
+        let mut chunks = Vec::new();
+        let done = pump(
+            text.as_bytes(),
+            16,
+            |seq, data| chunks.push((seq, data)),
+            || false,
+        )
+        .unwrap();
+        assert_eq!(done, Some((chunks.len() as u64, text.len() as u64)));
+        assert!(chunks
+            .iter()
+            .enumerate()
+            .all(|(i, (seq, _))| *seq == i as u64));
+        let joined: String = chunks.into_iter().map(|(_, data)| data).collect();
+        assert_eq!(joined, text);
+
+        assert_eq!(pump(text.as_bytes(), 16, |_, _| {}, || true).unwrap(), None);
+    }
+
+    #[test]
+    fn pages_walk_back_through_the_history() {
+        // 1000 lines of history above a 50-line pane
+        assert_eq!(page_range(1000, 50, None, 500), Some((-450, 49)));
+        assert_eq!(page_range(1000, 50, Some(-450), 500), Some((-950, -451)));
+        assert_eq!(page_range(1000, 50, Some(-950), 500), Some((-1000, -951)));
+        assert_eq!(page_range(1000, 50, Some(-1000), 500), None);
+        assert_eq!(page_range(0, 50, Some(400), 20), Some((30, 49)));
+    }
+}
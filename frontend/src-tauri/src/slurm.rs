@@ -0,0 +1,152 @@
+use crate::model::{BatchConfig, RunStatus};
+use crate::scheduler::{self, remote, JobScheduler};
+use crate::HostProfile;
+use std::path::Path;
+
+const SCRIPT_NAME: &str = "submit_arc.sh";
+
+// `bash -l` so the user's conda init runs before `conda activate`.
+const DEFAULT_TEMPLATE: &str = "#!/bin/bash -l
+#SBATCH --job-name={name}
+#SBATCH --output={work_dir}/slurm-%j.out
+#SBATCH --error={work_dir}/slurm-%j.err
+{directives}
+cd {work_dir}
+{command}
+";
+
+pub struct Slurm;
+
+fn directives(batch: &BatchConfig) -> String {
+    let mut lines = Vec::new();
+    if let Some(partition) = &batch.partition {
+        lines.push(format!("#SBATCH --partition={partition}"));
+    }
+    if let Some(time) = &batch.time_limit {
+        lines.push(format!("#SBATCH --time={time}"));
+    }
+    if let Some(cpus) = batch.cpus {
+        lines.push(format!("#SBATCH --cpus-per-task={cpus}"));
+    }
+    if let Some(memory) = &batch.memory {
+        lines.push(format!("#SBATCH --mem={memory}"));
+    }
+    lines.extend(batch.extra_directives.iter().cloned());
+    lines.join("\n")
+}
+
+fn render_script(batch: &BatchConfig, name: &str, work_dir: &Path, command: &str) -> String {
+    scheduler::render(
+        batch,
+        DEFAULT_TEMPLATE,
+        &directives(batch),
+        name,
+        work_dir,
+        command,
+    )
+}
+
+// `sbatch --parsable` prints "<job id>" or "<job id>;<cluster>".
+fn parse_sbatch(stdout: &str) -> Option<String> {
+    let id = stdout.trim().split(';').next()?.trim();
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+}
+
+// Maps squeue/sacct job states onto run states; unknown ones give None.
+fn map_state(state: &str) -> Option<RunStatus> {
+    // sacct reports e.g. "CANCELLED by 1234"
+    match state.split_whitespace().next()? {
+        "PENDING" | "CONFIGURING" | "REQUEUED" | "RESV_DEL_HOLD" => Some(RunStatus::Starting),
+        "RUNNING" | "COMPLETING" | "SUSPENDED" | "STAGE_OUT" => Some(RunStatus::Running),
+        "COMPLETED" => Some(RunStatus::Finished),
+        "CANCELLED" | "PREEMPTED" => Some(RunStatus::Cancelled),
+        "FAILED" | "TIMEOUT" | "OUT_OF_MEMORY" | "NODE_FAIL" | "BOOT_FAIL" | "DEADLINE" => {
+            Some(RunStatus::Failed)
+        }
+        _ => None,
+    }
+}
+
+impl JobScheduler for Slurm {
+    fn submit(
+        &self,
+        profile: &HostProfile,
+        batch: &BatchConfig,
+        name: &str,
+        work_dir: &Path,
+        command: &str,
+    ) -> Result<String, String> {
+        let script = render_script(batch, name, work_dir, command);
+        let stdout = scheduler::write_and_submit(
+            profile,
+            work_dir,
+            &[(SCRIPT_NAME, script)],
+            &format!("sbatch --parsable {SCRIPT_NAME}"),
+        )?;
+        parse_sbatch(&stdout).ok_or_else(|| format!("unexpected sbatch output: {}", stdout.trim()))
+    }
+
+    // squeue only knows jobs that are still around; sacct has the final state
+    // once the job has left the queue.
+    fn status(&self, profile: &HostProfile, job_id: &str) -> Option<RunStatus> {
+        let job = shell_escape::escape(job_id.into());
+        let queued = remote(profile, format!("squeue -h -j {job} -o %T")).unwrap_or_default();
+        if let Some(status) = queued.lines().next().and_then(map_state) {
+            return Some(status);
+        }
+        let accounted = remote(profile, format!("sacct -n -X -P -j {job} -o State")).ok()?;
+        accounted.lines().next().and_then(map_state)
+    }
+
+    fn is_queued(&self, profile: &HostProfile, job_id: &str) -> bool {
+        let job = shell_escape::escape(job_id.into());
+        remote(profile, format!("squeue -h -j {job} -o %T")).is_ok_and(|out| !out.trim().is_empty())
+    }
+
+    // A signal goes to the batch shell only, not every step of the job.
+    fn cancel(
+        &self,
+        profile: &HostProfile,
+        job_id: &str,
+        signal: Option<&str>,
+    ) -> Result<(), String> {
+        let job = shell_escape::escape(job_id.into());
+        let command = match signal {
+            Some(signal) => format!("scancel --batch --signal={signal} {job}"),
+            None => format!("scancel {job}"),
+        };
+        remote(profile, command).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{map_state, parse_sbatch, render_script};
+    use crate::model::{BatchConfig, RunStatus};
+    use std::path::Path;
+
+    #[test]
+    fn script_and_scheduler_output() {
+        let batch = BatchConfig {
+            partition: Some("long".into()),
+            cpus: Some(8),
+            ..BatchConfig::default()
+        };
+        let script = render_script(
+            &batch,
+            "CH4",
+            Path::new("/scratch/u/CH4"),
+            "python ARC.py input.yml",
+        );
+        assert!(script.contains("#SBATCH --job-name=CH4\n"));
+        assert!(script.contains("#SBATCH --partition=long\n#SBATCH --cpus-per-task=8\n"));
+        assert!(script.ends_with("cd /scratch/u/CH4\npython ARC.py input.yml\n"));
+
+        assert_eq!(parse_sbatch("4242;cluster\n"), Some("4242".into()));
+        assert_eq!(parse_sbatch("sbatch: error"), None);
+        assert_eq!(map_state("PENDING"), Some(RunStatus::Starting));
+        assert_eq!(map_state("CANCELLED by 1234"), Some(RunStatus::Cancelled));
+        assert_eq!(map_state("OUT_OF_MEMORY"), Some(RunStatus::Failed));
+        assert_eq!(map_state(""), None);
+    }
+}
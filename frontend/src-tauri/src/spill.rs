@@ -0,0 +1,84 @@
+use once_cell::sync::OnceCell;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+// Prefix of a capture that went to a file; the rest is JSON with the file's
+// path, its size in bytes and its line count. NUL never shows up in
+// captured text.
+pub const SPILL_PREFIX: &str = "\u{0}spilled\u{0}";
+const SPILL_DIR: &str = "spill";
+
+static DIR: OnceCell<PathBuf> = OnceCell::new();
+
+// Spill files only live until the frontend has read them, so whatever the
+// last session left behind is cleared out.
+pub fn init(app: &AppHandle) {
+    let dir = match app.path().app_data_dir() {
+        Ok(dir) => dir.join(SPILL_DIR),
+        Err(e) => {
+            tracing::warn!("no app data dir, large captures will go over IPC: {e}");
+            return;
+        }
+    };
+    let _ = std::fs::remove_dir_all(&dir);
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        tracing::warn!("creating {} failed: {e}", dir.display());
+        return;
+    }
+    let _ = DIR.set(dir);
+}
+
+fn write(dir: &Path, text: &str) -> Result<String, String> {
+    let path = dir.join(format!("{}.txt", uuid::Uuid::new_v4()));
+    std::fs::write(&path, text).map_err(|e| format!("write {}: {e}", path.display()))?;
+    let meta = json!({
+        "path": path.to_string_lossy(),
+        "bytes": text.len(),
+        "lines": text.lines().count(),
+    });
+    Ok(format!("{SPILL_PREFIX}{meta}"))
+}
+
+// Writes text longer than `threshold` to a file in the app data dir and
+// returns the marker pointing at it, keeping the IPC channel free of it.
+// Shorter text, or text that cannot be written, is returned as it is.
+pub fn spill(text: String, threshold: Option<usize>) -> String {
+    let (Some(threshold), Some(dir)) = (threshold, DIR.get()) else {
+        return text;
+    };
+    if text.len() <= threshold {
+        return text;
+    }
+    write(dir, &text).unwrap_or(text)
+}
+
+// Deletes a spill file once the frontend is done with it.
+pub fn release(path: &str) -> Result<(), String> {
+    let dir = DIR.get().ok_or("no spill dir")?;
+    let path = Path::new(path);
+    if path.parent() != Some(dir.as_path()) {
+        return Err(format!("{} is not a spill file", path.display()));
+    }
+    std::fs::remove_file(path).map_err(|e| format!("remove {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write, SPILL_PREFIX};
+
+    #[test]
+    fn spilled_text_is_replaced_by_a_pointer_to_it() {
+        let dir = std::env::temp_dir().join(format!("arc-spill-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let text = "Step 1\nStep 2\n".to_string();
+        let marker = write(&dir, &text).unwrap();
+        let meta: serde_json::Value =
+            serde_json::from_str(marker.strip_prefix(SPILL_PREFIX).unwrap()).unwrap();
+        assert_eq!(meta["bytes"], 14);
+        assert_eq!(meta["lines"], 2);
+        let path = meta["path"].as_str().unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), text);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,8 +1,11 @@
 // src-tauri/src/ssh.rs
+use crate::{perf, tmuxpath, utf8, HostProfile};
 use once_cell::sync::Lazy;
-use ssh2::Session;
-use std::sync::Mutex;
-use std::{net::TcpStream, path::Path};
+use ssh2::{Channel, Session};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::{net::TcpStream, path::Path, thread};
 
 pub struct SshCreds<'a> {
     pub host: &'a str,
@@ -12,15 +15,18 @@ pub struct SshCreds<'a> {
     pub key_path: Option<&'a Path>,
     pub key_pass: Option<&'a str>,
     pub use_agent: bool,
+    pub env_preset: Option<&'a str>, // prepended by run_remote_cmd, not by plain exec
+    pub max_concurrent_ops: Option<usize>, // None leaves the host unlimited
 }
 
 pub struct ExecOut {
     pub code: i32,
     pub stdout: String,
     pub stderr: String,
+    pub lossy: bool, // stdout had bytes that are not UTF-8
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 struct ConnKey {
     host: String,
     port: u16,
@@ -44,6 +50,72 @@ struct SshClient {
 
 static CLIENT: Lazy<Mutex<Option<SshClient>>> = Lazy::new(|| Mutex::new(None));
 
+// Channels opened ahead of time on the CLIENT session, so an exec skips the
+// channel-open round trip. Emptied whenever CLIENT is dropped or replaced.
+const SPARE_CHANNELS: usize = 2;
+static SPARE: Lazy<Mutex<Vec<(ConnKey, Channel)>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static REFILLING: AtomicBool = AtomicBool::new(false);
+
+// Connected sessions for hosts kept warm (see standby), waiting for CLIENT
+// to switch to them. A warm host's CLIENT session goes back here when
+// CLIENT moves on to another host.
+static WARM_KEYS: Lazy<Mutex<Vec<ConnKey>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static STANDBY: Lazy<Mutex<HashMap<ConnKey, Session>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct OpSlots {
+    running: usize,
+    cap: usize,
+}
+
+struct OpLimit {
+    slots: Mutex<OpSlots>,
+    freed: Condvar,
+}
+
+static LIMITS: Lazy<Mutex<HashMap<ConnKey, Arc<OpLimit>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Held for the length of one exec or transfer; dropping it lets the next
+// waiting caller for the host through.
+struct OpPermit(Arc<OpLimit>);
+
+impl Drop for OpPermit {
+    fn drop(&mut self) {
+        self.0.slots.lock().unwrap().running -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+// Blocks until the host has a free slot. Terminals, log streams and tmux
+// control channels stay open for minutes, so only the short operations
+// below are counted.
+fn acquire(creds: &SshCreds) -> Option<OpPermit> {
+    let cap = creds.max_concurrent_ops.filter(|cap| *cap > 0)?;
+    let limit = LIMITS
+        .lock()
+        .unwrap()
+        .entry(ConnKey::from(creds))
+        .or_insert_with(|| {
+            Arc::new(OpLimit {
+                slots: Mutex::new(OpSlots { running: 0, cap }),
+                freed: Condvar::new(),
+            })
+        })
+        .clone();
+    let mut slots = limit.slots.lock().unwrap();
+    if cap > slots.cap {
+        // the profile was edited to allow more; wake everyone to recheck
+        limit.freed.notify_all();
+    }
+    slots.cap = cap;
+    while slots.running >= slots.cap {
+        slots = limit.freed.wait(slots).unwrap();
+    }
+    slots.running += 1;
+    drop(slots);
+    Some(OpPermit(limit))
+}
+
 fn connect(creds: &SshCreds) -> Result<SshClient, String> {
     let stream = TcpStream::connect((creds.host, creds.port)).map_err(|e| format!("tcp: {}", e))?;
 
@@ -97,6 +169,12 @@ fn connect(creds: &SshCreds) -> Result<SshClient, String> {
     })
 }
 
+// A connection that is not shared through CLIENT; interactive terminals
+// switch it to non-blocking mode, which must not leak into exec().
+pub fn connect_dedicated(creds: &SshCreds) -> Result<Session, String> {
+    connect(creds).map(|client| client.sess)
+}
+
 fn ensure_client(
     creds: &SshCreds,
 ) -> Result<std::sync::MutexGuard<'static, Option<SshClient>>, String> {
@@ -106,12 +184,127 @@ fn ensure_client(
         None => true,
     };
     if need_new {
-        *guard = Some(connect(creds)?);
+        SPARE.lock().unwrap().clear();
+        if let Some(old) = guard.take() {
+            if WARM_KEYS.lock().unwrap().contains(&old.key) {
+                STANDBY.lock().unwrap().insert(old.key, old.sess);
+            }
+        }
+        let key = ConnKey::from(creds);
+        let standby = STANDBY.lock().unwrap().remove(&key);
+        *guard = Some(match standby {
+            Some(sess) => SshClient { key, sess },
+            None => connect(creds)?,
+        });
     }
     Ok(guard)
 }
 
+// Connects `creds` ahead of its first use and keeps the session alive, or
+// checks on the session already kept. A session that stopped answering is
+// replaced.
+pub fn keep_warm(creds: &SshCreds) -> Result<(), String> {
+    let key = ConnKey::from(creds);
+    {
+        let mut keys = WARM_KEYS.lock().unwrap();
+        if !keys.contains(&key) {
+            keys.push(key.clone());
+        }
+    }
+    let in_use = CLIENT
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|c| c.key == key);
+    if in_use {
+        return Ok(());
+    }
+    let kept = STANDBY.lock().unwrap().remove(&key);
+    let sess = match kept {
+        Some(sess) if sess.keepalive_send().is_ok() => sess,
+        _ => {
+            let sess = connect(creds)?.sess;
+            sess.set_keepalive(true, 30);
+            sess
+        }
+    };
+    STANDBY.lock().unwrap().insert(key, sess);
+    Ok(())
+}
+
+// Lets go of hosts no longer kept warm.
+pub fn keep_only_warm(creds: &[SshCreds]) {
+    let wanted: Vec<ConnKey> = creds.iter().map(ConnKey::from).collect();
+    WARM_KEYS.lock().unwrap().retain(|k| wanted.contains(k));
+    STANDBY.lock().unwrap().retain(|k, _| wanted.contains(k));
+}
+
+// Drops the shared session after a failure, with the channels opened on it.
+fn reset_client() {
+    *CLIENT.lock().unwrap() = None;
+    SPARE.lock().unwrap().clear();
+}
+
+// Tops the spare channels for `key` back up on a thread of its own.
+fn refill(sess: Session, key: ConnKey) {
+    if REFILLING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    thread::spawn(move || {
+        loop {
+            let spare = SPARE
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(k, _)| *k == key)
+                .count();
+            if spare >= SPARE_CHANNELS {
+                break;
+            }
+            match sess.channel_session() {
+                Ok(channel) => SPARE.lock().unwrap().push((key.clone(), channel)),
+                Err(_) => break,
+            }
+        }
+        REFILLING.store(false, Ordering::SeqCst);
+    });
+}
+
+// A spare channel for `creds` when there is one. Hosts with a
+// max_concurrent_ops cap get no spares: channels held open in the background
+// would count past the cap.
+fn take_spare(sess: &Session, creds: &SshCreds) -> Option<Channel> {
+    if creds.max_concurrent_ops.is_some_and(|cap| cap > 0) {
+        return None;
+    }
+    let key = ConnKey::from(creds);
+    let spare = {
+        let mut spares = SPARE.lock().unwrap();
+        let found = spares.iter().position(|(k, _)| *k == key);
+        found.map(|i| spares.swap_remove(i).1)
+    };
+    refill(sess.clone(), key);
+    spare
+}
+
+// Starts `cmd` on a spare channel, or on a newly opened one. A spare the
+// server has since closed is dropped for a new channel, so only a failure on
+// a fresh channel says the session itself is gone.
+fn start_exec(sess: &Session, creds: &SshCreds, cmd: &str) -> Result<Channel, String> {
+    if let Some(mut channel) = take_spare(sess, creds) {
+        if channel.exec(cmd).is_ok() {
+            return Ok(channel);
+        }
+    }
+    let mut channel = sess
+        .channel_session()
+        .map_err(|e| format!("channel: {e}"))?;
+    channel.exec(cmd).map_err(|e| format!("exec: {e}"))?;
+    Ok(channel)
+}
+
 pub fn exec(creds: &SshCreds, cmd: &str) -> Result<ExecOut, String> {
+    let _permit = acquire(creds);
     for attempt in 0..2 {
         // 1) get or create a session, but DO NOT hold the lock for network I/O
         let sess = {
@@ -126,40 +319,32 @@ pub fn exec(creds: &SshCreds, cmd: &str) -> Result<ExecOut, String> {
         }; // <-- mutex is dropped here
 
         // 2) do the SSH work without holding the mutex
-        match sess.channel_session() {
+        match start_exec(&sess, creds, cmd) {
             Ok(mut ch) => {
-                if let Err(e) = ch.exec(cmd) {
-                    // invalidate and retry once
-                    if attempt == 0 {
-                        let mut guard = CLIENT.lock().unwrap();
-                        *guard = None;
-                        continue;
-                    } else {
-                        return Err(format!("exec: {e}"));
-                    }
-                }
-
+                // bytes first: read_to_string would drop the whole output
+                // over one byte that is not UTF-8
                 use std::io::Read;
-                let mut out = String::new();
-                let mut err = String::new();
-                let _ = ch.read_to_string(&mut out);
+                let mut out = Vec::new();
+                let mut err = Vec::new();
+                let _ = ch.read_to_end(&mut out);
                 let mut ext = ch.stderr();
-                let _ = ext.read_to_string(&mut err);
+                let _ = ext.read_to_end(&mut err);
                 let _ = ch.wait_close();
                 let code = ch.exit_status().unwrap_or(1);
+                let (stdout, lossy) = utf8::decode(&out);
                 return Ok(ExecOut {
                     code,
-                    stdout: out,
-                    stderr: err,
+                    stdout,
+                    stderr: String::from_utf8_lossy(&err).into_owned(),
+                    lossy,
                 });
             }
             Err(e) => {
                 if attempt == 0 {
-                    let mut guard = CLIENT.lock().unwrap();
-                    *guard = None;
+                    reset_client();
                     continue;
                 } else {
-                    return Err(format!("channel: {e}"));
+                    return Err(e);
                 }
             }
         }
@@ -180,12 +365,13 @@ pub fn open_channel(creds: &SshCreds) -> Result<ssh2::Channel, String> {
             }
         };
 
+        // not a spare: the caller's exec on it could not tell a stale
+        // spare from a dead session
         match sess.channel_session() {
             Ok(channel) => return Ok(channel),
             Err(e) => {
                 if attempt == 0 {
-                    let mut guard = CLIENT.lock().unwrap();
-                    *guard = None;
+                    reset_client();
                     continue;
                 } else {
                     return Err(format!("channel: {e}"));
@@ -195,3 +381,194 @@ pub fn open_channel(creds: &SshCreds) -> Result<ssh2::Channel, String> {
     }
     Err("unreachable open_channel failure".into())
 }
+
+// Copies a local file to `remote` over SFTP on the shared connection.
+pub fn upload(creds: &SshCreds, local: &Path, remote: &Path) -> Result<(), String> {
+    let _permit = acquire(creds);
+    let sess = {
+        let guard = ensure_client(creds)?;
+        guard.as_ref().unwrap().sess.clone()
+    };
+    let sftp = sess.sftp().map_err(|e| format!("sftp: {e}"))?;
+    let mut src =
+        std::fs::File::open(local).map_err(|e| format!("open {}: {e}", local.display()))?;
+    let mut dst = sftp
+        .create(remote)
+        .map_err(|e| format!("sftp create {}: {e}", remote.display()))?;
+    std::io::copy(&mut src, &mut dst).map_err(|e| format!("upload {}: {e}", local.display()))?;
+    Ok(())
+}
+
+// Copies `remote` to a local file over SFTP, calling `progress` with the
+// bytes copied so far and the remote size after every chunk.
+pub fn download(
+    creds: &SshCreds,
+    remote: &Path,
+    local: &Path,
+    mut progress: impl FnMut(u64, u64),
+) -> Result<u64, String> {
+    use std::io::{Read, Write};
+    let _permit = acquire(creds);
+    let sess = {
+        let guard = ensure_client(creds)?;
+        guard.as_ref().unwrap().sess.clone()
+    };
+    let sftp = sess.sftp().map_err(|e| format!("sftp: {e}"))?;
+    let mut src = sftp
+        .open(remote)
+        .map_err(|e| format!("sftp open {}: {e}", remote.display()))?;
+    let total = src.stat().ok().and_then(|s| s.size).unwrap_or(0);
+    let mut dst =
+        std::fs::File::create(local).map_err(|e| format!("create {}: {e}", local.display()))?;
+    let mut buf = vec![0u8; 256 * 1024];
+    let mut copied = 0u64;
+    loop {
+        let n = src
+            .read(&mut buf)
+            .map_err(|e| format!("download {}: {e}", remote.display()))?;
+        if n == 0 {
+            break;
+        }
+        dst.write_all(&buf[..n])
+            .map_err(|e| format!("write {}: {e}", local.display()))?;
+        copied += n as u64;
+        progress(copied, total);
+    }
+    Ok(copied)
+}
+
+// `raw` as run_remote_cmd runs it: in a login shell, after the prelude and
+// the profile's env preset.
+pub fn remote_shell_command(creds: &SshCreds<'_>, raw: &str) -> String {
+    let prelude = "unset BASH_ENV TMUX PROMPT_COMMAND PS1; if [ -f /etc/profile ]; then source /etc/profile; fi";
+    // once the host's tmux is resolved, `tmux` in the command runs that
+    let raw = match tmuxpath::remote_cached(creds) {
+        Some(path) => format!("{}{}", tmuxpath::remote_alias(&path), raw),
+        None => raw.to_string(),
+    };
+    // the profile's env preset may span several lines, so it gets its own
+    let chained = match creds.env_preset {
+        Some(preset) => format!("{}; {}\n{}", prelude, preset, raw),
+        None => format!("{}; {}", prelude, raw),
+    };
+    format!("bash -lc {}", shell_escape::escape(chained.into()))
+}
+
+pub fn run_remote_cmd(creds: &SshCreds<'_>, raw: String) -> Result<ExecOut, String> {
+    let uses_tmux = raw.contains("tmux");
+    if uses_tmux {
+        let lookup = remote_shell_command(creds, "command -v tmux");
+        tmuxpath::resolve_remote(creds, &lookup);
+    }
+    let started = std::time::Instant::now();
+    let out = exec(creds, &remote_shell_command(creds, &raw))?;
+    perf::record(&raw, true, started.elapsed());
+    if uses_tmux {
+        tmuxpath::remote_exited(creds, out.code);
+    }
+    Ok(out)
+}
+
+// Resolve auth mode deterministically. Stored profiles are rewritten to an
+// explicit auth on load; the use_agent guess stays for profiles the frontend
+// still sends without one.
+pub fn auth_mode(profile: &HostProfile) -> &str {
+    profile.auth.as_deref().unwrap_or_else(|| {
+        // keep legacy behavior: default to agent unless told otherwise
+        if profile.use_agent.unwrap_or(true) {
+            "agent"
+        } else if profile.key_path.as_deref().is_some() {
+            "key"
+        } else {
+            "agent"
+        }
+    })
+}
+
+// SshCreds for a HostProfile, with no slow fallbacks.
+pub fn creds_from(profile: &HostProfile) -> SshCreds<'_> {
+    use std::path::Path;
+
+    let auth = auth_mode(profile);
+
+    let key_path = if auth == "key" {
+        profile.key_path.as_deref().and_then(|s| {
+            if s.trim().is_empty() {
+                None
+            } else {
+                Some(Path::new(s))
+            }
+        })
+    } else {
+        None
+    };
+
+    SshCreds {
+        host: &profile.host,
+        port: profile.port.unwrap_or(22),
+        user: &profile.user,
+        password: if auth == "password" {
+            profile.password.as_deref()
+        } else {
+            None
+        },
+        key_path,
+        key_pass: if auth == "key" {
+            profile.key_pass.as_deref()
+        } else {
+            None
+        },
+        use_agent: auth == "agent",
+        env_preset: profile
+            .env_preset
+            .as_deref()
+            .filter(|p| !p.trim().is_empty()),
+        max_concurrent_ops: profile.max_concurrent_ops.map(|n| n as usize),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{acquire, SshCreds};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    fn creds(cap: Option<usize>) -> SshCreds<'static> {
+        SshCreds {
+            host: "limited.example.org",
+            port: 22,
+            user: "arc",
+            password: None,
+            key_path: None,
+            key_pass: None,
+            use_agent: true,
+            env_preset: None,
+            max_concurrent_ops: cap,
+        }
+    }
+
+    #[test]
+    fn op_limit_queues_callers_past_the_cap() {
+        assert!(acquire(&creds(None)).is_none());
+        let inside = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..6)
+            .map(|_| {
+                let (inside, peak) = (inside.clone(), peak.clone());
+                thread::spawn(move || {
+                    let _permit = acquire(&creds(Some(2)));
+                    let now = inside.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(20));
+                    inside.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{creds_from, profiles, runs, ssh, HostProfile};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+// how often kept sessions are checked on, and new keep_warm profiles
+// picked up
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+fn warm_profiles() -> Vec<HostProfile> {
+    profiles::list()
+        .into_iter()
+        .filter(|p| p.profile.enabled && p.profile.keep_warm)
+        .map(|p| p.profile)
+        .collect()
+}
+
+// Connects every keep_warm profile right away and keeps the sessions alive,
+// so the first click on such a host skips the connect and auth. Hosts are
+// connected side by side; one that hangs does not hold up the rest.
+pub fn start() {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    thread::spawn(|| loop {
+        let warm = warm_profiles();
+        ssh::keep_only_warm(&warm.iter().map(creds_from).collect::<Vec<_>>());
+        let handles: Vec<_> = warm
+            .into_iter()
+            .map(|profile| {
+                thread::spawn(move || {
+                    if let Err(e) = ssh::keep_warm(&creds_from(&profile)) {
+                        tracing::warn!("keeping {} warm failed: {e}", runs::host_label(&profile));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            let _ = handle.join();
+        }
+        thread::sleep(CHECK_INTERVAL);
+    });
+}
@@ -0,0 +1,199 @@
+use crate::model::{ARCRun, RunStatus};
+use crate::{diagnostics, history, runs};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HostStats {
+    pub host: String,
+    pub runs: usize,
+    pub core_hours: f64,
+    pub median_duration_secs: Option<f64>, // over runs that have ended
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RunStats {
+    pub range: String,
+    pub since: Option<String>, // None for "all"
+    pub total: usize,
+    pub by_status: BTreeMap<String, usize>,
+    pub core_hours: f64,
+    pub per_host: Vec<HostStats>,
+    pub failure_causes: BTreeMap<String, usize>,
+}
+
+// "all", or a positive count with an h/d/w unit such as "24h" or "30d".
+pub fn parse_range(range: &str) -> Result<Option<Duration>, String> {
+    if range == "all" {
+        return Ok(None);
+    }
+    let bad = || format!("bad range: {range} (expected e.g. 24h, 7d, 4w or all)");
+    let (at, unit) = range.char_indices().last().ok_or_else(bad)?;
+    let count: i64 = range[..at].parse().map_err(|_| bad())?;
+    if count <= 0 {
+        return Err(bad());
+    }
+    let span = match unit {
+        'h' => Duration::try_hours(count),
+        'd' => Duration::try_days(count),
+        'w' => Duration::try_weeks(count),
+        _ => return Err(bad()),
+    };
+    span.map(Some)
+        .ok_or_else(|| format!("range too long: {range}"))
+}
+
+fn started(run: &ARCRun) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(run.started_at.as_deref()?)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn median(values: &mut [i64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) as f64 / 2.0
+    } else {
+        values[mid] as f64
+    })
+}
+
+// Batch jobs hold their requested cores for the whole job; pane runs count
+// the CPU time actually sampled.
+fn core_hours(run: &ARCRun, now: DateTime<Utc>) -> f64 {
+    let batch_cpus = run
+        .batch_job_id
+        .as_ref()
+        .and_then(|_| runs::launch_config(&run.id)?.batch?.cpus);
+    match (batch_cpus, started(run)) {
+        (Some(cpus), Some(start)) => {
+            let secs = history::duration_secs(run).unwrap_or((now - start).num_seconds());
+            cpus as f64 * secs.max(0) as f64 / 3600.0
+        }
+        _ => run.core_secs / 3600.0,
+    }
+}
+
+fn summarize(
+    runs: &[ARCRun],
+    range: &str,
+    since: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    cause: impl Fn(&ARCRun) -> &'static str,
+) -> RunStats {
+    let mut by_status = BTreeMap::new();
+    let mut failure_causes = BTreeMap::new();
+    let mut hosts: BTreeMap<String, (usize, f64, Vec<i64>)> = BTreeMap::new();
+    let mut total_core_hours = 0.0;
+    for run in runs {
+        *by_status.entry(format!("{:?}", run.status)).or_insert(0) += 1;
+        if run.status == RunStatus::Failed {
+            *failure_causes.entry(cause(run).to_string()).or_insert(0) += 1;
+        }
+        let used = core_hours(run, now);
+        total_core_hours += used;
+        let slot = hosts
+            .entry(run.host.clone().unwrap_or_else(|| "local".into()))
+            .or_default();
+        slot.0 += 1;
+        slot.1 += used;
+        slot.2.extend(history::duration_secs(run));
+    }
+    RunStats {
+        range: range.to_string(),
+        since: since.map(|t| t.to_rfc3339()),
+        total: runs.len(),
+        by_status,
+        core_hours: total_core_hours,
+        per_host: hosts
+            .into_iter()
+            .map(|(host, (runs, core_hours, mut durations))| HostStats {
+                host,
+                runs,
+                core_hours,
+                median_duration_secs: median(&mut durations),
+            })
+            .collect(),
+        failure_causes,
+    }
+}
+
+// Runs count towards the range they started in; queued ones only show up
+// under "all".
+pub fn runs_stats(range: Option<String>) -> Result<RunStats, String> {
+    let range = range.unwrap_or_else(|| "all".into());
+    let now = Utc::now();
+    let since = match parse_range(&range)? {
+        Some(span) => Some(
+            now.checked_sub_signed(span)
+                .ok_or_else(|| format!("range too long: {range}"))?,
+        ),
+        None => None,
+    };
+    let selected: Vec<ARCRun> = runs::list_runs()
+        .into_iter()
+        .filter(|r| match since {
+            Some(since) => started(r).is_some_and(|s| s >= since),
+            None => true,
+        })
+        .collect();
+    Ok(summarize(
+        &selected,
+        &range,
+        since,
+        now,
+        diagnostics::failure_cause,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_range, summarize};
+    use crate::model::{ARCRun, RunStatus};
+    use chrono::{Duration, Utc};
+
+    fn run(host: Option<&str>, status: RunStatus, secs: i64, core_secs: f64) -> ARCRun {
+        let start = Utc::now() - Duration::hours(1);
+        ARCRun {
+            started_at: Some(start.to_rfc3339()),
+            finished_at: Some((start + Duration::seconds(secs)).to_rfc3339()),
+            host: host.map(String::from),
+            core_secs,
+            ..ARCRun::for_test(&uuid::Uuid::new_v4().to_string(), status)
+        }
+    }
+
+    #[test]
+    fn stats_count_statuses_hosts_and_causes() {
+        assert_eq!(parse_range("all"), Ok(None));
+        assert_eq!(parse_range("7d"), Ok(Some(Duration::days(7))));
+        assert!(parse_range("7x").is_err());
+        assert!(parse_range("").is_err());
+        assert!(parse_range("7é").is_err());
+        assert!(parse_range("0d").is_err());
+        assert!(parse_range("-3h").is_err());
+        assert!(parse_range(&format!("{}w", i64::MAX)).is_err());
+
+        let runs = vec![
+            run(None, RunStatus::Finished, 100, 7200.0),
+            run(None, RunStatus::Failed, 300, 3600.0),
+            run(None, RunStatus::Finished, 200, 0.0),
+            run(Some("u@hpc:22"), RunStatus::Failed, 50, 0.0),
+        ];
+        let stats = summarize(&runs, "all", None, Utc::now(), |_| "time_limit");
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.by_status["Finished"], 2);
+        assert_eq!(stats.by_status["Failed"], 2);
+        assert_eq!(stats.core_hours, 3.0);
+        assert_eq!(stats.failure_causes["time_limit"], 2);
+        let local = &stats.per_host[0];
+        assert_eq!(local.host, "local");
+        assert_eq!(local.median_duration_secs, Some(200.0));
+        assert_eq!(stats.per_host[1].median_duration_secs, Some(50.0));
+    }
+}
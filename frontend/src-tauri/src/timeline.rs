@@ -0,0 +1,171 @@
+use crate::model::{ARCRun, RunStatus};
+use crate::{runs, stats};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Segment {
+    pub phase: String, // "queued", "conformers", "opt", "sp" or "post-processing"
+    pub start: String, // RFC 3339
+    pub end: String,   // RFC 3339
+}
+
+// One bar of the Gantt chart; phases may overlap since ARC works on several
+// species at once.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TimelineRow {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub status: RunStatus,
+    pub start: Option<String>, // queued_at, or started_at for runs not queued by the app
+    pub end: Option<String>,   // None while the run is queued or active
+    pub segments: Vec<Segment>,
+}
+
+fn parse_ts(ts: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn segment(phase: &str, start: &str, end: &str) -> Segment {
+    Segment {
+        phase: phase.to_string(),
+        start: start.to_string(),
+        end: end.to_string(),
+    }
+}
+
+fn row(run: &ARCRun, now: &str) -> TimelineRow {
+    let mut segments = Vec::new();
+    if let Some(queued) = run.queued_at.as_deref() {
+        let left = run.started_at.as_deref().or(run.finished_at.as_deref());
+        segments.push(segment("queued", queued, left.unwrap_or(now)));
+    }
+    let phases = run.progress.iter().flat_map(|p| &p.phases);
+    segments.extend(phases.map(|p| segment(&p.phase, &p.start, &p.end)));
+    // a finished run whose log never announced post-processing still spent
+    // the time after its last ESS job on it
+    let last_job = segments
+        .iter()
+        .filter(|s| s.phase != "queued")
+        .filter_map(|s| parse_ts(&s.end))
+        .max();
+    let posted = segments.iter().any(|s| s.phase == "post-processing");
+    if let (Some(last), Some(finished), false) = (
+        last_job,
+        run.finished_at
+            .as_deref()
+            .filter(|_| run.status == RunStatus::Finished),
+        posted,
+    ) {
+        if parse_ts(finished).is_some_and(|f| f > last) {
+            segments.push(segment("post-processing", &last.to_rfc3339(), finished));
+        }
+    }
+    TimelineRow {
+        id: run.id.clone(),
+        name: run.name.clone(),
+        host: run.host.clone().unwrap_or_else(|| "local".into()),
+        status: run.status.clone(),
+        start: run.queued_at.clone().or_else(|| run.started_at.clone()),
+        end: run.finished_at.clone(),
+        segments,
+    }
+}
+
+// Same ranges as runs_stats, matched against when a run was queued or started.
+pub fn runs_timeline(range: Option<String>) -> Result<Vec<TimelineRow>, String> {
+    let range = range.unwrap_or_else(|| "all".into());
+    let now = Utc::now();
+    let since = stats::parse_range(&range)?.map(|span| now - span);
+    let now = now.to_rfc3339();
+    let mut rows: Vec<TimelineRow> = runs::list_runs()
+        .iter()
+        .map(|run| row(run, &now))
+        .filter(|r| match since {
+            Some(since) => r
+                .start
+                .as_deref()
+                .and_then(parse_ts)
+                .is_some_and(|s| s >= since),
+            None => true,
+        })
+        .collect();
+    rows.sort_by(|a, b| a.start.cmp(&b.start));
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::row;
+    use crate::model::{ARCRun, PhaseSpan, RunProgress, RunStatus};
+
+    #[test]
+    fn row_adds_queue_wait_and_trailing_post_processing() {
+        let mut run: ARCRun = serde_json::from_value(serde_json::json!({
+            "id": "r1",
+            "name": "CH4",
+            "session": "arc",
+            "window_id": null,
+            "input_path": "/w/input.yml",
+            "work_dir": "/w",
+            "started_at": "2025-01-01T10:05:00+00:00",
+            "finished_at": "2025-01-01T12:00:00+00:00",
+            "status": "Finished",
+            "last_stdout": null,
+            "last_stderr": null,
+            "restarted_from": null,
+            "progress": null,
+            "archive_path": null,
+            "host": null,
+            "queued_at": "2025-01-01T10:00:00+00:00"
+        }))
+        .unwrap();
+        run.progress = Some(RunProgress {
+            phases: vec![PhaseSpan {
+                phase: "opt".into(),
+                start: "2025-01-01T10:06:00+00:00".into(),
+                end: "2025-01-01T11:30:00+00:00".into(),
+            }],
+            ..RunProgress::default()
+        });
+
+        let timeline = row(&run, "2025-01-02T00:00:00+00:00");
+        let phases: Vec<(&str, &str, &str)> = timeline
+            .segments
+            .iter()
+            .map(|s| (s.phase.as_str(), s.start.as_str(), s.end.as_str()))
+            .collect();
+        assert_eq!(
+            phases,
+            [
+                (
+                    "queued",
+                    "2025-01-01T10:00:00+00:00",
+                    "2025-01-01T10:05:00+00:00"
+                ),
+                (
+                    "opt",
+                    "2025-01-01T10:06:00+00:00",
+                    "2025-01-01T11:30:00+00:00"
+                ),
+                (
+                    "post-processing",
+                    "2025-01-01T11:30:00+00:00",
+                    "2025-01-01T12:00:00+00:00"
+                ),
+            ]
+        );
+        assert_eq!(timeline.start.as_deref(), Some("2025-01-01T10:00:00+00:00"));
+
+        run.status = RunStatus::Queued;
+        run.started_at = None;
+        run.finished_at = None;
+        run.progress = None;
+        let queued = row(&run, "2025-01-01T10:30:00+00:00");
+        assert_eq!(queued.segments.len(), 1);
+        assert_eq!(queued.segments[0].end, "2025-01-01T10:30:00+00:00");
+    }
+}
@@ -0,0 +1,285 @@
+use crate::backend::{LocalBackend, SshBackend, TmuxBackend};
+use crate::perf::TimedOutput;
+use crate::ssh::ExecOut;
+use crate::tmuxcmd::RemoteTmuxCommand;
+use crate::{coalesce, creds_from, listcache, run_remote_cmd, runs, tmuxpath, utf8, HostProfile};
+use serde::{Deserialize, Serialize};
+
+// Sessions and windows as the frontend sees them, and the tmux plumbing
+// under the commands: one invocation on the local server or over SSH, and
+// what listings and captures build on it.
+
+#[derive(Clone, Serialize)]
+pub struct TmuxWindow {
+    pub index: u32,
+    pub id: String,
+    pub name: String,
+    pub active: bool,
+    pub panes: u32,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TmuxSession {
+    pub name: String,
+    pub windows: u32,
+    pub attached: bool,
+}
+
+pub struct Snapshot {
+    pub windows: Vec<TmuxWindow>,
+    pub pane: String,
+}
+
+// How much a window listing fills in. Minimal leaves unnamed windows
+// unnamed instead of naming them after their pane's command, which costs
+// another list-windows call; badge counts and pickers do not need it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Detail {
+    Minimal,
+    #[default]
+    Full,
+}
+
+impl Detail {
+    // minimal listings are cached apart from full ones
+    pub fn cache_key(self, session: &str) -> String {
+        match self {
+            Detail::Full => session.to_string(),
+            Detail::Minimal => format!("{session}\u{0}minimal"),
+        }
+    }
+}
+
+pub fn is_placeholder_name(name: &str, index: u32) -> bool {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return true;
+    }
+    trimmed.parse::<u32>().map(|n| n == index).unwrap_or(false)
+}
+
+// Index, id and name of every window; windows without a name fall back to
+// their pane's command.
+pub const WINDOW_NAMES_FORMAT: &str =
+    "#{window_index}|#{window_id}|#{?window_name,#{window_name},#{pane_current_command}}";
+
+// Fills placeholder names from one WINDOW_NAMES_FORMAT listing of the session.
+pub fn apply_window_names(windows: &mut [TmuxWindow], listing: &str) {
+    for line in listing.lines() {
+        let mut it = line.splitn(3, '|');
+        let (Some(index), Some(id), Some(name)) = (it.next(), it.next(), it.next()) else {
+            continue;
+        };
+        let index: u32 = index.trim().parse().unwrap_or(u32::MAX);
+        let name = name.trim();
+        let found = windows.iter_mut().find(|w| {
+            if w.id.trim().is_empty() {
+                w.index == index
+            } else {
+                w.id.trim() == id.trim()
+            }
+        });
+        match found {
+            Some(win) if is_placeholder_name(&win.name, win.index) && !name.is_empty() => {
+                win.name = name.to_string();
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn ensure_window_ids(session: &str, windows: &mut [TmuxWindow]) {
+    for win in windows.iter_mut() {
+        if win.id.trim().is_empty() {
+            win.id = format!("{}:{}", session, win.index);
+        }
+    }
+}
+
+// Captures the last `last` lines of `target`; a pane without a tmux server
+// is empty. An identical capture already running answers this one too.
+pub fn local_capture(target: &str, last: u32) -> Result<String, String> {
+    coalesce::shared(None, format!("capture {target} {last}"), || {
+        LocalBackend.capture(target, last)
+    })
+}
+
+pub fn remote_capture_command(target: &str, lines: u32) -> String {
+    RemoteTmuxCommand::new("capture-pane")
+        .arg("-p")
+        .target(target)
+        .args(&["-S", &format!("-{lines}"), "-e", "-J"])
+        .build()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmuxCommand {
+    pub args: Vec<String>,
+}
+
+pub fn build_tmux_send_keys_commands(
+    target: &str,
+    keys: &str,
+    with_enter: bool,
+) -> Vec<TmuxCommand> {
+    let mut commands = vec![TmuxCommand {
+        args: vec![
+            "send-keys".into(),
+            "-t".into(),
+            target.to_string(),
+            "-l".into(),
+            keys.to_string(),
+        ],
+    }];
+    if with_enter {
+        commands.push(TmuxCommand {
+            args: vec![
+                "send-keys".into(),
+                "-t".into(),
+                target.to_string(),
+                "Enter".into(),
+            ],
+        });
+    }
+    commands
+}
+
+pub fn format_remote_tmux_command(command: &TmuxCommand) -> String {
+    RemoteTmuxCommand::from_args(&command.args).build()
+}
+
+// Runs one tmux invocation on the local server, or on the profile's host.
+pub fn tmux_exec(profile: Option<&HostProfile>, args: &[String]) -> Result<ExecOut, String> {
+    let out = match profile {
+        Some(profile) => {
+            let command = TmuxCommand {
+                args: args.to_vec(),
+            };
+            run_remote_cmd(&creds_from(profile), format_remote_tmux_command(&command))
+        }
+        None => {
+            let out = tmuxpath::local_command()?
+                .args(args)
+                .timed_output()
+                .map_err(tmuxpath::spawn_error)?;
+            let (stdout, lossy) = utf8::decode(&out.stdout);
+            Ok(ExecOut {
+                code: out.status.code().unwrap_or(1),
+                stdout,
+                stderr: String::from_utf8_lossy(&out.stderr).to_string(),
+                lossy,
+            })
+        }
+    };
+    let host = profile.map(runs::host_label);
+    match &out {
+        Ok(out) => tracing::debug!(
+            host,
+            ?args,
+            code = out.code,
+            stderr = out.stderr.trim(),
+            "tmux"
+        ),
+        Err(e) => tracing::warn!(host, ?args, "tmux failed: {e}"),
+    }
+    if listcache::invalidates(args) {
+        listcache::invalidate(profile);
+    }
+    out
+}
+
+pub fn remote_sessions(profile: &HostProfile) -> Result<Vec<TmuxSession>, String> {
+    SshBackend(profile.clone()).list_sessions()
+}
+
+// Falls back to the profile's default_session when the UI names none.
+pub fn session_or_default(
+    session: Option<String>,
+    profile: &HostProfile,
+) -> Result<String, String> {
+    session
+        .filter(|s| !s.trim().is_empty())
+        .or_else(|| profile.default_session.clone())
+        .ok_or_else(|| "no session given and the profile has no default_session".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_window_names, build_tmux_send_keys_commands, format_remote_tmux_command, TmuxCommand,
+        TmuxWindow,
+    };
+
+    #[test]
+    fn build_commands_include_enter_when_requested() {
+        let commands = build_tmux_send_keys_commands("arc:0", "ls -la", true);
+        assert_eq!(
+            commands,
+            vec![
+                TmuxCommand {
+                    args: vec![
+                        "send-keys".into(),
+                        "-t".into(),
+                        "arc:0".into(),
+                        "-l".into(),
+                        "ls -la".into(),
+                    ],
+                },
+                TmuxCommand {
+                    args: vec![
+                        "send-keys".into(),
+                        "-t".into(),
+                        "arc:0".into(),
+                        "Enter".into(),
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn build_commands_omit_enter_when_not_requested() {
+        let commands = build_tmux_send_keys_commands("arc:1", "whoami", false);
+        assert_eq!(
+            commands,
+            vec![TmuxCommand {
+                args: vec![
+                    "send-keys".into(),
+                    "-t".into(),
+                    "arc:1".into(),
+                    "-l".into(),
+                    "whoami".into(),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn remote_format_escapes_arguments() {
+        let commands = build_tmux_send_keys_commands("pane @1", "echo 'hi'", true);
+        let literal = format_remote_tmux_command(&commands[0]);
+        let enter = format_remote_tmux_command(&commands[1]);
+        assert_eq!(literal, r"tmux send-keys -t 'pane @1' -l 'echo '\''hi'\'''");
+        assert_eq!(enter, "tmux send-keys -t 'pane @1' Enter");
+    }
+
+    #[test]
+    fn placeholder_names_come_from_one_listing() {
+        let window = |index: u32, id: &str, name: &str| TmuxWindow {
+            index,
+            id: id.into(),
+            name: name.into(),
+            active: false,
+            panes: 1,
+        };
+        let mut windows = vec![
+            window(0, "@1", "0"),
+            window(1, "@2", "arc"),
+            window(2, "", ""),
+        ];
+        apply_window_names(&mut windows, "0|@1|python\n1|@2|bash\n2|@3|htop\n");
+        let names: Vec<&str> = windows.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, ["python", "arc", "htop"]);
+    }
+}
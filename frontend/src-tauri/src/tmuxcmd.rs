@@ -0,0 +1,73 @@
+use std::borrow::Cow;
+
+// One argument quoted for the remote shell.
+pub fn quote(arg: &str) -> String {
+    shell_escape::escape(Cow::from(arg)).to_string()
+}
+
+// A tmux invocation for a remote shell. Arguments are kept as given and
+// quoted once, when the command line is built, so no caller escapes a
+// session name or target itself and nothing gets escaped twice.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteTmuxCommand {
+    args: Vec<String>,
+}
+
+impl RemoteTmuxCommand {
+    pub fn new(subcommand: &str) -> Self {
+        Self::from_args(&[subcommand])
+    }
+
+    pub fn from_args<S: AsRef<str>>(args: &[S]) -> Self {
+        Self {
+            args: args.iter().map(|a| a.as_ref().to_string()).collect(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<S: AsRef<str>>(mut self, args: &[S]) -> Self {
+        self.args
+            .extend(args.iter().map(|a| a.as_ref().to_string()));
+        self
+    }
+
+    // `-t target`; `session:index`, a window id or a bare session all go
+    // through as one argument.
+    pub fn target(self, target: &str) -> Self {
+        self.arg("-t").arg(target)
+    }
+
+    pub fn build(&self) -> String {
+        let quoted: Vec<String> = self.args.iter().map(|a| quote(a)).collect();
+        format!("tmux {}", quoted.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RemoteTmuxCommand;
+
+    #[test]
+    fn arguments_are_quoted_exactly_once() {
+        let list = RemoteTmuxCommand::new("list-windows")
+            .target("it's $HOME")
+            .args(&["-F", "#{window_index}|#{window_name}"])
+            .build();
+        assert_eq!(
+            list,
+            r"tmux list-windows -t 'it'\''s $HOME' -F '#{window_index}|#{window_name}'"
+        );
+        let capture = RemoteTmuxCommand::new("capture-pane")
+            .arg("-p")
+            .target("arc run:3")
+            .args(&["-S", "-200"])
+            .build();
+        assert_eq!(capture, "tmux capture-pane -p -t 'arc run:3' -S -200");
+        let attach = RemoteTmuxCommand::from_args(&["-CC", "attach-session", "-t", "arc"]).build();
+        assert_eq!(attach, "tmux -CC attach-session -t arc");
+    }
+}
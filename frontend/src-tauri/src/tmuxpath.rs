@@ -0,0 +1,126 @@
+use crate::listcache;
+use crate::ssh::{self, SshCreds};
+use once_cell::sync::{Lazy, OnceCell};
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command as PCommand;
+use std::sync::{Mutex, RwLock};
+
+// the local tmux binary, resolved on first use; swapped for an empty cell
+// when a spawn says it is gone
+static LOCAL: Lazy<RwLock<OnceCell<PathBuf>>> = Lazy::new(|| RwLock::new(OnceCell::new()));
+
+// remote tmux paths per (user, host, port, env preset), as `command -v`
+// printed them under that preset
+type RemoteKey = (String, String, u16, String);
+
+static REMOTE: Lazy<Mutex<HashMap<RemoteKey, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn local() -> Result<PathBuf, String> {
+    LOCAL
+        .read()
+        .unwrap()
+        .get_or_try_init(|| which::which("tmux"))
+        .cloned()
+        .map_err(|e| e.to_string())
+}
+
+// `-L` socket name of the local server, for a server kept apart from the
+// user's default one, like the integration tests' throwaway server. None is
+// tmux's default socket.
+static LOCAL_SOCKET: RwLock<Option<String>> = RwLock::new(None);
+
+pub fn set_local_socket(name: Option<String>) {
+    *LOCAL_SOCKET.write().unwrap() = name;
+    // listings of the server talked to before
+    listcache::invalidate(None);
+}
+
+// The local tmux, talking to the local socket.
+pub fn local_command() -> Result<PCommand, String> {
+    let mut command = PCommand::new(local()?);
+    if let Some(socket) = LOCAL_SOCKET.read().unwrap().as_deref() {
+        command.args(["-L", socket]);
+    }
+    Ok(command)
+}
+
+// For spawns of the local path: a binary that went away since it was
+// resolved is looked up again next time.
+pub fn spawn_error(e: io::Error) -> String {
+    if e.kind() == io::ErrorKind::NotFound {
+        *LOCAL.write().unwrap() = OnceCell::new();
+    }
+    e.to_string()
+}
+
+fn remote_key(creds: &SshCreds<'_>) -> RemoteKey {
+    (
+        creds.user.to_string(),
+        creds.host.to_string(),
+        creds.port,
+        creds.env_preset.unwrap_or_default().to_string(),
+    )
+}
+
+pub fn remote_cached(creds: &SshCreds<'_>) -> Option<String> {
+    REMOTE.lock().unwrap().get(&remote_key(creds)).cloned()
+}
+
+// Resolves the host's tmux once, with a plain exec of the same shell
+// remote_shell_command builds. A host without tmux is asked again later.
+pub fn resolve_remote(creds: &SshCreds<'_>, shell_command: &str) {
+    if remote_cached(creds).is_some() {
+        return;
+    }
+    let Ok(out) = ssh::exec(creds, shell_command) else {
+        return;
+    };
+    let path = out.stdout.trim();
+    if out.code == 0 && path.starts_with('/') {
+        REMOTE
+            .lock()
+            .unwrap()
+            .insert(remote_key(creds), path.to_string());
+    }
+}
+
+// 127 is the shell's "command not found"; the cached path may be stale.
+pub fn remote_exited(creds: &SshCreds<'_>, code: i32) {
+    if code == 127 {
+        REMOTE.lock().unwrap().remove(&remote_key(creds));
+    }
+}
+
+// Makes `tmux` in a remote command run the resolved binary.
+pub fn remote_alias(path: &str) -> String {
+    format!(
+        "tmux() {{ {} \"$@\"; }}; ",
+        shell_escape::escape(path.into())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{remote_alias, spawn_error, LOCAL};
+    use std::io;
+    use std::path::PathBuf;
+
+    #[test]
+    fn a_missing_binary_is_resolved_again() {
+        LOCAL
+            .read()
+            .unwrap()
+            .get_or_init(|| PathBuf::from("/gone/tmux"));
+        spawn_error(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert!(LOCAL.read().unwrap().get().is_some());
+        spawn_error(io::Error::from(io::ErrorKind::NotFound));
+        assert!(LOCAL.read().unwrap().get().is_none());
+
+        assert_eq!(
+            remote_alias("/opt/tmux 3/bin/tmux"),
+            "tmux() { '/opt/tmux 3/bin/tmux' \"$@\"; }; "
+        );
+    }
+}
@@ -0,0 +1,93 @@
+// Prefix of a capture in which bytes that are not UTF-8 had to be replaced
+// with U+FFFD; the text follows. Like the other capture markers it starts
+// with NUL, which captured text never has.
+pub const LOSSY_PREFIX: &str = "\u{0}lossy\u{0}";
+
+// Decodes UTF-8 that arrives in pieces. A character cut in two by a read
+// waits for its other half; only bytes that can never be UTF-8 are replaced,
+// and lossy() says whether any were.
+#[derive(Debug, Default)]
+pub struct Utf8Decoder {
+    pending: Vec<u8>,
+    lossy: bool,
+}
+
+// Length of a character started at the end of `bytes` but not finished.
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let b = bytes[bytes.len() - back];
+        if b & 0xC0 == 0x80 {
+            continue; // continuation byte
+        }
+        let len = match b {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        return if len > back { back } else { 0 };
+    }
+    0
+}
+
+impl Utf8Decoder {
+    pub fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let cut = self.pending.len() - incomplete_tail(&self.pending);
+        let rest = self.pending.split_off(cut);
+        let ready = std::mem::replace(&mut self.pending, rest);
+        self.decode(ready)
+    }
+
+    // Whatever is left once nothing more is coming.
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        self.decode(rest)
+    }
+
+    pub fn lossy(&self) -> bool {
+        self.lossy
+    }
+
+    fn decode(&mut self, bytes: Vec<u8>) -> String {
+        String::from_utf8(bytes).unwrap_or_else(|e| {
+            self.lossy = true;
+            String::from_utf8_lossy(e.as_bytes()).into_owned()
+        })
+    }
+}
+
+// A whole buffer at once, and whether anything had to be replaced.
+pub fn decode(bytes: &[u8]) -> (String, bool) {
+    let mut decoder = Utf8Decoder::default();
+    let mut text = decoder.push(bytes);
+    text.push_str(&decoder.finish());
+    (text, decoder.lossy())
+}
+
+// `text` behind LOSSY_PREFIX when it is lossy.
+pub fn mark(text: String, lossy: bool) -> String {
+    if lossy {
+        format!("{LOSSY_PREFIX}{text}")
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, Utf8Decoder};
+
+    #[test]
+    fn split_characters_survive_and_bad_bytes_are_flagged() {
+        let bytes = "ΔE → 0.5 ✓".as_bytes();
+        let mut decoder = Utf8Decoder::default();
+        let text: String = bytes.chunks(3).map(|chunk| decoder.push(chunk)).collect();
+        assert_eq!(text + &decoder.finish(), "ΔE → 0.5 ✓");
+        assert!(!decoder.lossy());
+
+        assert_eq!(decode(b"a\xffb"), ("a\u{fffd}b".to_string(), true));
+        // a character still missing its end when the output stops
+        assert_eq!(decode(b"ok \xe2\x86"), ("ok \u{fffd}".to_string(), true));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::model::EnvVersions;
+use crate::{creds_from, run_remote_cmd, HostProfile};
+use std::borrow::Cow;
+use std::process::Command as PCommand;
+
+// Run by the environment's own python with ARC.py as argv[1]. Every lookup
+// is optional: a checkout without git or an RMG-Py without a database
+// still reports what it can. Only the last line of output is JSON.
+const PROBE: &str = r#"
+import json, os, subprocess, sys
+
+def git_head(path):
+    try:
+        out = subprocess.run(['git', '-C', path, 'rev-parse', '--short', 'HEAD'],
+                             capture_output=True, text=True, timeout=10)
+        return out.stdout.strip() or None
+    except Exception:
+        return None
+
+def package_version(name):
+    try:
+        from importlib.metadata import version
+        return version(name)
+    except Exception:
+        return None
+
+info = {}
+arc_dir = os.path.dirname(os.path.abspath(sys.argv[1]))
+sys.path.insert(0, arc_dir)
+try:
+    from arc.version import __version__ as arc_version
+    info['arc'] = arc_version
+except Exception:
+    info['arc'] = package_version('arc')
+info['arc_commit'] = git_head(arc_dir)
+try:
+    import rmgpy
+    info['rmg_py'] = getattr(rmgpy, '__version__', None) or package_version('rmgpy')
+    info['rmg_py_commit'] = git_head(os.path.dirname(os.path.dirname(rmgpy.__file__)))
+    from rmgpy import settings
+    database = settings['database.directory']
+    info['rmg_database'] = package_version('rmgdatabase')
+    info['rmg_database_commit'] = git_head(database)
+except Exception:
+    pass
+print(json.dumps(info))
+"#;
+
+fn parse_probe(stdout: &str) -> Result<EnvVersions, String> {
+    let line = stdout
+        .lines()
+        .rev()
+        .find(|l| l.trim_start().starts_with('{'))
+        .ok_or_else(|| "version probe printed nothing".to_string())?;
+    serde_json::from_str(line).map_err(|e| format!("bad version probe output: {e}"))
+}
+
+// With a conda env the env's python is used, the same way runs are launched.
+fn probe_command(python_path: &str, arc_path: &str, conda_env: Option<&str>) -> String {
+    let python = if conda_env.is_some() {
+        "python"
+    } else {
+        python_path
+    };
+    let probe = format!(
+        "{} -c {} {}",
+        shell_escape::escape(Cow::from(python)),
+        shell_escape::escape(Cow::from(PROBE)),
+        shell_escape::escape(Cow::from(arc_path))
+    );
+    match conda_env {
+        Some(env) => format!(
+            "conda activate {} && {}",
+            shell_escape::escape(Cow::from(env)),
+            probe
+        ),
+        None => probe,
+    }
+}
+
+pub fn detect_local(
+    python_path: &str,
+    arc_path: &str,
+    conda_env: Option<&str>,
+) -> Result<EnvVersions, String> {
+    let out = PCommand::new("bash")
+        .args(["-lc", &probe_command(python_path, arc_path, conda_env)])
+        .output()
+        .map_err(|e| format!("run version probe: {e}"))?;
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr).trim().to_string());
+    }
+    parse_probe(&String::from_utf8_lossy(&out.stdout))
+}
+
+pub fn detect_remote(
+    profile: &HostProfile,
+    python_path: &str,
+    arc_path: &str,
+    conda_env: Option<&str>,
+) -> Result<EnvVersions, String> {
+    let command = probe_command(python_path, arc_path, conda_env);
+    let out = run_remote_cmd(&creds_from(profile), command)?;
+    if out.code != 0 {
+        return Err(out.stderr.trim().to_string());
+    }
+    parse_probe(&out.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_probe;
+
+    #[test]
+    fn probe_output_takes_last_json_line() {
+        let stdout = "some banner from conda\n{\"arc\": \"1.1.0\", \"arc_commit\": \"a1b2c3d\", \"rmg_py\": null}\n";
+        let versions = parse_probe(stdout).unwrap();
+        assert_eq!(versions.arc.as_deref(), Some("1.1.0"));
+        assert_eq!(versions.arc_commit.as_deref(), Some("a1b2c3d"));
+        assert_eq!(versions.rmg_py, None);
+        assert!(parse_probe("Traceback\n").is_err());
+    }
+}
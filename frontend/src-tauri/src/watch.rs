@@ -0,0 +1,554 @@
+use crate::{tmux_exec, HostProfile};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+static MANAGER: Lazy<WatchManager> = Lazy::new(WatchManager::new);
+
+pub struct WatchManager {
+    inner: Mutex<HashMap<String, WatchHandle>>,
+}
+
+struct WatchHandle {
+    stop_tx: mpsc::Sender<()>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WindowActivity {
+    id: String,
+    index: u32,
+    name: String,
+    activity: u64,
+}
+
+#[derive(Debug, Default)]
+struct ActivityState {
+    activity: u64,
+    silent_reported: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ActivityEvent {
+    Active(WindowActivity),
+    Silent(WindowActivity, u64),
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn parse_activity(stdout: &str) -> Vec<WindowActivity> {
+    stdout
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|line| {
+            let mut it = line.split('|');
+            let index = it.next().unwrap_or("0").trim().parse().unwrap_or(0);
+            let id = it.next().unwrap_or("").trim().to_string();
+            let activity = it.next().unwrap_or("0").trim().parse().unwrap_or(0);
+            // the name goes last so a '|' inside it cannot shift the other fields
+            let name = it.collect::<Vec<_>>().join("|");
+            WindowActivity {
+                id,
+                index,
+                name,
+                activity,
+            }
+        })
+        .collect()
+}
+
+// The first sighting of a window only establishes a baseline; activity is
+// reported when `window_activity` moves forward, silence once per quiet spell.
+fn diff_activity(
+    seen: &mut HashMap<String, ActivityState>,
+    windows: Vec<WindowActivity>,
+    now: u64,
+    silence_secs: u64,
+) -> Vec<ActivityEvent> {
+    let mut events = Vec::new();
+    seen.retain(|id, _| windows.iter().any(|w| &w.id == id));
+    for win in windows {
+        let state = match seen.get_mut(&win.id) {
+            Some(state) => {
+                if win.activity > state.activity {
+                    state.activity = win.activity;
+                    state.silent_reported = false;
+                    events.push(ActivityEvent::Active(win.clone()));
+                }
+                state
+            }
+            None => seen.entry(win.id.clone()).or_insert(ActivityState {
+                activity: win.activity,
+                silent_reported: false,
+            }),
+        };
+        let quiet = now.saturating_sub(state.activity);
+        if !state.silent_reported && quiet >= silence_secs {
+            state.silent_reported = true;
+            events.push(ActivityEvent::Silent(win, quiet / 60));
+        }
+    }
+    events
+}
+
+fn capture_lines(stdout: &str) -> Vec<String> {
+    let mut lines: Vec<String> = stdout.lines().map(|l| l.trim_end().to_string()).collect();
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+// Index into `cur` where output not present in `prev` begins: the longest
+// tail of the previous capture that reappears at the head of the new one.
+fn new_output_start(prev: &[String], cur: &[String]) -> usize {
+    let max = prev.len().min(cur.len());
+    (1..=max)
+        .rev()
+        .find(|&k| prev[prev.len() - k..] == cur[..k])
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PatternMatch {
+    pattern: String,
+    line: String,
+    context: Vec<String>,
+}
+
+fn scan_patterns(
+    patterns: &[Regex],
+    lines: &[String],
+    start: usize,
+    context_lines: usize,
+) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    for (idx, line) in lines.iter().enumerate().skip(start) {
+        for re in patterns {
+            if re.is_match(line) {
+                let from = idx.saturating_sub(context_lines);
+                let to = (idx + context_lines + 1).min(lines.len());
+                matches.push(PatternMatch {
+                    pattern: re.as_str().to_string(),
+                    line: line.clone(),
+                    context: lines[from..to].to_vec(),
+                });
+            }
+        }
+    }
+    matches
+}
+
+// Idle panes are polled less and less often, up to IDLE_BACKOFF times the
+// requested interval; any change snaps back to it.
+const IDLE_BACKOFF: u32 = 8;
+
+fn next_interval(current: Duration, base: Duration, changed: bool) -> Duration {
+    if changed {
+        base
+    } else {
+        (current * 3 / 2).min(base * IDLE_BACKOFF)
+    }
+}
+
+fn content_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl WatchManager {
+    const ACTIVITY_EVENT: &'static str = "window-activity";
+    const SILENT_EVENT: &'static str = "window-silent";
+    const PATTERN_EVENT: &'static str = "pane-pattern-matched";
+    const PANE_EVENT: &'static str = "pane-updated";
+
+    fn new() -> Self {
+        Self {
+            inner: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn global() -> &'static Self {
+        &MANAGER
+    }
+
+    pub fn start_activity(
+        &self,
+        app: AppHandle,
+        profile: Option<HostProfile>,
+        session: String,
+        interval: Duration,
+        silence_minutes: u64,
+    ) -> Result<String, String> {
+        let args: Vec<String> = vec![
+            "list-windows".into(),
+            "-t".into(),
+            session.clone(),
+            "-F".into(),
+            "#{window_index}|#{window_id}|#{window_activity}|#{window_name}".into(),
+        ];
+        // fail fast on a bad session instead of inside the thread
+        let first = tmux_exec(profile.as_ref(), &args)?;
+        if first.code != 0 {
+            return Err(first.stderr);
+        }
+
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let watch_id = uuid::Uuid::new_v4().to_string();
+        let thread_id = watch_id.clone();
+        let thread = thread::spawn(move || {
+            let mut seen: HashMap<String, ActivityState> = HashMap::new();
+            let mut out = Some(first);
+            loop {
+                let result = match out.take() {
+                    Some(first) => Ok(first),
+                    None => tmux_exec(profile.as_ref(), &args),
+                };
+                if let Ok(result) = result {
+                    if result.code == 0 {
+                        let windows = parse_activity(&result.stdout);
+                        let events =
+                            diff_activity(&mut seen, windows, unix_now(), silence_minutes * 60);
+                        for event in events {
+                            let (name, win, minutes) = match event {
+                                ActivityEvent::Active(win) => {
+                                    (WatchManager::ACTIVITY_EVENT, win, None)
+                                }
+                                ActivityEvent::Silent(win, minutes) => {
+                                    (WatchManager::SILENT_EVENT, win, Some(minutes))
+                                }
+                            };
+                            let payload = json!({
+                                "watch_id": thread_id,
+                                "session": session,
+                                "window_id": win.id,
+                                "window_index": win.index,
+                                "window_name": win.name,
+                                "last_activity": win.activity,
+                                "silent_minutes": minutes,
+                                "threshold_minutes": silence_minutes,
+                            });
+                            let _ = app.emit(name, payload);
+                        }
+                    }
+                }
+                match stop_rx.recv_timeout(interval) {
+                    Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                    _ => break,
+                }
+            }
+        });
+
+        let handle = WatchHandle {
+            stop_tx,
+            thread: Some(thread),
+        };
+        let mut inner = self.inner.lock().unwrap();
+        inner.insert(watch_id.clone(), handle);
+        Ok(watch_id)
+    }
+
+    // Only output that shows up after the watch starts is scanned, so an old
+    // traceback in the scrollback does not fire on registration.
+    pub fn start_patterns(
+        &self,
+        app: AppHandle,
+        profile: Option<HostProfile>,
+        target: String,
+        patterns: Vec<Regex>,
+        context_lines: usize,
+        interval: Duration,
+    ) -> Result<String, String> {
+        let args: Vec<String> = vec![
+            "capture-pane".into(),
+            "-p".into(),
+            "-J".into(),
+            "-t".into(),
+            target.clone(),
+            "-S".into(),
+            "-400".into(),
+        ];
+        let first = tmux_exec(profile.as_ref(), &args)?;
+        if first.code != 0 {
+            return Err(first.stderr);
+        }
+
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let watch_id = uuid::Uuid::new_v4().to_string();
+        let thread_id = watch_id.clone();
+        let thread = thread::spawn(move || {
+            let mut prev = capture_lines(&first.stdout);
+            while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                let out = match tmux_exec(profile.as_ref(), &args) {
+                    Ok(out) if out.code == 0 => out,
+                    _ => continue,
+                };
+                let cur = capture_lines(&out.stdout);
+                let start = new_output_start(&prev, &cur);
+                for hit in scan_patterns(&patterns, &cur, start, context_lines) {
+                    let payload = json!({
+                        "watch_id": thread_id,
+                        "target": target,
+                        "pattern": hit.pattern,
+                        "line": hit.line,
+                        "context": hit.context,
+                    });
+                    let _ = app.emit(WatchManager::PATTERN_EVENT, payload);
+                }
+                prev = cur;
+            }
+        });
+
+        let handle = WatchHandle {
+            stop_tx,
+            thread: Some(thread),
+        };
+        let mut inner = self.inner.lock().unwrap();
+        inner.insert(watch_id.clone(), handle);
+        Ok(watch_id)
+    }
+
+    // Sends the pane's content right away, then again whenever it changes.
+    pub fn start_pane(
+        &self,
+        app: AppHandle,
+        profile: Option<HostProfile>,
+        target: String,
+        lines: u32,
+        interval: Duration,
+    ) -> Result<String, String> {
+        let args: Vec<String> = vec![
+            "capture-pane".into(),
+            "-p".into(),
+            "-e".into(),
+            "-J".into(),
+            "-t".into(),
+            target.clone(),
+            "-S".into(),
+            format!("-{lines}"),
+        ];
+        let first = tmux_exec(profile.as_ref(), &args)?;
+        if first.code != 0 {
+            return Err(first.stderr);
+        }
+
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let watch_id = uuid::Uuid::new_v4().to_string();
+        let thread_id = watch_id.clone();
+        let thread = thread::spawn(move || {
+            let mut last = None;
+            let mut out = Some(first);
+            let mut delay = interval;
+            loop {
+                let result = match out.take() {
+                    Some(first) => Ok(first),
+                    None => tmux_exec(profile.as_ref(), &args),
+                };
+                let mut changed = false;
+                if let Ok(result) = result {
+                    let hash = content_hash(&result.stdout);
+                    if result.code == 0 && last != Some(hash) {
+                        last = Some(hash);
+                        changed = true;
+                        let payload = json!({
+                            "watch_id": thread_id,
+                            "target": target,
+                            "content": result.stdout,
+                        });
+                        let _ = app.emit(WatchManager::PANE_EVENT, payload);
+                    }
+                }
+                delay = next_interval(delay, interval, changed);
+                match stop_rx.recv_timeout(delay) {
+                    Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                    _ => break,
+                }
+            }
+        });
+
+        let handle = WatchHandle {
+            stop_tx,
+            thread: Some(thread),
+        };
+        let mut inner = self.inner.lock().unwrap();
+        inner.insert(watch_id.clone(), handle);
+        Ok(watch_id)
+    }
+
+    pub fn stop(&self, id: &str) -> Result<(), String> {
+        let handle = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.remove(id)
+        };
+        match handle {
+            Some(mut handle) => {
+                let _ = handle.stop_tx.send(());
+                if let Some(thread) = handle.thread.take() {
+                    let _ = thread.join();
+                }
+                Ok(())
+            }
+            None => Err("watch not running".into()),
+        }
+    }
+}
+
+pub fn start_activity(
+    app: AppHandle,
+    profile: Option<HostProfile>,
+    session: String,
+    interval_secs: Option<u64>,
+    silence_minutes: Option<u64>,
+) -> Result<String, String> {
+    let interval = Duration::from_secs(interval_secs.unwrap_or(30).max(5));
+    let silence_minutes = silence_minutes.unwrap_or(10).max(1);
+    WatchManager::global().start_activity(app, profile, session, interval, silence_minutes)
+}
+
+pub fn start_patterns(
+    app: AppHandle,
+    profile: Option<HostProfile>,
+    target: String,
+    patterns: Vec<String>,
+    context_lines: Option<usize>,
+    interval_secs: Option<u64>,
+) -> Result<String, String> {
+    if patterns.is_empty() {
+        return Err("no patterns given".into());
+    }
+    let compiled = patterns
+        .iter()
+        .map(|p| Regex::new(p).map_err(|e| format!("invalid pattern {p:?}: {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let interval = Duration::from_secs(interval_secs.unwrap_or(5).max(1));
+    WatchManager::global().start_patterns(
+        app,
+        profile,
+        target,
+        compiled,
+        context_lines.unwrap_or(3),
+        interval,
+    )
+}
+
+pub fn start_pane(
+    app: AppHandle,
+    profile: Option<HostProfile>,
+    target: String,
+    interval_ms: Option<u64>,
+    lines: Option<u32>,
+) -> Result<String, String> {
+    let interval = Duration::from_millis(interval_ms.unwrap_or(2000).max(250));
+    WatchManager::global().start_pane(app, profile, target, lines.unwrap_or(800), interval)
+}
+
+pub fn stop_watch(id: String) -> Result<(), String> {
+    WatchManager::global().stop(&id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        diff_activity, new_output_start, next_interval, parse_activity, scan_patterns,
+        ActivityEvent, WindowActivity,
+    };
+    use regex::Regex;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn win(id: &str, activity: u64) -> WindowActivity {
+        WindowActivity {
+            id: id.into(),
+            index: 0,
+            name: "arc".into(),
+            activity,
+        }
+    }
+
+    #[test]
+    fn parse_activity_keeps_pipes_in_names() {
+        let windows = parse_activity("0|@1|1700000000|a|b\n");
+        assert_eq!(
+            windows,
+            vec![WindowActivity {
+                id: "@1".into(),
+                index: 0,
+                name: "a|b".into(),
+                activity: 1_700_000_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn activity_reported_after_baseline_and_silence_once() {
+        let mut seen = HashMap::new();
+        assert!(diff_activity(&mut seen, vec![win("@1", 100)], 110, 600).is_empty());
+        assert_eq!(
+            diff_activity(&mut seen, vec![win("@1", 120)], 130, 600),
+            vec![ActivityEvent::Active(win("@1", 120))]
+        );
+        assert_eq!(
+            diff_activity(&mut seen, vec![win("@1", 120)], 720, 600),
+            vec![ActivityEvent::Silent(win("@1", 120), 10)]
+        );
+        assert!(diff_activity(&mut seen, vec![win("@1", 120)], 900, 600).is_empty());
+    }
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn new_output_start_skips_overlap_with_previous_capture() {
+        let prev = lines(&["a", "b", "c"]);
+        assert_eq!(new_output_start(&prev, &lines(&["b", "c", "d", "e"])), 2);
+        assert_eq!(new_output_start(&prev, &prev), 3);
+        assert_eq!(new_output_start(&prev, &lines(&["x", "y"])), 0);
+    }
+
+    #[test]
+    fn scan_patterns_reports_context_around_new_matches() {
+        let cur = lines(&[
+            "Traceback (old)",
+            "x",
+            "Traceback (most recent call last):",
+            "y",
+        ]);
+        let re = vec![Regex::new("Traceback").unwrap()];
+        let hits = scan_patterns(&re, &cur, 2, 1);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line, "Traceback (most recent call last):");
+        assert_eq!(
+            hits[0].context,
+            lines(&["x", "Traceback (most recent call last):", "y"])
+        );
+    }
+
+    #[test]
+    fn idle_panes_back_off_until_they_change() {
+        let base = Duration::from_secs(2);
+        let mut delay = base;
+        for _ in 0..20 {
+            delay = next_interval(delay, base, false);
+        }
+        assert_eq!(delay, base * 8);
+        assert_eq!(
+            next_interval(Duration::from_secs(2), base, false),
+            Duration::from_secs(3)
+        );
+        assert_eq!(next_interval(delay, base, true), base);
+    }
+}
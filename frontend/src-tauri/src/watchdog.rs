@@ -0,0 +1,127 @@
+use crate::{creds_from, run_remote_cmd, HostProfile};
+use crate::{hostinfo, runs};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static TRACKER: Lazy<Mutex<Tracker>> = Lazy::new(|| Mutex::new(Tracker::default()));
+
+// What counts as a run making progress: arc.log growing, or an ESS job
+// starting or ending.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Activity {
+    pub log_size: Option<u64>,
+    pub running_jobs: Vec<String>,
+    pub jobs_completed: u32,
+}
+
+#[derive(Debug, Default)]
+struct Tracker {
+    seen: HashMap<String, (Activity, Instant)>,
+}
+
+impl Tracker {
+    fn quiet_for(&mut self, run_id: &str, activity: Activity, now: Instant) -> Duration {
+        match self.seen.get_mut(run_id) {
+            Some((last, since)) if *last == activity => now.duration_since(*since),
+            Some(entry) => {
+                *entry = (activity, now);
+                Duration::ZERO
+            }
+            None => {
+                self.seen.insert(run_id.to_string(), (activity, now));
+                Duration::ZERO
+            }
+        }
+    }
+}
+
+// How long the run has looked exactly like `activity`.
+pub fn quiet_for(run_id: &str, activity: Activity) -> Duration {
+    TRACKER
+        .lock()
+        .unwrap()
+        .quiet_for(run_id, activity, Instant::now())
+}
+
+pub fn forget(run_id: &str) {
+    TRACKER.lock().unwrap().seen.remove(run_id);
+}
+
+fn remote_first_line(profile: &HostProfile, command: String) -> Option<String> {
+    let out = run_remote_cmd(&creds_from(profile), command).ok()?;
+    let line = out.stdout.lines().next()?.trim().to_string();
+    (!line.is_empty()).then_some(line)
+}
+
+pub fn log_size(profile: Option<&HostProfile>, work_dir: &Path) -> Option<u64> {
+    match profile {
+        Some(profile) => {
+            let dir = shell_escape::escape(work_dir.to_string_lossy());
+            remote_first_line(
+                profile,
+                format!(
+                    "{} {dir}/arc.log {dir}/*/arc.log 2>/dev/null",
+                    hostinfo::tool_flavor(profile).stat("%s")
+                ),
+            )?
+            .parse()
+            .ok()
+        }
+        None => {
+            let log = runs::find_project_file(work_dir, "arc.log")?;
+            std::fs::metadata(log).ok().map(|m| m.len())
+        }
+    }
+}
+
+pub fn last_log_lines(profile: Option<&HostProfile>, work_dir: &Path, count: usize) -> Vec<String> {
+    let text = match profile {
+        Some(profile) => {
+            let dir = shell_escape::escape(work_dir.to_string_lossy());
+            let command = format!(
+                "tail -n {count} $(ls -1d {dir}/arc.log {dir}/*/arc.log 2>/dev/null | head -n 1)"
+            );
+            run_remote_cmd(&creds_from(profile), command)
+                .map(|out| out.stdout)
+                .unwrap_or_default()
+        }
+        None => runs::find_project_file(work_dir, "arc.log")
+            .and_then(|log| std::fs::read_to_string(log).ok())
+            .unwrap_or_default(),
+    };
+    let lines: Vec<String> = text.lines().map(|l| l.to_string()).collect();
+    lines[lines.len().saturating_sub(count)..].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Activity, Tracker};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn quiet_time_resets_on_any_activity() {
+        let mut tracker = Tracker::default();
+        let start = Instant::now();
+        let idle = Activity {
+            log_size: Some(100),
+            running_jobs: vec!["opt_a1".into()],
+            jobs_completed: 0,
+        };
+        assert_eq!(tracker.quiet_for("r", idle.clone(), start), Duration::ZERO);
+        let later = start + Duration::from_secs(600);
+        assert_eq!(
+            tracker.quiet_for("r", idle.clone(), later),
+            Duration::from_secs(600)
+        );
+
+        let job_ended = Activity {
+            running_jobs: vec![],
+            jobs_completed: 1,
+            ..idle
+        };
+        assert_eq!(tracker.quiet_for("r", job_ended, later), Duration::ZERO);
+    }
+}
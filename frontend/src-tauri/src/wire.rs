@@ -0,0 +1,80 @@
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tauri::ipc::Response;
+
+// Names of the binary encodings, as capabilities() announces them.
+pub const CAPTURE_FORMAT: &str = "utf8";
+pub const SNAPSHOT_FORMAT: &str = "snapshot-v1";
+
+// What this backend can put on the IPC wire, so the frontend only asks for
+// what it will get.
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    pub binary_capture: &'static str,
+    pub binary_snapshot: &'static str,
+    pub compression: Vec<&'static str>,
+    pub not_modified: bool,
+    pub spill: bool,
+    pub lossy_marker: bool,
+    pub stale_id_marker: bool,
+}
+
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        binary_capture: CAPTURE_FORMAT,
+        binary_snapshot: SNAPSHOT_FORMAT,
+        compression: vec!["gzip"],
+        not_modified: true,
+        spill: true,
+        lossy_marker: true,
+        stale_id_marker: true,
+    }
+}
+
+// A capture as a JSON string, or as its raw UTF-8 bytes (an ArrayBuffer on
+// the frontend) when `binary`.
+pub fn text(text: String, binary: bool) -> Response {
+    if binary {
+        Response::new(text.into_bytes())
+    } else {
+        Response::new(serde_json::Value::String(text).to_string())
+    }
+}
+
+// snapshot-v1: the byte length of the JSON window list as a little-endian
+// u32, the list itself, then the pane text as raw UTF-8 to the end.
+fn snapshot_frame(windows: &JsonValue, pane: &str) -> Vec<u8> {
+    let header = windows.to_string();
+    let mut frame = Vec::with_capacity(4 + header.len() + pane.len());
+    frame.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    frame.extend_from_slice(header.as_bytes());
+    frame.extend_from_slice(pane.as_bytes());
+    frame
+}
+
+// A snapshot as the usual {windows, pane} JSON, or as a snapshot-v1 frame
+// when `binary`.
+pub fn snapshot(windows: impl Serialize, pane: String, binary: bool) -> Result<Response, String> {
+    let windows = serde_json::to_value(windows).map_err(|e| e.to_string())?;
+    if binary {
+        return Ok(Response::new(snapshot_frame(&windows, &pane)));
+    }
+    let json = serde_json::json!({ "windows": windows, "pane": pane });
+    Ok(Response::new(json.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::snapshot_frame;
+    use serde_json::json;
+
+    #[test]
+    fn snapshot_frames_lead_with_the_window_list() {
+        let windows = json!([{ "index": 0, "id": "@1", "name": "arc" }]);
+        let frame = snapshot_frame(&windows, "Δ ok\n");
+        let len = u32::from_le_bytes(frame[..4].try_into().unwrap()) as usize;
+        let header: serde_json::Value = serde_json::from_slice(&frame[4..4 + len]).unwrap();
+        assert_eq!(header, windows);
+        assert_eq!(std::str::from_utf8(&frame[4 + len..]).unwrap(), "Δ ok\n");
+    }
+}
@@ -1,19 +1,41 @@
-use frontend_lib::model::{ARCRun, RunStatus};
+use frontend_lib::model::{ARCRun, EnvVersions, RunNote, RunProgress, RunStatus};
 use std::path::PathBuf;
 
 #[test]
 fn arc_run_json_roundtrip() {
     let run = ARCRun {
         id: "uuid-1234".into(),
-        name: "rmg_rxn_2025".into(),
-        session: "tmux-session-1".into(),
-        input_path: PathBuf::from("/tmp/input.py"),
-        work_dir: PathBuf::from("/tmp/workdir"),
+        window_id: Some("@1".into()),
         started_at: Some("2024-10-01T12:00:00Z".into()),
-        finished_at: None,
-        status: RunStatus::Running,
         last_stdout: Some(String::new()), // <-- wrap with Some(...)
         last_stderr: Some(String::new()), // <-- wrap with Some(...)
+        restarted_from: Some("uuid-0001".into()),
+        progress: Some(RunProgress {
+            species_total: 3,
+            species_converged: 1,
+            running_jobs: vec!["opt_a12".into()],
+            job_types: vec!["opt".into()],
+            ..RunProgress::default()
+        }),
+        archive_path: Some(PathBuf::from("/tmp/archive/rmg_rxn_2025.tar.gz")),
+        versions: Some(EnvVersions {
+            arc: Some("1.1.0".into()),
+            rmg_py_commit: Some("3f2a1bc".into()),
+            ..EnvVersions::default()
+        }),
+        host: Some("arc@cluster.example.org:22".into()),
+        tags: vec!["benchmark".into()],
+        notes: vec![RunNote {
+            at: "2025-01-01T12:30:00Z".into(),
+            text: "killed gaussian job for OH, wrong multiplicity".into(),
+        }],
+        ..ARCRun::new(
+            "rmg_rxn_2025".into(),
+            "tmux-session-1".into(),
+            PathBuf::from("/tmp/input.py"),
+            PathBuf::from("/tmp/workdir"),
+            RunStatus::Running,
+        )
     };
 
     let json = serde_json::to_string(&run).unwrap();
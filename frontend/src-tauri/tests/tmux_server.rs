@@ -0,0 +1,113 @@
+use frontend_lib::backend::{LocalBackend, TmuxBackend};
+use frontend_lib::requests::WindowRef;
+use frontend_lib::{local_capture, tmuxpath, Detail};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+static SERIAL: Mutex<()> = Mutex::new(());
+static STARTED: AtomicUsize = AtomicUsize::new(0);
+
+// A throwaway tmux server on its own `-L` socket, which the library's local
+// commands talk to while this is alive. The socket setting is process-wide,
+// so tests holding one run one at a time; dropping it kills the server.
+struct TmuxServer {
+    _serial: MutexGuard<'static, ()>,
+}
+
+impl TmuxServer {
+    // None, and the test passes without checking anything, where there is
+    // no tmux to run.
+    fn start() -> Option<Self> {
+        if tmuxpath::local().is_err() {
+            eprintln!("tmux not installed, skipping");
+            return None;
+        }
+        let serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let n = STARTED.fetch_add(1, Ordering::SeqCst);
+        let socket = format!("arc-test-{}-{n}", std::process::id());
+        tmuxpath::set_local_socket(Some(socket));
+        Some(Self { _serial: serial })
+    }
+
+    fn session(&self, name: &str) {
+        LocalBackend.new_session(name).unwrap();
+    }
+}
+
+impl Drop for TmuxServer {
+    fn drop(&mut self) {
+        let _ = LocalBackend.exec(&["kill-server".to_string()]);
+        tmuxpath::set_local_socket(None);
+    }
+}
+
+// Captures `target` until `expected` shows up, for output a shell has not
+// printed yet.
+fn capture_until(target: &str, expected: &str) -> String {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let text = local_capture(target, 50).unwrap();
+        if text.contains(expected) || Instant::now() > deadline {
+            return text;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn sessions_and_windows_are_listed() {
+    let Some(server) = TmuxServer::start() else {
+        return;
+    };
+    assert!(LocalBackend.list_sessions().unwrap().is_empty());
+
+    server.session("arc");
+    LocalBackend
+        .new_window("arc", Some("opt run"), Some("sleep 30"))
+        .unwrap();
+    let sessions = LocalBackend.list_sessions().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].name, "arc");
+    assert_eq!(sessions[0].windows, 2);
+
+    let windows = LocalBackend.list_windows("arc", Detail::Full).unwrap();
+    assert_eq!(windows.len(), 2);
+    assert!(windows.iter().all(|w| w.id.starts_with('@')));
+    assert_eq!(windows[1].name, "opt run");
+
+    LocalBackend.kill_session("arc").unwrap();
+    assert!(LocalBackend.list_sessions().unwrap().is_empty());
+}
+
+#[test]
+fn sent_keys_show_up_in_the_capture() {
+    let Some(server) = TmuxServer::start() else {
+        return;
+    };
+    server.session("keys");
+    LocalBackend
+        .send_keys("keys:0", "echo arc-$((40 + 2))", true)
+        .unwrap();
+    let text = capture_until("keys:0", "arc-42");
+    assert!(text.contains("arc-42"), "{text:?}");
+}
+
+#[test]
+fn a_stale_window_id_is_captured_by_index() {
+    let Some(server) = TmuxServer::start() else {
+        return;
+    };
+    server.session("stale");
+    let window = WindowRef {
+        session: "stale".into(),
+        window_index: 0,
+        window_id: Some("@999".into()),
+    };
+    let (_, stale_id) = window
+        .with_fallback(|target| local_capture(target, 10))
+        .unwrap();
+    assert!(stale_id);
+    let err = local_capture("stale:7", 10).unwrap_err();
+    assert!(err.contains("can't find window"), "{err}");
+}